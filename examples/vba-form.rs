@@ -5,11 +5,14 @@ use std::{
 
 use argh::FromArgs;
 use ms_oforms::{
-    controls::command_button::parse_command_button,
-    properties::{FormEmbeddedActiveXControl, FormEmbeddedActiveXControlCached},
+    common::ParseMode,
+    events::map_form_events,
+    frm::export_frm,
+    render::svg::SvgRenderer,
+    strings::{extract_strings, strings_to_json},
+    trace::trace_streams,
     OFormsFile,
 };
-use nom::error::VerboseError;
 
 #[derive(FromArgs)]
 /// Parse a VB form
@@ -20,32 +23,106 @@ struct Options {
 
     #[argh(option)]
     /// name of the form (e.g. `frmFoo`)
-    form: PathBuf,
+    form: Option<PathBuf>,
+
+    #[argh(switch)]
+    /// list the strings of all forms in the file, with flags for suspicious content
+    strings: bool,
+
+    #[argh(switch)]
+    /// print an annotated hex dump of the form and object streams of the form
+    trace: bool,
+
+    #[argh(option)]
+    /// list the event handlers in this VBA module of the form, by control
+    events: Option<PathBuf>,
+
+    #[argh(switch)]
+    /// print the strings, the trace or the events as JSON
+    json: bool,
+
+    #[argh(option)]
+    /// write an SVG preview to this file
+    svg: Option<PathBuf>,
+
+    #[argh(option)]
+    /// export the form to this `.frm` file (and the `.frx` next to it)
+    frm: Option<PathBuf>,
 }
 
 fn main() -> io::Result<()> {
     let opts: Options = argh::from_env();
     let file = std::fs::File::open(opts.file)?;
     let reader = BufReader::new(file);
-    let mut oforms = OFormsFile::open_in(reader, opts.form)?;
+    if opts.strings {
+        let strings = extract_strings(&mut OFormsFile::open(reader)?)?;
+        if opts.json {
+            println!("{}", strings_to_json(&strings));
+        } else {
+            for s in strings {
+                println!("{}", s);
+            }
+        }
+        return Ok(());
+    }
+    let form = opts.form.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "missing --form or --strings")
+    })?;
+    let mut oforms = OFormsFile::open_in(reader, form)?;
+    if opts.trace {
+        let (mut f, mut o) = (Vec::new(), Vec::new());
+        oforms.root_form_stream()?.read_to_end(&mut f)?;
+        oforms.root_object_stream()?.read_to_end(&mut o)?;
+        let traces = trace_streams(&f, &o, ParseMode::Strict);
+        if opts.json {
+            let json: Vec<_> = traces.iter().map(|(t, _)| t.to_json()).collect();
+            println!("[{}]", json.join(","));
+        } else {
+            for (trace, data) in traces {
+                println!("{}", trace.hex_dump(data));
+            }
+        }
+        return Ok(());
+    }
+    if let Some(path) = opts.events {
+        let source = std::fs::read_to_string(path)?;
+        let map = map_form_events(&mut oforms, &source)?;
+        if opts.json {
+            println!("{}", map.to_json());
+        } else {
+            print!("{}", map);
+        }
+        return Ok(());
+    }
     let c = oforms.root_comp_obj()?;
     println!("{:?}", c);
     let mut f = oforms.root_form()?;
     println!("{:#?}", f.form_control());
-    let mut iter = f.site_iter();
-    while let Some((ctrl, _depth, _control)) = iter.next() {
-        let mut s = iter.site_stream()?;
-        let mut buf = Vec::with_capacity(s.limit() as usize);
-        s.read_to_end(&mut buf)?;
-        println!("{:?} {}", ctrl, buf.len());
-
-        if let FormEmbeddedActiveXControl::ControlCached(
-            FormEmbeddedActiveXControlCached::CommandButton,
-        ) = ctrl
-        {
-            let (_, btn) = parse_command_button::<VerboseError<_>>(&buf).unwrap();
-            println!("{:?}", btn);
+    let mut controls = Vec::new();
+    for (site, control) in f.controls() {
+        match control {
+            Ok(control) => {
+                println!("{}: {:?}", site.name, control);
+                controls.push((site.clone(), control));
+            }
+            Err(e) => println!("{}: {}", site.name, e),
         }
     }
+    if let Some(path) = opts.svg {
+        let svg = SvgRenderer::default().render(
+            f.form_control(),
+            controls.iter().map(|(site, control)| (site, control)),
+        );
+        std::fs::write(path, svg)?;
+    }
+    if let Some(path) = opts.frm {
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("UserForm1");
+        let export = export_frm(&mut oforms, name)?;
+        std::fs::write(&path, export.frm)?;
+        std::fs::write(path.with_extension("frx"), export.frx)?;
+    }
     Ok(())
 }
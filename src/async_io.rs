@@ -0,0 +1,75 @@
+//! Loading an [`OFormsFile`][crate::OFormsFile] from an async source (feature `async`)
+//!
+//! The compound file is buffered into memory without blocking, after which all streams
+//! (`\x01CompObj`, `f`, `o` and child storages) are read from that buffer.
+//!
+//! # Limitations
+//!
+//! The whole file is read, even if only one form is needed, and it stays in memory as long
+//! as the [`BufferedOFormsFile`] exists. This is deliberate: the streams of a compound file
+//! are chains of sectors, and finding them needs the FAT and the directory, which are
+//! spread over the file. Reading only those sectors would need an async implementation of
+//! the compound file format, while [`cfb`] only supports blocking I/O. For large files on a
+//! local disk, opening the file with [`OFormsFile::open`][crate::OFormsFile::open] (e.g. in
+//! `tokio::task::spawn_blocking`) avoids the copy.
+
+use std::{convert::TryFrom, io, path::PathBuf};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+
+pub use crate::BufferedOFormsFile;
+
+async fn read_all<R>(mut reader: R) -> io::Result<Vec<u8>>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    let len = reader.seek(io::SeekFrom::End(0)).await?;
+    reader.seek(io::SeekFrom::Start(0)).await?;
+    let mut bytes = Vec::with_capacity(usize::try_from(len).unwrap_or(0));
+    reader.read_to_end(&mut bytes).await?;
+    Ok(bytes)
+}
+
+impl BufferedOFormsFile {
+    /// Create a new instance by reading the whole compound file from `reader`
+    ///
+    /// See the [module docs][self] for why the whole file is read.
+    pub async fn open_async<R>(reader: R) -> io::Result<Self>
+    where
+        R: AsyncRead + AsyncSeek + Unpin,
+    {
+        Self::open(io::Cursor::new(read_all(reader).await?))
+    }
+
+    /// Create a new instance by reading the whole compound file from `reader`
+    pub async fn open_in_async<R>(reader: R, prefix: PathBuf) -> io::Result<Self>
+    where
+        R: AsyncRead + AsyncSeek + Unpin,
+    {
+        Self::open_in(io::Cursor::new(read_all(reader).await?), prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::BufferedOFormsFile;
+
+    #[tokio::test]
+    async fn test_open_async() {
+        let mut buf = io::Cursor::new(Vec::new());
+        {
+            let mut cfb = cfb::CompoundFile::create(&mut buf).unwrap();
+            cfb.create_storage("/UserForm1").unwrap();
+            cfb.create_stream("/UserForm1/f").unwrap();
+            cfb.flush().unwrap();
+        }
+        buf.set_position(0);
+        let mut file = BufferedOFormsFile::open_in_async(buf, "/UserForm1".into())
+            .await
+            .unwrap();
+        assert!(file.root_form_stream().is_ok());
+        assert!(file.root_object_stream().is_err());
+    }
+}
@@ -1,6 +1,8 @@
 mod parser;
+mod writer;
 pub use parser::*;
 use uuid::{uuid, Uuid};
+pub use writer::*;
 
 use std::{
     ffi::{CStr, CString},
@@ -107,7 +109,43 @@ bitflags! {
 /// See: <https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-oleds/5f368864-fdeb-4865-b298-67289f3c4e8e>
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[non_exhaustive]
-pub struct CompObjHeader {}
+pub struct CompObjHeader {
+    /// The first reserved field, `0xFFFE0001` in the files written by Office
+    pub reserved1: u32,
+    /// The version of the stream, `0x00000A03` in the files written by Office
+    pub version: u32,
+    /// The first 4 of the 20 reserved bytes, `0xFFFFFFFF` if they are followed by a CLSID
+    pub marker: u32,
+    /// The last 16 of the 20 reserved bytes, as read
+    ///
+    /// Use [`CompObjHeader::class_id`] to get the CLSID only if the marker is present.
+    pub clsid: Uuid,
+}
+
+impl CompObjHeader {
+    pub fn new(version: u32, clsid: Uuid) -> Self {
+        Self {
+            reserved1: 0xFFFE0001,
+            version,
+            marker: 0xFFFFFFFF,
+            clsid,
+        }
+    }
+
+    /// The CLSID of the object, if the reserved bytes contain one
+    pub fn class_id(&self) -> Option<Uuid> {
+        match self.marker {
+            0xFFFFFFFF => Some(self.clsid),
+            _ => None,
+        }
+    }
+}
+
+impl Default for CompObjHeader {
+    fn default() -> Self {
+        Self::new(0x00000A03, Uuid::nil())
+    }
+}
 
 /// ## [MS-OLEDS] 2.3.8 CompObjStream
 ///
@@ -118,6 +156,51 @@ pub struct CompObj {
     pub header: CompObjHeader,
     pub ansi_user_type: CString,
     pub ansi_clipboard_format: ClipboardFormat,
+    /// An ANSI string that is empty in the files written by Office
+    pub reserved1: CString,
+    /// The Unicode strings after the `0x71B239F4` marker, if present
+    pub unicode: Option<CompObjUnicode>,
+}
+
+impl CompObj {
+    /// The CompObj stream of a form in a VBA project, as written by Office
+    pub fn user_form() -> Self {
+        Self {
+            header: CompObjHeader::default(),
+            ansi_user_type: CString::new("Microsoft DDS Form 2.0").unwrap(),
+            ansi_clipboard_format: ClipboardFormat::Custom(
+                CString::new("Embedded Object").unwrap(),
+            ),
+            reserved1: CString::default(),
+            unicode: Some(CompObjUnicode {
+                user_type: String::new(),
+                clipboard_format: ClipboardFormat::None,
+                reserved2: String::new(),
+            }),
+        }
+    }
+
+    /// The display name of the object type
+    ///
+    /// This is the Unicode user type if it is present and not empty, and the ANSI user type
+    /// otherwise.
+    pub fn user_type(&self) -> String {
+        match &self.unicode {
+            Some(unicode) if !unicode.user_type.is_empty() => unicode.user_type.clone(),
+            _ => self.ansi_user_type.to_string_lossy().into_owned(),
+        }
+    }
+}
+
+/// The Unicode part of a [`CompObj`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompObjUnicode {
+    /// The display name of the object type
+    pub user_type: String,
+    /// A clipboard format, [`ClipboardFormat::Custom`] is written as a Unicode string
+    pub clipboard_format: ClipboardFormat,
+    /// A string that is empty in the files written by Office
+    pub reserved2: String,
 }
 
 /// Clipboard formats
@@ -135,8 +218,14 @@ pub struct CompObj {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ClipboardFormat {
     None,
-    Standard(u32),
+    /// A standard clipboard format, after a `0xFFFFFFFF` or `0xFFFFFFFE` marker
+    Standard {
+        marker: u32,
+        format: u32,
+    },
     Custom(CString),
+    /// A custom format from the Unicode part of a [`CompObj`]
+    Unicode(String),
 }
 
 impl ClipboardFormat {
@@ -144,3 +233,32 @@ impl ClipboardFormat {
         Self::Custom(c.to_owned())
     }
 }
+
+/// Quote a string for use in JSON
+pub(crate) fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if u32::from(c) < 0x20 => out.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Serialize a map of printable properties as a JSON object
+#[cfg(feature = "wasm")]
+pub(crate) fn json_map(map: &std::collections::BTreeMap<String, String>) -> String {
+    let items: Vec<String> = map
+        .iter()
+        .map(|(key, value)| format!("{}:{}", json_string(key), json_string(value)))
+        .collect();
+    format!("{{{}}}", items.join(","))
+}
@@ -1,17 +1,330 @@
-use super::{ClipboardFormat, CompObj, CompObjHeader};
+use super::{ClipboardFormat, CompObj, CompObjHeader, CompObjUnicode};
+use encoding_rs::Encoding;
 use nom::bytes::complete::take;
-use nom::combinator::{map, map_opt, map_res, value, verify};
-use nom::error::{FromExternalError, ParseError};
-use nom::multi::length_data;
+use nom::combinator::{map, map_opt, map_res, opt, verify};
+use nom::error::{ErrorKind, FromExternalError, ParseError, VerboseError};
+use nom::multi::count;
 use nom::number::complete::{le_i16, le_i32, le_u16, le_u32, le_u8};
 use nom::IResult;
 use uuid::Uuid;
 
-use std::cell::Cell;
-use std::ffi::{CStr, FromBytesWithNulError};
+use std::cell::{Cell, RefCell};
+use std::ffi::{CStr, CString, FromBytesWithNulError};
+use std::fmt;
+
+use bitflags::{Bits, Flags};
+
+/// How to handle bits in masks and flags that are not defined in [MS-OFORMS]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Reject the structure, e.g. for validators
+    #[default]
+    Strict,
+    /// Keep the unknown bits, and record an [`UnknownBits`] warning
+    ///
+    /// Unknown bits in flags are retained in the parsed struct, and unknown bits of a property
+    /// mask in its `unknown_mask_bits` field. See [`ParseContext::warnings`], or
+    /// [`Controls::with_warnings`][crate::Controls::with_warnings] for the warnings of each
+    /// control of a form.
+    ///
+    /// The data of an unknown property can't be read, so these properties are assumed to have
+    /// none. If there is data left after the known properties, parsing fails and
+    /// [`ParseContext::take_unreadable`] returns the bits.
+    Lenient,
+}
+
+/// Bits that were kept when parsing in [`ParseMode::Lenient`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownBits {
+    /// The type of the mask or flags, e.g. `FormPropMask`
+    pub name: &'static str,
+    /// The unknown bits
+    pub bits: u64,
+}
+
+impl fmt::Display for UnknownBits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown bits 0x{:X} in {}", self.bits, self.name)
+    }
+}
+
+/// A field of a structure, as recorded by [`ParseContext::start_trace`]
+///
+/// Nested structures (e.g. the `TextProps` of a control) are recorded as a field, followed by
+/// the fields of that structure with a `depth` that is one higher.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceField {
+    /// The name of the field, e.g. `ForeColor`
+    pub name: &'static str,
+    /// The nesting level, zero for the fields of the outermost structure
+    pub depth: usize,
+    /// The offset of the field (after the padding) from the start of the traced data
+    pub offset: usize,
+    /// The size of the field, or `None` if it could not be parsed
+    pub length: Option<usize>,
+    /// The number of alignment padding bytes before the field
+    pub padding: usize,
+}
+
+#[derive(Debug)]
+struct Trace {
+    origin: usize,
+    depth: usize,
+    fields: Vec<TraceField>,
+}
+
+impl Trace {
+    fn offset(&self, input: &[u8]) -> usize {
+        (input.as_ptr() as usize).wrapping_sub(self.origin)
+    }
+}
+
+/// The [`ParseMode`] and the warnings of a parser
+#[derive(Debug, Default)]
+pub struct ParseContext {
+    mode: ParseMode,
+    encoding: Option<&'static Encoding>,
+    warnings: RefCell<Vec<UnknownBits>>,
+    unreadable: RefCell<Option<UnknownBits>>,
+    trace: RefCell<Option<Trace>>,
+}
+
+impl ParseContext {
+    pub fn new(mode: ParseMode) -> Self {
+        Self {
+            mode,
+            encoding: None,
+            warnings: RefCell::new(Vec::new()),
+            unreadable: RefCell::new(None),
+            trace: RefCell::new(None),
+        }
+    }
+
+    pub fn mode(&self) -> ParseMode {
+        self.mode
+    }
+
+    /// Decode compressed strings with `encoding` instead of Latin-1
+    ///
+    /// Forms store compressed strings in the ANSI code page of the system that saved them.
+    /// See [`code_page_encoding`] for the `PROJECTCODEPAGE` of a VBA project and
+    /// [`charset_encoding`] for the charset of a font.
+    ///
+    /// [`code_page_encoding`]: crate::properties::string::code_page_encoding
+    /// [`charset_encoding`]: crate::properties::string::charset_encoding
+    pub fn with_encoding(mut self, encoding: &'static Encoding) -> Self {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// The encoding of compressed strings, `None` for Latin-1
+    pub fn encoding(&self) -> Option<&'static Encoding> {
+        self.encoding
+    }
+
+    /// The unknown bits that were found so far
+    pub fn warnings(&self) -> Vec<UnknownBits> {
+        self.warnings.borrow().clone()
+    }
+
+    /// Remove and return the unknown bits that were found so far
+    pub fn take_warnings(&self) -> Vec<UnknownBits> {
+        self.warnings.take()
+    }
+
+    /// The number of unknown bits that were found so far
+    pub(crate) fn warning_count(&self) -> usize {
+        self.warnings.borrow().len()
+    }
+
+    /// The unknown bits that were found after the first `start`
+    pub(crate) fn warnings_since(&self, start: usize) -> Vec<UnknownBits> {
+        self.warnings
+            .borrow()
+            .get(start..)
+            .unwrap_or_default()
+            .to_vec()
+    }
+
+    /// Remove and return the unknown bits of a property mask that made parsing fail
+    ///
+    /// This is set when the properties of these bits have data, which can't be read.
+    pub fn take_unreadable(&self) -> Option<UnknownBits> {
+        self.unreadable.take()
+    }
+
+    /// Record the fields that are parsed from `data` (or a subslice of it) from now on
+    ///
+    /// The offsets of the [`TraceField`]s are relative to the start of `data`.
+    pub fn start_trace(&self, data: &[u8]) {
+        self.trace.replace(Some(Trace {
+            origin: data.as_ptr() as usize,
+            depth: 0,
+            fields: Vec::new(),
+        }));
+    }
+
+    /// Stop tracing and return the fields that were recorded
+    ///
+    /// When parsing failed, the last field with a `length` of `None` is the one that failed.
+    pub fn take_trace(&self) -> Vec<TraceField> {
+        self.trace.take().map(|t| t.fields).unwrap_or_default()
+    }
+
+    /// Parse a field (or nested structure) that is not aligned, and record it when tracing
+    pub(crate) fn field<'a, O, E, P>(
+        &self,
+        name: &'static str,
+        input: &'a [u8],
+        parser: P,
+    ) -> IResult<&'a [u8], O, E>
+    where
+        P: FnOnce(&'a [u8]) -> IResult<&'a [u8], O, E>,
+    {
+        self.traced(name, None, input, parser)
+    }
+
+    /// Parse a field that is read with `ap`, and record it with its padding when tracing
+    pub(crate) fn aligned<'a, O, E, P>(
+        &self,
+        name: &'static str,
+        ap: &AlignedParser,
+        input: &'a [u8],
+        parser: P,
+    ) -> IResult<&'a [u8], O, E>
+    where
+        P: FnOnce(&'a [u8]) -> IResult<&'a [u8], O, E>,
+    {
+        self.traced(name, Some(ap), input, parser)
+    }
+
+    fn traced<'a, O, E, P>(
+        &self,
+        name: &'static str,
+        ap: Option<&AlignedParser>,
+        input: &'a [u8],
+        parser: P,
+    ) -> IResult<&'a [u8], O, E>
+    where
+        P: FnOnce(&'a [u8]) -> IResult<&'a [u8], O, E>,
+    {
+        let index = self.trace.borrow_mut().as_mut().map(|trace| {
+            let offset = trace.offset(input);
+            trace.fields.push(TraceField {
+                name,
+                depth: trace.depth,
+                offset,
+                length: None,
+                padding: 0,
+            });
+            trace.depth += 1;
+            trace.fields.len() - 1
+        });
+        let index = match index {
+            Some(index) => index,
+            None => return parser(input),
+        };
+        if let Some(ap) = ap {
+            ap.start_field();
+        }
+        let result = parser(input);
+        let padding = ap.map_or(0, AlignedParser::field_padding);
+        if let Some(trace) = self.trace.borrow_mut().as_mut() {
+            trace.depth -= 1;
+            let end = result.as_ref().ok().map(|(rest, _)| trace.offset(rest));
+            let field = &mut trace.fields[index];
+            field.padding = padding;
+            field.offset += padding;
+            field.length = end.map(|end| end - field.offset);
+            // Properties that are not in the mask
+            if field.length == Some(0) && padding == 0 {
+                trace.fields.truncate(index);
+            }
+        }
+        result
+    }
+
+    /// A function to convert bits to flags `F`, for [`map_opt`]
+    ///
+    /// In [`ParseMode::Strict`], this is `F::from_bits`.
+    pub(crate) fn flags<F>(&self) -> impl Fn(F::Bits) -> Option<F> + '_
+    where
+        F: Flags,
+        F::Bits: Into<u64>,
+    {
+        move |bits| match self.mode {
+            ParseMode::Strict => F::from_bits(bits),
+            ParseMode::Lenient => {
+                let unknown = bits & !F::all().bits();
+                if unknown != F::Bits::EMPTY {
+                    self.warnings.borrow_mut().push(UnknownBits {
+                        name: short_type_name::<F>(),
+                        bits: unknown.into(),
+                    });
+                }
+                Some(F::from_bits_retain(bits))
+            }
+        }
+    }
+
+    /// Fail if `mask` has unknown bits and there is data left after the known properties
+    ///
+    /// `read` is the size of the DataBlock and ExtraDataBlock that was read, starting at the
+    /// mask, and `left` the size of the rest of the block. Only the padding to a multiple of
+    /// four bytes may be left if the unknown properties have no data.
+    pub(crate) fn check_mask_layout<'a, F, E>(
+        &self,
+        mask: &F,
+        read: usize,
+        left: usize,
+        input: &'a [u8],
+    ) -> Result<(), nom::Err<E>>
+    where
+        F: Flags,
+        F::Bits: Into<u64>,
+        E: ParseError<&'a [u8]>,
+    {
+        let unknown = mask.bits() & !F::all().bits();
+        if unknown == F::Bits::EMPTY || left <= (4 - read % 4) % 4 {
+            return Ok(());
+        }
+        self.unreadable.replace(Some(UnknownBits {
+            name: short_type_name::<F>(),
+            bits: unknown.into(),
+        }));
+        Err(nom::Err::Failure(E::from_error_kind(
+            input,
+            ErrorKind::Verify,
+        )))
+    }
+}
+
+/// The name of a type without its path, e.g. `FormPropMask`
+fn short_type_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Describe a parse error of `data`, with the offset where it happened
+pub(crate) fn error_message(data: &[u8], e: nom::Err<VerboseError<&[u8]>>) -> String {
+    match e {
+        nom::Err::Incomplete(_) => String::from("the data is incomplete"),
+        nom::Err::Error(e) | nom::Err::Failure(e) => match e.errors.first() {
+            Some((rest, kind)) => format!(
+                "invalid data at offset {}: {:?}",
+                data.len() - rest.len(),
+                kind
+            ),
+            None => String::from("invalid data"),
+        },
+    }
+}
 
 /// Parse a GUID and [`verify`] that it is a specific value
-pub fn tag_guid<'a>(guid: Uuid) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], Uuid> {
+pub fn tag_guid<'a, E>(guid: Uuid) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], Uuid, E>
+where
+    E: ParseError<&'a [u8]>,
+{
     verify(parse_guid, move |x| x == &guid)
 }
 
@@ -31,11 +344,21 @@ where
     ))
 }
 
-pub(crate) struct AlignedParser(Cell<usize>);
+pub(crate) struct AlignedParser {
+    offset: Cell<usize>,
+    /// Whether nothing was read since [`AlignedParser::start_field`]
+    field_start: Cell<bool>,
+    /// The padding before the current field
+    field_padding: Cell<usize>,
+}
 
 impl AlignedParser {
     pub(crate) fn new() -> Self {
-        Self(Cell::new(0))
+        Self {
+            offset: Cell::new(0),
+            field_start: Cell::new(false),
+            field_padding: Cell::new(0),
+        }
     }
 
     pub(crate) fn le_u32<'a, E: ParseError<&'a [u8]>>(
@@ -130,22 +453,42 @@ impl AlignedParser {
         input: &'a [u8],
         align: usize,
     ) -> IResult<&'a [u8], usize, E> {
-        let pad_from = self.0.get();
+        let pad_from = self.offset.get();
         let offset = pad_from % align;
         if offset == 0 {
+            self.field_start.set(false);
             Ok((input, pad_from))
         } else {
             let skip = align - offset;
             let (input, _pad) = take(skip)(input)?;
             let pad_end = pad_from + skip;
-            self.0.set(pad_end);
+            self.offset.set(pad_end);
+            if self.field_start.replace(false) {
+                self.field_padding.set(skip);
+            }
             Ok((input, pad_end))
         }
     }
 
+    pub(crate) fn offset(&self) -> usize {
+        self.offset.get()
+    }
+
     pub(crate) fn inc(&self, by: usize) {
-        let offset = self.0.get();
-        self.0.set(offset + by);
+        let offset = self.offset.get();
+        self.offset.set(offset + by);
+        self.field_start.set(false);
+    }
+
+    /// Start recording the padding before the next field
+    fn start_field(&self) {
+        self.field_start.set(true);
+        self.field_padding.set(0);
+    }
+
+    /// The padding before the field since [`AlignedParser::start_field`]
+    fn field_padding(&self) -> usize {
+        self.field_padding.get()
     }
 }
 
@@ -153,7 +496,19 @@ fn parse_comp_obj_header<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], CompObjHea
 where
     E: ParseError<&'a [u8]>,
 {
-    value(CompObjHeader {}, take(28usize))(input)
+    let (input, reserved1) = le_u32(input)?;
+    let (input, version) = le_u32(input)?;
+    let (input, marker) = le_u32(input)?;
+    let (input, clsid) = parse_guid(input)?;
+    Ok((
+        input,
+        CompObjHeader {
+            reserved1,
+            version,
+            marker,
+            clsid,
+        },
+    ))
 }
 
 fn parse_length_prefixed_ansi_string<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], &'a CStr, E>
@@ -161,7 +516,28 @@ where
     E: ParseError<&'a [u8]>,
     E: FromExternalError<&'a [u8], FromBytesWithNulError>,
 {
-    map_res(length_data(le_u32), CStr::from_bytes_with_nul)(input)
+    let (input, len) = le_u32(input)?;
+    match len {
+        0 => Ok((input, Default::default())),
+        len => map_res(take(len), CStr::from_bytes_with_nul)(input),
+    }
+}
+
+/// Parse a `LengthPrefixedUnicodeString`, with a length in characters including the null
+fn parse_length_prefixed_unicode_string<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], String, E>
+where
+    E: ParseError<&'a [u8]>,
+{
+    let (input, len) = le_u32(input)?;
+    match len {
+        0 => Ok((input, String::new())),
+        len => map_opt(count(le_u16, len as usize), |chars: Vec<u16>| {
+            match chars.split_last() {
+                Some((0, chars)) => String::from_utf16(chars).ok(),
+                _ => None,
+            }
+        })(input),
+    }
 }
 
 fn parse_ansi_clipboard_format<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], ClipboardFormat, E>
@@ -172,7 +548,10 @@ where
     let (input, marker_or_length) = le_u32(input)?;
     match marker_or_length {
         0x00000000 => Ok((input, ClipboardFormat::None)),
-        0xFFFFFFFE | 0xFFFFFFFF => map(le_u32, ClipboardFormat::Standard)(input),
+        marker @ (0xFFFFFFFE | 0xFFFFFFFF) => map(le_u32, |format| ClipboardFormat::Standard {
+            marker,
+            format,
+        })(input),
         len => map(
             map_res(take(len), CStr::from_bytes_with_nul),
             ClipboardFormat::custom,
@@ -180,6 +559,45 @@ where
     }
 }
 
+/// Parse a `ClipboardFormatOrUnicodeString`
+fn parse_unicode_clipboard_format<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], ClipboardFormat, E>
+where
+    E: ParseError<&'a [u8]>,
+{
+    let (rest, marker_or_length) = le_u32(input)?;
+    match marker_or_length {
+        0x00000000 => Ok((rest, ClipboardFormat::None)),
+        marker @ (0xFFFFFFFE | 0xFFFFFFFF) => map(le_u32, |format| ClipboardFormat::Standard {
+            marker,
+            format,
+        })(rest),
+        _ => map(
+            parse_length_prefixed_unicode_string,
+            ClipboardFormat::Unicode,
+        )(input),
+    }
+}
+
+/// The marker before the Unicode part of a CompObj stream
+const UNICODE_MARKER: u32 = 0x71B239F4;
+
+fn parse_comp_obj_unicode<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], CompObjUnicode, E>
+where
+    E: ParseError<&'a [u8]>,
+{
+    let (input, user_type) = parse_length_prefixed_unicode_string(input)?;
+    let (input, clipboard_format) = parse_unicode_clipboard_format(input)?;
+    let (input, reserved2) = parse_length_prefixed_unicode_string(input)?;
+    Ok((
+        input,
+        CompObjUnicode {
+            user_type,
+            clipboard_format,
+            reserved2,
+        },
+    ))
+}
+
 pub fn parse_comp_obj<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], CompObj, E>
 where
     E: ParseError<&'a [u8]>,
@@ -188,12 +606,24 @@ where
     let (input, header) = parse_comp_obj_header(input)?;
     let (input, ansi_user_type) = map(parse_length_prefixed_ansi_string, CStr::to_owned)(input)?;
     let (input, ansi_clipboard_format) = parse_ansi_clipboard_format(input)?;
+    // Older writers stop after the clipboard format
+    let (input, reserved1) = match input.is_empty() {
+        true => (input, CString::default()),
+        false => map(parse_length_prefixed_ansi_string, CStr::to_owned)(input)?,
+    };
+    // If the marker is missing or has another value, the rest MUST be ignored
+    let (input, unicode) = match opt(verify(le_u32, |m| *m == UNICODE_MARKER))(input)? {
+        (input, Some(_)) => map(parse_comp_obj_unicode, Some)(input)?,
+        (input, None) => (input, None),
+    };
     Ok((
         input,
         CompObj {
             header,
             ansi_user_type,
             ansi_clipboard_format,
+            reserved1,
+            unicode,
         },
     ))
 }
@@ -202,12 +632,14 @@ where
 mod tests {
     use std::ffi::CStr;
 
+    use uuid::Uuid;
+
     use crate::common::{
         parse_comp_obj,
         parser::{
             parse_ansi_clipboard_format, parse_comp_obj_header, parse_length_prefixed_ansi_string,
         },
-        ClipboardFormat, CompObj, CompObjHeader,
+        write_comp_obj, ClipboardFormat, CompObj, CompObjHeader, CompObjUnicode,
     };
 
     const DATA: &[u8] = include_bytes!("comp_obj.bin");
@@ -218,7 +650,7 @@ mod tests {
         let fmt = CStr::from_bytes_with_nul(b"Embedded Object\0").unwrap();
         assert_eq!(
             parse_comp_obj_header::<nom::error::Error<_>>(DATA).ok(),
-            Some((&DATA[28..], CompObjHeader {})),
+            Some((&DATA[28..], CompObjHeader::new(0x00000A03, Uuid::nil()))),
         );
         assert_eq!(
             parse_length_prefixed_ansi_string::<nom::error::Error<_>>(&DATA[28..]).ok(),
@@ -229,16 +661,73 @@ mod tests {
             Some((&DATA[75..], ClipboardFormat::Custom(fmt.to_owned()))),
         );
 
+        let comp_obj = CompObj::user_form();
         assert_eq!(
             parse_comp_obj::<nom::error::Error<_>>(DATA).ok(),
-            Some((
-                &DATA[75..],
-                CompObj {
-                    header: CompObjHeader {},
-                    ansi_user_type: user_type.to_owned(),
-                    ansi_clipboard_format: ClipboardFormat::Custom(fmt.to_owned()),
-                }
-            ))
-        )
+            Some((&DATA[DATA.len()..], comp_obj.clone()))
+        );
+        let mut bytes = Vec::new();
+        write_comp_obj(&mut bytes, &comp_obj).unwrap();
+        assert_eq!(bytes, DATA);
+    }
+
+    #[test]
+    fn test_unicode_user_type() {
+        let comp_obj = CompObj {
+            unicode: Some(CompObjUnicode {
+                user_type: String::from("Microsoft Forms 2.0 Formular"),
+                clipboard_format: ClipboardFormat::Unicode(String::from("Eingebettetes Objekt")),
+                reserved2: String::new(),
+            }),
+            ..CompObj::user_form()
+        };
+        let mut bytes = Vec::new();
+        write_comp_obj(&mut bytes, &comp_obj).unwrap();
+        let (_, parsed) = parse_comp_obj::<nom::error::Error<_>>(&bytes).unwrap();
+        assert_eq!(parsed, comp_obj);
+        assert_eq!(parsed.user_type(), "Microsoft Forms 2.0 Formular");
+    }
+
+    #[test]
+    fn test_round_trip_markers() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&0x00000000u32.to_le_bytes()); // Reserved1
+        data.extend_from_slice(&0x00000A03u32.to_le_bytes()); // Version
+        data.extend_from_slice(&0x00000000u32.to_le_bytes()); // Reserved2, without a CLSID
+        data.extend_from_slice(&[0xAB; 16]);
+        data.extend_from_slice(&2u32.to_le_bytes()); // AnsiUserType
+        data.extend_from_slice(b"A\0");
+        data.extend_from_slice(&0xFFFFFFFFu32.to_le_bytes()); // AnsiClipboardFormat
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes()); // Reserved1
+        data.extend_from_slice(&0x71B239F4u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes()); // UnicodeUserType
+        data.extend_from_slice(&0xFFFFFFFEu32.to_le_bytes()); // UnicodeClipboardFormat
+        data.extend_from_slice(&8u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes()); // Reserved2
+
+        let (rest, comp_obj) = parse_comp_obj::<nom::error::Error<_>>(&data).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(comp_obj.header.reserved1, 0);
+        assert_eq!(comp_obj.header.marker, 0);
+        assert_eq!(comp_obj.header.class_id(), None);
+        assert_eq!(
+            comp_obj.ansi_clipboard_format,
+            ClipboardFormat::Standard {
+                marker: 0xFFFFFFFF,
+                format: 3
+            }
+        );
+        assert_eq!(
+            comp_obj.unicode.as_ref().unwrap().clipboard_format,
+            ClipboardFormat::Standard {
+                marker: 0xFFFFFFFE,
+                format: 8
+            }
+        );
+
+        let mut bytes = Vec::new();
+        write_comp_obj(&mut bytes, &comp_obj).unwrap();
+        assert_eq!(bytes, data);
     }
 }
@@ -0,0 +1,163 @@
+use std::{convert::TryFrom, ffi::CStr, io};
+
+use uuid::Uuid;
+
+use super::{ClipboardFormat, CompObj};
+
+/// Write a GUID, the inverse of [`parse_guid`][super::parse_guid]
+pub fn write_guid(out: &mut Vec<u8>, guid: Uuid) {
+    out.extend_from_slice(&guid.to_bytes_le());
+}
+
+/// Writes the DataBlock and ExtraDataBlock of a control, padding every field to its natural
+/// alignment like [`AlignedParser`][super::AlignedParser] expects.
+pub(crate) struct AlignedWriter(Vec<u8>);
+
+impl AlignedWriter {
+    pub(crate) fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub(crate) fn align(&mut self, align: usize) {
+        while self.0.len() % align != 0 {
+            self.0.push(0);
+        }
+    }
+
+    pub(crate) fn le_u32(&mut self, value: u32) {
+        self.align(4);
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn le_i32(&mut self, value: i32) {
+        self.align(4);
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn le_u16(&mut self, value: u16) {
+        self.align(2);
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn le_i16(&mut self, value: i16) {
+        self.align(2);
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn le_u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    pub(crate) fn bytes(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    /// Overwrite an u32 that was written at `offset`, e.g. a PropMask
+    pub(crate) fn patch_u32(&mut self, offset: usize, value: u32) {
+        self.0[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// The written bytes, padded to a multiple of 4
+    pub(crate) fn finish(mut self) -> Vec<u8> {
+        self.align(4);
+        self.0
+    }
+}
+
+/// Write a control header (version, size) followed by the `data`
+pub(crate) fn write_with_header(
+    out: &mut Vec<u8>,
+    version: [u8; 2],
+    data: &[u8],
+) -> io::Result<()> {
+    let cb = u16::try_from(data.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} bytes don't fit into a control header", data.len()),
+        )
+    })?;
+    out.extend_from_slice(&version);
+    out.extend_from_slice(&cb.to_le_bytes());
+    out.extend_from_slice(data);
+    Ok(())
+}
+
+fn length_u32(len: usize) -> io::Result<u32> {
+    u32::try_from(len).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("a string of {} bytes is too long", len),
+        )
+    })
+}
+
+fn write_length_prefixed_ansi_string(out: &mut Vec<u8>, value: &CStr) -> io::Result<()> {
+    let bytes = value.to_bytes_with_nul();
+    match bytes.len() {
+        1 => out.extend_from_slice(&0u32.to_le_bytes()),
+        len => {
+            out.extend_from_slice(&length_u32(len)?.to_le_bytes());
+            out.extend_from_slice(bytes);
+        }
+    }
+    Ok(())
+}
+
+fn write_length_prefixed_unicode_string(out: &mut Vec<u8>, value: &str) -> io::Result<()> {
+    if value.is_empty() {
+        out.extend_from_slice(&0u32.to_le_bytes());
+        return Ok(());
+    }
+    let chars: Vec<u16> = value.encode_utf16().chain(Some(0)).collect();
+    out.extend_from_slice(&length_u32(chars.len())?.to_le_bytes());
+    for c in chars {
+        out.extend_from_slice(&c.to_le_bytes());
+    }
+    Ok(())
+}
+
+fn write_clipboard_format(
+    out: &mut Vec<u8>,
+    format: &ClipboardFormat,
+    unicode: bool,
+) -> io::Result<()> {
+    match format {
+        ClipboardFormat::None => out.extend_from_slice(&0u32.to_le_bytes()),
+        ClipboardFormat::Standard { marker, format } => {
+            out.extend_from_slice(&marker.to_le_bytes());
+            out.extend_from_slice(&format.to_le_bytes());
+        }
+        ClipboardFormat::Custom(name) if unicode => {
+            write_length_prefixed_unicode_string(out, &name.to_string_lossy())?
+        }
+        ClipboardFormat::Custom(name) => write_length_prefixed_ansi_string(out, name)?,
+        ClipboardFormat::Unicode(name) if unicode => {
+            write_length_prefixed_unicode_string(out, name)?
+        }
+        ClipboardFormat::Unicode(name) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("the clipboard format {:?} is not an ANSI string", name),
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Write a CompObj stream, the inverse of [`parse_comp_obj`][super::parse_comp_obj]
+pub fn write_comp_obj(out: &mut Vec<u8>, comp_obj: &CompObj) -> io::Result<()> {
+    out.extend_from_slice(&comp_obj.header.reserved1.to_le_bytes());
+    out.extend_from_slice(&comp_obj.header.version.to_le_bytes());
+    out.extend_from_slice(&comp_obj.header.marker.to_le_bytes());
+    write_guid(out, comp_obj.header.clsid);
+    write_length_prefixed_ansi_string(out, &comp_obj.ansi_user_type)?;
+    write_clipboard_format(out, &comp_obj.ansi_clipboard_format, false)?;
+    write_length_prefixed_ansi_string(out, &comp_obj.reserved1)?;
+    if let Some(unicode) = &comp_obj.unicode {
+        out.extend_from_slice(&0x71B239F4u32.to_le_bytes());
+        write_length_prefixed_unicode_string(out, &unicode.user_type)?;
+        write_clipboard_format(out, &unicode.clipboard_format, true)?;
+        write_length_prefixed_unicode_string(out, &unicode.reserved2)?;
+    }
+    Ok(())
+}
@@ -1,25 +1,90 @@
 //! ## 2.2.1 CommandButton Control
 
+use std::io;
+
 use nom::{
     bytes::complete::tag,
-    combinator::map_opt,
+    combinator::verify,
     error::{FromExternalError, ParseError},
     multi::length_value,
-    number::complete::{le_u16, le_u32},
+    number::complete::le_u16,
     sequence::preceded,
     IResult,
 };
+use num_traits::FromPrimitive;
 
-use crate::{common::AlignedParser, properties::color::OleColor};
+use crate::{
+    common::{write_with_header, AlignedParser, AlignedWriter, ParseContext},
+    properties::{
+        color::OleColor,
+        font::{parse_text_props_with, write_text_props, TextProps},
+        picture::{parse_guid_and_picture, write_guid_and_picture, GuidAndPicture},
+        string::stream::CountOfBytesWithCompressionFlag,
+        MousePointer, Size, VariousPropertyBits,
+    },
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CommandButtonControl {
+    /// The file format default is 0x80000012, COLOR_BTNTEXT from the system palette.
     pub fore_color: OleColor,
+    /// The file format default is 0x8000000F, COLOR_BTNFACE from the system palette.
     pub back_color: OleColor,
+    /// The file format default is 0x0000001B.
+    pub various_property_bits: VariousPropertyBits,
+    /// The file format default is a zero-length string.
+    pub caption: String,
+    /// An fmPicturePosition that specifies the location of the picture relative to the caption.
+    ///
+    /// The file format default is 0x00070001, fmPicturePositionAboveCenter.
+    pub picture_position: u32,
+    /// The width and height, in HIMETRIC units, of the control.
+    pub size: Size,
+    /// The file format default is 0x00, fmMousePointerDefault.
+    pub mouse_pointer: MousePointer,
+    /// The file format default is no picture.
+    pub picture: GuidAndPicture,
+    /// A character code that specifies the accelerator key. A value of zero specifies that there is
+    /// no accelerator.
+    ///
+    /// The file format default is 0x0000.
+    pub accelerator: u16,
+    /// The file format default is TRUE.
+    pub take_focus_on_click: bool,
+    /// The file format default is no custom icon.
+    pub mouse_icon: GuidAndPicture,
+    /// The font of the caption.
+    pub text_props: TextProps,
+    /// The bits of the property mask that are not defined in [MS-OFORMS]
+    ///
+    /// These are only kept in [`ParseMode::Lenient`][crate::common::ParseMode::Lenient], for
+    /// properties without data, and are written back unchanged. Zero by default.
+    pub unknown_mask_bits: u32,
+}
+
+impl Default for CommandButtonControl {
+    /// The file format defaults, with a size of zero
+    fn default() -> Self {
+        Self {
+            fore_color: OleColor::BTNTEXT,
+            back_color: OleColor::BTNFACE,
+            various_property_bits: VariousPropertyBits::from_bits_retain(0x0000001B),
+            caption: String::new(),
+            picture_position: 0x00070001,
+            size: Size::new(0, 0),
+            mouse_pointer: MousePointer::Default,
+            picture: GuidAndPicture::EMPTY,
+            accelerator: 0,
+            take_focus_on_click: true,
+            mouse_icon: GuidAndPicture::EMPTY,
+            text_props: TextProps::default(),
+            unknown_mask_bits: 0,
+        }
+    }
 }
 
 bitflags! {
-    struct CommandButtonPropMask: u32 {
+    pub(crate) struct CommandButtonPropMask: u32 {
         /// A - fForeColor (1 bit): Specifies whether the ForeColor property is stored in the DataBlock.ForeColor of the CommandButtonControl that contains this CommandButtonPropMask.
         const FORE_COLOR = 1 << 0;
         /// B - fBackColor (1 bit): Specifies whether the BackColor property is stored in the DataBlock.BackColor of the CommandButtonControl that contains this CommandButtonPropMask.
@@ -57,31 +122,206 @@ where
     E: ParseError<&'a [u8]>,
     E: FromExternalError<&'a [u8], u32>,
 {
-    length_value(parse_command_button_header, _parse_command_button)(input)
+    parse_command_button_with(input, &ParseContext::default())
+}
+
+/// Parse a [`CommandButtonControl`] with the [`ParseMode`][crate::common::ParseMode] of `ctx`
+pub fn parse_command_button_with<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], CommandButtonControl, E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    let (input, (mask, mut control)) = length_value(
+        |i| ctx.field("Header", i, parse_command_button_header),
+        |i| _parse_command_button(i, ctx),
+    )(input)?;
+
+    // Stream Data
+    let (input, picture) = match mask.contains(CommandButtonPropMask::PICTURE) {
+        true => ctx.field("Picture", input, parse_guid_and_picture)?,
+        false => (input, GuidAndPicture::EMPTY),
+    };
+    let (input, mouse_icon) = match mask.contains(CommandButtonPropMask::MOUSE_ICON) {
+        true => ctx.field("MouseIcon", input, parse_guid_and_picture)?,
+        false => (input, GuidAndPicture::EMPTY),
+    };
+
+    // Text Props
+    let (input, text_props) = ctx.field("TextProps", input, |input| {
+        parse_text_props_with(input, ctx)
+    })?;
+
+    control.picture = picture;
+    control.mouse_icon = mouse_icon;
+    control.text_props = text_props;
+    Ok((input, control))
 }
 
-fn _parse_command_button<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], CommandButtonControl, E>
+fn _parse_command_button<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], (CommandButtonPropMask, CommandButtonControl), E>
 where
     E: ParseError<&'a [u8]>,
     E: FromExternalError<&'a [u8], u32>,
 {
-    let (input, mask) = map_opt(le_u32, CommandButtonPropMask::from_bits)(input)?;
     let ap = AlignedParser::new();
+    let (input, mask) = ctx.aligned("PropMask", &ap, input, |input| {
+        ap.bitfield32(input, ctx.flags::<CommandButtonPropMask>())
+    })?;
 
     let (input, fore_color) = match mask.contains(CommandButtonPropMask::FORE_COLOR) {
-        true => ap.ole_color(input)?,
+        true => ctx.aligned("ForeColor", &ap, input, |input| ap.ole_color(input))?,
         false => (input, OleColor::BTNTEXT),
     };
     let (input, back_color) = match mask.contains(CommandButtonPropMask::BACK_COLOR) {
-        true => ap.ole_color(input)?,
+        true => ctx.aligned("BackColor", &ap, input, |input| ap.ole_color(input))?,
         false => (input, OleColor::BTNFACE),
     };
+    let (input, various_property_bits) =
+        match mask.contains(CommandButtonPropMask::VARIOUS_PROPERTY_BITS) {
+            true => ctx.aligned("VariousPropertyBits", &ap, input, |input| {
+                ap.bitfield32(input, VariousPropertyBits::from_u32)
+            })?,
+            false => (input, VariousPropertyBits::from_bits_retain(0x0000001B)),
+        };
+    let (input, caption_data) = match mask.contains(CommandButtonPropMask::CAPTION) {
+        true => ctx.aligned("CaptionData", &ap, input, |input| {
+            ap.length_and_compression(input)
+        })?,
+        false => (input, CountOfBytesWithCompressionFlag::EMPTY),
+    };
+    let (input, picture_position) = match mask.contains(CommandButtonPropMask::PICTURE_POSITION) {
+        true => ctx.aligned("PicturePosition", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 0x00070001),
+    };
+    let (input, mouse_pointer) = match mask.contains(CommandButtonPropMask::MOUSE_POINTER) {
+        true => ctx.aligned("MousePointer", &ap, input, |input| {
+            ap.bitfield8(input, MousePointer::from_u8)
+        })?,
+        false => (input, MousePointer::Default),
+    };
+    let (input, _picture) = match mask.contains(CommandButtonPropMask::PICTURE) {
+        true => ctx.aligned("Picture", &ap, input, |input| {
+            verify(|i| ap.le_u16(i), |x| *x == 0xFFFF)(input)
+        })?,
+        false => (input, 0),
+    };
+    let (input, accelerator) = match mask.contains(CommandButtonPropMask::ACCELERATOR) {
+        true => ctx.aligned("Accelerator", &ap, input, |input| ap.le_u16(input))?,
+        false => (input, 0),
+    };
+    let take_focus_on_click = !mask.contains(CommandButtonPropMask::TAKE_FOCUS_ON_CLICK);
+    let (input, _mouse_icon) = match mask.contains(CommandButtonPropMask::MOUSE_ICON) {
+        true => ctx.aligned("MouseIcon", &ap, input, |input| {
+            verify(|i| ap.le_u16(i), |x| *x == 0xFFFF)(input)
+        })?,
+        false => (input, 0),
+    };
 
+    // Extra Data Block
+    let (input, caption) = match mask.contains(CommandButtonPropMask::CAPTION) {
+        true => ctx.aligned("Caption", &ap, input, |input| {
+            ap.fm_string(input, caption_data, ctx.encoding())
+        })?,
+        false => (input, String::new()),
+    };
+    let (input, size) = match mask.contains(CommandButtonPropMask::SIZE) {
+        true => ctx.aligned("Size", &ap, input, |input| ap.fm_size(input))?,
+        false => (input, Size::new(0, 0)),
+    };
+
+    ctx.check_mask_layout(&mask, ap.offset(), input.len(), input)?;
+    let unknown_mask_bits = mask.bits() & !CommandButtonPropMask::all().bits();
     Ok((
         input,
-        CommandButtonControl {
-            fore_color,
-            back_color,
-        },
+        (
+            mask,
+            CommandButtonControl {
+                fore_color,
+                back_color,
+                various_property_bits,
+                caption,
+                picture_position,
+                size,
+                mouse_pointer,
+                picture: GuidAndPicture::EMPTY,
+                accelerator,
+                take_focus_on_click,
+                mouse_icon: GuidAndPicture::EMPTY,
+                text_props: TextProps::default(),
+                unknown_mask_bits,
+            },
+        ),
     ))
 }
+
+/// Write a [`CommandButtonControl`], storing only the properties that differ from the file format
+/// default
+pub fn write_command_button(out: &mut Vec<u8>, control: &CommandButtonControl) -> io::Result<()> {
+    let mut w = AlignedWriter::new();
+    let mut mask = CommandButtonPropMask::SIZE;
+    w.le_u32(0);
+
+    if control.fore_color != OleColor::BTNTEXT {
+        mask |= CommandButtonPropMask::FORE_COLOR;
+        w.ole_color(control.fore_color);
+    }
+    if control.back_color != OleColor::BTNFACE {
+        mask |= CommandButtonPropMask::BACK_COLOR;
+        w.ole_color(control.back_color);
+    }
+    if control.various_property_bits.bits() != 0x0000001B {
+        mask |= CommandButtonPropMask::VARIOUS_PROPERTY_BITS;
+        w.le_u32(control.various_property_bits.bits());
+    }
+    if !control.caption.is_empty() {
+        mask |= CommandButtonPropMask::CAPTION;
+        w.length_and_compression(&control.caption)?;
+    }
+    if control.picture_position != 0x00070001 {
+        mask |= CommandButtonPropMask::PICTURE_POSITION;
+        w.le_u32(control.picture_position);
+    }
+    if control.mouse_pointer != MousePointer::Default {
+        mask |= CommandButtonPropMask::MOUSE_POINTER;
+        w.le_u8(control.mouse_pointer as u8);
+    }
+    if control.picture != GuidAndPicture::EMPTY {
+        mask |= CommandButtonPropMask::PICTURE;
+        w.le_u16(0xFFFF);
+    }
+    if control.accelerator != 0 {
+        mask |= CommandButtonPropMask::ACCELERATOR;
+        w.le_u16(control.accelerator);
+    }
+    if !control.take_focus_on_click {
+        mask |= CommandButtonPropMask::TAKE_FOCUS_ON_CLICK;
+    }
+    if control.mouse_icon != GuidAndPicture::EMPTY {
+        mask |= CommandButtonPropMask::MOUSE_ICON;
+        w.le_u16(0xFFFF);
+    }
+
+    // Extra Data Block
+    if mask.contains(CommandButtonPropMask::CAPTION) {
+        w.fm_string(&control.caption)?;
+    }
+    w.fm_size(control.size);
+
+    w.patch_u32(
+        0,
+        mask.bits() | control.unknown_mask_bits & !CommandButtonPropMask::all().bits(),
+    );
+    write_with_header(out, [0x00, 0x02], &w.finish())?;
+
+    // Stream Data
+    write_guid_and_picture(out, &control.picture)?;
+    write_guid_and_picture(out, &control.mouse_icon)?;
+
+    // Text Props
+    write_text_props(out, &control.text_props)
+}
@@ -0,0 +1,329 @@
+//! ## 2.2.3 Image Control
+
+use std::io;
+
+use nom::{
+    bytes::complete::tag,
+    combinator::verify,
+    error::{FromExternalError, ParseError},
+    multi::length_value,
+    number::complete::le_u16,
+    sequence::preceded,
+    IResult,
+};
+use num_traits::FromPrimitive;
+
+use crate::{
+    common::{write_with_header, AlignedParser, AlignedWriter, ParseContext},
+    controls::user_form::BorderStyle,
+    properties::{
+        color::OleColor,
+        picture::{parse_guid_and_picture, write_guid_and_picture, GuidAndPicture},
+        MousePointer, PictureAlignment, PictureSizeMode, Size, SpecialEffect, VariousPropertyBits,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageControl {
+    /// Specifies whether the control resizes to display the entire picture.
+    ///
+    /// The file format default is FALSE.
+    pub auto_size: bool,
+    /// The file format default is 0x80000006, COLOR_WINDOWFRAME from the system palette.
+    pub border_color: OleColor,
+    /// The file format default is 0x8000000F, COLOR_BTNFACE from the system palette.
+    pub back_color: OleColor,
+    /// The file format default is 0x01, fmBorderStyleSingle.
+    pub border_style: BorderStyle,
+    /// The file format default is 0x00, fmMousePointerDefault.
+    pub mouse_pointer: MousePointer,
+    /// The file format default is 0x00, fmPictureSizeModeClip.
+    pub picture_size_mode: PictureSizeMode,
+    /// The file format default is 0x00, fmSpecialEffectFlat.
+    pub special_effect: SpecialEffect,
+    /// The width and height, in HIMETRIC units, of the control.
+    pub size: Size,
+    /// The file format default is no picture.
+    pub picture: GuidAndPicture,
+    /// The file format default is 0x02, fmPictureAlignmentCenter.
+    pub picture_alignment: PictureAlignment,
+    /// The file format default is FALSE.
+    pub picture_tiling: bool,
+    /// The file format default is 0x0000001B.
+    pub various_property_bits: VariousPropertyBits,
+    /// The file format default is no custom icon.
+    pub mouse_icon: GuidAndPicture,
+    /// The bits of the property mask that are not defined in [MS-OFORMS]
+    ///
+    /// These are only kept in [`ParseMode::Lenient`][crate::common::ParseMode::Lenient], for
+    /// properties without data, and are written back unchanged. Zero by default.
+    pub unknown_mask_bits: u32,
+}
+
+impl Default for ImageControl {
+    /// The file format defaults, with a size of zero
+    fn default() -> Self {
+        Self {
+            auto_size: false,
+            border_color: OleColor::WINDOWFRAME,
+            back_color: OleColor::BTNFACE,
+            border_style: BorderStyle::Single,
+            mouse_pointer: MousePointer::Default,
+            picture_size_mode: PictureSizeMode::Clip,
+            special_effect: SpecialEffect::Flat,
+            size: Size::new(0, 0),
+            picture: GuidAndPicture::EMPTY,
+            picture_alignment: PictureAlignment::Center,
+            picture_tiling: false,
+            various_property_bits: VariousPropertyBits::from_bits_retain(0x0000001B),
+            mouse_icon: GuidAndPicture::EMPTY,
+            unknown_mask_bits: 0,
+        }
+    }
+}
+
+bitflags! {
+    struct ImagePropMask: u32 {
+        /// C - fAutoSize (1 bit): Specifies whether the value of the AutoSize property is not the file format default.
+        const AUTO_SIZE = 1 << 2;
+        /// D - fBorderColor (1 bit): Specifies whether the BorderColor property is stored in the DataBlock.BorderColor of the ImageControl.
+        const BORDER_COLOR = 1 << 3;
+        /// E - fBackColor (1 bit): Specifies whether the BackColor property is stored in the DataBlock.BackColor of the ImageControl.
+        const BACK_COLOR = 1 << 4;
+        /// F - fBorderStyle (1 bit): Specifies whether the BorderStyle property is stored in the DataBlock.BorderStyle of the ImageControl.
+        const BORDER_STYLE = 1 << 5;
+        /// G - fMousePointer (1 bit): Specifies whether the MousePointer property is stored in the DataBlock.MousePointer of the ImageControl.
+        const MOUSE_POINTER = 1 << 6;
+        /// H - fPictureSizeMode (1 bit): Specifies whether the PictureSizeMode property is stored in the DataBlock.PictureSizeMode of the ImageControl.
+        const PICTURE_SIZE_MODE = 1 << 7;
+        /// I - fSpecialEffect (1 bit): Specifies whether the SpecialEffect property is stored in the DataBlock.SpecialEffect of the ImageControl.
+        const SPECIAL_EFFECT = 1 << 8;
+        /// J - fSize (1 bit): Specifies whether the Size property is stored in the ExtraDataBlock.Size of the ImageControl.
+        const SIZE = 1 << 9;
+        /// K - fPicture (1 bit): Specifies whether the Picture property is stored in the StreamData.Picture of the ImageControl. When this bit is set to 1, a value of 0xFFFF MUST be stored in the DataBlock.Picture of the ImageControl.
+        const PICTURE = 1 << 10;
+        /// L - fPictureAlignment (1 bit): Specifies whether the PictureAlignment property is stored in the DataBlock.PictureAlignment of the ImageControl.
+        const PICTURE_ALIGNMENT = 1 << 11;
+        /// M - fPictureTiling (1 bit): Specifies whether the value of the PictureTiling property is not the file format default.
+        const PICTURE_TILING = 1 << 12;
+        /// N - fVariousPropertyBits (1 bit): Specifies whether the VariousPropertyBits property is stored in the DataBlock.VariousPropertyBits of the ImageControl.
+        const VARIOUS_PROPERTY_BITS = 1 << 13;
+        /// O - fMouseIcon (1 bit): Specifies whether the MouseIcon property is stored in the StreamData.MouseIcon of the ImageControl. When this bit is set to 1, a value of 0xFFFF MUST be stored in the DataBlock.MouseIcon of the ImageControl.
+        const MOUSE_ICON = 1 << 14;
+    }
+}
+
+fn parse_image_header<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], u16, E>
+where
+    E: ParseError<&'a [u8]>,
+{
+    preceded(tag([0x00, 0x02]), le_u16)(input)
+}
+
+pub fn parse_image<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], ImageControl, E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    parse_image_with(input, &ParseContext::default())
+}
+
+/// Parse a [`ImageControl`] with the [`ParseMode`][crate::common::ParseMode] of `ctx`
+pub fn parse_image_with<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], ImageControl, E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    let (input, (mask, mut control)) = length_value(
+        |i| ctx.field("Header", i, parse_image_header),
+        |i| _parse_image(i, ctx),
+    )(input)?;
+
+    // Stream Data
+    let (input, picture) = match mask.contains(ImagePropMask::PICTURE) {
+        true => ctx.field("Picture", input, parse_guid_and_picture)?,
+        false => (input, GuidAndPicture::EMPTY),
+    };
+    let (input, mouse_icon) = match mask.contains(ImagePropMask::MOUSE_ICON) {
+        true => ctx.field("MouseIcon", input, parse_guid_and_picture)?,
+        false => (input, GuidAndPicture::EMPTY),
+    };
+
+    control.picture = picture;
+    control.mouse_icon = mouse_icon;
+    Ok((input, control))
+}
+
+fn _parse_image<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], (ImagePropMask, ImageControl), E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    let ap = AlignedParser::new();
+    let (input, mask) = ctx.aligned("PropMask", &ap, input, |input| {
+        ap.bitfield32(input, ctx.flags::<ImagePropMask>())
+    })?;
+
+    let auto_size = mask.contains(ImagePropMask::AUTO_SIZE);
+    let (input, border_color) = match mask.contains(ImagePropMask::BORDER_COLOR) {
+        true => ctx.aligned("BorderColor", &ap, input, |input| ap.ole_color(input))?,
+        false => (input, OleColor::WINDOWFRAME),
+    };
+    let (input, back_color) = match mask.contains(ImagePropMask::BACK_COLOR) {
+        true => ctx.aligned("BackColor", &ap, input, |input| ap.ole_color(input))?,
+        false => (input, OleColor::BTNFACE),
+    };
+    let (input, border_style) = match mask.contains(ImagePropMask::BORDER_STYLE) {
+        true => ctx.aligned("BorderStyle", &ap, input, |input| {
+            ap.bitfield8(input, BorderStyle::from_u8)
+        })?,
+        false => (input, BorderStyle::Single),
+    };
+    let (input, mouse_pointer) = match mask.contains(ImagePropMask::MOUSE_POINTER) {
+        true => ctx.aligned("MousePointer", &ap, input, |input| {
+            ap.bitfield8(input, MousePointer::from_u8)
+        })?,
+        false => (input, MousePointer::Default),
+    };
+    let (input, picture_size_mode) = match mask.contains(ImagePropMask::PICTURE_SIZE_MODE) {
+        true => ctx.aligned("PictureSizeMode", &ap, input, |input| {
+            ap.bitfield8(input, PictureSizeMode::from_u8)
+        })?,
+        false => (input, PictureSizeMode::Clip),
+    };
+    let (input, special_effect) = match mask.contains(ImagePropMask::SPECIAL_EFFECT) {
+        true => ctx.aligned("SpecialEffect", &ap, input, |input| {
+            ap.bitfield8(input, SpecialEffect::from_u8)
+        })?,
+        false => (input, SpecialEffect::Flat),
+    };
+    let (input, _picture) = match mask.contains(ImagePropMask::PICTURE) {
+        true => ctx.aligned("Picture", &ap, input, |input| {
+            verify(|i| ap.le_u16(i), |x| *x == 0xFFFF)(input)
+        })?,
+        false => (input, 0),
+    };
+    let (input, picture_alignment) = match mask.contains(ImagePropMask::PICTURE_ALIGNMENT) {
+        true => ctx.aligned("PictureAlignment", &ap, input, |input| {
+            ap.bitfield8(input, PictureAlignment::from_u8)
+        })?,
+        false => (input, PictureAlignment::Center),
+    };
+    let picture_tiling = mask.contains(ImagePropMask::PICTURE_TILING);
+    let (input, various_property_bits) = match mask.contains(ImagePropMask::VARIOUS_PROPERTY_BITS) {
+        true => ctx.aligned("VariousPropertyBits", &ap, input, |input| {
+            ap.bitfield32(input, VariousPropertyBits::from_u32)
+        })?,
+        false => (input, VariousPropertyBits::from_bits_retain(0x0000001B)),
+    };
+    let (input, _mouse_icon) = match mask.contains(ImagePropMask::MOUSE_ICON) {
+        true => ctx.aligned("MouseIcon", &ap, input, |input| {
+            verify(|i| ap.le_u16(i), |x| *x == 0xFFFF)(input)
+        })?,
+        false => (input, 0),
+    };
+
+    // Extra Data Block
+    let (input, size) = match mask.contains(ImagePropMask::SIZE) {
+        true => ctx.aligned("Size", &ap, input, |input| ap.fm_size(input))?,
+        false => (input, Size::new(0, 0)),
+    };
+
+    ctx.check_mask_layout(&mask, ap.offset(), input.len(), input)?;
+    let unknown_mask_bits = mask.bits() & !ImagePropMask::all().bits();
+    Ok((
+        input,
+        (
+            mask,
+            ImageControl {
+                auto_size,
+                border_color,
+                back_color,
+                border_style,
+                mouse_pointer,
+                picture_size_mode,
+                special_effect,
+                size,
+                picture: GuidAndPicture::EMPTY,
+                picture_alignment,
+                picture_tiling,
+                various_property_bits,
+                mouse_icon: GuidAndPicture::EMPTY,
+                unknown_mask_bits,
+            },
+        ),
+    ))
+}
+
+/// Write an [`ImageControl`], storing only the properties that differ from the file format default
+pub fn write_image(out: &mut Vec<u8>, control: &ImageControl) -> io::Result<()> {
+    let mut w = AlignedWriter::new();
+    let mut mask = ImagePropMask::SIZE;
+    w.le_u32(0);
+
+    if control.auto_size {
+        mask |= ImagePropMask::AUTO_SIZE;
+    }
+    if control.border_color != OleColor::WINDOWFRAME {
+        mask |= ImagePropMask::BORDER_COLOR;
+        w.ole_color(control.border_color);
+    }
+    if control.back_color != OleColor::BTNFACE {
+        mask |= ImagePropMask::BACK_COLOR;
+        w.ole_color(control.back_color);
+    }
+    if control.border_style != BorderStyle::Single {
+        mask |= ImagePropMask::BORDER_STYLE;
+        w.le_u8(control.border_style as u8);
+    }
+    if control.mouse_pointer != MousePointer::Default {
+        mask |= ImagePropMask::MOUSE_POINTER;
+        w.le_u8(control.mouse_pointer as u8);
+    }
+    if control.picture_size_mode != PictureSizeMode::Clip {
+        mask |= ImagePropMask::PICTURE_SIZE_MODE;
+        w.le_u8(control.picture_size_mode as u8);
+    }
+    if control.special_effect != SpecialEffect::Flat {
+        mask |= ImagePropMask::SPECIAL_EFFECT;
+        w.le_u8(control.special_effect as u8);
+    }
+    if control.picture != GuidAndPicture::EMPTY {
+        mask |= ImagePropMask::PICTURE;
+        w.le_u16(0xFFFF);
+    }
+    if control.picture_alignment != PictureAlignment::Center {
+        mask |= ImagePropMask::PICTURE_ALIGNMENT;
+        w.le_u8(control.picture_alignment as u8);
+    }
+    if control.picture_tiling {
+        mask |= ImagePropMask::PICTURE_TILING;
+    }
+    if control.various_property_bits.bits() != 0x0000001B {
+        mask |= ImagePropMask::VARIOUS_PROPERTY_BITS;
+        w.le_u32(control.various_property_bits.bits());
+    }
+    if control.mouse_icon != GuidAndPicture::EMPTY {
+        mask |= ImagePropMask::MOUSE_ICON;
+        w.le_u16(0xFFFF);
+    }
+
+    // Extra Data Block
+    w.fm_size(control.size);
+
+    w.patch_u32(
+        0,
+        mask.bits() | control.unknown_mask_bits & !ImagePropMask::all().bits(),
+    );
+    write_with_header(out, [0x00, 0x02], &w.finish())?;
+
+    // Stream Data
+    write_guid_and_picture(out, &control.picture)?;
+    write_guid_and_picture(out, &control.mouse_icon)
+}
@@ -0,0 +1,362 @@
+//! ## 2.2.4 Label Control
+
+use std::io;
+
+use nom::{
+    bytes::complete::tag,
+    combinator::verify,
+    error::{FromExternalError, ParseError},
+    multi::length_value,
+    number::complete::le_u16,
+    sequence::preceded,
+    IResult,
+};
+use num_traits::FromPrimitive;
+
+use crate::{
+    common::{write_with_header, AlignedParser, AlignedWriter, ParseContext},
+    controls::user_form::BorderStyle,
+    properties::{
+        color::OleColor,
+        font::{parse_text_props_with, write_text_props, TextProps},
+        picture::{parse_guid_and_picture, write_guid_and_picture, GuidAndPicture},
+        string::stream::CountOfBytesWithCompressionFlag,
+        MousePointer, Size, SpecialEffect, VariousPropertyBits,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelControl {
+    /// The file format default is 0x80000012, COLOR_BTNTEXT from the system palette.
+    pub fore_color: OleColor,
+    /// The file format default is 0x8000000F, COLOR_BTNFACE from the system palette.
+    pub back_color: OleColor,
+    /// The file format default is 0x0080001B.
+    pub various_property_bits: VariousPropertyBits,
+    /// The file format default is a zero-length string.
+    pub caption: String,
+    /// An fmPicturePosition that specifies the location of the picture relative to the caption.
+    ///
+    /// The file format default is 0x00070001, fmPicturePositionAboveCenter.
+    pub picture_position: u32,
+    /// The width and height, in HIMETRIC units, of the control.
+    pub size: Size,
+    /// The file format default is 0x00, fmMousePointerDefault.
+    pub mouse_pointer: MousePointer,
+    /// The file format default is 0x80000006, COLOR_WINDOWFRAME from the system palette.
+    pub border_color: OleColor,
+    /// The file format default is 0x0000, fmBorderStyleNone.
+    pub border_style: BorderStyle,
+    /// The file format default is 0x0000, fmSpecialEffectFlat.
+    pub special_effect: SpecialEffect,
+    /// The file format default is no picture.
+    pub picture: GuidAndPicture,
+    /// A character code that specifies the accelerator key. A value of zero specifies that there is
+    /// no accelerator.
+    ///
+    /// The file format default is 0x0000.
+    pub accelerator: u16,
+    /// The file format default is no custom icon.
+    pub mouse_icon: GuidAndPicture,
+    /// The font of the caption.
+    pub text_props: TextProps,
+    /// The bits of the property mask that are not defined in [MS-OFORMS]
+    ///
+    /// These are only kept in [`ParseMode::Lenient`][crate::common::ParseMode::Lenient], for
+    /// properties without data, and are written back unchanged. Zero by default.
+    pub unknown_mask_bits: u32,
+}
+
+impl Default for LabelControl {
+    /// The file format defaults, with a size of zero
+    fn default() -> Self {
+        Self {
+            fore_color: OleColor::BTNTEXT,
+            back_color: OleColor::BTNFACE,
+            various_property_bits: VariousPropertyBits::from_bits_retain(0x0080001B),
+            caption: String::new(),
+            picture_position: 0x00070001,
+            size: Size::new(0, 0),
+            mouse_pointer: MousePointer::Default,
+            border_color: OleColor::WINDOWFRAME,
+            border_style: BorderStyle::None,
+            special_effect: SpecialEffect::Flat,
+            picture: GuidAndPicture::EMPTY,
+            accelerator: 0,
+            mouse_icon: GuidAndPicture::EMPTY,
+            text_props: TextProps::default(),
+            unknown_mask_bits: 0,
+        }
+    }
+}
+
+bitflags! {
+    struct LabelPropMask: u32 {
+        /// A - fForeColor (1 bit): Specifies whether the ForeColor property is stored in the DataBlock.ForeColor of the LabelControl.
+        const FORE_COLOR = 1 << 0;
+        /// B - fBackColor (1 bit): Specifies whether the BackColor property is stored in the DataBlock.BackColor of the LabelControl.
+        const BACK_COLOR = 1 << 1;
+        /// C - fVariousPropertyBits (1 bit): Specifies whether the VariousPropertyBits property is stored in the DataBlock.VariousPropertyBits of the LabelControl.
+        const VARIOUS_PROPERTY_BITS = 1 << 2;
+        /// D - fCaption (1 bit): Specifies whether the size and compression flag of the Caption property are stored in the DataBlock.Caption and the Caption string is stored in the ExtraDataBlock.Caption of the LabelControl.
+        const CAPTION = 1 << 3;
+        /// E - fPicturePosition (1 bit): Specifies whether the PicturePosition property is stored in the DataBlock.PicturePosition of the LabelControl.
+        const PICTURE_POSITION = 1 << 4;
+        /// F - fSize (1 bit): Specifies whether the Size property is stored in the ExtraDataBlock.Size of the LabelControl.
+        const SIZE = 1 << 5;
+        /// G - fMousePointer (1 bit): Specifies whether the MousePointer property is stored in the DataBlock.MousePointer of the LabelControl.
+        const MOUSE_POINTER = 1 << 6;
+        /// H - fBorderColor (1 bit): Specifies whether the BorderColor property is stored in the DataBlock.BorderColor of the LabelControl.
+        const BORDER_COLOR = 1 << 7;
+        /// I - fBorderStyle (1 bit): Specifies whether the BorderStyle property is stored in the DataBlock.BorderStyle of the LabelControl.
+        const BORDER_STYLE = 1 << 8;
+        /// J - fSpecialEffect (1 bit): Specifies whether the SpecialEffect property is stored in the DataBlock.SpecialEffect of the LabelControl.
+        const SPECIAL_EFFECT = 1 << 9;
+        /// K - fPicture (1 bit): Specifies whether the Picture property is stored in the StreamData.Picture of the LabelControl. When this bit is set to 1, a value of 0xFFFF MUST be stored in the DataBlock.Picture of the LabelControl.
+        const PICTURE = 1 << 10;
+        /// L - fAccelerator (1 bit): Specifies whether the Accelerator property is stored in the DataBlock.Accelerator of the LabelControl.
+        const ACCELERATOR = 1 << 11;
+        /// M - fMouseIcon (1 bit): Specifies whether the MouseIcon property is stored in the StreamData.MouseIcon of the LabelControl. When this bit is set to 1, a value of 0xFFFF MUST be stored in the DataBlock.MouseIcon of the LabelControl.
+        const MOUSE_ICON = 1 << 12;
+    }
+}
+
+fn parse_label_header<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], u16, E>
+where
+    E: ParseError<&'a [u8]>,
+{
+    preceded(tag([0x00, 0x02]), le_u16)(input)
+}
+
+pub fn parse_label<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], LabelControl, E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    parse_label_with(input, &ParseContext::default())
+}
+
+/// Parse a [`LabelControl`] with the [`ParseMode`][crate::common::ParseMode] of `ctx`
+pub fn parse_label_with<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], LabelControl, E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    let (input, (mask, mut control)) = length_value(
+        |i| ctx.field("Header", i, parse_label_header),
+        |i| _parse_label(i, ctx),
+    )(input)?;
+
+    // Stream Data
+    let (input, picture) = match mask.contains(LabelPropMask::PICTURE) {
+        true => ctx.field("Picture", input, parse_guid_and_picture)?,
+        false => (input, GuidAndPicture::EMPTY),
+    };
+    let (input, mouse_icon) = match mask.contains(LabelPropMask::MOUSE_ICON) {
+        true => ctx.field("MouseIcon", input, parse_guid_and_picture)?,
+        false => (input, GuidAndPicture::EMPTY),
+    };
+
+    // Text Props
+    let (input, text_props) = ctx.field("TextProps", input, |input| {
+        parse_text_props_with(input, ctx)
+    })?;
+
+    control.picture = picture;
+    control.mouse_icon = mouse_icon;
+    control.text_props = text_props;
+    Ok((input, control))
+}
+
+fn _parse_label<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], (LabelPropMask, LabelControl), E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    let ap = AlignedParser::new();
+    let (input, mask) = ctx.aligned("PropMask", &ap, input, |input| {
+        ap.bitfield32(input, ctx.flags::<LabelPropMask>())
+    })?;
+
+    let (input, fore_color) = match mask.contains(LabelPropMask::FORE_COLOR) {
+        true => ctx.aligned("ForeColor", &ap, input, |input| ap.ole_color(input))?,
+        false => (input, OleColor::BTNTEXT),
+    };
+    let (input, back_color) = match mask.contains(LabelPropMask::BACK_COLOR) {
+        true => ctx.aligned("BackColor", &ap, input, |input| ap.ole_color(input))?,
+        false => (input, OleColor::BTNFACE),
+    };
+    let (input, various_property_bits) = match mask.contains(LabelPropMask::VARIOUS_PROPERTY_BITS) {
+        true => ctx.aligned("VariousPropertyBits", &ap, input, |input| {
+            ap.bitfield32(input, VariousPropertyBits::from_u32)
+        })?,
+        false => (input, VariousPropertyBits::from_bits_retain(0x0080001B)),
+    };
+    let (input, caption_data) = match mask.contains(LabelPropMask::CAPTION) {
+        true => ctx.aligned("CaptionData", &ap, input, |input| {
+            ap.length_and_compression(input)
+        })?,
+        false => (input, CountOfBytesWithCompressionFlag::EMPTY),
+    };
+    let (input, picture_position) = match mask.contains(LabelPropMask::PICTURE_POSITION) {
+        true => ctx.aligned("PicturePosition", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 0x00070001),
+    };
+    let (input, mouse_pointer) = match mask.contains(LabelPropMask::MOUSE_POINTER) {
+        true => ctx.aligned("MousePointer", &ap, input, |input| {
+            ap.bitfield8(input, MousePointer::from_u8)
+        })?,
+        false => (input, MousePointer::Default),
+    };
+    let (input, border_color) = match mask.contains(LabelPropMask::BORDER_COLOR) {
+        true => ctx.aligned("BorderColor", &ap, input, |input| ap.ole_color(input))?,
+        false => (input, OleColor::WINDOWFRAME),
+    };
+    let (input, border_style) = match mask.contains(LabelPropMask::BORDER_STYLE) {
+        true => ctx.aligned("BorderStyle", &ap, input, |input| {
+            ap.bitfield16(input, BorderStyle::from_u16)
+        })?,
+        false => (input, BorderStyle::None),
+    };
+    let (input, special_effect) = match mask.contains(LabelPropMask::SPECIAL_EFFECT) {
+        true => ctx.aligned("SpecialEffect", &ap, input, |input| {
+            ap.bitfield16(input, SpecialEffect::from_u16)
+        })?,
+        false => (input, SpecialEffect::Flat),
+    };
+    let (input, _picture) = match mask.contains(LabelPropMask::PICTURE) {
+        true => ctx.aligned("Picture", &ap, input, |input| {
+            verify(|i| ap.le_u16(i), |x| *x == 0xFFFF)(input)
+        })?,
+        false => (input, 0),
+    };
+    let (input, accelerator) = match mask.contains(LabelPropMask::ACCELERATOR) {
+        true => ctx.aligned("Accelerator", &ap, input, |input| ap.le_u16(input))?,
+        false => (input, 0),
+    };
+    let (input, _mouse_icon) = match mask.contains(LabelPropMask::MOUSE_ICON) {
+        true => ctx.aligned("MouseIcon", &ap, input, |input| {
+            verify(|i| ap.le_u16(i), |x| *x == 0xFFFF)(input)
+        })?,
+        false => (input, 0),
+    };
+
+    // Extra Data Block
+    let (input, caption) = match mask.contains(LabelPropMask::CAPTION) {
+        true => ctx.aligned("Caption", &ap, input, |input| {
+            ap.fm_string(input, caption_data, ctx.encoding())
+        })?,
+        false => (input, String::new()),
+    };
+    let (input, size) = match mask.contains(LabelPropMask::SIZE) {
+        true => ctx.aligned("Size", &ap, input, |input| ap.fm_size(input))?,
+        false => (input, Size::new(0, 0)),
+    };
+
+    ctx.check_mask_layout(&mask, ap.offset(), input.len(), input)?;
+    let unknown_mask_bits = mask.bits() & !LabelPropMask::all().bits();
+    Ok((
+        input,
+        (
+            mask,
+            LabelControl {
+                fore_color,
+                back_color,
+                various_property_bits,
+                caption,
+                picture_position,
+                size,
+                mouse_pointer,
+                border_color,
+                border_style,
+                special_effect,
+                picture: GuidAndPicture::EMPTY,
+                accelerator,
+                mouse_icon: GuidAndPicture::EMPTY,
+                text_props: TextProps::default(),
+                unknown_mask_bits,
+            },
+        ),
+    ))
+}
+
+/// Write a [`LabelControl`], storing only the properties that differ from the file format default
+pub fn write_label(out: &mut Vec<u8>, control: &LabelControl) -> io::Result<()> {
+    let mut w = AlignedWriter::new();
+    let mut mask = LabelPropMask::SIZE;
+    w.le_u32(0);
+
+    if control.fore_color != OleColor::BTNTEXT {
+        mask |= LabelPropMask::FORE_COLOR;
+        w.ole_color(control.fore_color);
+    }
+    if control.back_color != OleColor::BTNFACE {
+        mask |= LabelPropMask::BACK_COLOR;
+        w.ole_color(control.back_color);
+    }
+    if control.various_property_bits.bits() != 0x0080001B {
+        mask |= LabelPropMask::VARIOUS_PROPERTY_BITS;
+        w.le_u32(control.various_property_bits.bits());
+    }
+    if !control.caption.is_empty() {
+        mask |= LabelPropMask::CAPTION;
+        w.length_and_compression(&control.caption)?;
+    }
+    if control.picture_position != 0x00070001 {
+        mask |= LabelPropMask::PICTURE_POSITION;
+        w.le_u32(control.picture_position);
+    }
+    if control.mouse_pointer != MousePointer::Default {
+        mask |= LabelPropMask::MOUSE_POINTER;
+        w.le_u8(control.mouse_pointer as u8);
+    }
+    if control.border_color != OleColor::WINDOWFRAME {
+        mask |= LabelPropMask::BORDER_COLOR;
+        w.ole_color(control.border_color);
+    }
+    if control.border_style != BorderStyle::None {
+        mask |= LabelPropMask::BORDER_STYLE;
+        w.le_u16(control.border_style as u16);
+    }
+    if control.special_effect != SpecialEffect::Flat {
+        mask |= LabelPropMask::SPECIAL_EFFECT;
+        w.le_u16(control.special_effect as u16);
+    }
+    if control.picture != GuidAndPicture::EMPTY {
+        mask |= LabelPropMask::PICTURE;
+        w.le_u16(0xFFFF);
+    }
+    if control.accelerator != 0 {
+        mask |= LabelPropMask::ACCELERATOR;
+        w.le_u16(control.accelerator);
+    }
+    if control.mouse_icon != GuidAndPicture::EMPTY {
+        mask |= LabelPropMask::MOUSE_ICON;
+        w.le_u16(0xFFFF);
+    }
+
+    // Extra Data Block
+    if mask.contains(LabelPropMask::CAPTION) {
+        w.fm_string(&control.caption)?;
+    }
+    w.fm_size(control.size);
+
+    w.patch_u32(
+        0,
+        mask.bits() | control.unknown_mask_bits & !LabelPropMask::all().bits(),
+    );
+    write_with_header(out, [0x00, 0x02], &w.finish())?;
+
+    // Stream Data
+    write_guid_and_picture(out, &control.picture)?;
+    write_guid_and_picture(out, &control.mouse_icon)?;
+
+    // Text Props
+    write_text_props(out, &control.text_props)
+}
@@ -6,22 +6,410 @@
 //! - <https://github.com/LibreOffice/core/blob/master/include/oox/ole/axcontrol.hxx>
 //! - <https://learn.microsoft.com/en-us/dotnet/api/microsoft.vbe.interop.forms>
 
+use std::{io, path::PathBuf};
+
+use nom::{
+    combinator::{map, rest},
+    error::{FromExternalError, ParseError},
+    IResult,
+};
+use uuid::Uuid;
+
+use crate::common::ParseContext;
+use crate::properties::{
+    color::OleColor, font::TextProps, picture::GuidAndPicture, FormEmbeddedActiveXControlCached,
+    Size, VariousPropertyBits,
+};
+
 pub mod command_button;
 /// ## 2.2.2 Frame Control
 pub mod frame {}
-/// ## 2.2.3 Image Control
-pub mod image {}
-/// ## 2.2.4 Label Control
-pub mod label {}
-/// ## 2.2.5 MorphData Control
-pub mod morph_data {}
+pub mod image;
+pub mod label;
+pub mod morph_data;
 /// ## 2.2.6 MultiPage Control
 pub mod multi_page {}
-/// ## 2.2.7 ScrollBar Control
-pub mod scroll_bar {}
-/// ## 2.2.8 SpinButton Control
-pub mod spin_button {}
-/// ## 2.2.9 TabStrip Control
-pub mod tab_strip {}
+pub mod scroll_bar;
+pub mod spin_button;
+pub mod tab_strip;
+
+pub mod registry;
 
 pub mod user_form;
+
+use command_button::{parse_command_button_with, write_command_button, CommandButtonControl};
+use image::{parse_image_with, write_image, ImageControl};
+use label::{parse_label_with, write_label, LabelControl};
+use morph_data::{parse_morph_data_with, write_morph_data, DisplayStyle, MorphDataControl};
+use scroll_bar::{parse_scroll_bar_with, write_scroll_bar, ScrollBarControl};
+use spin_button::{parse_spin_button_with, write_spin_button, SpinButtonControl};
+use tab_strip::{parse_tab_strip_with, write_tab_strip, TabStripControl};
+
+/// A parent control (Frame, MultiPage, Page) that is persisted in its own storage
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParentControl {
+    /// The path of the storage, relative to the root of the [`OFormsFile`][crate::OFormsFile]
+    pub storage: PathBuf,
+}
+
+/// A fully parsed embedded control
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Control {
+    CommandButton(CommandButtonControl),
+    Label(LabelControl),
+    TextBox(MorphDataControl),
+    ListBox(MorphDataControl),
+    ComboBox(MorphDataControl),
+    CheckBox(MorphDataControl),
+    OptionButton(MorphDataControl),
+    ToggleButton(MorphDataControl),
+    Image(ImageControl),
+    SpinButton(SpinButtonControl),
+    ScrollBar(ScrollBarControl),
+    TabStrip(TabStripControl),
+    /// A Frame, see [`OFormsFile::form_in`][crate::OFormsFile::form_in]
+    Frame(ParentControl),
+    /// A MultiPage, see [`OFormsFile::form_in`][crate::OFormsFile::form_in]
+    ///
+    /// The sites in the form of a MultiPage are its pages.
+    MultiPage(ParentControl),
+    /// A page of a MultiPage, see [`OFormsFile::form_in`][crate::OFormsFile::form_in]
+    Page(ParentControl),
+    /// A control that this crate can't parse
+    Unknown {
+        clsid: Uuid,
+        bytes: Vec<u8>,
+    },
+}
+
+impl Control {
+    /// Pick the variant for a MorphData control from its DisplayStyle
+    pub fn from_morph_data(control: MorphDataControl) -> Self {
+        match control.display_style {
+            DisplayStyle::Text => Self::TextBox(control),
+            DisplayStyle::List => Self::ListBox(control),
+            DisplayStyle::Combo | DisplayStyle::DropList => Self::ComboBox(control),
+            DisplayStyle::CheckBox => Self::CheckBox(control),
+            DisplayStyle::OptionButton => Self::OptionButton(control),
+            DisplayStyle::Toggle => Self::ToggleButton(control),
+        }
+    }
+
+    /// The name of the control type, e.g. `CommandButton`
+    ///
+    /// This is `None` for unknown controls.
+    pub fn type_name(&self) -> Option<&'static str> {
+        match self {
+            Self::CommandButton(_) => Some("CommandButton"),
+            Self::Label(_) => Some("Label"),
+            Self::TextBox(_) => Some("TextBox"),
+            Self::ListBox(_) => Some("ListBox"),
+            Self::ComboBox(_) => Some("ComboBox"),
+            Self::CheckBox(_) => Some("CheckBox"),
+            Self::OptionButton(_) => Some("OptionButton"),
+            Self::ToggleButton(_) => Some("ToggleButton"),
+            Self::Image(_) => Some("Image"),
+            Self::SpinButton(_) => Some("SpinButton"),
+            Self::ScrollBar(_) => Some("ScrollBar"),
+            Self::TabStrip(_) => Some("TabStrip"),
+            Self::Frame(_) => Some("Frame"),
+            Self::MultiPage(_) => Some("MultiPage"),
+            Self::Page(_) => Some("Page"),
+            Self::Unknown { .. } => None,
+        }
+    }
+
+    /// The MorphData of a TextBox, ListBox, ComboBox, CheckBox, OptionButton or ToggleButton
+    pub fn morph_data(&self) -> Option<&MorphDataControl> {
+        match self {
+            Self::TextBox(c)
+            | Self::ListBox(c)
+            | Self::ComboBox(c)
+            | Self::CheckBox(c)
+            | Self::OptionButton(c)
+            | Self::ToggleButton(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// The storage of a Frame, MultiPage or Page
+    pub fn parent_control(&self) -> Option<&ParentControl> {
+        match self {
+            Self::Frame(p) | Self::MultiPage(p) | Self::Page(p) => Some(p),
+            _ => None,
+        }
+    }
+
+    /// The size of the control, in HIMETRIC units
+    ///
+    /// This is `None` for parent and unknown controls.
+    pub fn size(&self) -> Option<Size> {
+        match self {
+            Self::CommandButton(c) => Some(c.size),
+            Self::Label(c) => Some(c.size),
+            Self::Image(c) => Some(c.size),
+            Self::SpinButton(c) => Some(c.size),
+            Self::ScrollBar(c) => Some(c.size),
+            Self::TabStrip(c) => Some(c.size),
+            _ => self.morph_data().map(|c| c.size),
+        }
+    }
+
+    /// The caption of the control
+    pub fn caption(&self) -> Option<&str> {
+        match self {
+            Self::CommandButton(c) => Some(&c.caption),
+            Self::Label(c) => Some(&c.caption),
+            _ => self.morph_data().map(|c| c.caption.as_str()),
+        }
+    }
+
+    /// The background color of the control
+    pub fn back_color(&self) -> Option<OleColor> {
+        match self {
+            Self::CommandButton(c) => Some(c.back_color),
+            Self::Label(c) => Some(c.back_color),
+            Self::Image(c) => Some(c.back_color),
+            Self::SpinButton(c) => Some(c.back_color),
+            Self::ScrollBar(c) => Some(c.back_color),
+            Self::TabStrip(c) => Some(c.back_color),
+            _ => self.morph_data().map(|c| c.back_color),
+        }
+    }
+
+    /// The foreground (text) color of the control
+    pub fn fore_color(&self) -> Option<OleColor> {
+        match self {
+            Self::CommandButton(c) => Some(c.fore_color),
+            Self::Label(c) => Some(c.fore_color),
+            Self::SpinButton(c) => Some(c.fore_color),
+            Self::ScrollBar(c) => Some(c.fore_color),
+            Self::TabStrip(c) => Some(c.fore_color),
+            _ => self.morph_data().map(|c| c.fore_color),
+        }
+    }
+
+    /// The font of the control
+    pub fn text_props(&self) -> Option<&TextProps> {
+        match self {
+            Self::CommandButton(c) => Some(&c.text_props),
+            Self::Label(c) => Some(&c.text_props),
+            Self::TabStrip(c) => Some(&c.text_props),
+            _ => self.morph_data().map(|c| &c.text_props),
+        }
+    }
+
+    /// The boolean properties of the control
+    pub fn various_property_bits(&self) -> Option<VariousPropertyBits> {
+        match self {
+            Self::CommandButton(c) => Some(c.various_property_bits),
+            Self::Label(c) => Some(c.various_property_bits),
+            Self::Image(c) => Some(c.various_property_bits),
+            Self::SpinButton(c) => Some(c.various_property_bits),
+            Self::ScrollBar(c) => Some(c.various_property_bits),
+            Self::TabStrip(c) => Some(c.various_property_bits),
+            _ => self.morph_data().map(|c| c.various_property_bits),
+        }
+    }
+
+    /// The accelerator key of the control, if set
+    pub fn accelerator(&self) -> Option<char> {
+        let accelerator = match self {
+            Self::CommandButton(c) => c.accelerator,
+            Self::Label(c) => c.accelerator,
+            _ => self.morph_data()?.accelerator,
+        };
+        char::from_u32(u32::from(accelerator)).filter(|c| *c != '\0')
+    }
+
+    /// The picture of the control
+    pub fn picture(&self) -> Option<&GuidAndPicture> {
+        match self {
+            Self::CommandButton(c) => Some(&c.picture),
+            Self::Label(c) => Some(&c.picture),
+            Self::Image(c) => Some(&c.picture),
+            _ => self.morph_data().map(|c| &c.picture),
+        }
+    }
+
+    /// Parse the data of a control of type `kind` from the object stream
+    ///
+    /// Types without a parser are returned as [`Control::Unknown`].
+    pub fn parse<'a, E>(
+        kind: FormEmbeddedActiveXControlCached,
+        input: &'a [u8],
+    ) -> IResult<&'a [u8], Control, E>
+    where
+        E: ParseError<&'a [u8]>,
+        E: FromExternalError<&'a [u8], u32>,
+    {
+        Self::parse_with(kind, input, &ParseContext::default())
+    }
+
+    /// Parse the data of a control with the [`ParseMode`][crate::common::ParseMode] of `ctx`
+    pub fn parse_with<'a, E>(
+        kind: FormEmbeddedActiveXControlCached,
+        input: &'a [u8],
+        ctx: &ParseContext,
+    ) -> IResult<&'a [u8], Control, E>
+    where
+        E: ParseError<&'a [u8]>,
+        E: FromExternalError<&'a [u8], u32>,
+    {
+        use FormEmbeddedActiveXControlCached as C;
+        match kind {
+            C::CommandButton => map(
+                |i| parse_command_button_with(i, ctx),
+                Control::CommandButton,
+            )(input),
+            C::Label => map(|i| parse_label_with(i, ctx), Control::Label)(input),
+            C::TextBox => map(|i| parse_morph_data_with(i, ctx), Control::TextBox)(input),
+            C::ListBox => map(|i| parse_morph_data_with(i, ctx), Control::ListBox)(input),
+            C::ComboBox => map(|i| parse_morph_data_with(i, ctx), Control::ComboBox)(input),
+            C::CheckBox => map(|i| parse_morph_data_with(i, ctx), Control::CheckBox)(input),
+            C::OptionButton => map(|i| parse_morph_data_with(i, ctx), Control::OptionButton)(input),
+            C::ToggleButton => map(|i| parse_morph_data_with(i, ctx), Control::ToggleButton)(input),
+            C::MorphData => map(|i| parse_morph_data_with(i, ctx), Control::from_morph_data)(input),
+            C::Image => map(|i| parse_image_with(i, ctx), Control::Image)(input),
+            C::SpinButton => map(|i| parse_spin_button_with(i, ctx), Control::SpinButton)(input),
+            C::ScrollBar => map(|i| parse_scroll_bar_with(i, ctx), Control::ScrollBar)(input),
+            C::TabStrip => map(|i| parse_tab_strip_with(i, ctx), Control::TabStrip)(input),
+            C::Form | C::Frame | C::MultiPage => map(rest, |bytes: &[u8]| Control::Unknown {
+                clsid: kind.clsid(),
+                bytes: bytes.to_vec(),
+            })(input),
+        }
+    }
+
+    /// The entry in the global class cache for this control
+    ///
+    /// This is `None` for pages and unknown controls, which need an entry in the class table.
+    pub fn cached_class(&self) -> Option<FormEmbeddedActiveXControlCached> {
+        use FormEmbeddedActiveXControlCached as C;
+        match self {
+            Self::CommandButton(_) => Some(C::CommandButton),
+            Self::Label(_) => Some(C::Label),
+            Self::TextBox(_) => Some(C::TextBox),
+            Self::ListBox(_) => Some(C::ListBox),
+            Self::ComboBox(_) => Some(C::ComboBox),
+            Self::CheckBox(_) => Some(C::CheckBox),
+            Self::OptionButton(_) => Some(C::OptionButton),
+            Self::ToggleButton(_) => Some(C::ToggleButton),
+            Self::Image(_) => Some(C::Image),
+            Self::SpinButton(_) => Some(C::SpinButton),
+            Self::ScrollBar(_) => Some(C::ScrollBar),
+            Self::TabStrip(_) => Some(C::TabStrip),
+            Self::Frame(_) => Some(C::Frame),
+            Self::MultiPage(_) => Some(C::MultiPage),
+            Self::Page(_) | Self::Unknown { .. } => None,
+        }
+    }
+
+    /// Write the data of the control to the object stream, the inverse of [`Control::parse`]
+    ///
+    /// Parent controls are persisted in their own storage, so nothing is written for them.
+    pub fn write(&self, out: &mut Vec<u8>) -> io::Result<()> {
+        match self {
+            Self::CommandButton(c) => write_command_button(out, c),
+            Self::Label(c) => write_label(out, c),
+            Self::TextBox(c)
+            | Self::ListBox(c)
+            | Self::ComboBox(c)
+            | Self::CheckBox(c)
+            | Self::OptionButton(c)
+            | Self::ToggleButton(c) => write_morph_data(out, c),
+            Self::Image(c) => write_image(out, c),
+            Self::SpinButton(c) => write_spin_button(out, c),
+            Self::ScrollBar(c) => write_scroll_bar(out, c),
+            Self::TabStrip(c) => write_tab_strip(out, c),
+            Self::Frame(_) | Self::MultiPage(_) | Self::Page(_) => Ok(()),
+            Self::Unknown { bytes, .. } => {
+                out.extend_from_slice(bytes);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::error::VerboseError;
+
+    use super::{
+        morph_data::{ColumnInfo, DisplayStyle, MorphDataControl},
+        tab_strip::{TabFlags, TabStripControl},
+        Control,
+    };
+    use crate::properties::{FormEmbeddedActiveXControlCached, Size};
+
+    /// Write `control`, parse it back and check that the result is the same
+    fn round_trip(control: Control) -> Vec<u8> {
+        let kind = control.cached_class().unwrap();
+        let mut bytes = Vec::new();
+        control.write(&mut bytes).unwrap();
+        let (rest, parsed) = Control::parse::<VerboseError<_>>(kind, &bytes).unwrap();
+        assert!(rest.is_empty(), "{} bytes left", rest.len());
+        assert_eq!(parsed, control);
+        bytes
+    }
+
+    #[test]
+    fn test_parse_label() {
+        #[rustfmt::skip]
+        let bytes = [
+            0x00, 0x02, 0x14, 0x00, // version, cbLabel
+            0x28, 0x00, 0x00, 0x00, // PropMask: fCaption, fSize
+            0x03, 0x00, 0x00, 0x80, // Caption: 3 bytes, compressed
+            b'A', b'b', b'c', 0x00, // Caption, padding
+            0xE8, 0x03, 0x00, 0x00, // Size.Width
+            0xF4, 0x01, 0x00, 0x00, // Size.Height
+            0x00, 0x02, 0x04, 0x00, // TextProps: version, cbTextProps
+            0x00, 0x00, 0x00, 0x00, // TextProps: PropMask
+        ];
+        let (rest, control) =
+            Control::parse::<VerboseError<_>>(FormEmbeddedActiveXControlCached::Label, &bytes)
+                .unwrap();
+        assert!(rest.is_empty());
+        let Control::Label(label) = control else {
+            panic!("expected a label, got {:?}", control);
+        };
+        assert_eq!(label.caption, "Abc");
+        assert_eq!(label.size, Size::new(1000, 500));
+    }
+
+    #[test]
+    fn test_tab_strip_round_trip() {
+        let control = Control::TabStrip(TabStripControl {
+            items: vec![String::from("One"), String::from("Two")],
+            tab_flags: vec![TabFlags::VISIBLE | TabFlags::ENABLED, TabFlags::VISIBLE],
+            ..TabStripControl::default()
+        });
+        let bytes = round_trip(control);
+        // The TabFlagData follows the TextProps
+        assert_eq!(bytes[bytes.len() - 8..], [3, 0, 0, 0, 1, 0, 0, 0]);
+
+        round_trip(Control::TabStrip(TabStripControl::default()));
+    }
+
+    #[test]
+    fn test_morph_data_round_trip() {
+        let control = Control::ListBox(MorphDataControl {
+            display_style: DisplayStyle::List,
+            column_count: 3,
+            column_info: vec![
+                ColumnInfo { column_width: 1440 },
+                ColumnInfo::default(),
+                ColumnInfo { column_width: 0 },
+            ],
+            ..MorphDataControl::default()
+        });
+        let bytes = round_trip(control);
+        #[rustfmt::skip]
+        let column_info = [
+            0x00, 0x02, 0x08, 0x00, 0x01, 0x00, 0x00, 0x00, 0xA0, 0x05, 0x00, 0x00,
+            0x00, 0x02, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x02, 0x08, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        assert!(bytes.ends_with(&column_info));
+    }
+}
@@ -0,0 +1,760 @@
+//! ## 2.2.5 MorphData Control
+//!
+//! The MorphData control is the common persistence format of the CheckBox, ComboBox, ListBox,
+//! OptionButton, TextBox and ToggleButton controls.
+
+use std::{convert::TryFrom, io};
+
+use nom::{
+    bytes::complete::tag,
+    combinator::{map_opt, verify},
+    error::{FromExternalError, ParseError},
+    multi::{count, length_value},
+    number::complete::le_u16,
+    sequence::preceded,
+    IResult,
+};
+use num_traits::FromPrimitive;
+
+use crate::{
+    common::{write_with_header, AlignedParser, AlignedWriter, ParseContext},
+    controls::user_form::BorderStyle,
+    properties::{
+        color::OleColor,
+        font::{parse_text_props_with, write_text_props, TextProps},
+        picture::{parse_guid_and_picture, write_guid_and_picture, GuidAndPicture},
+        string::stream::CountOfBytesWithCompressionFlag,
+        MousePointer, Size, SpecialEffect, VariousPropertyBits,
+    },
+};
+
+/// Specifies the type of control that a MorphData control displays as (`fmDisplayStyle`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive, ToPrimitive)]
+pub enum DisplayStyle {
+    /// TextBox
+    Text = 0x01,
+    /// ListBox
+    List = 0x02,
+    /// ComboBox
+    Combo = 0x03,
+    /// CheckBox
+    CheckBox = 0x04,
+    /// OptionButton
+    OptionButton = 0x05,
+    /// ToggleButton
+    Toggle = 0x06,
+    /// ComboBox with a style of fmStyleDropDownList
+    DropList = 0x07,
+}
+
+/// The persisted properties of a column of a ListBox or ComboBox (`MorphDataColumnInfo`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ColumnInfo {
+    /// The width, in HIMETRIC units, of the column.
+    ///
+    /// The file format default is 0xFFFFFFFF (-1).
+    pub column_width: i32,
+}
+
+impl Default for ColumnInfo {
+    fn default() -> Self {
+        Self { column_width: -1 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MorphDataControl {
+    /// The file format default is 0x2C80081B.
+    pub various_property_bits: VariousPropertyBits,
+    /// The file format default is 0x80000005, COLOR_WINDOW from the system palette.
+    pub back_color: OleColor,
+    /// The file format default is 0x80000008, COLOR_WINDOWTEXT from the system palette.
+    pub fore_color: OleColor,
+    /// The maximum number of characters a user can enter. A value of zero specifies no maximum.
+    ///
+    /// The file format default is 0x00000000.
+    pub max_length: u32,
+    /// The file format default is 0x00, fmBorderStyleNone.
+    pub border_style: BorderStyle,
+    /// An fmScrollBars that specifies the scroll bars of the control.
+    ///
+    /// The file format default is 0x00, fmScrollBarsNone.
+    pub scroll_bars: u8,
+    /// The file format default is 0x01, fmDisplayStyleText.
+    pub display_style: DisplayStyle,
+    /// The file format default is 0x00, fmMousePointerDefault.
+    pub mouse_pointer: MousePointer,
+    /// The width and height, in HIMETRIC units, of the control.
+    pub size: Size,
+    /// A character code that is displayed instead of the characters entered. A value of zero
+    /// specifies that the characters entered are displayed.
+    ///
+    /// The file format default is 0x0000.
+    pub password_char: u16,
+    /// The width, in HIMETRIC units, of the drop-down list of a ComboBox.
+    ///
+    /// The file format default is 0x00000000, the width of the control.
+    pub list_width: u32,
+    /// The file format default is 0x0001.
+    pub bound_column: u16,
+    /// The file format default is 0xFFFF (-1).
+    pub text_column: i16,
+    /// The file format default is 0x0001.
+    pub column_count: i16,
+    /// The file format default is 0x0008.
+    pub list_rows: u16,
+    /// The MorphDataColumnInfo of the columns, which follow the TextProps.
+    ///
+    /// The number of entries is stored as ColumnInfoCount, with a file format default of
+    /// 0x0000.
+    pub column_info: Vec<ColumnInfo>,
+    /// An fmMatchEntry that specifies how the list is searched while the user types.
+    ///
+    /// The file format default is 0x02, fmMatchEntryNone.
+    pub match_entry: u8,
+    /// An fmListStyle that specifies the visual appearance of the list.
+    ///
+    /// The file format default is 0x00, fmListStylePlain.
+    pub list_style: u8,
+    /// An fmShowDropButtonType that specifies when to show the drop-down button.
+    ///
+    /// The file format default is 0x00, fmShowDropButtonWhenNever.
+    pub show_drop_button_when: u8,
+    /// An fmDropButtonStyle that specifies the symbol on the drop-down button.
+    ///
+    /// The file format default is 0x01, fmDropButtonStyleArrow.
+    pub drop_button_style: u8,
+    /// An fmMultiSelect that specifies whether multiple selection is allowed.
+    ///
+    /// The file format default is 0x00, fmMultiSelectSingle.
+    pub multi_select: u8,
+    /// The content of the control.
+    ///
+    /// The file format default is a zero-length string.
+    pub value: String,
+    /// The file format default is a zero-length string.
+    pub caption: String,
+    /// An fmPicturePosition that specifies the location of the picture relative to the caption.
+    ///
+    /// The file format default is 0x00070001, fmPicturePositionAboveCenter.
+    pub picture_position: u32,
+    /// The file format default is 0x80000006, COLOR_WINDOWFRAME from the system palette.
+    pub border_color: OleColor,
+    /// The file format default is 0x00000002, fmSpecialEffectSunken.
+    pub special_effect: SpecialEffect,
+    /// The file format default is no custom icon.
+    pub mouse_icon: GuidAndPicture,
+    /// The file format default is no picture.
+    pub picture: GuidAndPicture,
+    /// A character code that specifies the accelerator key. A value of zero specifies that there is
+    /// no accelerator.
+    ///
+    /// The file format default is 0x0000.
+    pub accelerator: u16,
+    /// The group of mutually exclusive OptionButtons this control belongs to.
+    ///
+    /// The file format default is a zero-length string.
+    pub group_name: String,
+    /// The font of the control.
+    pub text_props: TextProps,
+    /// The bits of the property mask that are not defined in [MS-OFORMS]
+    ///
+    /// These are only kept in [`ParseMode::Lenient`][crate::common::ParseMode::Lenient], for
+    /// properties without data, and are written back unchanged. Zero by default.
+    pub unknown_mask_bits: u64,
+}
+
+impl Default for MorphDataControl {
+    /// The file format defaults, with a size of zero
+    fn default() -> Self {
+        Self {
+            various_property_bits: VariousPropertyBits::from_bits_retain(0x2C80081B),
+            back_color: OleColor::WINDOW,
+            fore_color: OleColor::WINDOWTEXT,
+            max_length: 0,
+            border_style: BorderStyle::None,
+            scroll_bars: 0x00,
+            display_style: DisplayStyle::Text,
+            mouse_pointer: MousePointer::Default,
+            size: Size::new(0, 0),
+            password_char: 0x0000,
+            list_width: 0,
+            bound_column: 1,
+            text_column: -1,
+            column_count: 1,
+            list_rows: 8,
+            column_info: Vec::new(),
+            match_entry: 0x02,
+            list_style: 0x00,
+            show_drop_button_when: 0x00,
+            drop_button_style: 0x01,
+            multi_select: 0x00,
+            value: String::new(),
+            caption: String::new(),
+            picture_position: 0x00070001,
+            border_color: OleColor::WINDOWFRAME,
+            special_effect: SpecialEffect::Sunken,
+            mouse_icon: GuidAndPicture::EMPTY,
+            picture: GuidAndPicture::EMPTY,
+            accelerator: 0,
+            group_name: String::new(),
+            text_props: TextProps::default(),
+            unknown_mask_bits: 0,
+        }
+    }
+}
+
+bitflags! {
+    pub(crate) struct MorphDataPropMask: u64 {
+        /// A - fVariousPropertyBits (1 bit): Specifies whether the VariousPropertyBits property is stored in the DataBlock.
+        const VARIOUS_PROPERTY_BITS = 1 << 0;
+        /// B - fBackColor (1 bit): Specifies whether the BackColor property is stored in the DataBlock.
+        const BACK_COLOR = 1 << 1;
+        /// C - fForeColor (1 bit): Specifies whether the ForeColor property is stored in the DataBlock.
+        const FORE_COLOR = 1 << 2;
+        /// D - fMaxLength (1 bit): Specifies whether the MaxLength property is stored in the DataBlock.
+        const MAX_LENGTH = 1 << 3;
+        /// E - fBorderStyle (1 bit): Specifies whether the BorderStyle property is stored in the DataBlock.
+        const BORDER_STYLE = 1 << 4;
+        /// F - fScrollBars (1 bit): Specifies whether the ScrollBars property is stored in the DataBlock.
+        const SCROLL_BARS = 1 << 5;
+        /// G - fDisplayStyle (1 bit): Specifies whether the DisplayStyle property is stored in the DataBlock.
+        const DISPLAY_STYLE = 1 << 6;
+        /// H - fMousePointer (1 bit): Specifies whether the MousePointer property is stored in the DataBlock.
+        const MOUSE_POINTER = 1 << 7;
+        /// I - fSize (1 bit): Specifies whether the Size property is stored in the ExtraDataBlock.
+        const SIZE = 1 << 8;
+        /// J - fPasswordChar (1 bit): Specifies whether the PasswordChar property is stored in the DataBlock.
+        const PASSWORD_CHAR = 1 << 9;
+        /// K - fListWidth (1 bit): Specifies whether the ListWidth property is stored in the DataBlock.
+        const LIST_WIDTH = 1 << 10;
+        /// L - fBoundColumn (1 bit): Specifies whether the BoundColumn property is stored in the DataBlock.
+        const BOUND_COLUMN = 1 << 11;
+        /// M - fTextColumn (1 bit): Specifies whether the TextColumn property is stored in the DataBlock.
+        const TEXT_COLUMN = 1 << 12;
+        /// N - fColumnCount (1 bit): Specifies whether the ColumnCount property is stored in the DataBlock.
+        const COLUMN_COUNT = 1 << 13;
+        /// O - fListRows (1 bit): Specifies whether the ListRows property is stored in the DataBlock.
+        const LIST_ROWS = 1 << 14;
+        /// P - fcColumnInfo (1 bit): Specifies whether the count of column widths is stored in the DataBlock.
+        const COLUMN_INFO = 1 << 15;
+        /// Q - fMatchEntry (1 bit): Specifies whether the MatchEntry property is stored in the DataBlock.
+        const MATCH_ENTRY = 1 << 16;
+        /// R - fListStyle (1 bit): Specifies whether the ListStyle property is stored in the DataBlock.
+        const LIST_STYLE = 1 << 17;
+        /// S - fShowDropButtonWhen (1 bit): Specifies whether the ShowDropButtonWhen property is stored in the DataBlock.
+        const SHOW_DROP_BUTTON_WHEN = 1 << 18;
+        /// T - fDropButtonStyle (1 bit): Specifies whether the DropButtonStyle property is stored in the DataBlock.
+        const DROP_BUTTON_STYLE = 1 << 20;
+        /// U - fMultiSelect (1 bit): Specifies whether the MultiSelect property is stored in the DataBlock.
+        const MULTI_SELECT = 1 << 21;
+        /// V - fValue (1 bit): Specifies whether the Value property is stored in the DataBlock and ExtraDataBlock.
+        const VALUE = 1 << 22;
+        /// W - fCaption (1 bit): Specifies whether the Caption property is stored in the DataBlock and ExtraDataBlock.
+        const CAPTION = 1 << 23;
+        /// X - fPicturePosition (1 bit): Specifies whether the PicturePosition property is stored in the DataBlock.
+        const PICTURE_POSITION = 1 << 24;
+        /// Y - fBorderColor (1 bit): Specifies whether the BorderColor property is stored in the DataBlock.
+        const BORDER_COLOR = 1 << 25;
+        /// Z - fSpecialEffect (1 bit): Specifies whether the SpecialEffect property is stored in the DataBlock.
+        const SPECIAL_EFFECT = 1 << 26;
+        /// A1 - fMouseIcon (1 bit): Specifies whether the MouseIcon property is stored in the StreamData.
+        const MOUSE_ICON = 1 << 27;
+        /// B1 - fPicture (1 bit): Specifies whether the Picture property is stored in the StreamData.
+        const PICTURE = 1 << 28;
+        /// C1 - fAccelerator (1 bit): Specifies whether the Accelerator property is stored in the DataBlock.
+        const ACCELERATOR = 1 << 29;
+        /// Reserved (1 bit): MUST be set to 1.
+        const RESERVED = 1 << 31;
+        /// D1 - fGroupName (1 bit): Specifies whether the GroupName property is stored in the DataBlock and ExtraDataBlock.
+        const GROUP_NAME = 1 << 32;
+    }
+}
+
+bitflags! {
+    struct ColumnInfoPropMask: u32 {
+        /// A - fColumnWidth (1 bit): Specifies whether the ColumnWidth property is stored in the DataBlock.
+        const COLUMN_WIDTH = 1 << 0;
+    }
+}
+
+fn parse_morph_data_header<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], u16, E>
+where
+    E: ParseError<&'a [u8]>,
+{
+    preceded(tag([0x00, 0x02]), le_u16)(input)
+}
+
+pub fn parse_morph_data<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], MorphDataControl, E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    parse_morph_data_with(input, &ParseContext::default())
+}
+
+/// Parse a [`MorphDataControl`] with the [`ParseMode`][crate::common::ParseMode] of `ctx`
+pub fn parse_morph_data_with<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], MorphDataControl, E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    let (input, (mask, column_info_count, mut control)) = length_value(
+        |i| ctx.field("Header", i, parse_morph_data_header),
+        |i| _parse_morph_data(i, ctx),
+    )(input)?;
+
+    // Stream Data
+    let (input, mouse_icon) = match mask.contains(MorphDataPropMask::MOUSE_ICON) {
+        true => ctx.field("MouseIcon", input, parse_guid_and_picture)?,
+        false => (input, GuidAndPicture::EMPTY),
+    };
+    let (input, picture) = match mask.contains(MorphDataPropMask::PICTURE) {
+        true => ctx.field("Picture", input, parse_guid_and_picture)?,
+        false => (input, GuidAndPicture::EMPTY),
+    };
+
+    // Text Props
+    let (input, text_props) = ctx.field("TextProps", input, |input| {
+        parse_text_props_with(input, ctx)
+    })?;
+
+    // Column Info
+    let (input, column_info) = ctx.field("ColumnInfo", input, |input| {
+        count(
+            |i| parse_column_info(i, ctx),
+            usize::from(column_info_count),
+        )(input)
+    })?;
+
+    control.mouse_icon = mouse_icon;
+    control.picture = picture;
+    control.text_props = text_props;
+    control.column_info = column_info;
+    Ok((input, control))
+}
+
+fn parse_column_info<'a, E>(input: &'a [u8], ctx: &ParseContext) -> IResult<&'a [u8], ColumnInfo, E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    length_value(
+        |i| ctx.field("Header", i, parse_morph_data_header),
+        |input| {
+            let ap = AlignedParser::new();
+            let (input, mask) = ctx.aligned("PropMask", &ap, input, |input| {
+                ap.bitfield32(input, ctx.flags::<ColumnInfoPropMask>())
+            })?;
+            let (input, column_width) = match mask.contains(ColumnInfoPropMask::COLUMN_WIDTH) {
+                true => ctx.aligned("ColumnWidth", &ap, input, |input| ap.le_i32(input))?,
+                false => (input, -1),
+            };
+            Ok((input, ColumnInfo { column_width }))
+        },
+    )(input)
+}
+
+fn _parse_morph_data<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], (MorphDataPropMask, u16, MorphDataControl), E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    let ap = AlignedParser::new();
+    let (input, mask) = ctx.aligned(
+        "PropMask",
+        &ap,
+        input,
+        map_opt(
+            |i| {
+                let (i, lo) = ap.le_u32(i)?;
+                let (i, hi) = ap.le_u32(i)?;
+                Ok((i, u64::from(hi) << 32 | u64::from(lo)))
+            },
+            ctx.flags::<MorphDataPropMask>(),
+        ),
+    )?;
+
+    let (input, various_property_bits) =
+        match mask.contains(MorphDataPropMask::VARIOUS_PROPERTY_BITS) {
+            true => ctx.aligned("VariousPropertyBits", &ap, input, |input| {
+                ap.bitfield32(input, VariousPropertyBits::from_u32)
+            })?,
+            false => (input, VariousPropertyBits::from_bits_retain(0x2C80081B)),
+        };
+    let (input, back_color) = match mask.contains(MorphDataPropMask::BACK_COLOR) {
+        true => ctx.aligned("BackColor", &ap, input, |input| ap.ole_color(input))?,
+        false => (input, OleColor::WINDOW),
+    };
+    let (input, fore_color) = match mask.contains(MorphDataPropMask::FORE_COLOR) {
+        true => ctx.aligned("ForeColor", &ap, input, |input| ap.ole_color(input))?,
+        false => (input, OleColor::WINDOWTEXT),
+    };
+    let (input, max_length) = match mask.contains(MorphDataPropMask::MAX_LENGTH) {
+        true => ctx.aligned("MaxLength", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 0),
+    };
+    let (input, border_style) = match mask.contains(MorphDataPropMask::BORDER_STYLE) {
+        true => ctx.aligned("BorderStyle", &ap, input, |input| {
+            ap.bitfield8(input, BorderStyle::from_u8)
+        })?,
+        false => (input, BorderStyle::None),
+    };
+    let (input, scroll_bars) = match mask.contains(MorphDataPropMask::SCROLL_BARS) {
+        true => ctx.aligned("ScrollBars", &ap, input, |input| ap.le_u8(input))?,
+        false => (input, 0x00),
+    };
+    let (input, display_style) = match mask.contains(MorphDataPropMask::DISPLAY_STYLE) {
+        true => ctx.aligned("DisplayStyle", &ap, input, |input| {
+            ap.bitfield8(input, DisplayStyle::from_u8)
+        })?,
+        false => (input, DisplayStyle::Text),
+    };
+    let (input, mouse_pointer) = match mask.contains(MorphDataPropMask::MOUSE_POINTER) {
+        true => ctx.aligned("MousePointer", &ap, input, |input| {
+            ap.bitfield8(input, MousePointer::from_u8)
+        })?,
+        false => (input, MousePointer::Default),
+    };
+    let (input, password_char) = match mask.contains(MorphDataPropMask::PASSWORD_CHAR) {
+        true => ctx.aligned("PasswordChar", &ap, input, |input| ap.le_u16(input))?,
+        false => (input, 0x0000),
+    };
+    let (input, list_width) = match mask.contains(MorphDataPropMask::LIST_WIDTH) {
+        true => ctx.aligned("ListWidth", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 0),
+    };
+    let (input, bound_column) = match mask.contains(MorphDataPropMask::BOUND_COLUMN) {
+        true => ctx.aligned("BoundColumn", &ap, input, |input| ap.le_u16(input))?,
+        false => (input, 1),
+    };
+    let (input, text_column) = match mask.contains(MorphDataPropMask::TEXT_COLUMN) {
+        true => ctx.aligned("TextColumn", &ap, input, |input| ap.le_i16(input))?,
+        false => (input, -1),
+    };
+    let (input, column_count) = match mask.contains(MorphDataPropMask::COLUMN_COUNT) {
+        true => ctx.aligned("ColumnCount", &ap, input, |input| ap.le_i16(input))?,
+        false => (input, 1),
+    };
+    let (input, list_rows) = match mask.contains(MorphDataPropMask::LIST_ROWS) {
+        true => ctx.aligned("ListRows", &ap, input, |input| ap.le_u16(input))?,
+        false => (input, 8),
+    };
+    let (input, column_info_count) = match mask.contains(MorphDataPropMask::COLUMN_INFO) {
+        true => ctx.aligned("ColumnInfoCount", &ap, input, |input| ap.le_u16(input))?,
+        false => (input, 0),
+    };
+    let (input, match_entry) = match mask.contains(MorphDataPropMask::MATCH_ENTRY) {
+        true => ctx.aligned("MatchEntry", &ap, input, |input| ap.le_u8(input))?,
+        false => (input, 0x02),
+    };
+    let (input, list_style) = match mask.contains(MorphDataPropMask::LIST_STYLE) {
+        true => ctx.aligned("ListStyle", &ap, input, |input| ap.le_u8(input))?,
+        false => (input, 0x00),
+    };
+    let (input, show_drop_button_when) =
+        match mask.contains(MorphDataPropMask::SHOW_DROP_BUTTON_WHEN) {
+            true => ctx.aligned("ShowDropButtonWhen", &ap, input, |input| ap.le_u8(input))?,
+            false => (input, 0x00),
+        };
+    let (input, drop_button_style) = match mask.contains(MorphDataPropMask::DROP_BUTTON_STYLE) {
+        true => ctx.aligned("DropButtonStyle", &ap, input, |input| ap.le_u8(input))?,
+        false => (input, 0x01),
+    };
+    let (input, multi_select) = match mask.contains(MorphDataPropMask::MULTI_SELECT) {
+        true => ctx.aligned("MultiSelect", &ap, input, |input| ap.le_u8(input))?,
+        false => (input, 0x00),
+    };
+    let (input, value_data) = match mask.contains(MorphDataPropMask::VALUE) {
+        true => ctx.aligned("ValueData", &ap, input, |input| {
+            ap.length_and_compression(input)
+        })?,
+        false => (input, CountOfBytesWithCompressionFlag::EMPTY),
+    };
+    let (input, caption_data) = match mask.contains(MorphDataPropMask::CAPTION) {
+        true => ctx.aligned("CaptionData", &ap, input, |input| {
+            ap.length_and_compression(input)
+        })?,
+        false => (input, CountOfBytesWithCompressionFlag::EMPTY),
+    };
+    let (input, picture_position) = match mask.contains(MorphDataPropMask::PICTURE_POSITION) {
+        true => ctx.aligned("PicturePosition", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 0x00070001),
+    };
+    let (input, border_color) = match mask.contains(MorphDataPropMask::BORDER_COLOR) {
+        true => ctx.aligned("BorderColor", &ap, input, |input| ap.ole_color(input))?,
+        false => (input, OleColor::WINDOWFRAME),
+    };
+    let (input, special_effect) = match mask.contains(MorphDataPropMask::SPECIAL_EFFECT) {
+        true => ctx.aligned("SpecialEffect", &ap, input, |input| {
+            ap.bitfield32(input, SpecialEffect::from_u32)
+        })?,
+        false => (input, SpecialEffect::Sunken),
+    };
+    let (input, _mouse_icon) = match mask.contains(MorphDataPropMask::MOUSE_ICON) {
+        true => ctx.aligned("MouseIcon", &ap, input, |input| {
+            verify(|i| ap.le_u16(i), |x| *x == 0xFFFF)(input)
+        })?,
+        false => (input, 0),
+    };
+    let (input, _picture) = match mask.contains(MorphDataPropMask::PICTURE) {
+        true => ctx.aligned("Picture", &ap, input, |input| {
+            verify(|i| ap.le_u16(i), |x| *x == 0xFFFF)(input)
+        })?,
+        false => (input, 0),
+    };
+    let (input, accelerator) = match mask.contains(MorphDataPropMask::ACCELERATOR) {
+        true => ctx.aligned("Accelerator", &ap, input, |input| ap.le_u16(input))?,
+        false => (input, 0),
+    };
+    let (input, group_name_data) = match mask.contains(MorphDataPropMask::GROUP_NAME) {
+        true => ctx.aligned("GroupNameData", &ap, input, |input| {
+            ap.length_and_compression(input)
+        })?,
+        false => (input, CountOfBytesWithCompressionFlag::EMPTY),
+    };
+
+    // Extra Data Block
+    let (input, size) = match mask.contains(MorphDataPropMask::SIZE) {
+        true => ctx.aligned("Size", &ap, input, |input| ap.fm_size(input))?,
+        false => (input, Size::new(0, 0)),
+    };
+    let (input, value) = match mask.contains(MorphDataPropMask::VALUE) {
+        true => ctx.aligned("Value", &ap, input, |input| {
+            ap.fm_string(input, value_data, ctx.encoding())
+        })?,
+        false => (input, String::new()),
+    };
+    let (input, caption) = match mask.contains(MorphDataPropMask::CAPTION) {
+        true => ctx.aligned("Caption", &ap, input, |input| {
+            ap.fm_string(input, caption_data, ctx.encoding())
+        })?,
+        false => (input, String::new()),
+    };
+    let (input, group_name) = match mask.contains(MorphDataPropMask::GROUP_NAME) {
+        true => ctx.aligned("GroupName", &ap, input, |input| {
+            ap.fm_string(input, group_name_data, ctx.encoding())
+        })?,
+        false => (input, String::new()),
+    };
+
+    ctx.check_mask_layout(&mask, ap.offset(), input.len(), input)?;
+    let unknown_mask_bits = mask.bits() & !MorphDataPropMask::all().bits();
+    Ok((
+        input,
+        (
+            mask,
+            column_info_count,
+            MorphDataControl {
+                various_property_bits,
+                back_color,
+                fore_color,
+                max_length,
+                border_style,
+                scroll_bars,
+                display_style,
+                mouse_pointer,
+                size,
+                password_char,
+                list_width,
+                bound_column,
+                text_column,
+                column_count,
+                list_rows,
+                column_info: Vec::new(),
+                match_entry,
+                list_style,
+                show_drop_button_when,
+                drop_button_style,
+                multi_select,
+                value,
+                caption,
+                picture_position,
+                border_color,
+                special_effect,
+                mouse_icon: GuidAndPicture::EMPTY,
+                picture: GuidAndPicture::EMPTY,
+                accelerator,
+                group_name,
+                text_props: TextProps::default(),
+                unknown_mask_bits,
+            },
+        ),
+    ))
+}
+
+/// Write a [`MorphDataControl`], storing only the properties that differ from the file format
+/// default
+pub fn write_morph_data(out: &mut Vec<u8>, control: &MorphDataControl) -> io::Result<()> {
+    let mut w = AlignedWriter::new();
+    let mut mask = MorphDataPropMask::SIZE | MorphDataPropMask::RESERVED;
+    w.le_u32(0);
+    w.le_u32(0);
+
+    if control.various_property_bits.bits() != 0x2C80081B {
+        mask |= MorphDataPropMask::VARIOUS_PROPERTY_BITS;
+        w.le_u32(control.various_property_bits.bits());
+    }
+    if control.back_color != OleColor::WINDOW {
+        mask |= MorphDataPropMask::BACK_COLOR;
+        w.ole_color(control.back_color);
+    }
+    if control.fore_color != OleColor::WINDOWTEXT {
+        mask |= MorphDataPropMask::FORE_COLOR;
+        w.ole_color(control.fore_color);
+    }
+    if control.max_length != 0 {
+        mask |= MorphDataPropMask::MAX_LENGTH;
+        w.le_u32(control.max_length);
+    }
+    if control.border_style != BorderStyle::None {
+        mask |= MorphDataPropMask::BORDER_STYLE;
+        w.le_u8(control.border_style as u8);
+    }
+    if control.scroll_bars != 0x00 {
+        mask |= MorphDataPropMask::SCROLL_BARS;
+        w.le_u8(control.scroll_bars);
+    }
+    if control.display_style != DisplayStyle::Text {
+        mask |= MorphDataPropMask::DISPLAY_STYLE;
+        w.le_u8(control.display_style as u8);
+    }
+    if control.mouse_pointer != MousePointer::Default {
+        mask |= MorphDataPropMask::MOUSE_POINTER;
+        w.le_u8(control.mouse_pointer as u8);
+    }
+    if control.password_char != 0x0000 {
+        mask |= MorphDataPropMask::PASSWORD_CHAR;
+        w.le_u16(control.password_char);
+    }
+    if control.list_width != 0 {
+        mask |= MorphDataPropMask::LIST_WIDTH;
+        w.le_u32(control.list_width);
+    }
+    if control.bound_column != 1 {
+        mask |= MorphDataPropMask::BOUND_COLUMN;
+        w.le_u16(control.bound_column);
+    }
+    if control.text_column != -1 {
+        mask |= MorphDataPropMask::TEXT_COLUMN;
+        w.le_i16(control.text_column);
+    }
+    if control.column_count != 1 {
+        mask |= MorphDataPropMask::COLUMN_COUNT;
+        w.le_i16(control.column_count);
+    }
+    if control.list_rows != 8 {
+        mask |= MorphDataPropMask::LIST_ROWS;
+        w.le_u16(control.list_rows);
+    }
+    if !control.column_info.is_empty() {
+        let column_info_count = u16::try_from(control.column_info.len())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        mask |= MorphDataPropMask::COLUMN_INFO;
+        w.le_u16(column_info_count);
+    }
+    if control.match_entry != 0x02 {
+        mask |= MorphDataPropMask::MATCH_ENTRY;
+        w.le_u8(control.match_entry);
+    }
+    if control.list_style != 0x00 {
+        mask |= MorphDataPropMask::LIST_STYLE;
+        w.le_u8(control.list_style);
+    }
+    if control.show_drop_button_when != 0x00 {
+        mask |= MorphDataPropMask::SHOW_DROP_BUTTON_WHEN;
+        w.le_u8(control.show_drop_button_when);
+    }
+    if control.drop_button_style != 0x01 {
+        mask |= MorphDataPropMask::DROP_BUTTON_STYLE;
+        w.le_u8(control.drop_button_style);
+    }
+    if control.multi_select != 0x00 {
+        mask |= MorphDataPropMask::MULTI_SELECT;
+        w.le_u8(control.multi_select);
+    }
+    if !control.value.is_empty() {
+        mask |= MorphDataPropMask::VALUE;
+        w.length_and_compression(&control.value)?;
+    }
+    if !control.caption.is_empty() {
+        mask |= MorphDataPropMask::CAPTION;
+        w.length_and_compression(&control.caption)?;
+    }
+    if control.picture_position != 0x00070001 {
+        mask |= MorphDataPropMask::PICTURE_POSITION;
+        w.le_u32(control.picture_position);
+    }
+    if control.border_color != OleColor::WINDOWFRAME {
+        mask |= MorphDataPropMask::BORDER_COLOR;
+        w.ole_color(control.border_color);
+    }
+    if control.special_effect != SpecialEffect::Sunken {
+        mask |= MorphDataPropMask::SPECIAL_EFFECT;
+        w.le_u32(control.special_effect as u32);
+    }
+    if control.mouse_icon != GuidAndPicture::EMPTY {
+        mask |= MorphDataPropMask::MOUSE_ICON;
+        w.le_u16(0xFFFF);
+    }
+    if control.picture != GuidAndPicture::EMPTY {
+        mask |= MorphDataPropMask::PICTURE;
+        w.le_u16(0xFFFF);
+    }
+    if control.accelerator != 0 {
+        mask |= MorphDataPropMask::ACCELERATOR;
+        w.le_u16(control.accelerator);
+    }
+    if !control.group_name.is_empty() {
+        mask |= MorphDataPropMask::GROUP_NAME;
+        w.length_and_compression(&control.group_name)?;
+    }
+
+    // Extra Data Block
+    w.fm_size(control.size);
+    if mask.contains(MorphDataPropMask::VALUE) {
+        w.fm_string(&control.value)?;
+    }
+    if mask.contains(MorphDataPropMask::CAPTION) {
+        w.fm_string(&control.caption)?;
+    }
+    if mask.contains(MorphDataPropMask::GROUP_NAME) {
+        w.fm_string(&control.group_name)?;
+    }
+
+    let bits = mask.bits() | control.unknown_mask_bits & !MorphDataPropMask::all().bits();
+    w.patch_u32(0, bits as u32);
+    w.patch_u32(4, (bits >> 32) as u32);
+    write_with_header(out, [0x00, 0x02], &w.finish())?;
+
+    // Stream Data
+    write_guid_and_picture(out, &control.mouse_icon)?;
+    write_guid_and_picture(out, &control.picture)?;
+
+    // Text Props
+    write_text_props(out, &control.text_props)?;
+
+    // Column Info
+    for info in &control.column_info {
+        write_column_info(out, info)?;
+    }
+    Ok(())
+}
+
+fn write_column_info(out: &mut Vec<u8>, info: &ColumnInfo) -> io::Result<()> {
+    let mut w = AlignedWriter::new();
+    let mut mask = ColumnInfoPropMask::empty();
+    w.le_u32(0);
+    if info.column_width != -1 {
+        mask |= ColumnInfoPropMask::COLUMN_WIDTH;
+        w.le_i32(info.column_width);
+    }
+    w.patch_u32(0, mask.bits());
+    write_with_header(out, [0x00, 0x02], &w.finish())
+}
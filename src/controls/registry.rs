@@ -0,0 +1,289 @@
+//! Registry of well-known ActiveX control classes
+//!
+//! Controls that are not in the global cache of [`FormEmbeddedActiveXControlCached`] are
+//! referenced through a [`SiteClassInfo`][crate::controls::user_form::class_table::SiteClassInfo],
+//! which only carries the CLSID of the control. This module maps such CLSIDs back to a friendly
+//! name and, for the Forms 2.0 controls, to the cached control type that selects the parser.
+
+use num_traits::FromPrimitive;
+use uuid::{uuid, Uuid};
+
+use crate::{
+    controls::user_form::{
+        class_table::SiteClassInfo,
+        ole_site_concrete::{Clsid, OleSiteConcreteControl},
+    },
+    properties::{FormEmbeddedActiveXControl, FormEmbeddedActiveXControlCached},
+};
+
+/// `{C62A69F0-16DC-11CE-9E98-00AA00574A4F}` Forms.Form.1
+pub const CLSID_FORM: Uuid = uuid!("C62A69F0-16DC-11CE-9E98-00AA00574A4F");
+/// `{4C599241-6926-101B-9992-00000B65C6F9}` Forms.Image.1
+pub const CLSID_IMAGE: Uuid = uuid!("4C599241-6926-101B-9992-00000B65C6F9");
+/// `{6E182020-F460-11CE-9BCD-00AA00608E01}` Forms.Frame.1
+pub const CLSID_FRAME: Uuid = uuid!("6E182020-F460-11CE-9BCD-00AA00608E01");
+/// `{79176FB0-B7F2-11CE-97EF-00AA006D2776}` Forms.SpinButton.1
+pub const CLSID_SPIN_BUTTON: Uuid = uuid!("79176FB0-B7F2-11CE-97EF-00AA006D2776");
+/// `{D7053240-CE69-11CD-A777-00DD01143C57}` Forms.CommandButton.1
+pub const CLSID_COMMAND_BUTTON: Uuid = uuid!("D7053240-CE69-11CD-A777-00DD01143C57");
+/// `{EAE50EB0-4A62-11CE-BED6-00AA00611080}` Forms.TabStrip.1
+pub const CLSID_TAB_STRIP: Uuid = uuid!("EAE50EB0-4A62-11CE-BED6-00AA00611080");
+/// `{978C9E23-D4B0-11CE-BF2D-00AA003F40D0}` Forms.Label.1
+pub const CLSID_LABEL: Uuid = uuid!("978C9E23-D4B0-11CE-BF2D-00AA003F40D0");
+/// `{8BD21D10-EC42-11CE-9E0D-00AA006002F3}` Forms.TextBox.1
+pub const CLSID_TEXT_BOX: Uuid = uuid!("8BD21D10-EC42-11CE-9E0D-00AA006002F3");
+/// `{8BD21D20-EC42-11CE-9E0D-00AA006002F3}` Forms.ListBox.1
+pub const CLSID_LIST_BOX: Uuid = uuid!("8BD21D20-EC42-11CE-9E0D-00AA006002F3");
+/// `{8BD21D30-EC42-11CE-9E0D-00AA006002F3}` Forms.ComboBox.1
+pub const CLSID_COMBO_BOX: Uuid = uuid!("8BD21D30-EC42-11CE-9E0D-00AA006002F3");
+/// `{8BD21D40-EC42-11CE-9E0D-00AA006002F3}` Forms.CheckBox.1
+pub const CLSID_CHECK_BOX: Uuid = uuid!("8BD21D40-EC42-11CE-9E0D-00AA006002F3");
+/// `{8BD21D50-EC42-11CE-9E0D-00AA006002F3}` Forms.OptionButton.1
+pub const CLSID_OPTION_BUTTON: Uuid = uuid!("8BD21D50-EC42-11CE-9E0D-00AA006002F3");
+/// `{8BD21D60-EC42-11CE-9E0D-00AA006002F3}` Forms.ToggleButton.1
+pub const CLSID_TOGGLE_BUTTON: Uuid = uuid!("8BD21D60-EC42-11CE-9E0D-00AA006002F3");
+/// `{DFD181E0-5E2F-11CE-A449-00AA004A803D}` Forms.ScrollBar.1
+pub const CLSID_SCROLL_BAR: Uuid = uuid!("DFD181E0-5E2F-11CE-A449-00AA004A803D");
+/// `{46E31370-3F7A-11CE-BED6-00AA00611080}` Forms.MultiPage.1
+pub const CLSID_MULTI_PAGE: Uuid = uuid!("46E31370-3F7A-11CE-BED6-00AA00611080");
+/// `{5CEF5610-713D-11CE-80C9-00AA00611080}` Forms.Page.1
+///
+/// The pages of a MultiPage. They have no entry in the global class cache, so they are
+/// always referenced through the class table.
+pub const CLSID_PAGE: Uuid = uuid!("5CEF5610-713D-11CE-80C9-00AA00611080");
+
+/// A well-known ActiveX control class
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KnownClass {
+    /// The CLSID of the control
+    pub clsid: Uuid,
+    /// The ProgID or display name of the control
+    pub name: &'static str,
+    /// The Forms 2.0 control type with this CLSID, if any
+    pub cached: Option<FormEmbeddedActiveXControlCached>,
+}
+
+impl KnownClass {
+    const fn forms(clsid: Uuid, name: &'static str, c: FormEmbeddedActiveXControlCached) -> Self {
+        Self {
+            clsid,
+            name,
+            cached: Some(c),
+        }
+    }
+
+    const fn other(clsid: Uuid, name: &'static str) -> Self {
+        Self {
+            clsid,
+            name,
+            cached: None,
+        }
+    }
+}
+
+/// All known control classes
+pub const KNOWN_CLASSES: &[KnownClass] = {
+    use FormEmbeddedActiveXControlCached as C;
+    &[
+        // Microsoft Forms 2.0
+        KnownClass::forms(CLSID_FORM, "Forms.Form.1", C::Form),
+        KnownClass::forms(CLSID_IMAGE, "Forms.Image.1", C::Image),
+        KnownClass::forms(CLSID_FRAME, "Forms.Frame.1", C::Frame),
+        KnownClass::forms(CLSID_SPIN_BUTTON, "Forms.SpinButton.1", C::SpinButton),
+        KnownClass::forms(
+            CLSID_COMMAND_BUTTON,
+            "Forms.CommandButton.1",
+            C::CommandButton,
+        ),
+        KnownClass::forms(CLSID_TAB_STRIP, "Forms.TabStrip.1", C::TabStrip),
+        KnownClass::forms(CLSID_LABEL, "Forms.Label.1", C::Label),
+        KnownClass::forms(CLSID_TEXT_BOX, "Forms.TextBox.1", C::TextBox),
+        KnownClass::forms(CLSID_LIST_BOX, "Forms.ListBox.1", C::ListBox),
+        KnownClass::forms(CLSID_COMBO_BOX, "Forms.ComboBox.1", C::ComboBox),
+        KnownClass::forms(CLSID_CHECK_BOX, "Forms.CheckBox.1", C::CheckBox),
+        KnownClass::forms(CLSID_OPTION_BUTTON, "Forms.OptionButton.1", C::OptionButton),
+        KnownClass::forms(CLSID_TOGGLE_BUTTON, "Forms.ToggleButton.1", C::ToggleButton),
+        KnownClass::forms(CLSID_SCROLL_BAR, "Forms.ScrollBar.1", C::ScrollBar),
+        KnownClass::forms(CLSID_MULTI_PAGE, "Forms.MultiPage.1", C::MultiPage),
+        KnownClass::other(CLSID_PAGE, "Forms.Page.1"),
+        // Microsoft Windows Common Controls 6.0 (MSCOMCTL.OCX)
+        KnownClass::other(
+            uuid!("C74190B6-8589-11D1-B16A-00C0F0283628"),
+            "MSComctlLib.TreeCtrl.2",
+        ),
+        KnownClass::other(
+            uuid!("BDD1F04B-858B-11D1-B16A-00C0F0283628"),
+            "MSComctlLib.ListViewCtrl.2",
+        ),
+        KnownClass::other(
+            uuid!("35053A22-8589-11D1-B16A-00C0F0283628"),
+            "MSComctlLib.ProgCtrl.2",
+        ),
+        KnownClass::other(
+            uuid!("F08DF954-8592-11D1-B16A-00C0F0283628"),
+            "MSComctlLib.Slider.2",
+        ),
+        KnownClass::other(
+            uuid!("8E3867A3-8586-11D1-B16A-00C0F0283628"),
+            "MSComctlLib.SBarCtrl.2",
+        ),
+        KnownClass::other(
+            uuid!("66833FE6-8583-11D1-B16A-00C0F0283628"),
+            "MSComctlLib.Toolbar.2",
+        ),
+        KnownClass::other(
+            uuid!("1EFB6596-857C-11D1-B16A-00C0F0283628"),
+            "MSComctlLib.TabStrip.2",
+        ),
+        KnownClass::other(
+            uuid!("2C247F23-8591-11D1-B16A-00C0F0283628"),
+            "MSComctlLib.ImageListCtrl.2",
+        ),
+        KnownClass::other(
+            uuid!("DD9DA666-8594-11D1-B16A-00C0F0283628"),
+            "MSComctlLib.ImageComboCtl.2",
+        ),
+        // Microsoft Windows Common Controls 5.0 (COMCTL32.OCX)
+        KnownClass::other(
+            uuid!("0713E8A2-850A-101B-AFC0-4210102A8DA7"),
+            "COMCTL.TreeCtrl.1",
+        ),
+        KnownClass::other(
+            uuid!("58DA8D8A-9D6A-101B-AFC0-4210102A8DA7"),
+            "COMCTL.ListViewCtrl.1",
+        ),
+        KnownClass::other(
+            uuid!("0713E8D2-850A-101B-AFC0-4210102A8DA7"),
+            "COMCTL.ProgCtrl.1",
+        ),
+        // Microsoft Windows Common Controls-2 6.0 (MSCOMCT2.OCX)
+        KnownClass::other(
+            uuid!("20DD1B9E-87C4-11D1-8BE3-0000F8754DA1"),
+            "MSComCtl2.DTPicker.2",
+        ),
+        KnownClass::other(
+            uuid!("232E456A-87C3-11D1-8BE3-0000F8754DA1"),
+            "MSComCtl2.MonthView.2",
+        ),
+        KnownClass::other(
+            uuid!("603C7E80-87C2-11D1-8BE3-0000F8754DA1"),
+            "MSComCtl2.UpDown.2",
+        ),
+        // Microsoft Calendar Control (MSCAL.OCX)
+        KnownClass::other(
+            uuid!("8E27C92B-1264-101C-8A2F-040224009C02"),
+            "MSCAL.Calendar.7",
+        ),
+        // Others
+        KnownClass::other(
+            uuid!("8856F961-340A-11D0-A96B-00C04FD705A2"),
+            "Shell.Explorer.2",
+        ),
+        KnownClass::other(
+            uuid!("00024512-0000-0000-C000-000000000046"),
+            "RefEdit.Ctrl",
+        ),
+    ]
+};
+
+/// Look up a control class by its CLSID
+pub fn lookup(clsid: &Uuid) -> Option<&'static KnownClass> {
+    KNOWN_CLASSES.iter().find(|c| &c.clsid == clsid)
+}
+
+impl FormEmbeddedActiveXControlCached {
+    /// Get the CLSID of this control type
+    pub fn clsid(&self) -> Uuid {
+        match self {
+            Self::Form => CLSID_FORM,
+            Self::Image => CLSID_IMAGE,
+            Self::Frame => CLSID_FRAME,
+            Self::MorphData | Self::TextBox => CLSID_TEXT_BOX,
+            Self::SpinButton => CLSID_SPIN_BUTTON,
+            Self::CommandButton => CLSID_COMMAND_BUTTON,
+            Self::TabStrip => CLSID_TAB_STRIP,
+            Self::Label => CLSID_LABEL,
+            Self::ListBox => CLSID_LIST_BOX,
+            Self::ComboBox => CLSID_COMBO_BOX,
+            Self::CheckBox => CLSID_CHECK_BOX,
+            Self::OptionButton => CLSID_OPTION_BUTTON,
+            Self::ToggleButton => CLSID_TOGGLE_BUTTON,
+            Self::ScrollBar => CLSID_SCROLL_BAR,
+            Self::MultiPage => CLSID_MULTI_PAGE,
+        }
+    }
+
+    /// Get the Forms 2.0 control type with the given CLSID
+    pub fn from_clsid(clsid: &Uuid) -> Option<Self> {
+        lookup(clsid).and_then(|c| c.cached)
+    }
+}
+
+impl<'a> FormEmbeddedActiveXControl<'a> {
+    /// Get the CLSID of this control
+    pub fn clsid(&self) -> Uuid {
+        match self {
+            Self::ControlCached(c) => c.clsid(),
+            Self::ControlNonCached(info) => info.cls_id,
+        }
+    }
+
+    /// Get the Forms 2.0 control type, resolving non-cached controls by their CLSID
+    pub fn resolve(&self) -> Option<FormEmbeddedActiveXControlCached> {
+        match self {
+            Self::ControlCached(c) => Some(*c),
+            Self::ControlNonCached(info) => {
+                FormEmbeddedActiveXControlCached::from_clsid(&info.cls_id)
+            }
+        }
+    }
+
+    /// Get the known class of this control, if any
+    pub fn known_class(&self) -> Option<&'static KnownClass> {
+        lookup(&self.clsid())
+    }
+}
+
+impl<S> OleSiteConcreteControl<S> {
+    /// Get the Forms 2.0 control type of this site, with the class table of its form
+    pub(crate) fn control_kind(
+        &self,
+        site_classes: &[SiteClassInfo],
+    ) -> Option<FormEmbeddedActiveXControlCached> {
+        match self.clsid_cache_index {
+            Clsid::Global(index) => FormEmbeddedActiveXControlCached::from_u16(index),
+            Clsid::ClassTable(index) => site_classes
+                .get(usize::from(index))
+                .and_then(|info| FormEmbeddedActiveXControlCached::from_clsid(&info.cls_id)),
+            Clsid::Invalid => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lookup, KNOWN_CLASSES};
+    use crate::properties::FormEmbeddedActiveXControlCached as C;
+
+    #[test]
+    fn test_round_trip() {
+        for c in [
+            C::Form,
+            C::Image,
+            C::Frame,
+            C::CommandButton,
+            C::Label,
+            C::MultiPage,
+        ] {
+            assert_eq!(C::from_clsid(&c.clsid()), Some(c));
+        }
+        assert_eq!(C::from_clsid(&C::MorphData.clsid()), Some(C::TextBox));
+    }
+
+    #[test]
+    fn test_unique() {
+        for (i, c) in KNOWN_CLASSES.iter().enumerate() {
+            assert_eq!(lookup(&c.clsid), Some(&KNOWN_CLASSES[i]));
+        }
+    }
+}
@@ -0,0 +1,374 @@
+//! ## 2.2.7 ScrollBar Control
+
+use std::io;
+
+use nom::{
+    bytes::complete::tag,
+    combinator::verify,
+    error::{FromExternalError, ParseError},
+    multi::length_value,
+    number::complete::le_u16,
+    sequence::preceded,
+    IResult,
+};
+use num_traits::FromPrimitive;
+
+use crate::{
+    common::{write_with_header, AlignedParser, AlignedWriter, ParseContext},
+    properties::{
+        color::OleColor,
+        picture::{parse_guid_and_picture, write_guid_and_picture, GuidAndPicture},
+        MousePointer, Size, VariousPropertyBits,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScrollBarControl {
+    /// The file format default is 0x80000012, COLOR_BTNTEXT from the system palette.
+    pub fore_color: OleColor,
+    /// The file format default is 0x8000000F, COLOR_BTNFACE from the system palette.
+    pub back_color: OleColor,
+    /// The file format default is 0x0000001B.
+    pub various_property_bits: VariousPropertyBits,
+    /// The width and height, in HIMETRIC units, of the control.
+    pub size: Size,
+    /// The file format default is 0x00, fmMousePointerDefault.
+    pub mouse_pointer: MousePointer,
+    /// The file format default is 0x00000000.
+    pub min: i32,
+    /// The file format default is 0x00007FFF (32767).
+    pub max: i32,
+    /// The file format default is 0x00000000.
+    pub position: i32,
+    /// The file format default is 0x00000001.
+    pub prev_enabled: u32,
+    /// The file format default is 0x00000001.
+    pub next_enabled: u32,
+    /// The file format default is 0x00000001.
+    pub small_change: i32,
+    /// The file format default is 0x00000001.
+    pub large_change: i32,
+    /// An fmOrientation that specifies whether the control is displayed horizontally or vertically.
+    ///
+    /// The file format default is 0xFFFFFFFF, fmOrientationAuto.
+    pub orientation: i32,
+    /// Specifies whether the size of the scroll box is proportional to the scrolling region.
+    ///
+    /// The file format default is 0xFFFF (TRUE).
+    pub proportional_thumb: i16,
+    /// The delay, in milliseconds, between events when the user holds down a button.
+    ///
+    /// The file format default is 0x00000032 (50).
+    pub delay: i32,
+    /// The file format default is no custom icon.
+    pub mouse_icon: GuidAndPicture,
+    /// The bits of the property mask that are not defined in [MS-OFORMS]
+    ///
+    /// These are only kept in [`ParseMode::Lenient`][crate::common::ParseMode::Lenient], for
+    /// properties without data, and are written back unchanged. Zero by default.
+    pub unknown_mask_bits: u32,
+}
+
+impl Default for ScrollBarControl {
+    /// The file format defaults, with a size of zero
+    fn default() -> Self {
+        Self {
+            fore_color: OleColor::BTNTEXT,
+            back_color: OleColor::BTNFACE,
+            various_property_bits: VariousPropertyBits::from_bits_retain(0x0000001B),
+            size: Size::new(0, 0),
+            mouse_pointer: MousePointer::Default,
+            min: 0,
+            max: 32767,
+            position: 0,
+            prev_enabled: 1,
+            next_enabled: 1,
+            small_change: 1,
+            large_change: 1,
+            orientation: -1,
+            proportional_thumb: -1,
+            delay: 50,
+            mouse_icon: GuidAndPicture::EMPTY,
+            unknown_mask_bits: 0,
+        }
+    }
+}
+
+bitflags! {
+    pub(crate) struct ScrollBarPropMask: u32 {
+        /// A - fForeColor (1 bit): Specifies whether the ForeColor property is stored in the DataBlock.
+        const FORE_COLOR = 1 << 0;
+        /// B - fBackColor (1 bit): Specifies whether the BackColor property is stored in the DataBlock.
+        const BACK_COLOR = 1 << 1;
+        /// C - fVariousPropertyBits (1 bit): Specifies whether the VariousPropertyBits property is stored in the DataBlock.
+        const VARIOUS_PROPERTY_BITS = 1 << 2;
+        /// D - fSize (1 bit): Specifies whether the Size property is stored in the ExtraDataBlock.
+        const SIZE = 1 << 3;
+        /// E - fMousePointer (1 bit): Specifies whether the MousePointer property is stored in the DataBlock.
+        const MOUSE_POINTER = 1 << 4;
+        /// F - fMin (1 bit): Specifies whether the Min property is stored in the DataBlock.
+        const MIN = 1 << 5;
+        /// G - fMax (1 bit): Specifies whether the Max property is stored in the DataBlock.
+        const MAX = 1 << 6;
+        /// H - fPosition (1 bit): Specifies whether the Position property is stored in the DataBlock.
+        const POSITION = 1 << 7;
+        /// I - UnusedBits1 (1 bit): MUST be set to zero.
+        const UNUSED1 = 1 << 8;
+        /// J - fPrevEnabled (1 bit): Specifies whether the PrevEnabled property is stored in the DataBlock.
+        const PREV_ENABLED = 1 << 9;
+        /// K - fNextEnabled (1 bit): Specifies whether the NextEnabled property is stored in the DataBlock.
+        const NEXT_ENABLED = 1 << 10;
+        /// L - fSmallChange (1 bit): Specifies whether the SmallChange property is stored in the DataBlock.
+        const SMALL_CHANGE = 1 << 11;
+        /// M - fLargeChange (1 bit): Specifies whether the LargeChange property is stored in the DataBlock.
+        const LARGE_CHANGE = 1 << 12;
+        /// N - fOrientation (1 bit): Specifies whether the Orientation property is stored in the DataBlock.
+        const ORIENTATION = 1 << 13;
+        /// O - fProportionalThumb (1 bit): Specifies whether the ProportionalThumb property is stored in the DataBlock.
+        const PROPORTIONAL_THUMB = 1 << 14;
+        /// P - fDelay (1 bit): Specifies whether the Delay property is stored in the DataBlock.
+        const DELAY = 1 << 15;
+        /// Q - fMouseIcon (1 bit): Specifies whether the MouseIcon property is stored in the StreamData.
+        const MOUSE_ICON = 1 << 16;
+    }
+}
+
+fn parse_scroll_bar_header<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], u16, E>
+where
+    E: ParseError<&'a [u8]>,
+{
+    preceded(tag([0x00, 0x02]), le_u16)(input)
+}
+
+pub fn parse_scroll_bar<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], ScrollBarControl, E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    parse_scroll_bar_with(input, &ParseContext::default())
+}
+
+/// Parse a [`ScrollBarControl`] with the [`ParseMode`][crate::common::ParseMode] of `ctx`
+pub fn parse_scroll_bar_with<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], ScrollBarControl, E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    let (input, (mask, mut control)) = length_value(
+        |i| ctx.field("Header", i, parse_scroll_bar_header),
+        |i| _parse_scroll_bar(i, ctx),
+    )(input)?;
+
+    // Stream Data
+    let (input, mouse_icon) = match mask.contains(ScrollBarPropMask::MOUSE_ICON) {
+        true => ctx.field("MouseIcon", input, parse_guid_and_picture)?,
+        false => (input, GuidAndPicture::EMPTY),
+    };
+
+    control.mouse_icon = mouse_icon;
+    Ok((input, control))
+}
+
+fn _parse_scroll_bar<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], (ScrollBarPropMask, ScrollBarControl), E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    let ap = AlignedParser::new();
+    let (input, mask) = ctx.aligned("PropMask", &ap, input, |input| {
+        ap.bitfield32(input, ctx.flags::<ScrollBarPropMask>())
+    })?;
+
+    let (input, fore_color) = match mask.contains(ScrollBarPropMask::FORE_COLOR) {
+        true => ctx.aligned("ForeColor", &ap, input, |input| ap.ole_color(input))?,
+        false => (input, OleColor::BTNTEXT),
+    };
+    let (input, back_color) = match mask.contains(ScrollBarPropMask::BACK_COLOR) {
+        true => ctx.aligned("BackColor", &ap, input, |input| ap.ole_color(input))?,
+        false => (input, OleColor::BTNFACE),
+    };
+    let (input, various_property_bits) =
+        match mask.contains(ScrollBarPropMask::VARIOUS_PROPERTY_BITS) {
+            true => ctx.aligned("VariousPropertyBits", &ap, input, |input| {
+                ap.bitfield32(input, VariousPropertyBits::from_u32)
+            })?,
+            false => (input, VariousPropertyBits::from_bits_retain(0x0000001B)),
+        };
+    let (input, mouse_pointer) = match mask.contains(ScrollBarPropMask::MOUSE_POINTER) {
+        true => ctx.aligned("MousePointer", &ap, input, |input| {
+            ap.bitfield8(input, MousePointer::from_u8)
+        })?,
+        false => (input, MousePointer::Default),
+    };
+    let (input, min) = match mask.contains(ScrollBarPropMask::MIN) {
+        true => ctx.aligned("Min", &ap, input, |input| ap.le_i32(input))?,
+        false => (input, 0),
+    };
+    let (input, max) = match mask.contains(ScrollBarPropMask::MAX) {
+        true => ctx.aligned("Max", &ap, input, |input| ap.le_i32(input))?,
+        false => (input, 32767),
+    };
+    let (input, position) = match mask.contains(ScrollBarPropMask::POSITION) {
+        true => ctx.aligned("Position", &ap, input, |input| ap.le_i32(input))?,
+        false => (input, 0),
+    };
+    let (input, _unused1) = match mask.contains(ScrollBarPropMask::UNUSED1) {
+        true => ctx.aligned("Unused1", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 0),
+    };
+    let (input, prev_enabled) = match mask.contains(ScrollBarPropMask::PREV_ENABLED) {
+        true => ctx.aligned("PrevEnabled", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 1),
+    };
+    let (input, next_enabled) = match mask.contains(ScrollBarPropMask::NEXT_ENABLED) {
+        true => ctx.aligned("NextEnabled", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 1),
+    };
+    let (input, small_change) = match mask.contains(ScrollBarPropMask::SMALL_CHANGE) {
+        true => ctx.aligned("SmallChange", &ap, input, |input| ap.le_i32(input))?,
+        false => (input, 1),
+    };
+    let (input, large_change) = match mask.contains(ScrollBarPropMask::LARGE_CHANGE) {
+        true => ctx.aligned("LargeChange", &ap, input, |input| ap.le_i32(input))?,
+        false => (input, 1),
+    };
+    let (input, orientation) = match mask.contains(ScrollBarPropMask::ORIENTATION) {
+        true => ctx.aligned("Orientation", &ap, input, |input| ap.le_i32(input))?,
+        false => (input, -1),
+    };
+    let (input, proportional_thumb) = match mask.contains(ScrollBarPropMask::PROPORTIONAL_THUMB) {
+        true => ctx.aligned("ProportionalThumb", &ap, input, |input| ap.le_i16(input))?,
+        false => (input, -1),
+    };
+    let (input, delay) = match mask.contains(ScrollBarPropMask::DELAY) {
+        true => ctx.aligned("Delay", &ap, input, |input| ap.le_i32(input))?,
+        false => (input, 50),
+    };
+    let (input, _mouse_icon) = match mask.contains(ScrollBarPropMask::MOUSE_ICON) {
+        true => ctx.aligned("MouseIcon", &ap, input, |input| {
+            verify(|i| ap.le_u16(i), |x| *x == 0xFFFF)(input)
+        })?,
+        false => (input, 0),
+    };
+
+    // Extra Data Block
+    let (input, size) = match mask.contains(ScrollBarPropMask::SIZE) {
+        true => ctx.aligned("Size", &ap, input, |input| ap.fm_size(input))?,
+        false => (input, Size::new(0, 0)),
+    };
+
+    ctx.check_mask_layout(&mask, ap.offset(), input.len(), input)?;
+    let unknown_mask_bits = mask.bits() & !ScrollBarPropMask::all().bits();
+    Ok((
+        input,
+        (
+            mask,
+            ScrollBarControl {
+                fore_color,
+                back_color,
+                various_property_bits,
+                size,
+                mouse_pointer,
+                min,
+                max,
+                position,
+                prev_enabled,
+                next_enabled,
+                small_change,
+                large_change,
+                orientation,
+                proportional_thumb,
+                delay,
+                mouse_icon: GuidAndPicture::EMPTY,
+                unknown_mask_bits,
+            },
+        ),
+    ))
+}
+
+/// Write a [`ScrollBarControl`], storing only the properties that differ from the file format
+/// default
+pub fn write_scroll_bar(out: &mut Vec<u8>, control: &ScrollBarControl) -> io::Result<()> {
+    let mut w = AlignedWriter::new();
+    let mut mask = ScrollBarPropMask::SIZE;
+    w.le_u32(0);
+
+    if control.fore_color != OleColor::BTNTEXT {
+        mask |= ScrollBarPropMask::FORE_COLOR;
+        w.ole_color(control.fore_color);
+    }
+    if control.back_color != OleColor::BTNFACE {
+        mask |= ScrollBarPropMask::BACK_COLOR;
+        w.ole_color(control.back_color);
+    }
+    if control.various_property_bits.bits() != 0x0000001B {
+        mask |= ScrollBarPropMask::VARIOUS_PROPERTY_BITS;
+        w.le_u32(control.various_property_bits.bits());
+    }
+    if control.mouse_pointer != MousePointer::Default {
+        mask |= ScrollBarPropMask::MOUSE_POINTER;
+        w.le_u8(control.mouse_pointer as u8);
+    }
+    if control.min != 0 {
+        mask |= ScrollBarPropMask::MIN;
+        w.le_i32(control.min);
+    }
+    if control.max != 32767 {
+        mask |= ScrollBarPropMask::MAX;
+        w.le_i32(control.max);
+    }
+    if control.position != 0 {
+        mask |= ScrollBarPropMask::POSITION;
+        w.le_i32(control.position);
+    }
+    if control.prev_enabled != 1 {
+        mask |= ScrollBarPropMask::PREV_ENABLED;
+        w.le_u32(control.prev_enabled);
+    }
+    if control.next_enabled != 1 {
+        mask |= ScrollBarPropMask::NEXT_ENABLED;
+        w.le_u32(control.next_enabled);
+    }
+    if control.small_change != 1 {
+        mask |= ScrollBarPropMask::SMALL_CHANGE;
+        w.le_i32(control.small_change);
+    }
+    if control.large_change != 1 {
+        mask |= ScrollBarPropMask::LARGE_CHANGE;
+        w.le_i32(control.large_change);
+    }
+    if control.orientation != -1 {
+        mask |= ScrollBarPropMask::ORIENTATION;
+        w.le_i32(control.orientation);
+    }
+    if control.proportional_thumb != -1 {
+        mask |= ScrollBarPropMask::PROPORTIONAL_THUMB;
+        w.le_i16(control.proportional_thumb);
+    }
+    if control.delay != 50 {
+        mask |= ScrollBarPropMask::DELAY;
+        w.le_i32(control.delay);
+    }
+    if control.mouse_icon != GuidAndPicture::EMPTY {
+        mask |= ScrollBarPropMask::MOUSE_ICON;
+        w.le_u16(0xFFFF);
+    }
+
+    // Extra Data Block
+    w.fm_size(control.size);
+
+    w.patch_u32(
+        0,
+        mask.bits() | control.unknown_mask_bits & !ScrollBarPropMask::all().bits(),
+    );
+    write_with_header(out, [0x00, 0x02], &w.finish())?;
+
+    // Stream Data
+    write_guid_and_picture(out, &control.mouse_icon)
+}
@@ -0,0 +1,344 @@
+//! ## 2.2.8 SpinButton Control
+
+use std::io;
+
+use nom::{
+    bytes::complete::tag,
+    combinator::verify,
+    error::{FromExternalError, ParseError},
+    multi::length_value,
+    number::complete::le_u16,
+    sequence::preceded,
+    IResult,
+};
+use num_traits::FromPrimitive;
+
+use crate::{
+    common::{write_with_header, AlignedParser, AlignedWriter, ParseContext},
+    properties::{
+        color::OleColor,
+        picture::{parse_guid_and_picture, write_guid_and_picture, GuidAndPicture},
+        MousePointer, Size, VariousPropertyBits,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpinButtonControl {
+    /// The file format default is 0x80000012, COLOR_BTNTEXT from the system palette.
+    pub fore_color: OleColor,
+    /// The file format default is 0x8000000F, COLOR_BTNFACE from the system palette.
+    pub back_color: OleColor,
+    /// The file format default is 0x0000001B.
+    pub various_property_bits: VariousPropertyBits,
+    /// The width and height, in HIMETRIC units, of the control.
+    pub size: Size,
+    /// The file format default is 0x00000000.
+    pub min: i32,
+    /// The file format default is 0x00000064 (100).
+    pub max: i32,
+    /// The file format default is 0x00000000.
+    pub position: i32,
+    /// The file format default is 0x00000001.
+    pub prev_enabled: u32,
+    /// The file format default is 0x00000001.
+    pub next_enabled: u32,
+    /// The file format default is 0x00000001.
+    pub small_change: i32,
+    /// An fmOrientation that specifies whether the control is displayed horizontally or vertically.
+    ///
+    /// The file format default is 0xFFFFFFFF, fmOrientationAuto.
+    pub orientation: i32,
+    /// The delay, in milliseconds, between events when the user holds down the button.
+    ///
+    /// The file format default is 0x00000032 (50).
+    pub delay: i32,
+    /// The file format default is no custom icon.
+    pub mouse_icon: GuidAndPicture,
+    /// The file format default is 0x00, fmMousePointerDefault.
+    pub mouse_pointer: MousePointer,
+    /// The bits of the property mask that are not defined in [MS-OFORMS]
+    ///
+    /// These are only kept in [`ParseMode::Lenient`][crate::common::ParseMode::Lenient], for
+    /// properties without data, and are written back unchanged. Zero by default.
+    pub unknown_mask_bits: u32,
+}
+
+impl Default for SpinButtonControl {
+    /// The file format defaults, with a size of zero
+    fn default() -> Self {
+        Self {
+            fore_color: OleColor::BTNTEXT,
+            back_color: OleColor::BTNFACE,
+            various_property_bits: VariousPropertyBits::from_bits_retain(0x0000001B),
+            size: Size::new(0, 0),
+            min: 0,
+            max: 100,
+            position: 0,
+            prev_enabled: 1,
+            next_enabled: 1,
+            small_change: 1,
+            orientation: -1,
+            delay: 50,
+            mouse_icon: GuidAndPicture::EMPTY,
+            mouse_pointer: MousePointer::Default,
+            unknown_mask_bits: 0,
+        }
+    }
+}
+
+bitflags! {
+    pub(crate) struct SpinButtonPropMask: u32 {
+        /// A - fForeColor (1 bit): Specifies whether the ForeColor property is stored in the DataBlock.
+        const FORE_COLOR = 1 << 0;
+        /// B - fBackColor (1 bit): Specifies whether the BackColor property is stored in the DataBlock.
+        const BACK_COLOR = 1 << 1;
+        /// C - fVariousPropertyBits (1 bit): Specifies whether the VariousPropertyBits property is stored in the DataBlock.
+        const VARIOUS_PROPERTY_BITS = 1 << 2;
+        /// D - fSize (1 bit): Specifies whether the Size property is stored in the ExtraDataBlock.
+        const SIZE = 1 << 3;
+        /// E - UnusedBits1 (1 bit): MUST be set to zero.
+        const UNUSED1 = 1 << 4;
+        /// F - fMin (1 bit): Specifies whether the Min property is stored in the DataBlock.
+        const MIN = 1 << 5;
+        /// G - fMax (1 bit): Specifies whether the Max property is stored in the DataBlock.
+        const MAX = 1 << 6;
+        /// H - fPosition (1 bit): Specifies whether the Position property is stored in the DataBlock.
+        const POSITION = 1 << 7;
+        /// I - fPrevEnabled (1 bit): Specifies whether the PrevEnabled property is stored in the DataBlock.
+        const PREV_ENABLED = 1 << 8;
+        /// J - fNextEnabled (1 bit): Specifies whether the NextEnabled property is stored in the DataBlock.
+        const NEXT_ENABLED = 1 << 9;
+        /// K - fSmallChange (1 bit): Specifies whether the SmallChange property is stored in the DataBlock.
+        const SMALL_CHANGE = 1 << 10;
+        /// L - fOrientation (1 bit): Specifies whether the Orientation property is stored in the DataBlock.
+        const ORIENTATION = 1 << 11;
+        /// M - fDelay (1 bit): Specifies whether the Delay property is stored in the DataBlock.
+        const DELAY = 1 << 12;
+        /// N - fMouseIcon (1 bit): Specifies whether the MouseIcon property is stored in the StreamData.
+        const MOUSE_ICON = 1 << 13;
+        /// O - fMousePointer (1 bit): Specifies whether the MousePointer property is stored in the DataBlock.
+        const MOUSE_POINTER = 1 << 14;
+    }
+}
+
+fn parse_spin_button_header<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], u16, E>
+where
+    E: ParseError<&'a [u8]>,
+{
+    preceded(tag([0x00, 0x02]), le_u16)(input)
+}
+
+pub fn parse_spin_button<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], SpinButtonControl, E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    parse_spin_button_with(input, &ParseContext::default())
+}
+
+/// Parse a [`SpinButtonControl`] with the [`ParseMode`][crate::common::ParseMode] of `ctx`
+pub fn parse_spin_button_with<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], SpinButtonControl, E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    let (input, (mask, mut control)) = length_value(
+        |i| ctx.field("Header", i, parse_spin_button_header),
+        |i| _parse_spin_button(i, ctx),
+    )(input)?;
+
+    // Stream Data
+    let (input, mouse_icon) = match mask.contains(SpinButtonPropMask::MOUSE_ICON) {
+        true => ctx.field("MouseIcon", input, parse_guid_and_picture)?,
+        false => (input, GuidAndPicture::EMPTY),
+    };
+
+    control.mouse_icon = mouse_icon;
+    Ok((input, control))
+}
+
+fn _parse_spin_button<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], (SpinButtonPropMask, SpinButtonControl), E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    let ap = AlignedParser::new();
+    let (input, mask) = ctx.aligned("PropMask", &ap, input, |input| {
+        ap.bitfield32(input, ctx.flags::<SpinButtonPropMask>())
+    })?;
+
+    let (input, fore_color) = match mask.contains(SpinButtonPropMask::FORE_COLOR) {
+        true => ctx.aligned("ForeColor", &ap, input, |input| ap.ole_color(input))?,
+        false => (input, OleColor::BTNTEXT),
+    };
+    let (input, back_color) = match mask.contains(SpinButtonPropMask::BACK_COLOR) {
+        true => ctx.aligned("BackColor", &ap, input, |input| ap.ole_color(input))?,
+        false => (input, OleColor::BTNFACE),
+    };
+    let (input, various_property_bits) =
+        match mask.contains(SpinButtonPropMask::VARIOUS_PROPERTY_BITS) {
+            true => ctx.aligned("VariousPropertyBits", &ap, input, |input| {
+                ap.bitfield32(input, VariousPropertyBits::from_u32)
+            })?,
+            false => (input, VariousPropertyBits::from_bits_retain(0x0000001B)),
+        };
+    let (input, _unused1) = match mask.contains(SpinButtonPropMask::UNUSED1) {
+        true => ctx.aligned("Unused1", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 0),
+    };
+    let (input, min) = match mask.contains(SpinButtonPropMask::MIN) {
+        true => ctx.aligned("Min", &ap, input, |input| ap.le_i32(input))?,
+        false => (input, 0),
+    };
+    let (input, max) = match mask.contains(SpinButtonPropMask::MAX) {
+        true => ctx.aligned("Max", &ap, input, |input| ap.le_i32(input))?,
+        false => (input, 100),
+    };
+    let (input, position) = match mask.contains(SpinButtonPropMask::POSITION) {
+        true => ctx.aligned("Position", &ap, input, |input| ap.le_i32(input))?,
+        false => (input, 0),
+    };
+    let (input, prev_enabled) = match mask.contains(SpinButtonPropMask::PREV_ENABLED) {
+        true => ctx.aligned("PrevEnabled", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 1),
+    };
+    let (input, next_enabled) = match mask.contains(SpinButtonPropMask::NEXT_ENABLED) {
+        true => ctx.aligned("NextEnabled", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 1),
+    };
+    let (input, small_change) = match mask.contains(SpinButtonPropMask::SMALL_CHANGE) {
+        true => ctx.aligned("SmallChange", &ap, input, |input| ap.le_i32(input))?,
+        false => (input, 1),
+    };
+    let (input, orientation) = match mask.contains(SpinButtonPropMask::ORIENTATION) {
+        true => ctx.aligned("Orientation", &ap, input, |input| ap.le_i32(input))?,
+        false => (input, -1),
+    };
+    let (input, delay) = match mask.contains(SpinButtonPropMask::DELAY) {
+        true => ctx.aligned("Delay", &ap, input, |input| ap.le_i32(input))?,
+        false => (input, 50),
+    };
+    let (input, _mouse_icon) = match mask.contains(SpinButtonPropMask::MOUSE_ICON) {
+        true => ctx.aligned("MouseIcon", &ap, input, |input| {
+            verify(|i| ap.le_u16(i), |x| *x == 0xFFFF)(input)
+        })?,
+        false => (input, 0),
+    };
+    let (input, mouse_pointer) = match mask.contains(SpinButtonPropMask::MOUSE_POINTER) {
+        true => ctx.aligned("MousePointer", &ap, input, |input| {
+            ap.bitfield8(input, MousePointer::from_u8)
+        })?,
+        false => (input, MousePointer::Default),
+    };
+
+    // Extra Data Block
+    let (input, size) = match mask.contains(SpinButtonPropMask::SIZE) {
+        true => ctx.aligned("Size", &ap, input, |input| ap.fm_size(input))?,
+        false => (input, Size::new(0, 0)),
+    };
+
+    ctx.check_mask_layout(&mask, ap.offset(), input.len(), input)?;
+    let unknown_mask_bits = mask.bits() & !SpinButtonPropMask::all().bits();
+    Ok((
+        input,
+        (
+            mask,
+            SpinButtonControl {
+                fore_color,
+                back_color,
+                various_property_bits,
+                size,
+                min,
+                max,
+                position,
+                prev_enabled,
+                next_enabled,
+                small_change,
+                orientation,
+                delay,
+                mouse_icon: GuidAndPicture::EMPTY,
+                mouse_pointer,
+                unknown_mask_bits,
+            },
+        ),
+    ))
+}
+
+/// Write a [`SpinButtonControl`], storing only the properties that differ from the file format
+/// default
+pub fn write_spin_button(out: &mut Vec<u8>, control: &SpinButtonControl) -> io::Result<()> {
+    let mut w = AlignedWriter::new();
+    let mut mask = SpinButtonPropMask::SIZE;
+    w.le_u32(0);
+
+    if control.fore_color != OleColor::BTNTEXT {
+        mask |= SpinButtonPropMask::FORE_COLOR;
+        w.ole_color(control.fore_color);
+    }
+    if control.back_color != OleColor::BTNFACE {
+        mask |= SpinButtonPropMask::BACK_COLOR;
+        w.ole_color(control.back_color);
+    }
+    if control.various_property_bits.bits() != 0x0000001B {
+        mask |= SpinButtonPropMask::VARIOUS_PROPERTY_BITS;
+        w.le_u32(control.various_property_bits.bits());
+    }
+    if control.min != 0 {
+        mask |= SpinButtonPropMask::MIN;
+        w.le_i32(control.min);
+    }
+    if control.max != 100 {
+        mask |= SpinButtonPropMask::MAX;
+        w.le_i32(control.max);
+    }
+    if control.position != 0 {
+        mask |= SpinButtonPropMask::POSITION;
+        w.le_i32(control.position);
+    }
+    if control.prev_enabled != 1 {
+        mask |= SpinButtonPropMask::PREV_ENABLED;
+        w.le_u32(control.prev_enabled);
+    }
+    if control.next_enabled != 1 {
+        mask |= SpinButtonPropMask::NEXT_ENABLED;
+        w.le_u32(control.next_enabled);
+    }
+    if control.small_change != 1 {
+        mask |= SpinButtonPropMask::SMALL_CHANGE;
+        w.le_i32(control.small_change);
+    }
+    if control.orientation != -1 {
+        mask |= SpinButtonPropMask::ORIENTATION;
+        w.le_i32(control.orientation);
+    }
+    if control.delay != 50 {
+        mask |= SpinButtonPropMask::DELAY;
+        w.le_i32(control.delay);
+    }
+    if control.mouse_icon != GuidAndPicture::EMPTY {
+        mask |= SpinButtonPropMask::MOUSE_ICON;
+        w.le_u16(0xFFFF);
+    }
+    if control.mouse_pointer != MousePointer::Default {
+        mask |= SpinButtonPropMask::MOUSE_POINTER;
+        w.le_u8(control.mouse_pointer as u8);
+    }
+
+    // Extra Data Block
+    w.fm_size(control.size);
+
+    w.patch_u32(
+        0,
+        mask.bits() | control.unknown_mask_bits & !SpinButtonPropMask::all().bits(),
+    );
+    write_with_header(out, [0x00, 0x02], &w.finish())?;
+
+    // Stream Data
+    write_guid_and_picture(out, &control.mouse_icon)
+}
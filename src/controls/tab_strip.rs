@@ -0,0 +1,489 @@
+//! ## 2.2.9 TabStrip Control
+
+use std::io;
+
+use nom::{
+    bytes::complete::tag,
+    combinator::{map_opt, verify},
+    error::{FromExternalError, ParseError},
+    multi::{count, length_value},
+    number::complete::{le_u16, le_u32},
+    sequence::preceded,
+    IResult,
+};
+use num_traits::FromPrimitive;
+
+use crate::{
+    common::{write_with_header, AlignedParser, AlignedWriter, ParseContext},
+    properties::{
+        color::OleColor,
+        font::{parse_text_props_with, write_text_props, TextProps},
+        picture::{parse_guid_and_picture, write_guid_and_picture, GuidAndPicture},
+        string::fm_string_array_size,
+        MousePointer, Size, VariousPropertyBits,
+    },
+};
+
+bitflags! {
+    /// Specifies whether a tab of a TabStrip is visible and enabled (`TabFlagData`).
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct TabFlags: u32 {
+        /// A - fVisible (1 bit): Specifies whether the tab is visible.
+        const VISIBLE = 1 << 0;
+        /// B - fEnabled (1 bit): Specifies whether the tab is enabled.
+        const ENABLED = 1 << 1;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TabStripControl {
+    /// The index of the selected tab.
+    ///
+    /// The file format default is 0x00000000.
+    pub list_index: i32,
+    /// The file format default is 0x8000000F, COLOR_BTNFACE from the system palette.
+    pub back_color: OleColor,
+    /// The file format default is 0x80000012, COLOR_BTNTEXT from the system palette.
+    pub fore_color: OleColor,
+    /// The width and height, in HIMETRIC units, of the control.
+    pub size: Size,
+    /// The captions of the tabs.
+    pub items: Vec<String>,
+    /// The file format default is 0x00, fmMousePointerDefault.
+    pub mouse_pointer: MousePointer,
+    /// An fmTabOrientation that specifies the location of the tabs.
+    ///
+    /// The file format default is 0x00000000, fmTabOrientationTop.
+    pub tab_orientation: u32,
+    /// An fmTabStyle that specifies the style of the tabs.
+    ///
+    /// The file format default is 0x00000000, fmTabStyleTabs.
+    pub tab_style: u32,
+    /// Specifies whether the control has more than one row of tabs.
+    ///
+    /// The file format default is FALSE.
+    pub multi_row: bool,
+    /// The width, in HIMETRIC units, of the tabs. A value of zero specifies automatic width.
+    ///
+    /// The file format default is 0x00000000.
+    pub tab_fixed_width: u32,
+    /// The height, in HIMETRIC units, of the tabs. A value of zero specifies automatic height.
+    ///
+    /// The file format default is 0x00000000.
+    pub tab_fixed_height: u32,
+    /// Specifies whether tooltips are displayed for the tabs.
+    ///
+    /// The file format default is FALSE.
+    pub tooltips: bool,
+    /// The tooltips of the tabs.
+    pub tip_strings: Vec<String>,
+    /// The names of the tabs.
+    pub tab_names: Vec<String>,
+    /// The file format default is 0x00000019.
+    pub various_property_bits: VariousPropertyBits,
+    /// The number of tabs allocated in the control.
+    ///
+    /// The file format default is 0x00000000.
+    pub tabs_allocated: u32,
+    /// The tags of the tabs.
+    pub tags: Vec<String>,
+    /// The TabFlagData of the tabs, which follow the TextProps.
+    ///
+    /// The number of entries is stored as TabData, with a file format default of 0x00000000.
+    pub tab_flags: Vec<TabFlags>,
+    /// The accelerator keys of the tabs.
+    pub accelerators: Vec<String>,
+    /// The file format default is no custom icon.
+    pub mouse_icon: GuidAndPicture,
+    /// The font of the tab captions.
+    pub text_props: TextProps,
+    /// The bits of the property mask that are not defined in [MS-OFORMS]
+    ///
+    /// These are only kept in [`ParseMode::Lenient`][crate::common::ParseMode::Lenient], for
+    /// properties without data, and are written back unchanged. Zero by default.
+    pub unknown_mask_bits: u32,
+}
+
+impl Default for TabStripControl {
+    /// The file format defaults, with a size of zero
+    fn default() -> Self {
+        Self {
+            list_index: 0,
+            back_color: OleColor::BTNFACE,
+            fore_color: OleColor::BTNTEXT,
+            size: Size::new(0, 0),
+            items: Vec::new(),
+            mouse_pointer: MousePointer::Default,
+            tab_orientation: 0,
+            tab_style: 0,
+            multi_row: false,
+            tab_fixed_width: 0,
+            tab_fixed_height: 0,
+            tooltips: false,
+            tip_strings: Vec::new(),
+            tab_names: Vec::new(),
+            various_property_bits: VariousPropertyBits::from_bits_retain(0x00000019),
+            tabs_allocated: 0,
+            tags: Vec::new(),
+            tab_flags: Vec::new(),
+            accelerators: Vec::new(),
+            mouse_icon: GuidAndPicture::EMPTY,
+            text_props: TextProps::default(),
+            unknown_mask_bits: 0,
+        }
+    }
+}
+
+bitflags! {
+    struct TabStripPropMask: u32 {
+        /// A - fListIndex (1 bit): Specifies whether the ListIndex property is stored in the DataBlock.
+        const LIST_INDEX = 1 << 0;
+        /// B - fBackColor (1 bit): Specifies whether the BackColor property is stored in the DataBlock.
+        const BACK_COLOR = 1 << 1;
+        /// C - fForeColor (1 bit): Specifies whether the ForeColor property is stored in the DataBlock.
+        const FORE_COLOR = 1 << 2;
+        /// E - fSize (1 bit): Specifies whether the Size property is stored in the ExtraDataBlock.
+        const SIZE = 1 << 4;
+        /// F - fItems (1 bit): Specifies whether the tab captions are stored in the DataBlock and ExtraDataBlock.
+        const ITEMS = 1 << 5;
+        /// G - fMousePointer (1 bit): Specifies whether the MousePointer property is stored in the DataBlock.
+        const MOUSE_POINTER = 1 << 6;
+        /// I - fTabOrientation (1 bit): Specifies whether the TabOrientation property is stored in the DataBlock.
+        const TAB_ORIENTATION = 1 << 8;
+        /// J - fTabStyle (1 bit): Specifies whether the TabStyle property is stored in the DataBlock.
+        const TAB_STYLE = 1 << 9;
+        /// K - fMultiRow (1 bit): Specifies whether the value of the MultiRow property is not the file format default.
+        const MULTI_ROW = 1 << 10;
+        /// L - fTabFixedWidth (1 bit): Specifies whether the TabFixedWidth property is stored in the DataBlock.
+        const TAB_FIXED_WIDTH = 1 << 11;
+        /// M - fTabFixedHeight (1 bit): Specifies whether the TabFixedHeight property is stored in the DataBlock.
+        const TAB_FIXED_HEIGHT = 1 << 12;
+        /// N - fTooltips (1 bit): Specifies whether the value of the Tooltips property is not the file format default.
+        const TOOLTIPS = 1 << 13;
+        /// P - fTipStrings (1 bit): Specifies whether the tooltips are stored in the DataBlock and ExtraDataBlock.
+        const TIP_STRINGS = 1 << 15;
+        /// R - fNames (1 bit): Specifies whether the tab names are stored in the DataBlock and ExtraDataBlock.
+        const NAMES = 1 << 17;
+        /// S - fVariousPropertyBits (1 bit): Specifies whether the VariousPropertyBits property is stored in the DataBlock.
+        const VARIOUS_PROPERTY_BITS = 1 << 18;
+        /// T - fNewVersion (1 bit): Specifies whether the control was saved by a newer version.
+        const NEW_VERSION = 1 << 19;
+        /// U - fTabsAllocated (1 bit): Specifies whether the TabsAllocated property is stored in the DataBlock.
+        const TABS_ALLOCATED = 1 << 20;
+        /// V - fTags (1 bit): Specifies whether the tab tags are stored in the DataBlock and ExtraDataBlock.
+        const TAGS = 1 << 21;
+        /// W - fTabData (1 bit): Specifies whether the TabData property is stored in the DataBlock.
+        const TAB_DATA = 1 << 22;
+        /// X - fAccelerator (1 bit): Specifies whether the accelerators are stored in the DataBlock and ExtraDataBlock.
+        const ACCELERATOR = 1 << 23;
+        /// Y - fMouseIcon (1 bit): Specifies whether the MouseIcon property is stored in the StreamData.
+        const MOUSE_ICON = 1 << 24;
+    }
+}
+
+fn parse_tab_strip_header<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], u16, E>
+where
+    E: ParseError<&'a [u8]>,
+{
+    preceded(tag([0x00, 0x02]), le_u16)(input)
+}
+
+pub fn parse_tab_strip<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], TabStripControl, E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    parse_tab_strip_with(input, &ParseContext::default())
+}
+
+/// Parse a [`TabStripControl`] with the [`ParseMode`][crate::common::ParseMode] of `ctx`
+pub fn parse_tab_strip_with<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], TabStripControl, E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    let (input, (mask, tab_data, mut control)) = length_value(
+        |i| ctx.field("Header", i, parse_tab_strip_header),
+        |i| _parse_tab_strip(i, ctx),
+    )(input)?;
+
+    // Stream Data
+    let (input, mouse_icon) = match mask.contains(TabStripPropMask::MOUSE_ICON) {
+        true => ctx.field("MouseIcon", input, parse_guid_and_picture)?,
+        false => (input, GuidAndPicture::EMPTY),
+    };
+
+    // Text Props
+    let (input, text_props) = ctx.field("TextProps", input, |input| {
+        parse_text_props_with(input, ctx)
+    })?;
+
+    // Tab Flag Data
+    let (input, tab_flags) = ctx.field("TabFlagData", input, |input| {
+        count(map_opt(le_u32, ctx.flags::<TabFlags>()), tab_data as usize)(input)
+    })?;
+
+    control.mouse_icon = mouse_icon;
+    control.text_props = text_props;
+    control.tab_flags = tab_flags;
+    Ok((input, control))
+}
+
+fn _parse_tab_strip<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], (TabStripPropMask, u32, TabStripControl), E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+{
+    let ap = AlignedParser::new();
+    let (input, mask) = ctx.aligned("PropMask", &ap, input, |input| {
+        ap.bitfield32(input, ctx.flags::<TabStripPropMask>())
+    })?;
+
+    let (input, list_index) = match mask.contains(TabStripPropMask::LIST_INDEX) {
+        true => ctx.aligned("ListIndex", &ap, input, |input| ap.le_i32(input))?,
+        false => (input, 0),
+    };
+    let (input, back_color) = match mask.contains(TabStripPropMask::BACK_COLOR) {
+        true => ctx.aligned("BackColor", &ap, input, |input| ap.ole_color(input))?,
+        false => (input, OleColor::BTNFACE),
+    };
+    let (input, fore_color) = match mask.contains(TabStripPropMask::FORE_COLOR) {
+        true => ctx.aligned("ForeColor", &ap, input, |input| ap.ole_color(input))?,
+        false => (input, OleColor::BTNTEXT),
+    };
+    let (input, items_size) = match mask.contains(TabStripPropMask::ITEMS) {
+        true => ctx.aligned("ItemsSize", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 0),
+    };
+    let (input, mouse_pointer) = match mask.contains(TabStripPropMask::MOUSE_POINTER) {
+        true => ctx.aligned("MousePointer", &ap, input, |input| {
+            ap.bitfield8(input, MousePointer::from_u8)
+        })?,
+        false => (input, MousePointer::Default),
+    };
+    let (input, tab_orientation) = match mask.contains(TabStripPropMask::TAB_ORIENTATION) {
+        true => ctx.aligned("TabOrientation", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 0),
+    };
+    let (input, tab_style) = match mask.contains(TabStripPropMask::TAB_STYLE) {
+        true => ctx.aligned("TabStyle", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 0),
+    };
+    let multi_row = mask.contains(TabStripPropMask::MULTI_ROW);
+    let (input, tab_fixed_width) = match mask.contains(TabStripPropMask::TAB_FIXED_WIDTH) {
+        true => ctx.aligned("TabFixedWidth", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 0),
+    };
+    let (input, tab_fixed_height) = match mask.contains(TabStripPropMask::TAB_FIXED_HEIGHT) {
+        true => ctx.aligned("TabFixedHeight", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 0),
+    };
+    let tooltips = mask.contains(TabStripPropMask::TOOLTIPS);
+    let (input, tip_strings_size) = match mask.contains(TabStripPropMask::TIP_STRINGS) {
+        true => ctx.aligned("TipStringsSize", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 0),
+    };
+    let (input, names_size) = match mask.contains(TabStripPropMask::NAMES) {
+        true => ctx.aligned("NamesSize", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 0),
+    };
+    let (input, various_property_bits) =
+        match mask.contains(TabStripPropMask::VARIOUS_PROPERTY_BITS) {
+            true => ctx.aligned("VariousPropertyBits", &ap, input, |input| {
+                ap.bitfield32(input, VariousPropertyBits::from_u32)
+            })?,
+            false => (input, VariousPropertyBits::from_bits_retain(0x00000019)),
+        };
+    let (input, tabs_allocated) = match mask.contains(TabStripPropMask::TABS_ALLOCATED) {
+        true => ctx.aligned("TabsAllocated", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 0),
+    };
+    let (input, tags_size) = match mask.contains(TabStripPropMask::TAGS) {
+        true => ctx.aligned("TagsSize", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 0),
+    };
+    let (input, tab_data) = match mask.contains(TabStripPropMask::TAB_DATA) {
+        true => ctx.aligned("TabData", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 0),
+    };
+    let (input, accelerator_size) = match mask.contains(TabStripPropMask::ACCELERATOR) {
+        true => ctx.aligned("AcceleratorSize", &ap, input, |input| ap.le_u32(input))?,
+        false => (input, 0),
+    };
+    let (input, _mouse_icon) = match mask.contains(TabStripPropMask::MOUSE_ICON) {
+        true => ctx.aligned("MouseIcon", &ap, input, |input| {
+            verify(|i| ap.le_u16(i), |x| *x == 0xFFFF)(input)
+        })?,
+        false => (input, 0),
+    };
+
+    // Extra Data Block
+    let (input, size) = match mask.contains(TabStripPropMask::SIZE) {
+        true => ctx.aligned("Size", &ap, input, |input| ap.fm_size(input))?,
+        false => (input, Size::new(0, 0)),
+    };
+    let (input, items) = ctx.aligned("Items", &ap, input, |input| {
+        ap.fm_string_array(input, items_size, ctx.encoding())
+    })?;
+    let (input, tip_strings) = ctx.aligned("TipStrings", &ap, input, |input| {
+        ap.fm_string_array(input, tip_strings_size, ctx.encoding())
+    })?;
+    let (input, tab_names) = ctx.aligned("TabNames", &ap, input, |input| {
+        ap.fm_string_array(input, names_size, ctx.encoding())
+    })?;
+    let (input, tags) = ctx.aligned("Tags", &ap, input, |input| {
+        ap.fm_string_array(input, tags_size, ctx.encoding())
+    })?;
+    let (input, accelerators) = ctx.aligned("Accelerators", &ap, input, |input| {
+        ap.fm_string_array(input, accelerator_size, ctx.encoding())
+    })?;
+
+    ctx.check_mask_layout(&mask, ap.offset(), input.len(), input)?;
+    let unknown_mask_bits = mask.bits() & !TabStripPropMask::all().bits();
+    Ok((
+        input,
+        (
+            mask,
+            tab_data,
+            TabStripControl {
+                list_index,
+                back_color,
+                fore_color,
+                size,
+                items,
+                mouse_pointer,
+                tab_orientation,
+                tab_style,
+                multi_row,
+                tab_fixed_width,
+                tab_fixed_height,
+                tooltips,
+                tip_strings,
+                tab_names,
+                various_property_bits,
+                tabs_allocated,
+                tags,
+                tab_flags: Vec::new(),
+                accelerators,
+                mouse_icon: GuidAndPicture::EMPTY,
+                text_props: TextProps::default(),
+                unknown_mask_bits,
+            },
+        ),
+    ))
+}
+
+/// Write a [`TabStripControl`], storing only the properties that differ from the file format
+/// default
+pub fn write_tab_strip(out: &mut Vec<u8>, control: &TabStripControl) -> io::Result<()> {
+    let mut w = AlignedWriter::new();
+    let mut mask = TabStripPropMask::SIZE;
+    w.le_u32(0);
+
+    if control.list_index != 0 {
+        mask |= TabStripPropMask::LIST_INDEX;
+        w.le_i32(control.list_index);
+    }
+    if control.back_color != OleColor::BTNFACE {
+        mask |= TabStripPropMask::BACK_COLOR;
+        w.ole_color(control.back_color);
+    }
+    if control.fore_color != OleColor::BTNTEXT {
+        mask |= TabStripPropMask::FORE_COLOR;
+        w.ole_color(control.fore_color);
+    }
+    if !control.items.is_empty() {
+        mask |= TabStripPropMask::ITEMS;
+        w.le_u32(fm_string_array_size(&control.items)?);
+    }
+    if control.mouse_pointer != MousePointer::Default {
+        mask |= TabStripPropMask::MOUSE_POINTER;
+        w.le_u8(control.mouse_pointer as u8);
+    }
+    if control.tab_orientation != 0 {
+        mask |= TabStripPropMask::TAB_ORIENTATION;
+        w.le_u32(control.tab_orientation);
+    }
+    if control.tab_style != 0 {
+        mask |= TabStripPropMask::TAB_STYLE;
+        w.le_u32(control.tab_style);
+    }
+    if control.multi_row {
+        mask |= TabStripPropMask::MULTI_ROW;
+    }
+    if control.tab_fixed_width != 0 {
+        mask |= TabStripPropMask::TAB_FIXED_WIDTH;
+        w.le_u32(control.tab_fixed_width);
+    }
+    if control.tab_fixed_height != 0 {
+        mask |= TabStripPropMask::TAB_FIXED_HEIGHT;
+        w.le_u32(control.tab_fixed_height);
+    }
+    if control.tooltips {
+        mask |= TabStripPropMask::TOOLTIPS;
+    }
+    if !control.tip_strings.is_empty() {
+        mask |= TabStripPropMask::TIP_STRINGS;
+        w.le_u32(fm_string_array_size(&control.tip_strings)?);
+    }
+    if !control.tab_names.is_empty() {
+        mask |= TabStripPropMask::NAMES;
+        w.le_u32(fm_string_array_size(&control.tab_names)?);
+    }
+    if control.various_property_bits.bits() != 0x00000019 {
+        mask |= TabStripPropMask::VARIOUS_PROPERTY_BITS;
+        w.le_u32(control.various_property_bits.bits());
+    }
+    if control.tabs_allocated != 0 {
+        mask |= TabStripPropMask::TABS_ALLOCATED;
+        w.le_u32(control.tabs_allocated);
+    }
+    if !control.tags.is_empty() {
+        mask |= TabStripPropMask::TAGS;
+        w.le_u32(fm_string_array_size(&control.tags)?);
+    }
+    if !control.tab_flags.is_empty() {
+        mask |= TabStripPropMask::TAB_DATA;
+        w.le_u32(control.tab_flags.len() as u32);
+    }
+    if !control.accelerators.is_empty() {
+        mask |= TabStripPropMask::ACCELERATOR;
+        w.le_u32(fm_string_array_size(&control.accelerators)?);
+    }
+    if control.mouse_icon != GuidAndPicture::EMPTY {
+        mask |= TabStripPropMask::MOUSE_ICON;
+        w.le_u16(0xFFFF);
+    }
+
+    // Extra Data Block
+    w.fm_size(control.size);
+    w.fm_string_array(&control.items)?;
+    w.fm_string_array(&control.tip_strings)?;
+    w.fm_string_array(&control.tab_names)?;
+    w.fm_string_array(&control.tags)?;
+    w.fm_string_array(&control.accelerators)?;
+
+    w.patch_u32(
+        0,
+        mask.bits() | control.unknown_mask_bits & !TabStripPropMask::all().bits(),
+    );
+    write_with_header(out, [0x00, 0x02], &w.finish())?;
+
+    // Stream Data
+    write_guid_and_picture(out, &control.mouse_icon)?;
+
+    // Text Props
+    write_text_props(out, &control.text_props)?;
+
+    // Tab Flag Data
+    for flags in &control.tab_flags {
+        out.extend_from_slice(&flags.bits().to_le_bytes());
+    }
+    Ok(())
+}
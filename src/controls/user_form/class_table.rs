@@ -1,6 +1,6 @@
 use uuid::Uuid;
 
-use crate::common::{VarFlags, VarType};
+use crate::common::{VarFlags, VarType, IID_IDISPATCH};
 
 bitflags! {
     /// A bit field that specifies Boolean properties of a SiteClassInfo.
@@ -127,4 +127,34 @@ pub struct SiteClassInfo {
     ///
     /// The file format default is {00020400-0000-0000-C000-000000000046}.
     pub default_proc: Uuid,
+    /// The bits of the property mask that are not defined in [MS-OFORMS]
+    ///
+    /// These are only kept in [`ParseMode::Lenient`][crate::common::ParseMode::Lenient], for
+    /// properties without data, and are written back unchanged. Zero by default.
+    pub unknown_mask_bits: u32,
+}
+
+impl SiteClassInfo {
+    /// The entry for the control with CLSID `cls_id`, with file format defaults for all other
+    /// properties
+    pub fn new(cls_id: Uuid) -> Self {
+        Self {
+            class_table_flags: ClsTableFlags::empty(),
+            var_flags: VarFlags::empty(),
+            count_of_methods: 0x00000000,
+            dispid_bind: 0xFFFFFFFF,
+            get_bind_index: 0x0000,
+            put_bind_index: 0x0000,
+            bind_type: VarType::EMPTY,
+            get_value_index: 0x0000,
+            put_value_index: 0x0000,
+            value_type: VarType::EMPTY,
+            dispid_rowset: 0xFFFFFFFF,
+            set_rowset: 0x0000,
+            cls_id,
+            disp_event: IID_IDISPATCH,
+            default_proc: IID_IDISPATCH,
+            unknown_mask_bits: 0,
+        }
+    }
 }
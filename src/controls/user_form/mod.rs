@@ -8,7 +8,10 @@ pub mod class_table;
 pub mod designex;
 pub mod ole_site_concrete;
 mod parser;
+pub mod tree;
+mod writer;
 
+use crate::properties::string::FmStr;
 use crate::properties::{
     color::OleColor, font::GuidAndFont, picture::GuidAndPicture, MousePointer, PictureAlignment,
     PictureSizeMode, Position, Size, SpecialEffect,
@@ -17,6 +20,7 @@ use class_table::{ClsTableFlags, SiteClassInfo};
 use ole_site_concrete::OleSiteConcreteControl;
 
 pub use parser::*;
+pub use writer::*;
 pub mod stream;
 
 bitflags! {
@@ -33,7 +37,7 @@ bitflags! {
 }
 
 #[repr(u8)]
-#[derive(Debug, FromPrimitive, ToPrimitive)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive, ToPrimitive)]
 pub enum BorderStyle {
     /// The control has no visible border line.
     None = 0x00,
@@ -43,7 +47,7 @@ pub enum BorderStyle {
 
 /// Specifies the behavior of the TAB key in the last control of a form
 #[repr(u8)]
-#[derive(Debug, FromPrimitive, ToPrimitive)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive, ToPrimitive)]
 pub enum Cycle {
     /// The focus is next set to the first control on the next form, returning to the first control of this form only after all controls on all other forms have been reached.
     AllForms = 0x00,
@@ -71,18 +75,31 @@ bitflags! {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Site {
+pub struct Site<S = String> {
     pub depth: u8,
-    pub kind: SiteKind,
+    pub kind: SiteKind<S>,
+}
+
+impl<S> Site<S> {
+    /// Convert all strings with `f`
+    pub fn map_strings<T, F: FnMut(S) -> T>(self, f: F) -> Site<T> {
+        Site {
+            depth: self.depth,
+            kind: match self.kind {
+                SiteKind::Ole(ole) => SiteKind::Ole(ole.map_strings(f)),
+            },
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum SiteKind {
-    Ole(OleSiteConcreteControl),
+pub enum SiteKind<S = String> {
+    Ole(OleSiteConcreteControl<S>),
 }
 
-#[derive(Debug)]
-pub struct FormControl {
+/// The strings are [`String`]s by default, see [`FormControlRef`] for a borrowed variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormControl<S = String> {
     /// default: 0x8000000F = COLOR_BTNFACE from the system palette.
     pub back_color: OleColor,
     /// default: 0x00000004 = FORM_FLAG_ENABLED
@@ -92,7 +109,7 @@ pub struct FormControl {
     /// default: 0x00 = None
     pub border_style: BorderStyle,
     /// default: ""
-    pub caption: String,
+    pub caption: S,
     /// default: AllForms
     pub cycle: Cycle,
     /// default: (4000, 3000)
@@ -159,8 +176,107 @@ pub struct FormControl {
     /// The file format default is 100, or actual size.
     pub zoom: u32,
     /// All contained sites
-    pub sites: Vec<Site>,
+    pub sites: Vec<Site<S>>,
 
     /// All contained site classes
     pub site_classes: Vec<SiteClassInfo>,
+    /// The bits of the property mask that are not defined in [MS-OFORMS]
+    ///
+    /// These are only kept in [`ParseMode::Lenient`][crate::common::ParseMode::Lenient], for
+    /// properties without data, and are written back unchanged. Zero by default.
+    pub unknown_mask_bits: u32,
+}
+
+impl<S: Default> Default for FormControl<S> {
+    /// The file format defaults, without any sites
+    fn default() -> Self {
+        Self {
+            back_color: OleColor::BTNFACE,
+            boolean_properties: FormFlags::ENABLED,
+            border_color: OleColor::BTNTEXT,
+            border_style: BorderStyle::None,
+            caption: S::default(),
+            cycle: Cycle::AllForms,
+            displayed_size: Size::new(4000, 3000),
+            draw_buffer: 0,
+            font: GuidAndFont::EMPTY,
+            fore_color: OleColor::BTNTEXT,
+            group_count: 0,
+            logical_size: Size::new(4000, 3000),
+            mouse_icon: GuidAndPicture::EMPTY,
+            mouse_pointer: MousePointer::Default,
+            next_available_id: 0,
+            picture: GuidAndPicture::EMPTY,
+            picture_alignment: PictureAlignment::Center,
+            picture_size_mode: PictureSizeMode::Clip,
+            picture_tiling: false,
+            scroll_bars: FormScrollBarFlags::DEFAULT,
+            scroll_position: Position::default(),
+            shape_cookie: 0,
+            special_effect: SpecialEffect::Flat,
+            zoom: 100,
+            sites: Vec::new(),
+            site_classes: Vec::new(),
+            unknown_mask_bits: 0,
+        }
+    }
+}
+
+/// A [`FormControl`] that borrows its strings from the form stream
+pub type FormControlRef<'a> = FormControl<FmStr<'a>>;
+
+impl<S> FormControl<S> {
+    /// The size of an embedded control as displayed, scaled by [`zoom`][Self::zoom]
+    pub fn zoomed_size(&self, size: Size) -> Size {
+        size.zoomed(self.zoom)
+    }
+
+    /// The position of an embedded control as displayed, scaled by [`zoom`][Self::zoom]
+    pub fn zoomed_position(&self, position: Position) -> Position {
+        position.zoomed(self.zoom)
+    }
+
+    /// Convert all strings with `f`
+    pub fn map_strings<T, F: FnMut(S) -> T>(self, mut f: F) -> FormControl<T> {
+        FormControl {
+            back_color: self.back_color,
+            boolean_properties: self.boolean_properties,
+            border_color: self.border_color,
+            border_style: self.border_style,
+            caption: f(self.caption),
+            cycle: self.cycle,
+            displayed_size: self.displayed_size,
+            draw_buffer: self.draw_buffer,
+            font: self.font,
+            fore_color: self.fore_color,
+            group_count: self.group_count,
+            logical_size: self.logical_size,
+            mouse_icon: self.mouse_icon,
+            mouse_pointer: self.mouse_pointer,
+            next_available_id: self.next_available_id,
+            picture: self.picture,
+            picture_alignment: self.picture_alignment,
+            picture_size_mode: self.picture_size_mode,
+            picture_tiling: self.picture_tiling,
+            scroll_bars: self.scroll_bars,
+            scroll_position: self.scroll_position,
+            shape_cookie: self.shape_cookie,
+            special_effect: self.special_effect,
+            zoom: self.zoom,
+            sites: self
+                .sites
+                .into_iter()
+                .map(|site| site.map_strings(&mut f))
+                .collect(),
+            site_classes: self.site_classes,
+            unknown_mask_bits: self.unknown_mask_bits,
+        }
+    }
+}
+
+impl FormControlRef<'_> {
+    /// Decode all strings
+    pub fn into_owned(self) -> FormControl {
+        self.map_strings(String::from)
+    }
 }
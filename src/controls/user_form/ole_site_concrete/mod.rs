@@ -1,11 +1,13 @@
 //! Specifies properties stored for each embedded control in a UserForm control.
 mod parser;
+mod writer;
 use std::num::NonZeroU16;
 
 pub use parser::*;
+pub use writer::*;
 pub mod stream;
 
-use crate::properties::Position;
+use crate::properties::{string::FmStr, Position};
 
 bitflags! {
     /// Specifies Boolean properties of an embedded control on a form.
@@ -55,8 +57,10 @@ pub enum Clsid {
 }
 
 /// Specifies properties stored for each embedded control in a UserForm control.
+///
+/// The strings are [`String`]s by default, see [`OleSiteConcreteControlRef`] for a borrowed variant.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct OleSiteConcreteControl {
+pub struct OleSiteConcreteControl<S = String> {
     pub id: i32,
     pub help_context_id: i32,
     /// A SITE_FLAG that specifies Boolean properties of an embedded control on a form.
@@ -94,13 +98,13 @@ pub struct OleSiteConcreteControl {
     /// An fmString that specifies the name of a control.
     ///
     /// The file format default is a zero-length string.
-    pub name: String,
+    pub name: S,
 
     /// An fmString that is associated with a control and that contains data entered by the user. SHOULD be
     /// ignored.<13>
     ///
     /// The file format default is a zero-length string.
-    pub tag: String,
+    pub tag: S,
 
     /// An fmPosition that specifies the location of the top-left corner of an embedded control on a form,
     /// relative to the top-left corner of the LogicalSize of the form.
@@ -111,24 +115,88 @@ pub struct OleSiteConcreteControl {
     /// An fmString that specifies the tooltip for the control.
     ///
     /// The file format default is a zero-length string.
-    pub control_tip_text: String,
+    pub control_tip_text: S,
 
     /// An fmString that specifies the license key of a control.
     ///
     /// The file format default is a zero-length string.
-    pub runtime_lic_key: String,
+    pub runtime_lic_key: S,
 
     /// An fmString that specifies a cell in a worksheet that sets the Value property of a control when the
     /// control is loaded and to which the new value of the Value property is stored after it changes in the
     /// control.
     ///
     /// The file format default is a zero-length string.
-    pub control_source: String,
+    pub control_source: S,
 
     /// An fmString that specifies the source for the list of values in a ComboBox or ListBox that is embedded
     /// in a form. This property MUST NOT be set for other controls. The format of the string is a range of
     /// cells in a worksheet.
     ///
     /// The file format default is a zero-length string.
-    pub row_source: String,
+    pub row_source: S,
+    /// The bits of the property mask that are not defined in [MS-OFORMS]
+    ///
+    /// These are only kept in [`ParseMode::Lenient`][crate::common::ParseMode::Lenient], for
+    /// properties without data, and are written back unchanged. Zero by default.
+    pub unknown_mask_bits: u32,
+}
+
+/// An [`OleSiteConcreteControl`] that borrows its strings from the form stream
+pub type OleSiteConcreteControlRef<'a> = OleSiteConcreteControl<FmStr<'a>>;
+
+impl<S: Default> Default for OleSiteConcreteControl<S> {
+    /// A site with the file format defaults of all properties
+    fn default() -> Self {
+        OleSiteConcreteControl {
+            id: 0,
+            help_context_id: 0,
+            bit_flags: SiteFlags::TAB_STOP
+                | SiteFlags::VISIBLE
+                | SiteFlags::STREAMED
+                | SiteFlags::AUTO_SIZE,
+            object_stream_size: 0,
+            tab_index: -1,
+            clsid_cache_index: Clsid::Invalid,
+            group_id: None,
+            name: S::default(),
+            tag: S::default(),
+            site_position: Position::default(),
+            control_tip_text: S::default(),
+            runtime_lic_key: S::default(),
+            control_source: S::default(),
+            row_source: S::default(),
+            unknown_mask_bits: 0,
+        }
+    }
+}
+
+impl<S> OleSiteConcreteControl<S> {
+    /// Convert all strings with `f`
+    pub fn map_strings<T, F: FnMut(S) -> T>(self, mut f: F) -> OleSiteConcreteControl<T> {
+        OleSiteConcreteControl {
+            id: self.id,
+            help_context_id: self.help_context_id,
+            bit_flags: self.bit_flags,
+            object_stream_size: self.object_stream_size,
+            tab_index: self.tab_index,
+            clsid_cache_index: self.clsid_cache_index,
+            group_id: self.group_id,
+            name: f(self.name),
+            tag: f(self.tag),
+            site_position: self.site_position,
+            control_tip_text: f(self.control_tip_text),
+            runtime_lic_key: f(self.runtime_lic_key),
+            control_source: f(self.control_source),
+            row_source: f(self.row_source),
+            unknown_mask_bits: self.unknown_mask_bits,
+        }
+    }
+}
+
+impl OleSiteConcreteControlRef<'_> {
+    /// Decode all strings
+    pub fn into_owned(self) -> OleSiteConcreteControl {
+        self.map_strings(String::from)
+    }
 }
@@ -1,7 +1,8 @@
 use super::stream::*;
 use super::*;
-use crate::common::AlignedParser;
-use crate::properties::string::{parse_string, stream::CountOfBytesWithCompressionFlag};
+use crate::common::{AlignedParser, ParseContext};
+use crate::properties::string::{parse_str_ref, stream::CountOfBytesWithCompressionFlag, FmStr};
+use encoding_rs::Encoding;
 use nom::bytes::complete::{tag, take};
 use nom::combinator::{map, map_opt, map_parser};
 use nom::error::{context, ContextError, ParseError};
@@ -129,16 +130,17 @@ impl AlignedParser {
         mask: SitePropMask,
         flag: SitePropMask,
         length_and_compression: CountOfBytesWithCompressionFlag,
-    ) -> IResult<&'a [u8], String, E>
+        encoding: Option<&'static Encoding>,
+    ) -> IResult<&'a [u8], FmStr<'a>, E>
     where
         E: ParseError<&'a [u8]>,
     {
         if mask.contains(flag) {
-            let (input, s) = parse_string(length_and_compression)(input)?;
+            let (input, s) = parse_str_ref(length_and_compression)(input)?;
             self.inc(length_and_compression.len() as usize);
-            Ok((input, s))
+            Ok((input, s.with_encoding(encoding)))
         } else {
-            Ok((input, String::from("")))
+            Ok((input, FmStr::default()))
         }
     }
 
@@ -165,16 +167,49 @@ impl AlignedParser {
 pub fn parse_ole_site_concrete<'a, E>(
     input: &'a [u8],
 ) -> IResult<&'a [u8], OleSiteConcreteControl, E>
+where
+    E: ParseError<&'a [u8]>,
+    E: ContextError<&'a [u8]>,
+{
+    map(parse_ole_site_concrete_ref, |site| {
+        site.map_strings(String::from)
+    })(input)
+}
+
+/// Parse an [`OleSiteConcreteControl`] without decoding the strings
+pub fn parse_ole_site_concrete_ref<'a, E>(
+    input: &'a [u8],
+) -> IResult<&'a [u8], OleSiteConcreteControlRef<'a>, E>
+where
+    E: ParseError<&'a [u8]>,
+    E: ContextError<&'a [u8]>,
+{
+    parse_ole_site_concrete_ref_with(input, &ParseContext::default())
+}
+
+/// Parse an [`OleSiteConcreteControl`] without decoding the strings, with the
+/// [`ParseMode`][crate::common::ParseMode] of `ctx`
+pub fn parse_ole_site_concrete_ref_with<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], OleSiteConcreteControlRef<'a>, E>
 where
     E: ParseError<&'a [u8]>,
     E: ContextError<&'a [u8]>,
 {
     // Header
-    let (input, cb_site) = context("header", parse_ole_site_concrete_header)(input)?;
-    map_parser(take(cb_site), _parse_ole_site_concrete)(input)
+    let (input, cb_site) = ctx.field(
+        "Header",
+        input,
+        context("header", parse_ole_site_concrete_header),
+    )?;
+    map_parser(take(cb_site), |i| _parse_ole_site_concrete(i, ctx))(input)
 }
 
-fn _parse_ole_site_concrete<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], OleSiteConcreteControl, E>
+fn _parse_ole_site_concrete<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], OleSiteConcreteControlRef<'a>, E>
 where
     E: ParseError<&'a [u8]>,
     E: ContextError<&'a [u8]>,
@@ -183,21 +218,37 @@ where
     let _i = input;
 
     // Mask
-    let (_i, mask) = context("mask", map_opt(|i| ap.le_u32(i), SitePropMask::from_bits))(_i)?;
+    let (_i, mask) = ctx.aligned(
+        "PropMask",
+        &ap,
+        _i,
+        context(
+            "mask",
+            map_opt(|i| ap.le_u32(i), ctx.flags::<SitePropMask>()),
+        ),
+    )?;
 
     // Name Data
-    let (_i, name_data) = ap.parse_cobwcf(_i, mask, SitePropMask::NAME)?;
+    let (_i, name_data) = ctx.aligned("NameData", &ap, _i, |_i| {
+        ap.parse_cobwcf(_i, mask, SitePropMask::NAME)
+    })?;
 
     // Tag Data
-    let (_i, tag_data) = ap.parse_cobwcf(_i, mask, SitePropMask::TAG)?;
+    let (_i, tag_data) = ctx.aligned("TagData", &ap, _i, |_i| {
+        ap.parse_cobwcf(_i, mask, SitePropMask::TAG)
+    })?;
     // ID
-    let (_i, id) = ap.parse_id(_i, mask, SitePropMask::ID)?;
+    let (_i, id) = ctx.aligned("Id", &ap, _i, |_i| ap.parse_id(_i, mask, SitePropMask::ID))?;
     // Help Context ID
-    let (_i, help_context_id) = ap.parse_id(_i, mask, SitePropMask::HELP_CONTEXT_ID)?;
+    let (_i, help_context_id) = ctx.aligned("HelpContextId", &ap, _i, |_i| {
+        ap.parse_id(_i, mask, SitePropMask::HELP_CONTEXT_ID)
+    })?;
 
     // Bit Flags
     let (_i, bit_flags) = if mask.contains(SitePropMask::BIT_FLAGS) {
-        map_opt(|i| ap.le_u32(i), SiteFlags::from_bits)(_i)?
+        ctx.aligned("BitFlags", &ap, _i, |_i| {
+            map_opt(|i| ap.le_u32(i), ctx.flags::<SiteFlags>())(_i)
+        })?
     } else {
         (
             _i,
@@ -206,57 +257,101 @@ where
     };
 
     // Object Stream Size
-    let (_i, object_stream_size) =
-        ap.parse_u32(_i, mask, SitePropMask::OBJECT_STREAM_SIZE, 0x00000000)?;
+    let (_i, object_stream_size) = ctx.aligned("ObjectStreamSize", &ap, _i, |_i| {
+        ap.parse_u32(_i, mask, SitePropMask::OBJECT_STREAM_SIZE, 0x00000000)
+    })?;
     // Tab Index
-    let (_i, tab_index) = ap.parse_i16(_i, mask, SitePropMask::TAB_INDEX, -1)?;
+    let (_i, tab_index) = ctx.aligned("TabIndex", &ap, _i, |_i| {
+        ap.parse_i16(_i, mask, SitePropMask::TAB_INDEX, -1)
+    })?;
     // CLSID Cache Index
-    let (_i, clsid_cache_index) = map(
-        |i| ap.parse_clsid_cache_index(i, mask, SitePropMask::CLSID_CACHE_INDEX),
-        Clsid::from,
-    )(_i)?;
+    let (_i, clsid_cache_index) = ctx.aligned("ClsidCacheIndex", &ap, _i, |_i| {
+        map(
+            |i| ap.parse_clsid_cache_index(i, mask, SitePropMask::CLSID_CACHE_INDEX),
+            Clsid::from,
+        )(_i)
+    })?;
 
     // Group ID
-    let (_i, group_id) = ap.parse_u16(_i, mask, SitePropMask::GROUP_ID, 0x0000)?;
+    let (_i, group_id) = ctx.aligned("GroupId", &ap, _i, |_i| {
+        ap.parse_u16(_i, mask, SitePropMask::GROUP_ID, 0x0000)
+    })?;
     let group_id = NonZeroU16::new(group_id);
 
-    let (_i, control_tip_text_data) = ap.parse_cobwcf(_i, mask, SitePropMask::CONTROL_TIP_TEXT)?;
-    let (_i, runtime_lic_key_data) = ap.parse_cobwcf(_i, mask, SitePropMask::RUNTIME_LIC_KEY)?;
-    let (_i, control_source_data) = ap.parse_cobwcf(_i, mask, SitePropMask::CONTROL_SOURCE)?;
-    let (_i, row_source_data) = ap.parse_cobwcf(_i, mask, SitePropMask::ROW_SOURCE)?;
+    let (_i, control_tip_text_data) = ctx.aligned("ControlTipTextData", &ap, _i, |_i| {
+        ap.parse_cobwcf(_i, mask, SitePropMask::CONTROL_TIP_TEXT)
+    })?;
+    let (_i, runtime_lic_key_data) = ctx.aligned("RuntimeLicKeyData", &ap, _i, |_i| {
+        ap.parse_cobwcf(_i, mask, SitePropMask::RUNTIME_LIC_KEY)
+    })?;
+    let (_i, control_source_data) = ctx.aligned("ControlSourceData", &ap, _i, |_i| {
+        ap.parse_cobwcf(_i, mask, SitePropMask::CONTROL_SOURCE)
+    })?;
+    let (_i, row_source_data) = ctx.aligned("RowSourceData", &ap, _i, |_i| {
+        ap.parse_cobwcf(_i, mask, SitePropMask::ROW_SOURCE)
+    })?;
 
     let (_i, _) = ap.align(_i, 4)?; // Padding5 (variable): MUST be set to zero. The size of this field is the least number of bytes required to make the total size, in bytes, of this SiteDataBlock divisible by 4.
 
-    let (_i, name) = ap.parse_str(_i, mask, SitePropMask::NAME, name_data)?;
+    let (_i, name) = ctx.aligned("Name", &ap, _i, |_i| {
+        ap.parse_str(_i, mask, SitePropMask::NAME, name_data, ctx.encoding())
+    })?;
 
     //ap.align(_i, 4)?;
-    let (_i, tag) = ap.parse_str(_i, mask, SitePropMask::TAG, tag_data)?;
+    let (_i, tag) = ctx.aligned("Tag", &ap, _i, |_i| {
+        ap.parse_str(_i, mask, SitePropMask::TAG, tag_data, ctx.encoding())
+    })?;
 
     //ap.align(_i, 4)?;
-    let (_i, site_position) = ap.parse_position(_i, mask, SitePropMask::POSITION)?;
+    let (_i, site_position) = ctx.aligned("SitePosition", &ap, _i, |_i| {
+        ap.parse_position(_i, mask, SitePropMask::POSITION)
+    })?;
 
     //ap.align(_i, 4)?;
-    let (_i, control_tip_text) = ap.parse_str(
-        _i,
-        mask,
-        SitePropMask::CONTROL_TIP_TEXT,
-        control_tip_text_data,
-    )?;
+    let (_i, control_tip_text) = ctx.aligned("ControlTipText", &ap, _i, |_i| {
+        ap.parse_str(
+            _i,
+            mask,
+            SitePropMask::CONTROL_TIP_TEXT,
+            control_tip_text_data,
+            ctx.encoding(),
+        )
+    })?;
 
     //ap.align(_i, 4)?;
-    let (_i, runtime_lic_key) = ap.parse_str(
-        _i,
-        mask,
-        SitePropMask::RUNTIME_LIC_KEY,
-        runtime_lic_key_data,
-    )?;
+    let (_i, runtime_lic_key) = ctx.aligned("RuntimeLicKey", &ap, _i, |_i| {
+        ap.parse_str(
+            _i,
+            mask,
+            SitePropMask::RUNTIME_LIC_KEY,
+            runtime_lic_key_data,
+            ctx.encoding(),
+        )
+    })?;
 
     //ap.align(_i, 4)?;
-    let (_i, control_source) =
-        ap.parse_str(_i, mask, SitePropMask::CONTROL_SOURCE, control_source_data)?;
+    let (_i, control_source) = ctx.aligned("ControlSource", &ap, _i, |_i| {
+        ap.parse_str(
+            _i,
+            mask,
+            SitePropMask::CONTROL_SOURCE,
+            control_source_data,
+            ctx.encoding(),
+        )
+    })?;
 
     //ap.align(_i, 4)?;
-    let (_i, row_source) = ap.parse_str(_i, mask, SitePropMask::ROW_SOURCE, row_source_data)?;
+    let (_i, row_source) = ctx.aligned("RowSource", &ap, _i, |_i| {
+        ap.parse_str(
+            _i,
+            mask,
+            SitePropMask::ROW_SOURCE,
+            row_source_data,
+            ctx.encoding(),
+        )
+    })?;
+
+    ctx.check_mask_layout(&mask, ap.offset(), _i.len(), _i)?;
 
     Ok((
         _i,
@@ -275,6 +370,38 @@ where
             runtime_lic_key,
             control_source,
             row_source,
+            unknown_mask_bits: mask.bits() & !SitePropMask::all().bits(),
         },
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use nom::error::VerboseError;
+
+    use super::parse_ole_site_concrete_ref;
+    use crate::{controls::user_form::ole_site_concrete::write_ole_site_concrete, fixture::site};
+
+    #[test]
+    fn test_parse_ole_site_concrete_ref() {
+        let mut original = site(3, "Label1");
+        original.tag = String::from("Täg");
+        original.object_stream_size = 48;
+        let mut bytes = Vec::new();
+        write_ole_site_concrete(&mut bytes, &original).unwrap();
+
+        let (rest, parsed) = parse_ole_site_concrete_ref::<VerboseError<_>>(&bytes).unwrap();
+        assert!(rest.is_empty());
+        // The strings are not decoded, they point into the stream
+        assert_eq!(parsed.name.as_bytes(), b"Label1");
+        assert!(bytes
+            .as_ptr_range()
+            .contains(&parsed.name.as_bytes().as_ptr()));
+        assert!(parsed.name.is_compressed());
+        // Not ASCII, so the writer stores it as UTF-16
+        assert_eq!(parsed.tag.as_bytes(), b"T\0\xe4\0g\0");
+        assert!(!parsed.tag.is_compressed());
+        assert!(parsed.control_tip_text.as_bytes().is_empty());
+        assert_eq!(parsed.into_owned(), original);
+    }
+}
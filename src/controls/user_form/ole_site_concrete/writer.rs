@@ -0,0 +1,132 @@
+use std::io;
+
+use super::stream::*;
+use super::*;
+use crate::common::{write_with_header, AlignedWriter};
+use crate::properties::string::encode_fm_string;
+
+impl From<&Clsid> for ClsidCacheIndex {
+    fn from(clsid: &Clsid) -> Self {
+        match *clsid {
+            Clsid::ClassTable(i) => {
+                ClsidCacheIndex::from_bits_retain(i) | ClsidCacheIndex::IS_FROM_CLASS_TABLE
+            }
+            Clsid::Invalid => ClsidCacheIndex::INVALID,
+            Clsid::Global(i) => ClsidCacheIndex::from_bits_retain(i),
+        }
+    }
+}
+
+/// Write an [`OleSiteConcreteControl`], storing only the properties that differ from the file
+/// format default
+pub fn write_ole_site_concrete<S: AsRef<str>>(
+    out: &mut Vec<u8>,
+    site: &OleSiteConcreteControl<S>,
+) -> io::Result<()> {
+    let strings = [
+        (SitePropMask::NAME, site.name.as_ref()),
+        (SitePropMask::TAG, site.tag.as_ref()),
+        (
+            SitePropMask::CONTROL_TIP_TEXT,
+            site.control_tip_text.as_ref(),
+        ),
+        (SitePropMask::RUNTIME_LIC_KEY, site.runtime_lic_key.as_ref()),
+        (SitePropMask::CONTROL_SOURCE, site.control_source.as_ref()),
+        (SitePropMask::ROW_SOURCE, site.row_source.as_ref()),
+    ];
+    let mut mask = SitePropMask::empty();
+    for (flag, value) in &strings {
+        if !value.is_empty() {
+            mask |= *flag;
+        }
+    }
+    let default_flags =
+        SiteFlags::TAB_STOP | SiteFlags::VISIBLE | SiteFlags::STREAMED | SiteFlags::AUTO_SIZE;
+
+    let mut w = AlignedWriter::new();
+    w.le_u32(0);
+    let string = |w: &mut AlignedWriter, mask: SitePropMask, flag: SitePropMask, value: &str| {
+        if mask.contains(flag) {
+            w.length_and_compression(value)
+        } else {
+            Ok(())
+        }
+    };
+    string(&mut w, mask, SitePropMask::NAME, site.name.as_ref())?;
+    string(&mut w, mask, SitePropMask::TAG, site.tag.as_ref())?;
+    if site.id != 0 {
+        mask |= SitePropMask::ID;
+        w.le_i32(site.id);
+    }
+    if site.help_context_id != 0 {
+        mask |= SitePropMask::HELP_CONTEXT_ID;
+        w.le_i32(site.help_context_id);
+    }
+    if site.bit_flags != default_flags {
+        mask |= SitePropMask::BIT_FLAGS;
+        w.le_u32(site.bit_flags.bits());
+    }
+    if site.object_stream_size != 0 {
+        mask |= SitePropMask::OBJECT_STREAM_SIZE;
+        w.le_u32(site.object_stream_size);
+    }
+    if site.tab_index != -1 {
+        mask |= SitePropMask::TAB_INDEX;
+        w.le_i16(site.tab_index);
+    }
+    if site.clsid_cache_index != Clsid::Invalid {
+        mask |= SitePropMask::CLSID_CACHE_INDEX;
+        w.le_u16(ClsidCacheIndex::from(&site.clsid_cache_index).bits());
+    }
+    if let Some(group_id) = site.group_id {
+        mask |= SitePropMask::GROUP_ID;
+        w.le_u16(group_id.get());
+    }
+    string(
+        &mut w,
+        mask,
+        SitePropMask::CONTROL_TIP_TEXT,
+        site.control_tip_text.as_ref(),
+    )?;
+    string(
+        &mut w,
+        mask,
+        SitePropMask::RUNTIME_LIC_KEY,
+        site.runtime_lic_key.as_ref(),
+    )?;
+    string(
+        &mut w,
+        mask,
+        SitePropMask::CONTROL_SOURCE,
+        site.control_source.as_ref(),
+    )?;
+    string(
+        &mut w,
+        mask,
+        SitePropMask::ROW_SOURCE,
+        site.row_source.as_ref(),
+    )?;
+    w.align(4);
+
+    // Extra Data Block, the strings are not padded
+    for (flag, value) in &strings[..2] {
+        if mask.contains(*flag) {
+            w.bytes(&encode_fm_string(value)?.1);
+        }
+    }
+    if site.site_position != Position::default() {
+        mask |= SitePropMask::POSITION;
+        w.fm_position(site.site_position);
+    }
+    for (flag, value) in &strings[2..] {
+        if mask.contains(*flag) {
+            w.bytes(&encode_fm_string(value)?.1);
+        }
+    }
+
+    w.patch_u32(
+        0,
+        mask.bits() | site.unknown_mask_bits & !SitePropMask::all().bits(),
+    );
+    write_with_header(out, [0x00, 0x00], &w.finish())
+}
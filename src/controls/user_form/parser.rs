@@ -1,4 +1,4 @@
-use nom::bytes::complete::tag;
+use nom::bytes::complete::{tag, take};
 use nom::combinator::{map, map_opt, success, verify};
 use nom::error::{context, ContextError, FromExternalError, ParseError};
 use nom::multi::count;
@@ -8,18 +8,18 @@ use nom::IResult;
 use num_traits::FromPrimitive;
 use uuid::Uuid;
 
-use super::ole_site_concrete::parse_ole_site_concrete;
+use super::ole_site_concrete::parse_ole_site_concrete_ref_with;
 use super::{
-    stream::*, BorderStyle, ClsTableFlags, Cycle, FormControl, FormFlags, FormScrollBarFlags, Site,
-    SiteClassInfo, SiteKind,
+    stream::*, BorderStyle, ClsTableFlags, Cycle, FormControl, FormControlRef, FormFlags,
+    FormScrollBarFlags, Site, SiteClassInfo, SiteKind,
 };
-use crate::common::{parse_guid, AlignedParser, VarFlags, VarType, IID_IDISPATCH};
+use crate::common::{parse_guid, AlignedParser, ParseContext, VarFlags, VarType, IID_IDISPATCH};
 use crate::properties::font::GuidAndFont;
-use crate::properties::picture::GuidAndPicture;
+use crate::properties::picture::{parse_guid_and_picture, GuidAndPicture};
 use crate::properties::{
     color::OleColor,
     font::parse_guid_and_font,
-    string::{parse_string, stream::CountOfBytesWithCompressionFlag},
+    string::{parse_str_ref, stream::CountOfBytesWithCompressionFlag, FmStr},
 };
 use crate::properties::{
     MousePointer, PictureAlignment, PictureSizeMode, Position, Size, SpecialEffect,
@@ -39,6 +39,17 @@ where
 }
 
 pub fn parse_site_class_info<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], SiteClassInfo, E>
+where
+    E: ParseError<&'a [u8]>,
+{
+    parse_site_class_info_with(input, &ParseContext::default())
+}
+
+/// Parse a [`SiteClassInfo`] with the [`ParseMode`][crate::common::ParseMode] of `ctx`
+pub fn parse_site_class_info_with<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], SiteClassInfo, E>
 where
     E: ParseError<&'a [u8]>,
 {
@@ -46,91 +57,101 @@ where
     let _i = input;
 
     // Class Header
-    let (_i, _cb_class_table) = parse_site_class_info_header(_i)?;
+    let (_i, cb_class_table) = ctx.field("Header", _i, parse_site_class_info_header)?;
 
     // Mask
-    let (_i, mask) = ap.bitfield32(_i, ClassInfoPropMask::from_bits)?;
+    let (_i, mask) = ctx.aligned("PropMask", &ap, _i, |_i| {
+        ap.bitfield32(_i, ctx.flags::<ClassInfoPropMask>())
+    })?;
 
     // Class Table Flags
     let (_i, class_table_flags) = if mask.contains(ClassInfoPropMask::CLASS_FLAGS) {
-        ap.bitfield16(_i, ClsTableFlags::from_bits)?
+        ctx.aligned("ClassTableFlags", &ap, _i, |_i| {
+            ap.bitfield16(_i, ctx.flags::<ClsTableFlags>())
+        })?
     } else {
         (_i, ClsTableFlags::empty())
     };
 
     // Var Flags
     let (_i, var_flags) = if mask.contains(ClassInfoPropMask::CLASS_FLAGS) {
-        ap.bitfield16(_i, VarFlags::from_bits)?
+        ctx.aligned("VarFlags", &ap, _i, |_i| {
+            ap.bitfield16(_i, ctx.flags::<VarFlags>())
+        })?
     } else {
         (_i, VarFlags::empty())
     };
 
     // Count of Methods
     let (_i, count_of_methods) = if mask.contains(ClassInfoPropMask::COUNT_OF_METHODS) {
-        ap.le_u32(_i)?
+        ctx.aligned("CountOfMethods", &ap, _i, |_i| ap.le_u32(_i))?
     } else {
         (_i, 0x00000000)
     };
 
     // DispID Bind
     let (_i, dispid_bind) = if mask.contains(ClassInfoPropMask::DISPID_BIND) {
-        ap.le_u32(_i)?
+        ctx.aligned("DispidBind", &ap, _i, |_i| ap.le_u32(_i))?
     } else {
         (_i, 0xFFFFFFFF)
     };
 
     // Get Bind Index
     let (_i, get_bind_index) = if mask.contains(ClassInfoPropMask::GET_BIND_INDEX) {
-        ap.le_u16(_i)?
+        ctx.aligned("GetBindIndex", &ap, _i, |_i| ap.le_u16(_i))?
     } else {
         (_i, 0x0000)
     };
 
     // Put Bind Index
     let (_i, put_bind_index) = if mask.contains(ClassInfoPropMask::PUT_BIND_INDEX) {
-        ap.le_u16(_i)?
+        ctx.aligned("PutBindIndex", &ap, _i, |_i| ap.le_u16(_i))?
     } else {
         (_i, 0x0000)
     };
 
     // Bind Type
     let (_i, bind_type) = if mask.contains(ClassInfoPropMask::BIND_TYPE) {
-        ap.bitfield16(_i, VarType::from_bits)?
+        ctx.aligned("BindType", &ap, _i, |_i| {
+            ap.bitfield16(_i, VarType::from_bits)
+        })?
     } else {
         (_i, VarType::EMPTY)
     };
 
     // Get Value Index
     let (_i, get_value_index) = if mask.contains(ClassInfoPropMask::GET_VALUE_INDEX) {
-        ap.le_u16(_i)?
+        ctx.aligned("GetValueIndex", &ap, _i, |_i| ap.le_u16(_i))?
     } else {
         (_i, 0x0000)
     };
 
     // Put Value Index
     let (_i, put_value_index) = if mask.contains(ClassInfoPropMask::PUT_VALUE_INDEX) {
-        ap.le_u16(_i)?
+        ctx.aligned("PutValueIndex", &ap, _i, |_i| ap.le_u16(_i))?
     } else {
         (_i, 0x0000)
     };
 
     // Value Type
     let (_i, value_type) = if mask.contains(ClassInfoPropMask::VALUE_TYPE) {
-        ap.bitfield16(_i, VarType::from_bits)?
+        ctx.aligned("ValueType", &ap, _i, |_i| {
+            ap.bitfield16(_i, VarType::from_bits)
+        })?
     } else {
         (_i, VarType::EMPTY)
     };
 
     // DispID Rowset
     let (_i, dispid_rowset) = if mask.contains(ClassInfoPropMask::DISPID_ROWSET) {
-        ap.le_u32(_i)?
+        ctx.aligned("DispidRowset", &ap, _i, |_i| ap.le_u32(_i))?
     } else {
         (_i, 0xFFFFFFFF)
     };
 
     // Set Rowset
     let (_i, set_rowset) = if mask.contains(ClassInfoPropMask::SET_ROWSET) {
-        ap.le_u16(_i)?
+        ctx.aligned("SetRowset", &ap, _i, |_i| ap.le_u16(_i))?
     } else {
         (_i, 0x0000)
     };
@@ -139,25 +160,28 @@ where
 
     // CLS ID
     let (_i, cls_id) = if mask.contains(ClassInfoPropMask::CLS_ID) {
-        parse_guid(_i)?
+        ctx.field("ClsId", _i, |_i| parse_guid(_i))?
     } else {
         (_i, Uuid::nil())
     };
 
     // Disp Event
     let (_i, disp_event) = if mask.contains(ClassInfoPropMask::DISP_EVENT) {
-        parse_guid(_i)?
+        ctx.field("DispEvent", _i, |_i| parse_guid(_i))?
     } else {
         (_i, IID_IDISPATCH)
     };
 
     // Default Proc
     let (_i, default_proc) = if mask.contains(ClassInfoPropMask::DEFAULT_PROC) {
-        parse_guid(_i)?
+        ctx.field("DefaultProc", _i, |_i| parse_guid(_i))?
     } else {
         (_i, IID_IDISPATCH)
     };
 
+    let left = usize::from(cb_class_table).saturating_sub(ap.offset());
+    ctx.check_mask_layout(&mask, ap.offset(), left, _i)?;
+
     Ok((
         _i,
         SiteClassInfo {
@@ -176,6 +200,7 @@ where
             cls_id,
             disp_event,
             default_proc,
+            unknown_mask_bits: mask.bits() & !ClassInfoPropMask::all().bits(),
         },
     ))
 }
@@ -241,29 +266,102 @@ where
 pub fn parse_sites<'a, E>(
     site_depths_and_types: Vec<SiteDepthAndType>,
 ) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], Vec<Site>, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    map(parse_sites_ref(site_depths_and_types), |sites| {
+        sites
+            .into_iter()
+            .map(|site| site.map_strings(String::from))
+            .collect()
+    })
+}
+
+/// Parse the sites without decoding their strings
+pub fn parse_sites_ref<'a, E>(
+    site_depths_and_types: Vec<SiteDepthAndType>,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], Vec<Site<FmStr<'a>>>, E>
 where
     E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
 {
     move |input: &'a [u8]| {
-        let mut result = Vec::with_capacity(site_depths_and_types.len());
-        let mut data = input;
-        for site_depth_and_type in &site_depths_and_types {
-            let (rest, site) = match site_depth_and_type.r#type {
-                SiteType::Ole => map(context("ole_site_concrete", parse_ole_site_concrete), |x| {
-                    Site {
-                        kind: SiteKind::Ole(x),
-                        depth: site_depth_and_type.depth,
-                    }
-                })(data)?,
-            };
-            result.push(site);
-            data = rest;
-        }
-        Ok((data, result))
+        parse_sites_ref_with(input, &site_depths_and_types, &ParseContext::default())
+    }
+}
+
+/// Parse the sites without decoding their strings, with the
+/// [`ParseMode`][crate::common::ParseMode] of `ctx`
+pub fn parse_sites_ref_with<'a, E>(
+    input: &'a [u8],
+    site_depths_and_types: &[SiteDepthAndType],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], Vec<Site<FmStr<'a>>>, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    let mut result = Vec::with_capacity(site_depths_and_types.len());
+    let mut data = input;
+    for site_depth_and_type in site_depths_and_types {
+        let (rest, site) = match site_depth_and_type.r#type {
+            SiteType::Ole => map(
+                context("ole_site_concrete", |i| {
+                    ctx.field("OleSiteConcrete", i, |i| {
+                        parse_ole_site_concrete_ref_with(i, ctx)
+                    })
+                }),
+                |x| Site {
+                    kind: SiteKind::Ole(x),
+                    depth: site_depth_and_type.depth,
+                },
+            )(data)?,
+        };
+        result.push(site);
+        data = rest;
     }
+    Ok((data, result))
 }
 
 pub fn parse_form_control<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], FormControl, E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+    E: ContextError<&'a [u8]>,
+{
+    map(parse_form_control_ref, FormControlRef::into_owned)(input)
+}
+
+/// Parse a [`FormControl`] with the [`ParseMode`][crate::common::ParseMode] of `ctx`
+pub fn parse_form_control_with<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], FormControl, E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+    E: ContextError<&'a [u8]>,
+{
+    map(
+        |i| parse_form_control_ref_with(i, ctx),
+        FormControlRef::into_owned,
+    )(input)
+}
+
+/// Parse a [`FormControl`] without decoding the strings
+pub fn parse_form_control_ref<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], FormControlRef<'a>, E>
+where
+    E: ParseError<&'a [u8]>,
+    E: FromExternalError<&'a [u8], u32>,
+    E: ContextError<&'a [u8]>,
+{
+    parse_form_control_ref_with(input, &ParseContext::default())
+}
+
+/// Parse a [`FormControl`] without decoding the strings, with the
+/// [`ParseMode`][crate::common::ParseMode] of `ctx`
+pub fn parse_form_control_ref_with<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], FormControlRef<'a>, E>
 where
     E: ParseError<&'a [u8]>,
     E: FromExternalError<&'a [u8], u32>,
@@ -273,150 +371,180 @@ where
     let _i = input;
 
     // Form Control Header
-    let (_i, _cb_form) = context("form_control_header", parse_form_control_header)(_i)?;
+    let (_i, cb_form) = ctx.field(
+        "Header",
+        _i,
+        context("form_control_header", parse_form_control_header),
+    )?;
+    let data_block = _i;
 
     // Mask
-    let (_i, mask) = ap.bitfield32(_i, FormPropMask::from_bits)?;
+    let (_i, mask) = ctx.aligned("PropMask", &ap, _i, |_i| {
+        ap.bitfield32(_i, ctx.flags::<FormPropMask>())
+    })?;
 
     // Picture Tiling
     let picture_tiling = mask.contains(FormPropMask::PICTURE_TILING);
 
     // Back Color
     let (_i, back_color) = if mask.contains(FormPropMask::BACK_COLOR) {
-        ap.ole_color(_i)?
+        ctx.aligned("BackColor", &ap, _i, |_i| ap.ole_color(_i))?
     } else {
         (_i, OleColor::BTNFACE)
     };
 
     // Fore color
     let (_i, fore_color) = if mask.contains(FormPropMask::FORE_COLOR) {
-        ap.ole_color(_i)?
+        ctx.aligned("ForeColor", &ap, _i, |_i| ap.ole_color(_i))?
     } else {
         (_i, OleColor::BTNTEXT)
     };
 
     // Next available ID
     let (_i, next_available_id) = if mask.contains(FormPropMask::NEXT_AVAILABLE_ID) {
-        ap.le_u32(_i)?
+        ctx.aligned("NextAvailableId", &ap, _i, |_i| ap.le_u32(_i))?
     } else {
         (_i, 0x00000000)
     };
 
     // Boolean Properties
     let (_i, boolean_properties) = if mask.contains(FormPropMask::BOOLEAN_PROPERTIES) {
-        ap.bitfield32(_i, FormFlags::from_bits)?
+        ctx.aligned("BooleanProperties", &ap, _i, |_i| {
+            ap.bitfield32(_i, ctx.flags::<FormFlags>())
+        })?
     } else {
         (_i, FormFlags::ENABLED)
     };
 
     // Border style
     let (_i, border_style) = if mask.contains(FormPropMask::BORDER_STYLE) {
-        ap.bitfield8(_i, BorderStyle::from_u8)?
+        ctx.aligned("BorderStyle", &ap, _i, |_i| {
+            ap.bitfield8(_i, BorderStyle::from_u8)
+        })?
     } else {
         (_i, BorderStyle::None)
     };
 
     // Mouse Pointer
     let (_i, mouse_pointer) = if mask.contains(FormPropMask::MOUSE_POINTER) {
-        ap.bitfield8(_i, MousePointer::from_u8)?
+        ctx.aligned("MousePointer", &ap, _i, |_i| {
+            ap.bitfield8(_i, MousePointer::from_u8)
+        })?
     } else {
         (_i, MousePointer::Default)
     };
 
     // Scroll Bars
     let (_i, scroll_bars) = if mask.contains(FormPropMask::SCROLL_BARS) {
-        ap.bitfield8(_i, FormScrollBarFlags::from_bits)?
+        ctx.aligned("ScrollBars", &ap, _i, |_i| {
+            ap.bitfield8(_i, ctx.flags::<FormScrollBarFlags>())
+        })?
     } else {
         (_i, FormScrollBarFlags::DEFAULT)
     };
 
     // Group Count
     let (_i, group_count) = if mask.contains(FormPropMask::GROUP_CNT) {
-        ap.le_u32(_i)?
+        ctx.aligned("GroupCount", &ap, _i, |_i| ap.le_u32(_i))?
     } else {
         (_i, 0x00000000)
     };
 
     // Mouse Icon
     let (_i, _mouse_icon) = if mask.contains(FormPropMask::MOUSE_ICON) {
-        verify(|i| ap.le_u16(i), |x| *x == 0xFFFF)(_i)?
+        ctx.aligned("MouseIcon", &ap, _i, |_i| {
+            verify(|i| ap.le_u16(i), |x| *x == 0xFFFF)(_i)
+        })?
     } else {
         (_i, 0)
     };
 
     // Cycle
     let (_i, cycle) = if mask.contains(FormPropMask::CYCLE) {
-        ap.bitfield8(_i, Cycle::from_u8)?
+        ctx.aligned("Cycle", &ap, _i, |_i| ap.bitfield8(_i, Cycle::from_u8))?
     } else {
         (_i, Cycle::AllForms)
     };
 
     // Special Effect
     let (_i, special_effect) = if mask.contains(FormPropMask::SPECIAL_EFFECT) {
-        ap.bitfield8(_i, SpecialEffect::from_u8)?
+        ctx.aligned("SpecialEffect", &ap, _i, |_i| {
+            ap.bitfield8(_i, SpecialEffect::from_u8)
+        })?
     } else {
         (_i, SpecialEffect::Flat)
     };
 
     // Border Color
     let (_i, border_color) = if mask.contains(FormPropMask::BORDER_COLOR) {
-        ap.ole_color(_i)?
+        ctx.aligned("BorderColor", &ap, _i, |_i| ap.ole_color(_i))?
     } else {
         (_i, OleColor::BTNTEXT)
     };
 
     // Caption (length and compression)
     let (_i, caption_length) = if mask.contains(FormPropMask::CAPTION) {
-        ap.bitfield32(_i, CountOfBytesWithCompressionFlag::from_bits)?
+        ctx.aligned("CaptionLength", &ap, _i, |_i| {
+            ap.bitfield32(_i, CountOfBytesWithCompressionFlag::from_bits)
+        })?
     } else {
         (_i, CountOfBytesWithCompressionFlag::EMPTY)
     };
 
     // Mouse Icon
     let (_i, _font) = if mask.contains(FormPropMask::FONT) {
-        verify(|i| ap.le_u16(i), |x| *x == 0xFFFF)(_i)
+        ctx.aligned("Font", &ap, _i, verify(|i| ap.le_u16(i), |x| *x == 0xFFFF))
     } else {
         Ok((_i, 0))
     }?;
 
     // Picture
     let (_i, _picture) = if mask.contains(FormPropMask::PICTURE) {
-        verify(|i| ap.le_u16(i), |x| *x == 0xFFFF)(_i)
+        ctx.aligned(
+            "Picture",
+            &ap,
+            _i,
+            verify(|i| ap.le_u16(i), |x| *x == 0xFFFF),
+        )
     } else {
         Ok((_i, 0))
     }?;
 
     // Zoom
     let (_i, zoom) = if mask.contains(FormPropMask::ZOOM) {
-        ap.le_u32(_i)?
+        ctx.aligned("Zoom", &ap, _i, |_i| ap.le_u32(_i))?
     } else {
         (_i, 100u32)
     };
 
     // Picture Alignment
     let (_i, picture_alignment) = if mask.contains(FormPropMask::PICTURE_ALIGNMENT) {
-        ap.bitfield8(_i, PictureAlignment::from_u8)?
+        ctx.aligned("PictureAlignment", &ap, _i, |_i| {
+            ap.bitfield8(_i, PictureAlignment::from_u8)
+        })?
     } else {
         (_i, PictureAlignment::Center)
     };
 
     // Picture Size Mode
     let (_i, picture_size_mode) = if mask.contains(FormPropMask::PICTURE_SIZE_MODE) {
-        ap.bitfield8(_i, PictureSizeMode::from_u8)?
+        ctx.aligned("PictureSizeMode", &ap, _i, |_i| {
+            ap.bitfield8(_i, PictureSizeMode::from_u8)
+        })?
     } else {
         (_i, PictureSizeMode::Clip)
     };
 
     // Shape Cookie
     let (_i, shape_cookie) = if mask.contains(FormPropMask::SHAPE_COOKIE) {
-        ap.le_u32(_i)?
+        ctx.aligned("ShapeCookie", &ap, _i, |_i| ap.le_u32(_i))?
     } else {
         (_i, 0u32)
     };
 
     // Draw Buffer
     let (_i, draw_buffer) = if mask.contains(FormPropMask::DRAW_BUFFER) {
-        ap.le_u32(_i)?
+        ctx.aligned("DrawBuffer", &ap, _i, |_i| ap.le_u32(_i))?
     } else {
         (_i, 0u32)
     };
@@ -425,7 +553,7 @@ where
 
     // Displayed Size
     let (_i, displayed_size) = if mask.contains(FormPropMask::DISPLAYED_SIZE) {
-        let (_ir, displayed_size) = Size::parse(_i)?;
+        let (_ir, displayed_size) = ctx.field("DisplayedSize", _i, Size::parse)?;
         ap.inc(8);
         (_ir, displayed_size)
     } else {
@@ -434,7 +562,7 @@ where
 
     // Logical Size
     let (_i, logical_size) = if mask.contains(FormPropMask::LOGICAL_SIZE) {
-        let (_ir, logical_size) = Size::parse(_i)?;
+        let (_ir, logical_size) = ctx.field("LogicalSize", _i, Size::parse)?;
         ap.inc(8);
         (_ir, logical_size)
     } else {
@@ -443,7 +571,7 @@ where
 
     // Scroll Position
     let (_i, scroll_position) = if mask.contains(FormPropMask::SCROLL_POSITION) {
-        let (_ir, scroll_position) = Position::parse(_i)?;
+        let (_ir, scroll_position) = ctx.field("ScrollPosition", _i, Position::parse)?;
         ap.inc(8);
         (_ir, scroll_position)
     } else {
@@ -452,52 +580,80 @@ where
 
     // Caption
     let (_i, caption) = if mask.contains(FormPropMask::CAPTION) {
-        context("caption", parse_string(caption_length))(_i)?
+        ctx.field("Caption", _i, |_i| {
+            context(
+                "caption",
+                map(parse_str_ref(caption_length), |s| {
+                    s.with_encoding(ctx.encoding())
+                }),
+            )(_i)
+        })?
     } else {
-        (_i, String::from(""))
+        (_i, FmStr::default())
     };
 
-    // Picture
-    // TODO
+    // Skip the padding at the end of the ExtraDataBlock
+    let read = data_block.len() - _i.len();
+    let left = usize::from(cb_form).saturating_sub(read);
+    ctx.check_mask_layout(&mask, read, left, _i)?;
+    let (_i, _padding) = ctx.field("Padding", _i, take(left))?;
 
     // Mouse Icon
-    let (_i, mouse_icon) = (_i, GuidAndPicture::EMPTY);
+    let (_i, mouse_icon) = if mask.contains(FormPropMask::MOUSE_ICON) {
+        ctx.field("MouseIcon", _i, |_i| {
+            context("mouse_icon", parse_guid_and_picture)(_i)
+        })?
+    } else {
+        (_i, GuidAndPicture::EMPTY)
+    };
 
     // Font
     let (_i, font) = if mask.contains(FormPropMask::FONT) {
-        context("font", parse_guid_and_font)(_i)?
+        ctx.field("Font", _i, |_i| context("font", parse_guid_and_font)(_i))?
     } else {
         (_i, GuidAndFont::EMPTY)
     };
 
     // Picture
-    // TODO
-
-    let (_i, picture) = (_i, GuidAndPicture::EMPTY);
+    let (_i, picture) = if mask.contains(FormPropMask::PICTURE) {
+        ctx.field("Picture", _i, |_i| {
+            context("picture", parse_guid_and_picture)(_i)
+        })?
+    } else {
+        (_i, GuidAndPicture::EMPTY)
+    };
 
     // Size Class Info (count)
     let (_i, count_of_site_class_info) =
         if boolean_properties.contains(FormFlags::DONTSAVECLASSTABLE) {
             (_i, 0)
         } else {
-            let (_ir, x) = le_u16(_i)?;
+            let (_ir, x) = ctx.field("CountOfSiteClassInfo", _i, le_u16)?;
             (_ir, x as usize)
         };
 
     let (_i, site_classes) = context(
         "site_classes",
-        count(parse_site_class_info, count_of_site_class_info),
+        count(
+            |i| ctx.field("SiteClassInfo", i, |i| parse_site_class_info_with(i, ctx)),
+            count_of_site_class_info,
+        ),
     )(_i)?;
 
     // TODO: DesignEx?
-    let (_i, count_of_sites) = le_u32(_i)?;
-    let (_i, _count_of_bytes) = le_u32(_i)?;
-
-    let (_i, site_depths_and_types) = context(
-        "site_depths_and_types",
-        parse_site_depths_and_types(count_of_sites),
-    )(_i)?;
-    let (_i, sites) = context("sites", parse_sites(site_depths_and_types))(_i)?;
+    let (_i, count_of_sites) = ctx.field("CountOfSites", _i, le_u32)?;
+    let (_i, _count_of_bytes) = ctx.field("CountOfBytes", _i, le_u32)?;
+
+    let (_i, site_depths_and_types) = context("site_depths_and_types", |i| {
+        ctx.field(
+            "SiteDepthsAndTypes",
+            i,
+            parse_site_depths_and_types(count_of_sites),
+        )
+    })(_i)?;
+    let (_i, sites) = context("sites", |i| {
+        parse_sites_ref_with(i, &site_depths_and_types, ctx)
+    })(_i)?;
 
     Ok((
         _i,
@@ -528,6 +684,113 @@ where
             picture_tiling,
             sites,
             site_classes,
+            unknown_mask_bits: mask.bits() & !FormPropMask::all().bits(),
         },
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use nom::error::VerboseError;
+
+    use super::{parse_form_control, parse_form_control_ref, parse_form_control_with};
+    use crate::common::{ParseContext, ParseMode, UnknownBits};
+    use crate::controls::user_form::{write_form_control, FormControl, FormFlags, Site, SiteKind};
+    use crate::fixture::site;
+    use crate::properties::color::OleColor;
+
+    #[test]
+    fn test_parse_form_control_ref() {
+        let mut label = site(1, "Label1");
+        label.tag = String::from("tag");
+        let form = FormControl {
+            caption: String::from("Form1"),
+            draw_buffer: 32000,
+            next_available_id: 2,
+            sites: vec![Site {
+                depth: 0,
+                kind: SiteKind::Ole(label),
+            }],
+            ..FormControl::<String>::default()
+        };
+        let mut bytes = Vec::new();
+        write_form_control(&mut bytes, &form).unwrap();
+
+        let (rest, parsed) = parse_form_control_ref::<VerboseError<_>>(&bytes).unwrap();
+        assert!(rest.is_empty());
+        // The strings are not decoded, they point into the stream
+        assert_eq!(parsed.caption.as_bytes(), b"Form1");
+        assert!(bytes
+            .as_ptr_range()
+            .contains(&parsed.caption.as_bytes().as_ptr()));
+        match &parsed.sites[..] {
+            [Site {
+                kind: SiteKind::Ole(site),
+                ..
+            }] => assert_eq!(site.tag.as_bytes(), b"tag"),
+            sites => panic!("unexpected {:?}", sites),
+        }
+        assert_eq!(parsed.into_owned(), form);
+    }
+
+    #[test]
+    fn test_unknown_bits() {
+        let form = FormControl {
+            boolean_properties: FormFlags::ENABLED | FormFlags::from_bits_retain(0x0001_0000),
+            ..FormControl::<String>::default()
+        };
+        let mut bytes = Vec::new();
+        write_form_control(&mut bytes, &form).unwrap();
+        // an unknown bit in the mask, after the header
+        bytes[7] |= 0x80;
+
+        assert!(parse_form_control::<VerboseError<_>>(&bytes).is_err());
+
+        let ctx = ParseContext::new(ParseMode::Lenient);
+        let (_, parsed) = parse_form_control_with::<VerboseError<_>>(&bytes, &ctx).unwrap();
+        assert_eq!(parsed.boolean_properties, form.boolean_properties);
+        assert_eq!(parsed.unknown_mask_bits, 0x8000_0000);
+        let mut written = Vec::new();
+        write_form_control(&mut written, &parsed).unwrap();
+        assert_eq!(written, bytes);
+        assert_eq!(
+            ctx.warnings(),
+            [
+                UnknownBits {
+                    name: "FormPropMask",
+                    bits: 0x8000_0000,
+                },
+                UnknownBits {
+                    name: "FormFlags",
+                    bits: 0x0001_0000,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_properties_with_data() {
+        let form = FormControl {
+            back_color: OleColor::WINDOWTEXT,
+            ..FormControl::<String>::default()
+        };
+        let mut bytes = Vec::new();
+        write_form_control(&mut bytes, &form).unwrap();
+        // an unknown bit instead of the BackColor
+        bytes[4] &= !0x02;
+        bytes[7] |= 0x80;
+
+        let ctx = ParseContext::new(ParseMode::Lenient);
+        assert!(matches!(
+            parse_form_control_with::<VerboseError<_>>(&bytes, &ctx),
+            Err(nom::Err::Failure(_))
+        ));
+        assert_eq!(
+            ctx.take_unreadable(),
+            Some(UnknownBits {
+                name: "FormPropMask",
+                bits: 0x8000_0000,
+            })
+        );
+    }
+}
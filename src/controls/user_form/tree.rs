@@ -0,0 +1,212 @@
+//! Parent-child links between the sites of a [`FormControl`]
+//!
+//! The `SiteDepthsAndTypes` of a form list the depth of every site in the control hierarchy.
+//! A site at depth `n + 1` is a child of the closest preceding site at depth `n`.
+
+use std::collections::BTreeMap;
+
+use super::{FormControl, Site, SiteKind};
+
+/// A problem with the depth values of the sites
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SiteTreeIssue {
+    /// The site at `index` has no preceding site at depth `depth - 1`
+    Orphan { index: usize, id: i32, depth: u8 },
+    /// The site at `index` uses the same ID as an earlier site
+    DuplicateId { index: usize, id: i32 },
+}
+
+/// A node in a [`SiteTree`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiteNode {
+    /// The index into [`FormControl::sites`]
+    pub index: usize,
+    /// The depth of the site
+    pub depth: u8,
+    /// The ID of the parent site, if any
+    pub parent: Option<i32>,
+    /// The IDs of the child sites, in file order
+    pub children: Vec<i32>,
+}
+
+/// The hierarchy of the sites of a form, keyed by control ID
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SiteTree {
+    nodes: BTreeMap<i32, SiteNode>,
+    roots: Vec<i32>,
+    issues: Vec<SiteTreeIssue>,
+}
+
+fn site_id<S>(site: &Site<S>) -> i32 {
+    match &site.kind {
+        SiteKind::Ole(ole) => ole.id,
+    }
+}
+
+impl SiteTree {
+    /// Build the tree from the sites of a form
+    ///
+    /// Orphaned sites are added as roots, later sites with a duplicate ID are skipped, so their
+    /// children are orphans. Both are reported in [`SiteTree::issues`].
+    pub fn new<S>(sites: &[Site<S>]) -> Self {
+        let mut tree = SiteTree::default();
+        // The IDs of the current ancestors, indexed by depth
+        let mut stack: Vec<i32> = Vec::new();
+        for (index, site) in sites.iter().enumerate() {
+            let id = site_id(site);
+            // A skipped duplicate still ends the subtrees of its depth
+            let depth = usize::from(site.depth);
+            stack.truncate(depth);
+            if tree.nodes.contains_key(&id) {
+                tree.issues.push(SiteTreeIssue::DuplicateId { index, id });
+                continue;
+            }
+            let parent = match depth {
+                0 => None,
+                _ if depth == stack.len() => Some(stack[depth - 1]),
+                _ => {
+                    // Orphans can't be parents either, as their depth is not part of the stack
+                    tree.issues.push(SiteTreeIssue::Orphan {
+                        index,
+                        id,
+                        depth: site.depth,
+                    });
+                    None
+                }
+            };
+            match parent {
+                Some(p) => tree.nodes.get_mut(&p).unwrap().children.push(id),
+                None => tree.roots.push(id),
+            }
+            if depth == stack.len() {
+                stack.push(id);
+            }
+            tree.nodes.insert(
+                id,
+                SiteNode {
+                    index,
+                    depth: site.depth,
+                    parent,
+                    children: Vec::new(),
+                },
+            );
+        }
+        tree
+    }
+
+    /// The IDs of all sites without a parent, in file order
+    pub fn roots(&self) -> &[i32] {
+        &self.roots
+    }
+
+    /// Get the node for a control ID
+    pub fn get(&self, id: i32) -> Option<&SiteNode> {
+        self.nodes.get(&id)
+    }
+
+    /// Get the ID of the parent of a control
+    pub fn parent(&self, id: i32) -> Option<i32> {
+        self.nodes.get(&id).and_then(|n| n.parent)
+    }
+
+    /// Get the IDs of the children of a control
+    pub fn children(&self, id: i32) -> &[i32] {
+        self.nodes.get(&id).map_or(&[], |n| &n.children)
+    }
+
+    /// Iterate over all nodes, ordered by control ID
+    pub fn iter(&self) -> impl Iterator<Item = (i32, &SiteNode)> {
+        self.nodes.iter().map(|(id, node)| (*id, node))
+    }
+
+    /// All inconsistencies found while building the tree
+    pub fn issues(&self) -> &[SiteTreeIssue] {
+        &self.issues
+    }
+
+    /// The IDs of all orphaned sites
+    pub fn orphans(&self) -> impl Iterator<Item = i32> + '_ {
+        self.issues.iter().filter_map(|i| match i {
+            SiteTreeIssue::Orphan { id, .. } => Some(*id),
+            SiteTreeIssue::DuplicateId { .. } => None,
+        })
+    }
+
+    /// Whether all depths are consistent and all IDs are unique
+    pub fn is_consistent(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl<S> FormControl<S> {
+    /// Build the [`SiteTree`] of this form
+    pub fn site_tree(&self) -> SiteTree {
+        SiteTree::new(&self.sites)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SiteTree, SiteTreeIssue};
+    use crate::controls::user_form::{ole_site_concrete::OleSiteConcreteControl, Site, SiteKind};
+
+    fn site(id: i32, depth: u8) -> Site {
+        Site {
+            depth,
+            kind: SiteKind::Ole(OleSiteConcreteControl {
+                id,
+                ..OleSiteConcreteControl::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn test_site_tree() {
+        let sites = [
+            site(1, 0),
+            site(2, 1),
+            site(3, 2),
+            site(4, 1),
+            site(5, 0),
+            site(6, 2),
+            site(7, 1),
+            site(4, 0),
+        ];
+        let tree = SiteTree::new(&sites);
+        assert_eq!(tree.roots(), &[1, 5, 6]);
+        assert_eq!(tree.children(1), &[2, 4]);
+        assert_eq!(tree.children(2), &[3]);
+        assert_eq!(tree.children(5), &[7]);
+        assert_eq!(tree.parent(3), Some(2));
+        assert_eq!(tree.parent(5), None);
+        assert_eq!(tree.orphans().collect::<Vec<_>>(), vec![6]);
+        assert_eq!(
+            tree.issues(),
+            &[
+                SiteTreeIssue::Orphan {
+                    index: 5,
+                    id: 6,
+                    depth: 2
+                },
+                SiteTreeIssue::DuplicateId { index: 7, id: 4 },
+            ]
+        );
+
+        // The children of a duplicate are not added to the previous site of its depth
+        let tree = SiteTree::new(&[site(1, 0), site(2, 1), site(1, 0), site(3, 1)]);
+        assert_eq!(tree.roots(), &[1, 3]);
+        assert_eq!(tree.children(1), &[2]);
+        assert_eq!(tree.parent(3), None);
+        assert_eq!(
+            tree.issues(),
+            &[
+                SiteTreeIssue::DuplicateId { index: 2, id: 1 },
+                SiteTreeIssue::Orphan {
+                    index: 3,
+                    id: 3,
+                    depth: 1
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,263 @@
+use std::{convert::TryFrom, io};
+
+use super::ole_site_concrete::write_ole_site_concrete;
+use super::{
+    stream::*, BorderStyle, Cycle, FormControl, FormFlags, FormScrollBarFlags, SiteClassInfo,
+    SiteKind,
+};
+use crate::common::{write_guid, write_with_header, AlignedWriter, IID_IDISPATCH};
+use crate::properties::{
+    color::OleColor,
+    font::{write_guid_and_font, FormFont},
+    picture::{write_guid_and_picture, GuidAndPicture},
+    MousePointer, PictureAlignment, PictureSizeMode, Position, Size, SpecialEffect,
+};
+
+/// Write a [`SiteClassInfo`], storing only the properties that differ from the file format
+/// default
+pub fn write_site_class_info(out: &mut Vec<u8>, info: &SiteClassInfo) -> io::Result<()> {
+    let mut w = AlignedWriter::new();
+    let mut mask = ClassInfoPropMask::empty();
+    w.le_u32(0);
+
+    if !info.class_table_flags.is_empty() || !info.var_flags.is_empty() {
+        mask |= ClassInfoPropMask::CLASS_FLAGS;
+        w.le_u16(info.class_table_flags.bits());
+        w.le_u16(info.var_flags.bits());
+    }
+    if info.count_of_methods != 0x00000000 {
+        mask |= ClassInfoPropMask::COUNT_OF_METHODS;
+        w.le_u32(info.count_of_methods);
+    }
+    if info.dispid_bind != 0xFFFFFFFF {
+        mask |= ClassInfoPropMask::DISPID_BIND;
+        w.le_u32(info.dispid_bind);
+    }
+    if info.get_bind_index != 0x0000 {
+        mask |= ClassInfoPropMask::GET_BIND_INDEX;
+        w.le_u16(info.get_bind_index);
+    }
+    if info.put_bind_index != 0x0000 {
+        mask |= ClassInfoPropMask::PUT_BIND_INDEX;
+        w.le_u16(info.put_bind_index);
+    }
+    if !info.bind_type.is_empty() {
+        mask |= ClassInfoPropMask::BIND_TYPE;
+        w.le_u16(info.bind_type.bits());
+    }
+    if info.get_value_index != 0x0000 {
+        mask |= ClassInfoPropMask::GET_VALUE_INDEX;
+        w.le_u16(info.get_value_index);
+    }
+    if info.put_value_index != 0x0000 {
+        mask |= ClassInfoPropMask::PUT_VALUE_INDEX;
+        w.le_u16(info.put_value_index);
+    }
+    if !info.value_type.is_empty() {
+        mask |= ClassInfoPropMask::VALUE_TYPE;
+        w.le_u16(info.value_type.bits());
+    }
+    if info.dispid_rowset != 0xFFFFFFFF {
+        mask |= ClassInfoPropMask::DISPID_ROWSET;
+        w.le_u32(info.dispid_rowset);
+    }
+    if info.set_rowset != 0x0000 {
+        mask |= ClassInfoPropMask::SET_ROWSET;
+        w.le_u16(info.set_rowset);
+    }
+    w.align(4);
+
+    let mut guids = Vec::new();
+    if !info.cls_id.is_nil() {
+        mask |= ClassInfoPropMask::CLS_ID;
+        write_guid(&mut guids, info.cls_id);
+    }
+    if info.disp_event != IID_IDISPATCH {
+        mask |= ClassInfoPropMask::DISP_EVENT;
+        write_guid(&mut guids, info.disp_event);
+    }
+    if info.default_proc != IID_IDISPATCH {
+        mask |= ClassInfoPropMask::DEFAULT_PROC;
+        write_guid(&mut guids, info.default_proc);
+    }
+    w.bytes(&guids);
+
+    w.patch_u32(
+        0,
+        mask.bits() | info.unknown_mask_bits & !ClassInfoPropMask::all().bits(),
+    );
+    write_with_header(out, [0x00, 0x00], &w.finish())
+}
+
+/// Write the `SiteDepthsAndTypes`, grouping runs of sites with the same depth and type
+fn write_site_depths_and_types(out: &mut Vec<u8>, depths: &[u8]) {
+    let start = out.len();
+    let mut rest = depths;
+    while let Some(&depth) = rest.first() {
+        let count = rest.iter().take(0x7F).take_while(|d| **d == depth).count();
+        if count > 1 {
+            out.extend_from_slice(&[depth, TypeOrCount::IS_COUNT.bits() | count as u8]);
+        } else {
+            out.push(depth);
+        }
+        out.push(SiteType::Ole as u8);
+        rest = &rest[count..];
+    }
+    while (out.len() - start) % 4 != 0 {
+        out.push(0);
+    }
+}
+
+/// Write a [`FormControl`] including its sites, the inverse of
+/// [`parse_form_control`][super::parse_form_control]
+///
+/// The properties that differ from the file format default are stored, except for the
+/// DrawBuffer, which is required.
+pub fn write_form_control<S: AsRef<str>>(
+    out: &mut Vec<u8>,
+    form: &FormControl<S>,
+) -> io::Result<()> {
+    let mut w = AlignedWriter::new();
+    let mut mask = FormPropMask::DRAW_BUFFER;
+    w.le_u32(0);
+    let caption = form.caption.as_ref();
+
+    if form.back_color != OleColor::BTNFACE {
+        mask |= FormPropMask::BACK_COLOR;
+        w.ole_color(form.back_color);
+    }
+    if form.fore_color != OleColor::BTNTEXT {
+        mask |= FormPropMask::FORE_COLOR;
+        w.ole_color(form.fore_color);
+    }
+    if form.next_available_id != 0x00000000 {
+        mask |= FormPropMask::NEXT_AVAILABLE_ID;
+        w.le_u32(form.next_available_id);
+    }
+    if form.boolean_properties != FormFlags::ENABLED {
+        mask |= FormPropMask::BOOLEAN_PROPERTIES;
+        w.le_u32(form.boolean_properties.bits());
+    }
+    if form.border_style != BorderStyle::None {
+        mask |= FormPropMask::BORDER_STYLE;
+        w.le_u8(form.border_style as u8);
+    }
+    if form.mouse_pointer != MousePointer::Default {
+        mask |= FormPropMask::MOUSE_POINTER;
+        w.le_u8(form.mouse_pointer as u8);
+    }
+    if form.scroll_bars != FormScrollBarFlags::DEFAULT {
+        mask |= FormPropMask::SCROLL_BARS;
+        w.le_u8(form.scroll_bars.bits());
+    }
+    if form.group_count != 0x00000000 {
+        mask |= FormPropMask::GROUP_CNT;
+        w.le_u32(form.group_count);
+    }
+    if form.mouse_icon != GuidAndPicture::EMPTY {
+        mask |= FormPropMask::MOUSE_ICON;
+        w.le_u16(0xFFFF);
+    }
+    if !matches!(form.cycle, Cycle::AllForms) {
+        mask |= FormPropMask::CYCLE;
+        w.le_u8(Cycle::CurrentForm as u8);
+    }
+    if form.special_effect != SpecialEffect::Flat {
+        mask |= FormPropMask::SPECIAL_EFFECT;
+        w.le_u8(form.special_effect as u8);
+    }
+    if form.border_color != OleColor::BTNTEXT {
+        mask |= FormPropMask::BORDER_COLOR;
+        w.ole_color(form.border_color);
+    }
+    if !caption.is_empty() {
+        mask |= FormPropMask::CAPTION;
+        w.length_and_compression(caption)?;
+    }
+    if form.font.font != FormFont::Empty {
+        mask |= FormPropMask::FONT;
+        w.le_u16(0xFFFF);
+    }
+    if form.picture != GuidAndPicture::EMPTY {
+        mask |= FormPropMask::PICTURE;
+        w.le_u16(0xFFFF);
+    }
+    if form.zoom != 100 {
+        mask |= FormPropMask::ZOOM;
+        w.le_u32(form.zoom);
+    }
+    if form.picture_alignment != PictureAlignment::Center {
+        mask |= FormPropMask::PICTURE_ALIGNMENT;
+        w.le_u8(form.picture_alignment as u8);
+    }
+    if form.picture_tiling {
+        mask |= FormPropMask::PICTURE_TILING;
+    }
+    if form.picture_size_mode != PictureSizeMode::Clip {
+        mask |= FormPropMask::PICTURE_SIZE_MODE;
+        w.le_u8(form.picture_size_mode as u8);
+    }
+    if form.shape_cookie != 0 {
+        mask |= FormPropMask::SHAPE_COOKIE;
+        w.le_u32(form.shape_cookie);
+    }
+    w.le_u32(form.draw_buffer);
+
+    // Extra Data Block
+    if form.displayed_size != Size::new(4000, 3000) {
+        mask |= FormPropMask::DISPLAYED_SIZE;
+        w.fm_size(form.displayed_size);
+    }
+    if form.logical_size != Size::new(4000, 3000) {
+        mask |= FormPropMask::LOGICAL_SIZE;
+        w.fm_size(form.logical_size);
+    }
+    if form.scroll_position != Position::default() {
+        mask |= FormPropMask::SCROLL_POSITION;
+        w.fm_position(form.scroll_position);
+    }
+    if mask.contains(FormPropMask::CAPTION) {
+        w.fm_string(caption)?;
+    }
+
+    w.patch_u32(
+        0,
+        mask.bits() | form.unknown_mask_bits & !FormPropMask::all().bits(),
+    );
+    write_with_header(out, [0x00, 0x04], &w.finish())?;
+
+    // Stream Data
+    write_guid_and_picture(out, &form.mouse_icon)?;
+    if mask.contains(FormPropMask::FONT) {
+        write_guid_and_font(out, &form.font)?;
+    }
+    write_guid_and_picture(out, &form.picture)?;
+
+    // Site Data
+    if !form
+        .boolean_properties
+        .contains(FormFlags::DONTSAVECLASSTABLE)
+    {
+        let count = u16::try_from(form.site_classes.len())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        out.extend_from_slice(&count.to_le_bytes());
+        for info in &form.site_classes {
+            write_site_class_info(out, info)?;
+        }
+    }
+
+    let mut sites = Vec::new();
+    let depths: Vec<u8> = form.sites.iter().map(|s| s.depth).collect();
+    write_site_depths_and_types(&mut sites, &depths);
+    for site in &form.sites {
+        let SiteKind::Ole(ole) = &site.kind;
+        write_ole_site_concrete(&mut sites, ole)?;
+    }
+    let invalid = |e| io::Error::new(io::ErrorKind::InvalidData, e);
+    let count_of_sites = u32::try_from(form.sites.len()).map_err(invalid)?;
+    let count_of_bytes = u32::try_from(sites.len()).map_err(invalid)?;
+    out.extend_from_slice(&count_of_sites.to_le_bytes());
+    out.extend_from_slice(&count_of_bytes.to_le_bytes());
+    out.extend_from_slice(&sites);
+    Ok(())
+}
@@ -0,0 +1,578 @@
+//! Structural differences between two versions of a form
+//!
+//! A [`FormSnapshot`] flattens a form, its parent controls and all embedded controls into
+//! named properties with printable values. [`diff`] compares two snapshots, matching the
+//! controls by their ID, or by name if the ID is not in both versions.
+//!
+//! ```no_run
+//! # use ms_oforms::{diff::{diff, FormSnapshot}, OFormsFile};
+//! # fn main() -> std::io::Result<()> {
+//! let old = FormSnapshot::load(&mut OFormsFile::open(std::fs::File::open("old.bin")?)?)?;
+//! let new = FormSnapshot::load(&mut OFormsFile::open(std::fs::File::open("new.bin")?)?)?;
+//! print!("{}", diff(&old, &new));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    io::{self, Read, Seek},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    common::json_string,
+    controls::{user_form::ole_site_concrete::OleSiteConcreteControl, Control},
+    properties::{
+        values::{control_props, form_props, site_props},
+        Position,
+    },
+    OFormsFile,
+};
+
+/// A control in a [`FormSnapshot`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControlSnapshot {
+    /// The ID of the site
+    pub id: i32,
+    /// The name of the control
+    pub name: String,
+    /// The type of the control, e.g. `CommandButton`, or the CLSID of an unknown control
+    pub type_name: String,
+    /// The name of the parent control, if any
+    pub parent: Option<String>,
+    /// The position within the parent control (or the form)
+    pub position: Position,
+    /// The site and control properties, e.g. `site.tab_index` or `control.caption`
+    ///
+    /// For a parent control, this includes the properties of its form, e.g. `form.caption`.
+    pub properties: BTreeMap<String, String>,
+}
+
+/// A flattened form with all its controls, see the [module docs][self]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormSnapshot {
+    /// The properties of the root form
+    pub form: BTreeMap<String, String>,
+    /// All controls, with parent controls before their children
+    pub controls: Vec<ControlSnapshot>,
+}
+
+impl FormSnapshot {
+    /// Read the root form of `file` and all its parent controls
+    pub fn load<T: Read + Seek>(file: &mut OFormsFile<T>) -> io::Result<Self> {
+        let form = file.root_form()?.into_form_control();
+        let mut snapshot = FormSnapshot::default();
+        form_props(&mut snapshot.form, "", &form);
+        snapshot.load_controls(file, Path::new(""), None)?;
+        Ok(snapshot)
+    }
+
+    fn load_controls<T: Read + Seek>(
+        &mut self,
+        file: &mut OFormsFile<T>,
+        storage: &Path,
+        parent: Option<&str>,
+    ) -> io::Result<()> {
+        let mut form = file.form_in(storage)?;
+        let controls: Vec<(OleSiteConcreteControl, io::Result<Control>)> = form
+            .controls()
+            .map(|(site, control)| (site.clone(), control))
+            .collect();
+        for (site, control) in controls {
+            let mut properties = BTreeMap::new();
+            site_props(&mut properties, &site);
+            let mut children: Option<PathBuf> = None;
+            let type_name = match &control {
+                Ok(control) => {
+                    control_props(&mut properties, control);
+                    if let Some(p) = control.parent_control() {
+                        let form = file.form_in(&p.storage)?.into_form_control();
+                        form_props(&mut properties, "form.", &form);
+                        children = Some(p.storage.clone());
+                    }
+                    match control {
+                        Control::Unknown { clsid, .. } => {
+                            format!("{{{}}}", clsid.hyphenated()).to_uppercase()
+                        }
+                        _ => control.type_name().unwrap_or_default().to_owned(),
+                    }
+                }
+                Err(e) => {
+                    properties.insert(String::from("control"), format!("error: {}", e));
+                    String::from("?")
+                }
+            };
+            let name = site.name.clone();
+            self.controls.push(ControlSnapshot {
+                id: site.id,
+                name: site.name,
+                type_name,
+                parent: parent.map(str::to_owned),
+                position: site.site_position,
+                properties,
+            });
+            if let Some(storage) = children {
+                self.load_controls(file, &storage, Some(&name))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A property with a different value in both versions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertyChange {
+    pub property: String,
+    /// The old value, or `None` if the property was added
+    pub old: Option<String>,
+    /// The new value, or `None` if the property was removed
+    pub new: Option<String>,
+}
+
+/// A difference between two versions of a form
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// A property of the root form changed
+    Form(PropertyChange),
+    /// A control only exists in the new version
+    Added {
+        id: i32,
+        name: String,
+        type_name: String,
+    },
+    /// A control only exists in the old version
+    Removed {
+        id: i32,
+        name: String,
+        type_name: String,
+    },
+    /// A control has a different position or parent
+    Moved {
+        id: i32,
+        name: String,
+        from: (Option<String>, Position),
+        to: (Option<String>, Position),
+    },
+    /// A property of a control changed
+    Control {
+        id: i32,
+        name: String,
+        change: PropertyChange,
+    },
+}
+
+/// The result of [`diff`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormDiff {
+    pub changes: Vec<Change>,
+}
+
+fn diff_props(
+    old: &BTreeMap<String, String>,
+    new: &BTreeMap<String, String>,
+) -> Vec<PropertyChange> {
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    keys.into_iter()
+        .filter(|k| old.get(*k) != new.get(*k))
+        .map(|k| PropertyChange {
+            property: k.clone(),
+            old: old.get(k).cloned(),
+            new: new.get(k).cloned(),
+        })
+        .collect()
+}
+
+/// Compare two versions of a form
+///
+/// Controls are matched by ID first. Controls whose ID only exists in one version are then
+/// matched by name, ignoring ASCII case.
+pub fn diff(old: &FormSnapshot, new: &FormSnapshot) -> FormDiff {
+    let mut changes: Vec<Change> = diff_props(&old.form, &new.form)
+        .into_iter()
+        .map(Change::Form)
+        .collect();
+
+    let new_ids: BTreeSet<i32> = new.controls.iter().map(|c| c.id).collect();
+    let old_ids: BTreeSet<i32> = old.controls.iter().map(|c| c.id).collect();
+    let mut matched_new = BTreeSet::new();
+    let mut pairs = Vec::new();
+    let mut removed = Vec::new();
+    for o in &old.controls {
+        let found = new.controls.iter().enumerate().find(|(_, n)| {
+            n.id == o.id
+                || (!new_ids.contains(&o.id)
+                    && !old_ids.contains(&n.id)
+                    && n.name.eq_ignore_ascii_case(&o.name))
+        });
+        match found {
+            Some((i, n)) if matched_new.insert(i) => pairs.push((o, n)),
+            _ => removed.push(o),
+        }
+    }
+
+    for o in removed {
+        changes.push(Change::Removed {
+            id: o.id,
+            name: o.name.clone(),
+            type_name: o.type_name.clone(),
+        });
+    }
+    for (i, n) in new.controls.iter().enumerate() {
+        if !matched_new.contains(&i) {
+            changes.push(Change::Added {
+                id: n.id,
+                name: n.name.clone(),
+                type_name: n.type_name.clone(),
+            });
+        }
+    }
+    for (o, n) in pairs {
+        if o.position != n.position || o.parent != n.parent {
+            changes.push(Change::Moved {
+                id: n.id,
+                name: n.name.clone(),
+                from: (o.parent.clone(), o.position),
+                to: (n.parent.clone(), n.position),
+            });
+        }
+        let mut props = Vec::new();
+        if o.name != n.name {
+            props.push(PropertyChange {
+                property: String::from("name"),
+                old: Some(o.name.clone()),
+                new: Some(n.name.clone()),
+            });
+        }
+        if o.type_name != n.type_name {
+            props.push(PropertyChange {
+                property: String::from("type"),
+                old: Some(o.type_name.clone()),
+                new: Some(n.type_name.clone()),
+            });
+        }
+        props.extend(diff_props(&o.properties, &n.properties));
+        for change in props {
+            changes.push(Change::Control {
+                id: n.id,
+                name: n.name.clone(),
+                change,
+            });
+        }
+    }
+    FormDiff { changes }
+}
+
+fn json_opt(value: &Option<String>) -> String {
+    value
+        .as_deref()
+        .map_or_else(|| String::from("null"), json_string)
+}
+
+fn json_location((parent, position): &(Option<String>, Position)) -> String {
+    format!(
+        r#"{{"parent":{},"left":{},"top":{}}}"#,
+        json_opt(parent),
+        position.left,
+        position.top
+    )
+}
+
+impl PropertyChange {
+    fn json_fields(&self) -> String {
+        format!(
+            r#""property":{},"old":{},"new":{}"#,
+            json_string(&self.property),
+            json_opt(&self.old),
+            json_opt(&self.new)
+        )
+    }
+}
+
+impl Change {
+    /// The change as a JSON object
+    pub fn to_json(&self) -> String {
+        match self {
+            Self::Form(change) => format!(r#"{{"kind":"form",{}}}"#, change.json_fields()),
+            Self::Added {
+                id,
+                name,
+                type_name,
+            } => format!(
+                r#"{{"kind":"added","id":{},"name":{},"type":{}}}"#,
+                id,
+                json_string(name),
+                json_string(type_name)
+            ),
+            Self::Removed {
+                id,
+                name,
+                type_name,
+            } => format!(
+                r#"{{"kind":"removed","id":{},"name":{},"type":{}}}"#,
+                id,
+                json_string(name),
+                json_string(type_name)
+            ),
+            Self::Moved { id, name, from, to } => format!(
+                r#"{{"kind":"moved","id":{},"name":{},"from":{},"to":{}}}"#,
+                id,
+                json_string(name),
+                json_location(from),
+                json_location(to)
+            ),
+            Self::Control { id, name, change } => format!(
+                r#"{{"kind":"property","id":{},"name":{},{}}}"#,
+                id,
+                json_string(name),
+                change.json_fields()
+            ),
+        }
+    }
+}
+
+impl FormDiff {
+    /// Whether both versions are the same
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// The changes as a JSON array
+    pub fn to_json(&self) -> String {
+        let items: Vec<String> = self.changes.iter().map(Change::to_json).collect();
+        format!("[{}]", items.join(","))
+    }
+}
+
+impl fmt::Display for PropertyChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |v: &Option<String>| v.clone().unwrap_or_else(|| String::from("(none)"));
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.property,
+            value(&self.old),
+            value(&self.new)
+        )
+    }
+}
+
+fn fmt_location(
+    f: &mut fmt::Formatter<'_>,
+    (parent, position): &(Option<String>, Position),
+) -> fmt::Result {
+    write!(f, "({}, {})", position.left, position.top)?;
+    if let Some(parent) = parent {
+        write!(f, " in {}", parent)?;
+    }
+    Ok(())
+}
+
+/// One line per change, with a `+`, `-`, `>` (moved) or `~` (changed) marker
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Form(change) => write!(f, "~ form: {}", change),
+            Self::Added {
+                id,
+                name,
+                type_name,
+            } => write!(f, "+ {} (ID {}): {}", name, id, type_name),
+            Self::Removed {
+                id,
+                name,
+                type_name,
+            } => write!(f, "- {} (ID {}): {}", name, id, type_name),
+            Self::Moved { id, name, from, to } => {
+                write!(f, "> {} (ID {}): moved from ", name, id)?;
+                fmt_location(f, from)?;
+                write!(f, " to ")?;
+                fmt_location(f, to)
+            }
+            Self::Control { id, name, change } => {
+                write!(f, "~ {} (ID {}): {}", name, id, change)
+            }
+        }
+    }
+}
+
+impl fmt::Display for FormDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, Change, FormSnapshot};
+    use crate::{
+        controls::{
+            command_button::CommandButtonControl,
+            label::LabelControl,
+            morph_data::{DisplayStyle, MorphDataControl},
+            Control,
+        },
+        fixture::{site, FormBuilder},
+        properties::{Position, Size},
+    };
+
+    const SIZE: Size = Size::new(4000, 3000);
+
+    fn snapshot(form: FormBuilder) -> FormSnapshot {
+        FormSnapshot::load(&mut form.file()).unwrap()
+    }
+
+    fn lines(old: &FormSnapshot, new: &FormSnapshot) -> Vec<String> {
+        diff(old, new)
+            .to_string()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    fn button(caption: &str) -> Control {
+        Control::CommandButton(CommandButtonControl {
+            caption: caption.to_string(),
+            ..CommandButtonControl::default()
+        })
+    }
+
+    fn label() -> Control {
+        Control::Label(LabelControl::default())
+    }
+
+    /// A form with a CommandButton `cmd` and another control `lbl`
+    fn controls(caption: &str, button_caption: &str, lbl: Control) -> FormBuilder {
+        FormBuilder::new(SIZE)
+            .with_form(|form| form.caption = caption.to_string())
+            .control(site(1, "cmd"), button(button_caption))
+            .control(site(2, "lbl"), lbl)
+    }
+
+    fn check_box() -> Control {
+        Control::CheckBox(MorphDataControl {
+            display_style: DisplayStyle::CheckBox,
+            ..MorphDataControl::default()
+        })
+    }
+
+    #[test]
+    fn test_unchanged() {
+        let old = snapshot(controls("Old", "OK", label()));
+        assert!(diff(&old, &old).is_empty());
+        assert_eq!(diff(&old, &old).to_json(), "[]");
+        assert!(diff(&FormSnapshot::default(), &FormSnapshot::default()).is_empty());
+    }
+
+    #[test]
+    fn test_properties() {
+        let old = snapshot(controls("Old", "OK", label()));
+        let new = snapshot(controls("New", "Okay", label()));
+        assert_eq!(
+            lines(&old, &new),
+            [
+                "~ form: caption: \"Old\" -> \"New\"",
+                "~ cmd (ID 1): control.caption: \"OK\" -> \"Okay\"",
+            ]
+        );
+        assert!(diff(&old, &new)
+            .to_json()
+            .contains(r#"{"kind":"form","property":"caption","old":"\"Old\"","new":"\"New\""}"#));
+    }
+
+    #[test]
+    fn test_added_and_removed() {
+        let old = snapshot(controls("Old", "OK", label()));
+        let new = snapshot(
+            FormBuilder::new(SIZE)
+                .with_form(|form| form.caption = String::from("Old"))
+                .control(site(1, "cmd"), button("OK")),
+        );
+        let d = diff(&old, &new);
+        assert_eq!(
+            d.changes,
+            [Change::Removed {
+                id: 2,
+                name: String::from("lbl"),
+                type_name: String::from("Label"),
+            }]
+        );
+        assert_eq!(d.to_string(), "- lbl (ID 2): Label\n");
+
+        let d = diff(&new, &old);
+        assert_eq!(
+            d.to_json(),
+            r#"[{"kind":"added","id":2,"name":"lbl","type":"Label"}]"#
+        );
+    }
+
+    #[test]
+    fn test_type_change() {
+        let old = snapshot(controls("Old", "OK", label()));
+        let text_box = Control::TextBox(MorphDataControl::default());
+        let new = snapshot(controls("Old", "OK", text_box));
+        let lines = lines(&old, &new);
+        assert_eq!(lines[0], "~ lbl (ID 2): type: Label -> TextBox");
+        assert!(!lines
+            .iter()
+            .any(|l| l.starts_with('+') || l.starts_with('-')));
+    }
+
+    #[test]
+    fn test_moved() {
+        let old = snapshot(controls("Old", "OK", label()));
+        let mut cmd = site(1, "cmd");
+        cmd.site_position = Position::new(2540, 0);
+        let new = snapshot(
+            FormBuilder::new(SIZE)
+                .with_form(|form| form.caption = String::from("Old"))
+                .control(cmd, button("OK"))
+                .control(site(2, "lbl"), label()),
+        );
+        assert_eq!(
+            lines(&old, &new),
+            ["> cmd (ID 1): moved from (0, 0) to (2540, 0)"]
+        );
+    }
+
+    #[test]
+    fn test_moved_into_frame() {
+        let old = snapshot(
+            FormBuilder::new(SIZE)
+                .frame(site(1, "fra"), FormBuilder::new(SIZE))
+                .control(site(2, "chk"), check_box()),
+        );
+        let new = snapshot(FormBuilder::new(SIZE).frame(
+            site(1, "fra"),
+            FormBuilder::new(SIZE).control(site(2, "chk"), check_box()),
+        ));
+        assert_eq!(new.controls[1].parent.as_deref(), Some("fra"));
+        assert_eq!(
+            lines(&old, &new),
+            ["> chk (ID 2): moved from (0, 0) to (0, 0) in fra"]
+        );
+    }
+
+    #[test]
+    fn test_match_by_name() {
+        let old = snapshot(controls("Old", "OK", label()));
+        let mut new = old.clone();
+        new.controls[0].id = 7;
+        new.controls[0].name = String::from("CMD");
+        assert_eq!(lines(&old, &new), ["~ CMD (ID 7): name: cmd -> CMD"]);
+
+        // a new name and ID is a different control
+        new.controls[0].name = String::from("cmdOK");
+        assert_eq!(
+            lines(&old, &new),
+            [
+                "- cmd (ID 1): CommandButton",
+                "+ cmdOK (ID 7): CommandButton"
+            ]
+        );
+    }
+}
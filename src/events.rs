@@ -0,0 +1,422 @@
+//! Links between the event handlers in the VBA code of a form and its controls
+//!
+//! VBA connects a procedure to a control by its name: `Private Sub cmdOK_Click()` handles the
+//! `Click` event of the control named `cmdOK`, and `UserForm_Initialize` handles an event of the
+//! form itself. This module finds these procedures in the source text of a form module and
+//! matches them with the [`OleSiteConcreteControl::name`] of the controls.
+//!
+//! ```no_run
+//! # use ms_oforms::{events::map_form_events, OFormsFile};
+//! # fn main() -> std::io::Result<()> {
+//! let file = std::fs::File::open("form.bin")?;
+//! let mut oforms = OFormsFile::open_in(file, "frmMain".into())?;
+//! let source = std::fs::read_to_string("frmMain.frm")?;
+//! print!("{}", map_form_events(&mut oforms, &source)?);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Write},
+    io::{self, Read, Seek},
+    path::PathBuf,
+};
+
+use crate::{
+    common::json_string,
+    controls::{user_form::ole_site_concrete::OleSiteConcreteControl, Control},
+    OFormsFile,
+};
+
+/// The events of the form and the controls in the Microsoft Forms 2.0 library
+///
+/// A `Sub` is only considered an event handler if its name ends with one of these.
+const EVENTS: &[&str] = &[
+    "Activate",
+    "AddControl",
+    "AfterUpdate",
+    "BeforeDragOver",
+    "BeforeDropOrPaste",
+    "BeforeUpdate",
+    "Change",
+    "Click",
+    "DblClick",
+    "Deactivate",
+    "DropButtonClick",
+    "Enter",
+    "Error",
+    "Exit",
+    "Initialize",
+    "KeyDown",
+    "KeyPress",
+    "KeyUp",
+    "Layout",
+    "MouseDown",
+    "MouseMove",
+    "MouseUp",
+    "QueryClose",
+    "RemoveControl",
+    "Resize",
+    "Scroll",
+    "SpinDown",
+    "SpinUp",
+    "Terminate",
+    "Zoom",
+];
+
+/// The name of the form object in the code of a form module
+const FORM_OBJECT: &str = "UserForm";
+
+/// A `Sub` that handles an event, e.g. `cmdOK_Click`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventHandler {
+    /// The name of the procedure, e.g. `cmdOK_Click`
+    pub procedure: String,
+    /// The name of the object, e.g. `cmdOK`
+    pub object: String,
+    /// The name of the event, e.g. `Click`
+    pub event: String,
+    /// The (1-based) line of the procedure declaration
+    pub line: usize,
+}
+
+/// A control and the procedures that handle its events
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControlEvents {
+    /// The ID of the site
+    pub id: i32,
+    /// The name of the control
+    pub name: String,
+    /// The event handlers, in the order of the source
+    pub handlers: Vec<EventHandler>,
+}
+
+/// The event handlers of a form module, matched with the controls of the form
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventMap {
+    /// The handlers of the form itself, e.g. `UserForm_Initialize`
+    pub form: Vec<EventHandler>,
+    /// All controls, in the order they were passed to [`map_events`]
+    pub controls: Vec<ControlEvents>,
+    /// Handlers for objects that are neither a control of the form nor a `WithEvents` variable
+    pub orphans: Vec<EventHandler>,
+}
+
+impl EventMap {
+    /// The controls that have no event handlers
+    pub fn without_handlers(&self) -> impl Iterator<Item = &ControlEvents> {
+        self.controls.iter().filter(|c| c.handlers.is_empty())
+    }
+
+    /// Serialize the map as a JSON object
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{\"form\":");
+        handlers_json(&mut out, &self.form);
+        out.push_str(",\"controls\":[");
+        for (i, control) in self.controls.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(
+                out,
+                "{{\"id\":{},\"name\":{},\"handlers\":",
+                control.id,
+                json_string(&control.name)
+            );
+            handlers_json(&mut out, &control.handlers);
+            out.push('}');
+        }
+        out.push_str("],\"orphans\":");
+        handlers_json(&mut out, &self.orphans);
+        out.push('}');
+        out
+    }
+}
+
+fn handlers_json(out: &mut String, handlers: &[EventHandler]) {
+    out.push('[');
+    for (i, handler) in handlers.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let _ = write!(
+            out,
+            "{{\"procedure\":{},\"event\":{},\"line\":{}}}",
+            json_string(&handler.procedure),
+            json_string(&handler.event),
+            handler.line
+        );
+    }
+    out.push(']');
+}
+
+fn write_events(f: &mut fmt::Formatter<'_>, handlers: &[EventHandler]) -> fmt::Result {
+    for (i, handler) in handlers.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{} (line {})", handler.event, handler.line)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for EventMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.form.is_empty() {
+            write!(f, "{}: ", FORM_OBJECT)?;
+            write_events(f, &self.form)?;
+            writeln!(f)?;
+        }
+        for control in &self.controls {
+            write!(f, "{} (ID {}): ", control.name, control.id)?;
+            if control.handlers.is_empty() {
+                write!(f, "no handlers")?;
+            } else {
+                write_events(f, &control.handlers)?;
+            }
+            writeln!(f)?;
+        }
+        for handler in &self.orphans {
+            writeln!(
+                f,
+                "orphaned: {} (line {}) has no control named {}",
+                handler.procedure, handler.line, handler.object
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The logical lines of `source`, with line continuations (` _`) joined
+///
+/// Each line comes with the (1-based) number of its first physical line.
+fn logical_lines(source: &str) -> Vec<(usize, String)> {
+    let mut out = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+    for (i, line) in source.lines().enumerate() {
+        let (start, mut text) = current.take().unwrap_or_else(|| (i + 1, String::new()));
+        let trimmed = line.trim_end();
+        match trimmed.strip_suffix(" _") {
+            Some(rest) => {
+                text.push_str(rest);
+                text.push(' ');
+                current = Some((start, text));
+            }
+            None => {
+                text.push_str(trimmed);
+                out.push((start, text));
+            }
+        }
+    }
+    out.extend(current);
+    out
+}
+
+/// The name of the `Sub` declared by `line`, if any
+fn sub_name(line: &str) -> Option<&str> {
+    let mut words = line.split_whitespace().peekable();
+    for modifier in &["Public", "Private", "Friend", "Static"] {
+        if words
+            .peek()
+            .is_some_and(|w| w.eq_ignore_ascii_case(modifier))
+        {
+            words.next();
+        }
+    }
+    if !words.next()?.eq_ignore_ascii_case("Sub") {
+        return None;
+    }
+    let name = words.next()?;
+    let name = name.split('(').next().unwrap_or(name);
+    Some(name).filter(|n| !n.is_empty())
+}
+
+/// The variables declared `WithEvents` in `line`
+fn with_events_names(line: &str) -> impl Iterator<Item = &str> {
+    line.split(',').filter_map(|part| {
+        let mut words = part.split_whitespace();
+        words.find(|w| w.eq_ignore_ascii_case("WithEvents"))?;
+        words.next()
+    })
+}
+
+/// Find the event handlers in the source of a form module
+///
+/// An event handler is a `Sub` named `Object_Event`, where `Event` is one of the events of the
+/// Forms 2.0 controls. Other procedures are ignored. Line continuations are joined, comments
+/// are skipped.
+pub fn find_event_handlers(source: &str) -> Vec<EventHandler> {
+    let mut out = Vec::new();
+    for (line, text) in logical_lines(source) {
+        let name = match sub_name(&text) {
+            Some(name) => name,
+            None => continue,
+        };
+        // Event names don't contain underscores, but control names may
+        let (object, event) = match name.rfind('_') {
+            Some(pos) => (&name[..pos], &name[pos + 1..]),
+            None => continue,
+        };
+        if object.is_empty() || !EVENTS.iter().any(|e| e.eq_ignore_ascii_case(event)) {
+            continue;
+        }
+        out.push(EventHandler {
+            procedure: name.to_string(),
+            object: object.to_string(),
+            event: event.to_string(),
+            line,
+        });
+    }
+    out
+}
+
+/// Match the event handlers in `source` with `controls`
+///
+/// Names are compared case-insensitively, as in VBA. Handlers for `UserForm` belong to the
+/// form. Handlers for a variable that is declared `WithEvents` in the module are neither linked
+/// nor reported as orphaned.
+pub fn map_events<'a, I>(source: &str, controls: I) -> EventMap
+where
+    I: IntoIterator<Item = &'a OleSiteConcreteControl>,
+{
+    let mut map = EventMap {
+        controls: controls
+            .into_iter()
+            .map(|site| ControlEvents {
+                id: site.id,
+                name: site.name.clone(),
+                handlers: Vec::new(),
+            })
+            .collect(),
+        ..EventMap::default()
+    };
+    let variables: BTreeSet<String> = logical_lines(source)
+        .iter()
+        .flat_map(|(_, text)| with_events_names(text).map(str::to_lowercase))
+        .collect();
+    for handler in find_event_handlers(source) {
+        if handler.object.eq_ignore_ascii_case(FORM_OBJECT) {
+            map.form.push(handler);
+        } else if let Some(control) = map
+            .controls
+            .iter_mut()
+            .find(|c| c.name.eq_ignore_ascii_case(&handler.object))
+        {
+            control.handlers.push(handler);
+        } else if !variables.contains(&handler.object.to_lowercase()) {
+            map.orphans.push(handler);
+        }
+    }
+    map
+}
+
+/// Match the event handlers in `source` with the controls of the root form of `file`
+///
+/// This includes the controls in Frame, MultiPage and Page controls, because their events are
+/// handled in the module of the form, too.
+pub fn map_form_events<T: Read + Seek>(
+    file: &mut OFormsFile<T>,
+    source: &str,
+) -> io::Result<EventMap> {
+    let mut sites = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(storage) = pending.pop() {
+        let mut form = file.form_in(&storage)?;
+        let mut children = Vec::new();
+        for (site, control) in form.controls() {
+            if let Some(p) = control.as_ref().ok().and_then(Control::parent_control) {
+                children.push(p.storage.clone());
+            }
+            sites.push(site.clone());
+        }
+        pending.extend(children.into_iter().rev());
+    }
+    Ok(map_events(source, &sites))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{map_events, map_form_events};
+    use crate::{
+        controls::{command_button::CommandButtonControl, Control},
+        fixture::{site, FormBuilder},
+        properties::Size,
+    };
+
+    #[test]
+    fn test_map_events() {
+        let source = "Attribute VB_Name = \"frmMain\"\n\
+            Private WithEvents btn As MSForms.CommandButton\n\
+            \n\
+            Private Sub UserForm_Initialize()\n\
+            End Sub\n\
+            ' Sub cmdCancel_Click()\n\
+            Private Sub cmd_OK_Click()\n\
+            End Sub\n\
+            Private Sub txtName_KeyDown(ByVal KeyCode As MSForms.ReturnInteger, _\n\
+            \x20   ByVal Shift As Integer)\n\
+            End Sub\n\
+            Public Sub cmdOld_Click()\n\
+            End Sub\n\
+            Sub btn_Click()\n\
+            End Sub\n\
+            Private Sub Load_Data()\n\
+            End Sub\n\
+            Private Function txtName_Change() As Boolean\n\
+            End Function\n";
+        let sites = [site(1, "CMD_OK"), site(2, "txtName"), site(3, "lblTitle")];
+        let map = map_events(source, &sites);
+
+        let form: Vec<_> = map
+            .form
+            .iter()
+            .map(|h| (h.event.as_str(), h.line))
+            .collect();
+        assert_eq!(form, [("Initialize", 4)]);
+        let controls: Vec<_> = map
+            .controls
+            .iter()
+            .map(|c| {
+                let events: Vec<_> = c.handlers.iter().map(|h| h.event.as_str()).collect();
+                (c.name.as_str(), events)
+            })
+            .collect();
+        assert_eq!(
+            controls,
+            [
+                ("CMD_OK", vec!["Click"]),
+                ("txtName", vec!["KeyDown"]),
+                ("lblTitle", vec![]),
+            ]
+        );
+        let orphans: Vec<_> = map.orphans.iter().map(|h| h.procedure.as_str()).collect();
+        assert_eq!(orphans, ["cmdOld_Click"]);
+        let without: Vec<_> = map.without_handlers().map(|c| c.id).collect();
+        assert_eq!(without, [3]);
+        assert!(map.to_string().contains("lblTitle (ID 3): no handlers"));
+    }
+
+    #[test]
+    fn test_map_form_events() {
+        let size = Size::new(4000, 3000);
+        let button = Control::CommandButton(CommandButtonControl::default());
+        let page = FormBuilder::new(size).control(site(3, "cmdOK"), button);
+        let pages = FormBuilder::new(size).page(site(2, "Page1"), page);
+        let mut file = FormBuilder::new(size)
+            .multi_page(site(1, "mp"), pages)
+            .file();
+        let source = "Private Sub mp_Change()\n\
+            End Sub\n\
+            Private Sub cmdOK_Click()\n\
+            End Sub\n";
+        let map = map_form_events(&mut file, source).unwrap();
+        let names: Vec<_> = map
+            .controls
+            .iter()
+            .map(|c| (c.name.as_str(), c.handlers.len()))
+            .collect();
+        assert_eq!(names, [("mp", 1), ("Page1", 0), ("cmdOK", 1)]);
+        assert!(map.orphans.is_empty());
+    }
+}
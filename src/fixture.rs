@@ -0,0 +1,142 @@
+//! Compound files for tests, built from control values with the writers
+
+use std::{io, path::Path};
+
+use uuid::Uuid;
+
+use crate::{
+    controls::{
+        registry::CLSID_PAGE,
+        user_form::{
+            class_table::SiteClassInfo,
+            ole_site_concrete::{Clsid, OleSiteConcreteControl, SiteFlags},
+            FormControl, Site, SiteKind,
+        },
+        Control,
+    },
+    frm::ImportedForm,
+    properties::{FormEmbeddedActiveXControlCached, Size},
+    BufferedOFormsFile,
+};
+
+/// A site with ID `id`, named `name`, with the file format defaults of all other properties
+pub(crate) fn site(id: i32, name: &str) -> OleSiteConcreteControl {
+    OleSiteConcreteControl {
+        id,
+        name: name.to_owned(),
+        ..OleSiteConcreteControl::default()
+    }
+}
+
+/// A form with its object stream and the forms of its parent controls
+pub(crate) struct FormBuilder(ImportedForm);
+
+impl FormBuilder {
+    /// An empty form, displayed with `size` (in HIMETRIC units)
+    pub(crate) fn new(size: Size) -> Self {
+        Self(ImportedForm {
+            vb_frame: None,
+            form: FormControl {
+                displayed_size: size,
+                logical_size: size,
+                ..FormControl::default()
+            },
+            objects: Vec::new(),
+            children: Vec::new(),
+        })
+    }
+
+    /// Change the properties of the form with `f`
+    pub(crate) fn with_form(mut self, f: impl FnOnce(&mut FormControl)) -> Self {
+        f(&mut self.0.form);
+        self
+    }
+
+    /// Add `control` in `site`
+    ///
+    /// The class and the size in the object stream of the site are set from the control.
+    pub(crate) fn control(mut self, mut site: OleSiteConcreteControl, control: Control) -> Self {
+        site.clsid_cache_index = match (control.cached_class(), &control) {
+            (Some(cached), _) => Clsid::Global(cached as u16),
+            (None, Control::Unknown { clsid, .. }) => self.class(*clsid),
+            (None, _) => panic!("{:?} is not streamed", control),
+        };
+        let start = self.0.objects.len();
+        control.write(&mut self.0.objects).unwrap();
+        site.object_stream_size = (self.0.objects.len() - start) as u32;
+        self.site(site)
+    }
+
+    /// Add a Frame in `site`, with the controls of `form`
+    pub(crate) fn frame(self, site: OleSiteConcreteControl, form: FormBuilder) -> Self {
+        let class = Clsid::Global(FormEmbeddedActiveXControlCached::Frame as u16);
+        self.parent(site, class, form)
+    }
+
+    /// Add a MultiPage in `site`, with the pages of `form`
+    pub(crate) fn multi_page(self, site: OleSiteConcreteControl, form: FormBuilder) -> Self {
+        let class = Clsid::Global(FormEmbeddedActiveXControlCached::MultiPage as u16);
+        self.parent(site, class, form)
+    }
+
+    /// Add a page of a MultiPage in `site`, with the controls of `form`
+    ///
+    /// Pages have no entry in the global class cache, so the class table gets one.
+    pub(crate) fn page(mut self, site: OleSiteConcreteControl, form: FormBuilder) -> Self {
+        let class = self.class(CLSID_PAGE);
+        self.parent(site, class, form)
+    }
+
+    /// Change the object stream with `f`, e.g. to set bits that the writers never set
+    pub(crate) fn with_objects(mut self, f: impl FnOnce(&mut [u8])) -> Self {
+        f(&mut self.0.objects);
+        self
+    }
+
+    fn class(&mut self, clsid: Uuid) -> Clsid {
+        let classes = &mut self.0.form.site_classes;
+        let index = match classes.iter().position(|info| info.cls_id == clsid) {
+            Some(index) => index,
+            None => {
+                classes.push(SiteClassInfo::new(clsid));
+                classes.len() - 1
+            }
+        };
+        Clsid::ClassTable(index as u16)
+    }
+
+    fn parent(mut self, mut site: OleSiteConcreteControl, class: Clsid, form: FormBuilder) -> Self {
+        site.clsid_cache_index = class;
+        site.bit_flags.remove(SiteFlags::STREAMED);
+        self.0.children.push((site.id, form.0));
+        self.site(site)
+    }
+
+    fn site(mut self, site: OleSiteConcreteControl) -> Self {
+        let form = &mut self.0.form;
+        form.next_available_id = form.next_available_id.max(site.id as u32 + 1);
+        form.sites.push(Site {
+            depth: 0,
+            kind: SiteKind::Ole(site),
+        });
+        self
+    }
+
+    /// Write the form to `storage` of a new compound file and return the bytes of the file
+    ///
+    /// Missing parent storages are created, `"/"` is the root storage.
+    pub(crate) fn compound_file(&self, storage: &str) -> Vec<u8> {
+        let mut buf = io::Cursor::new(Vec::new());
+        {
+            let mut cfb = cfb::CompoundFile::create(&mut buf).unwrap();
+            self.0.write_to(&mut cfb, Path::new(storage)).unwrap();
+            cfb.flush().unwrap();
+        }
+        buf.into_inner()
+    }
+
+    /// Write the form to the root storage of a new compound file and open it
+    pub(crate) fn file(&self) -> BufferedOFormsFile {
+        BufferedOFormsFile::from_bytes(self.compound_file("/")).unwrap()
+    }
+}
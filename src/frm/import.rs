@@ -0,0 +1,1182 @@
+use std::{
+    convert::TryFrom,
+    io::{self, Read, Seek, Write},
+    num::{NonZeroU16, NonZeroU32},
+    path::Path,
+};
+
+use cfb::CompoundFile;
+use encoding_rs::WINDOWS_1252;
+use num_traits::FromPrimitive;
+use uuid::Uuid;
+
+use super::{
+    default_form, default_site_flags, named_site_flags, named_various_bits, parse_frm,
+    parse_frm_bool, parse_frm_int, parse_frm_string, parse_frx_ref, various_flags, FrmBlock,
+    CLASS_PREFIX, SITE_FLAGS,
+};
+use crate::{
+    common::{write_comp_obj, CompObj, CLSID_STD_FONT},
+    controls::{
+        command_button::CommandButtonControl,
+        image::ImageControl,
+        label::LabelControl,
+        morph_data::{ColumnInfo, DisplayStyle, MorphDataControl},
+        scroll_bar::ScrollBarControl,
+        spin_button::SpinButtonControl,
+        tab_strip::{TabFlags, TabStripControl},
+        user_form::{
+            class_table::SiteClassInfo,
+            ole_site_concrete::{Clsid, OleSiteConcreteControl, SiteFlags},
+            write_form_control, FormControl, FormFlags, FormScrollBarFlags, Site, SiteKind,
+        },
+        Control,
+    },
+    properties::{
+        color::OleColor,
+        font::{FontEffects, FontFlags, FormFont, GuidAndFont, StdFont, TextProps},
+        picture::{GuidAndPicture, StdPicture},
+        units::{points_to_twips, twips_to_himetric},
+        FormEmbeddedActiveXControlCached, Position, Size, VariousPropertyBits,
+    },
+    BufferedOFormsFile, OFormsFile,
+};
+
+/// The properties of the root form that are stored in the `\x03VBFrame` stream
+const VB_FRAME_PROPERTIES: &[&str] = &[
+    "Caption",
+    "ClientHeight",
+    "ClientLeft",
+    "ClientTop",
+    "ClientWidth",
+    "HelpContextID",
+    "RightToLeft",
+    "ShowModal",
+    "StartUpPosition",
+    "Tag",
+    "TypeInfoVer",
+    "WhatsThisButton",
+    "WhatsThisHelp",
+];
+
+/// The signature at the start of a compound file
+const CFB_SIGNATURE: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+/// A form imported from `.frm` and `.frx`, ready to be written to a compound file
+#[derive(Debug)]
+pub struct ImportedForm {
+    /// The `\x03VBFrame` stream, for the root form
+    pub vb_frame: Option<String>,
+    /// The form, with one site for every control
+    pub form: FormControl,
+    /// The object stream (`o`) with the data of all streamed controls
+    pub objects: Vec<u8>,
+    /// The parent controls (Frame), by the ID of their site
+    pub children: Vec<(i32, ImportedForm)>,
+}
+
+impl ImportedForm {
+    /// Read the form in `storage` of `file`, with the storages of its parent controls
+    pub fn read_from<T: Read + Seek>(file: &mut OFormsFile<T>, storage: &Path) -> io::Result<Self> {
+        let form = file.form_in(storage)?.into_form_control();
+        let mut objects = Vec::new();
+        file.read_stream_into(storage.join("o"), &mut objects)?;
+        let mut children = Vec::new();
+        for site in &form.sites {
+            let SiteKind::Ole(site) = &site.kind;
+            let child = storage.join(format!("i{:02}", site.id));
+            if !site.bit_flags.contains(SiteFlags::STREAMED)
+                && file.is_storage(file.prefix.join(&child))
+            {
+                children.push((site.id, Self::read_from(file, &child)?));
+            }
+        }
+        Ok(Self {
+            vb_frame: None,
+            form,
+            objects,
+            children,
+        })
+    }
+
+    /// The contents of the form stream (`f`)
+    pub fn form_stream(&self) -> io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        write_form_control(&mut out, &self.form)?;
+        Ok(out)
+    }
+
+    /// Write the form to `storage`, with the parent controls in the storages `i{id:02}` below it
+    ///
+    /// The root form (with a `\x03VBFrame`) also gets a `\x01CompObj` stream.
+    pub fn write_to<F: Read + Write + Seek>(
+        &self,
+        cfb: &mut CompoundFile<F>,
+        storage: &Path,
+    ) -> io::Result<()> {
+        if !cfb.is_storage(storage) {
+            cfb.create_storage_all(storage)?;
+        }
+        cfb.create_stream(storage.join("f"))?
+            .write_all(&self.form_stream()?)?;
+        cfb.create_stream(storage.join("o"))?
+            .write_all(&self.objects)?;
+        if let Some(vb_frame) = &self.vb_frame {
+            let (bytes, _, _) = WINDOWS_1252.encode(vb_frame);
+            cfb.create_stream(storage.join("\x03VBFrame"))?
+                .write_all(&bytes)?;
+            let mut comp_obj = Vec::new();
+            write_comp_obj(&mut comp_obj, &CompObj::user_form())?;
+            cfb.create_stream(storage.join("\x01CompObj"))?
+                .write_all(&comp_obj)?;
+        }
+        for (id, child) in &self.children {
+            child.write_to(cfb, &storage.join(format!("i{:02}", id)))?;
+        }
+        Ok(())
+    }
+}
+
+/// Import a form exported by the VBA editor (or [`export_frm`][super::export_frm])
+///
+/// `frx` is the contents of the companion `.frx` file, which may be empty if the `.frm` does
+/// not reference it.
+///
+/// The VBA editor only writes the properties of the designer window to the `.frm`, and the
+/// form itself as the `OleObjectBlob` of the root block: a compound file in the `.frx`. If
+/// there is no such blob, the form is built from the `Begin` blocks of the controls, as
+/// written by [`export_frm`][super::export_frm]. MultiPage controls are only supported in
+/// the blob. A control with an `OleObjectBlob` is imported with these bytes in the object
+/// stream, whatever its class.
+pub fn import_frm(frm: &str, frx: &[u8]) -> io::Result<ImportedForm> {
+    let doc = parse_frm(frm)?;
+    let mut imported = match doc.form.get("OleObjectBlob") {
+        Some(value) if doc.form.children.is_empty() => {
+            let blob = ole_object_blob(frx, &doc.form.name, value)?;
+            let mut file = BufferedOFormsFile::from_bytes(blob.to_vec())?;
+            ImportedForm::read_from(&mut file, Path::new(""))?
+        }
+        Some(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{}: the form has both an OleObjectBlob and controls",
+                    doc.form.name
+                ),
+            ))
+        }
+        None => Importer { frx, next_id: 0 }.form(&doc.form, None)?,
+    };
+
+    let mut vb_frame = format!(
+        "VERSION {}\r\nBegin {} {} \r\n",
+        doc.version, doc.form.class, doc.form.name
+    );
+    for (key, value) in &doc.form.properties {
+        if VB_FRAME_PROPERTIES
+            .iter()
+            .any(|p| p.eq_ignore_ascii_case(key))
+        {
+            vb_frame.push_str(&format!("   {:<16}=   {}\r\n", key, value));
+        }
+    }
+    vb_frame.push_str("End\r\n");
+    imported.vb_frame = Some(vb_frame);
+    Ok(imported)
+}
+
+/// The compound file of the `OleObjectBlob` referenced by `value`
+///
+/// The VBA editor writes a short header before the compound file, so the start of the
+/// compound file is found by its signature.
+fn ole_object_blob<'a>(frx: &'a [u8], name: &str, value: &str) -> io::Result<&'a [u8]> {
+    let error = || invalid(name, "OleObjectBlob", value);
+    let blob = parse_frx_ref(value)
+        .and_then(|offset| frx.get(offset..))
+        .ok_or_else(error)?;
+    let start = blob
+        .windows(CFB_SIGNATURE.len())
+        .take(64)
+        .position(|w| w == CFB_SIGNATURE)
+        .ok_or_else(error)?;
+    Ok(&blob[start..])
+}
+
+/// The entry at `offset` in the `.frx`: a `u32` length, then the data
+fn frx_entry_at(frx: &[u8], offset: usize) -> Option<&[u8]> {
+    let start = offset.checked_add(4)?;
+    let len = frx.get(offset..start)?;
+    let len = u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize;
+    frx.get(start..start.checked_add(len)?)
+}
+
+fn invalid(name: &str, key: &str, value: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{}: invalid value for {}: {}", name, key, value),
+    )
+}
+
+/// The kind of control in a `Begin` line
+enum Class {
+    Cached(FormEmbeddedActiveXControlCached),
+    Other(Uuid),
+}
+
+fn class(block: &FrmBlock) -> io::Result<Class> {
+    use FormEmbeddedActiveXControlCached as C;
+    let unsupported = || {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{}: unsupported control class {}", block.name, block.class),
+        )
+    };
+    if let Some(guid) = block
+        .class
+        .strip_prefix('{')
+        .and_then(|c| c.strip_suffix('}'))
+    {
+        let clsid = Uuid::parse_str(guid).map_err(|_| unsupported())?;
+        return Ok(match C::from_clsid(&clsid) {
+            Some(cached) => Class::Cached(cached),
+            None => Class::Other(clsid),
+        });
+    }
+    let name = match block.class.get(..CLASS_PREFIX.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(CLASS_PREFIX) => {
+            &block.class[CLASS_PREFIX.len()..]
+        }
+        _ => return Err(unsupported()),
+    };
+    let cached = [
+        C::CommandButton,
+        C::Label,
+        C::TextBox,
+        C::ListBox,
+        C::ComboBox,
+        C::CheckBox,
+        C::OptionButton,
+        C::ToggleButton,
+        C::Image,
+        C::SpinButton,
+        C::ScrollBar,
+        C::TabStrip,
+        C::Frame,
+        C::MultiPage,
+    ]
+    .iter()
+    .find(|c| format!("{:?}", c).eq_ignore_ascii_case(name))
+    .copied()
+    .ok_or_else(unsupported)?;
+    Ok(Class::Cached(cached))
+}
+
+struct Importer<'a> {
+    frx: &'a [u8],
+    next_id: i32,
+}
+
+/// The properties of one block, with helpers to convert the values
+struct Props<'a, 'b> {
+    block: &'b FrmBlock,
+    frx: &'a [u8],
+}
+
+impl<'a, 'b> Props<'a, 'b> {
+    fn error(&self, key: &str, value: &str) -> io::Error {
+        invalid(&self.block.name, key, value)
+    }
+
+    /// The payload of the `.frx` entry referenced by `value`
+    fn frx_entry(&self, key: &str, value: &str) -> io::Result<Option<&'a [u8]>> {
+        let offset = match parse_frx_ref(value) {
+            Some(offset) => offset,
+            None => return Ok(None),
+        };
+        let entry = frx_entry_at(self.frx, offset).ok_or_else(|| self.error(key, value))?;
+        Ok(Some(entry))
+    }
+
+    fn string(&self, key: &str) -> io::Result<Option<String>> {
+        let value = match self.block.get(key) {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        if let Some(entry) = self.frx_entry(key, value)? {
+            let text = String::from_utf8(entry.to_vec()).map_err(|_| self.error(key, value))?;
+            return Ok(Some(text));
+        }
+        Ok(Some(
+            parse_frm_string(value).unwrap_or_else(|| value.to_owned()),
+        ))
+    }
+
+    /// The first character of a string, e.g. for `Accelerator`
+    fn char(&self, key: &str) -> io::Result<Option<u16>> {
+        Ok(self
+            .string(key)?
+            .and_then(|s| s.encode_utf16().next())
+            .filter(|c| *c != 0))
+    }
+
+    fn list(&self, key: &str) -> io::Result<Vec<String>> {
+        let value = match self.block.get(key) {
+            Some(value) => value,
+            None => return Ok(Vec::new()),
+        };
+        let mut entry = self
+            .frx_entry(key, value)?
+            .ok_or_else(|| self.error(key, value))?;
+        let next_u16 = |entry: &mut &[u8]| match entry {
+            [a, b, rest @ ..] => {
+                *entry = rest;
+                Ok(u16::from_le_bytes([*a, *b]) as usize)
+            }
+            _ => Err(self.error(key, value)),
+        };
+        let count = next_u16(&mut entry)?;
+        let mut items = Vec::with_capacity(count);
+        for _ in 0..count {
+            let len = next_u16(&mut entry)?;
+            if entry.len() < len {
+                return Err(self.error(key, value));
+            }
+            let (item, rest) = entry.split_at(len);
+            items.push(String::from_utf8(item.to_vec()).map_err(|_| self.error(key, value))?);
+            entry = rest;
+        }
+        Ok(items)
+    }
+
+    fn picture(&self, key: &str) -> io::Result<Option<GuidAndPicture>> {
+        let value = match self.block.get(key) {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        let data = self
+            .frx_entry(key, value)?
+            .ok_or_else(|| self.error(key, value))?;
+        Ok(Some(GuidAndPicture::StdPicture(StdPicture {
+            data: data.to_vec(),
+        })))
+    }
+
+    fn int<T: TryFrom<i64>>(&self, key: &str) -> io::Result<Option<T>> {
+        match self.block.get(key) {
+            Some(value) => parse_frm_int(value)
+                .and_then(|i| T::try_from(i).ok())
+                .map(Some)
+                .ok_or_else(|| self.error(key, value)),
+            None => Ok(None),
+        }
+    }
+
+    fn enumeration<T: FromPrimitive>(&self, key: &str) -> io::Result<Option<T>> {
+        match self.block.get(key) {
+            Some(value) => parse_frm_int(value)
+                .and_then(T::from_i64)
+                .map(Some)
+                .ok_or_else(|| self.error(key, value)),
+            None => Ok(None),
+        }
+    }
+
+    fn bool(&self, key: &str) -> io::Result<Option<bool>> {
+        match self.block.get(key) {
+            Some(value) => parse_frm_bool(value)
+                .map(Some)
+                .ok_or_else(|| self.error(key, value)),
+            None => Ok(None),
+        }
+    }
+
+    fn color(&self, key: &str) -> io::Result<Option<OleColor>> {
+        match self.block.get(key) {
+            Some(value) => parse_frm_int(value)
+                .and_then(|i| u32::try_from(i).ok())
+                .and_then(OleColor::from_u32)
+                .map(Some)
+                .ok_or_else(|| self.error(key, value)),
+            None => Ok(None),
+        }
+    }
+
+    /// A length in twips, converted to HIMETRIC
+    fn himetric(&self, key: &str) -> io::Result<Option<i32>> {
+        match self.block.get(key) {
+            Some(value) => parse_frm_int(value)
+                .and_then(|i| i32::try_from(twips_to_himetric(i)).ok())
+                .map(Some)
+                .ok_or_else(|| self.error(key, value)),
+            None => Ok(None),
+        }
+    }
+
+    /// A length in twips that can't be negative, converted to HIMETRIC
+    fn length(&self, key: &str) -> io::Result<Option<u32>> {
+        match self.himetric(key)? {
+            Some(v) => u32::try_from(v)
+                .map(Some)
+                .map_err(|_| self.error(key, &v.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    fn size(&self, default: Size) -> io::Result<Size> {
+        self.size_from("Width", "Height", default)
+    }
+
+    /// The size from the `width` and `height` properties (in twips)
+    fn size_from(&self, width: &str, height: &str, default: Size) -> io::Result<Size> {
+        Ok(Size {
+            width: self.length(width)?.unwrap_or(default.width),
+            height: self.length(height)?.unwrap_or(default.height),
+        })
+    }
+
+    /// Apply the properties that are stored in [`VariousPropertyBits`]
+    ///
+    /// The bits in `except` are only read from `PropertyBits`, see [`various_flags`].
+    fn various(
+        &self,
+        bits: &mut VariousPropertyBits,
+        except: VariousPropertyBits,
+    ) -> io::Result<()> {
+        if let Some(other) = self.int::<u32>("PropertyBits")? {
+            let named = named_various_bits(except);
+            *bits = VariousPropertyBits::from_bits_retain(bits.bits() & named | other & !named);
+        }
+        for (key, flag) in various_flags(except) {
+            if let Some(value) = self.bool(key)? {
+                bits.set(flag, value);
+            }
+        }
+        if let Some(value) = self.bool("BackStyle")? {
+            bits.set(VariousPropertyBits::BACK_STYLE, value);
+        }
+        if let Some(value) = self.block.get("IMEMode") {
+            let mode = parse_frm_int(value)
+                .filter(|mode| (0..=0xF).contains(mode))
+                .ok_or_else(|| self.error("IMEMode", value))?;
+            bits.remove(VariousPropertyBits::IME_MODE);
+            bits.insert(VariousPropertyBits::from_bits_retain((mode as u32) << 15));
+        }
+        Ok(())
+    }
+
+    /// The group `name`, as a block with the same name as this one
+    fn group(&self, name: &str) -> Option<FrmBlock> {
+        let group = self.block.group(name)?;
+        Some(FrmBlock {
+            name: self.block.name.clone(),
+            properties: group.to_vec(),
+            ..FrmBlock::default()
+        })
+    }
+
+    /// The `Font` group and `TextAlign` of a control
+    fn text_props(&self) -> io::Result<TextProps> {
+        let mut tp = TextProps::default();
+        if let Some(align) = self.enumeration("TextAlign")? {
+            tp.paragraph_align = align;
+        }
+        let font = match self.group("Font") {
+            Some(font) => font,
+            None => return Ok(tp),
+        };
+        let font = Props {
+            block: &font,
+            frx: self.frx,
+        };
+        if let Some(name) = font.string("Name")? {
+            tp.font_name = name;
+        }
+        if let Some(size) = font.block.get("Size") {
+            let points: f64 = size.parse().map_err(|_| font.error("Size", size))?;
+            tp.font_height =
+                u32::try_from(points_to_twips(points)).map_err(|_| font.error("Size", size))?;
+        }
+        if let Some(charset) = font.int("Charset")? {
+            tp.font_char_set = charset;
+        }
+        if let Some(weight) = font.int("Weight")? {
+            tp.font_weight = weight;
+        }
+        if let Some(pitch_and_family) = font.int("PitchAndFamily")? {
+            tp.font_pitch_and_family = pitch_and_family;
+        }
+        let effects = [
+            ("Underline", FontEffects::UNDERLINE),
+            ("Italic", FontEffects::ITALIC),
+            ("Strikethrough", FontEffects::STRIKEOUT),
+        ];
+        for (key, effect) in effects {
+            tp.font_effects
+                .set(effect, font.bool(key)?.unwrap_or(false));
+        }
+        let bold = font.bool("Bold")?.unwrap_or(tp.font_weight >= 700);
+        tp.font_effects.set(FontEffects::BOLD, bold);
+        if let Some(other) = font.int::<u32>("Effects")? {
+            let named = FontEffects::BOLD
+                | FontEffects::ITALIC
+                | FontEffects::UNDERLINE
+                | FontEffects::STRIKEOUT;
+            tp.font_effects |= FontEffects::from_bits_retain(other & !named.bits());
+        }
+        Ok(tp)
+    }
+
+    /// The `Font` group of a form
+    fn std_font(&self) -> io::Result<GuidAndFont> {
+        let font = match self.group("Font") {
+            Some(font) => font,
+            None => return Ok(GuidAndFont::EMPTY),
+        };
+        let font = Props {
+            block: &font,
+            frx: self.frx,
+        };
+        let default = TextProps::default();
+        let height = match font.block.get("Size") {
+            // The height is in ten-thousandths of a point
+            Some(size) => size
+                .parse::<f64>()
+                .ok()
+                .map(|points| (points * 10000.0).round())
+                .filter(|height| (1.0..=f64::from(u32::MAX)).contains(height))
+                .and_then(|height| NonZeroU32::new(height as u32))
+                .ok_or_else(|| font.error("Size", size))?,
+            None => {
+                NonZeroU32::new(default.font_height * 500).ok_or_else(|| font.error("Size", "0"))?
+            }
+        };
+        let mut flags = FontFlags::empty();
+        let names = [
+            ("Bold", FontFlags::BOLD),
+            ("Italic", FontFlags::ITALIC),
+            ("Underline", FontFlags::UNDERLINE),
+            ("Strikethrough", FontFlags::STRIKETHROUGH),
+        ];
+        for (key, flag) in names {
+            flags.set(flag, font.bool(key)?.unwrap_or(false));
+        }
+        Ok(GuidAndFont {
+            guid: CLSID_STD_FONT,
+            font: FormFont::StdFont(StdFont {
+                charset: font
+                    .int("Charset")?
+                    .unwrap_or(i16::from(default.font_char_set)),
+                flags,
+                weight: font.int("Weight")?.unwrap_or(400),
+                height,
+                font_face: font.string("Name")?.unwrap_or(default.font_name),
+            }),
+        })
+    }
+}
+
+impl<'a> Importer<'a> {
+    fn props<'b>(&self, block: &'b FrmBlock) -> Props<'a, 'b> {
+        Props {
+            block,
+            frx: self.frx,
+        }
+    }
+
+    /// Build the form for the root block (`parent` is `None`) or a Frame
+    fn form(&mut self, block: &FrmBlock, parent: Option<&FrmBlock>) -> io::Result<ImportedForm> {
+        let p = self.props(block);
+        let mut form = default_form();
+        let size = match parent {
+            None => p.size_from("ClientWidth", "ClientHeight", Size::new(4000, 3000))?,
+            Some(_) => p.size(form.displayed_size)?,
+        };
+        form.displayed_size = size;
+        form.logical_size = p.size_from("ScrollWidth", "ScrollHeight", size)?;
+        form.scroll_position = Position {
+            left: p
+                .himetric("ScrollLeft")?
+                .unwrap_or(form.scroll_position.left),
+            top: p.himetric("ScrollTop")?.unwrap_or(form.scroll_position.top),
+        };
+        let keep = FormScrollBarFlags::KEEP_HORIZONTAL | FormScrollBarFlags::KEEP_VERTICAL;
+        let bars = form.scroll_bars.bits();
+        let shown = p.int::<u8>("ScrollBars")?.unwrap_or(bars & !keep.bits());
+        let kept = match p.int::<u8>("KeepScrollBarsVisible")? {
+            Some(kept) => kept << 2,
+            None => bars,
+        };
+        form.scroll_bars =
+            FormScrollBarFlags::from_bits_retain(shown & !keep.bits() | kept & keep.bits());
+        if let Some(caption) = p.string("Caption")? {
+            form.caption = caption;
+        }
+        form.back_color = p.color("BackColor")?.unwrap_or(form.back_color);
+        form.fore_color = p.color("ForeColor")?.unwrap_or(form.fore_color);
+        form.border_color = p.color("BorderColor")?.unwrap_or(form.border_color);
+        form.border_style = p.enumeration("BorderStyle")?.unwrap_or(form.border_style);
+        form.special_effect = p
+            .enumeration("SpecialEffect")?
+            .unwrap_or(form.special_effect);
+        form.cycle = p.enumeration("Cycle")?.unwrap_or(form.cycle);
+        form.zoom = p.int("Zoom")?.unwrap_or(form.zoom);
+        form.mouse_pointer = p.enumeration("MousePointer")?.unwrap_or(form.mouse_pointer);
+        form.draw_buffer = p.int("DrawBuffer")?.unwrap_or(form.draw_buffer);
+        form.group_count = p.int("GroupCount")?.unwrap_or(form.group_count);
+        form.shape_cookie = p.int("ShapeCookie")?.unwrap_or(form.shape_cookie);
+        form.picture = p.picture("Picture")?.unwrap_or(GuidAndPicture::EMPTY);
+        form.mouse_icon = p.picture("MouseIcon")?.unwrap_or(GuidAndPicture::EMPTY);
+        form.picture_alignment = p
+            .enumeration("PictureAlignment")?
+            .unwrap_or(form.picture_alignment);
+        form.picture_size_mode = p
+            .enumeration("PictureSizeMode")?
+            .unwrap_or(form.picture_size_mode);
+        form.picture_tiling = p.bool("PictureTiling")?.unwrap_or(form.picture_tiling);
+        if let Some(flags) = p.int::<u32>("FormFlags")? {
+            let enabled = form.boolean_properties & FormFlags::ENABLED;
+            form.boolean_properties =
+                FormFlags::from_bits_retain(flags & !FormFlags::ENABLED.bits()) | enabled;
+        }
+        if let Some(enabled) = p.bool("Enabled")? {
+            form.boolean_properties.set(FormFlags::ENABLED, enabled);
+        }
+        form.font = p.std_font()?;
+
+        let mut objects = Vec::new();
+        let mut children = Vec::new();
+        for child in &block.children {
+            let (mut site, control) = self.control(child, &mut form.site_classes)?;
+            match control {
+                Some(control) => {
+                    let start = objects.len();
+                    control.write(&mut objects)?;
+                    site.object_stream_size = (objects.len() - start) as u32;
+                }
+                None => {
+                    let id = site.id;
+                    children.push((id, self.form(child, Some(block))?));
+                }
+            }
+            form.sites.push(Site {
+                depth: 0,
+                kind: SiteKind::Ole(site),
+            });
+        }
+        let next_id = u32::try_from(self.next_id.saturating_add(1)).unwrap_or_default();
+        form.next_available_id = p.int("NextAvailableID")?.unwrap_or(next_id);
+        Ok(ImportedForm {
+            vb_frame: None,
+            form,
+            objects,
+            children,
+        })
+    }
+
+    /// Build the site and the control of a `Begin` block
+    ///
+    /// The control is `None` for a Frame, which is imported as a child form.
+    fn control(
+        &mut self,
+        block: &FrmBlock,
+        site_classes: &mut Vec<SiteClassInfo>,
+    ) -> io::Result<(OleSiteConcreteControl, Option<Control>)> {
+        use FormEmbeddedActiveXControlCached as C;
+        let p = self.props(block);
+        let class = class(block)?;
+        let id = p.int("ID")?.unwrap_or(self.next_id.saturating_add(1));
+        self.next_id = self.next_id.max(id);
+
+        let mut bit_flags = default_site_flags(matches!(class, Class::Cached(C::Frame)));
+        if let Some(other) = p.int::<u32>("SiteFlags")? {
+            let named = named_site_flags();
+            bit_flags = SiteFlags::from_bits_retain(bit_flags.bits() & named | other & !named);
+        }
+        for (key, flag) in SITE_FLAGS {
+            if let Some(value) = p.bool(key)? {
+                bit_flags.set(flag, value);
+            }
+        }
+        let mut site = OleSiteConcreteControl {
+            id,
+            help_context_id: p.int("HelpContextID")?.unwrap_or(0),
+            bit_flags,
+            object_stream_size: 0,
+            tab_index: p.int("TabIndex")?.unwrap_or(-1),
+            clsid_cache_index: Clsid::Invalid,
+            group_id: p.int("GroupID")?.and_then(NonZeroU16::new),
+            name: block.name.clone(),
+            tag: p.string("Tag")?.unwrap_or_default(),
+            site_position: Position {
+                left: p.himetric("Left")?.unwrap_or(0),
+                top: p.himetric("Top")?.unwrap_or(0),
+            },
+            control_tip_text: p.string("ControlTipText")?.unwrap_or_default(),
+            runtime_lic_key: p.string("RuntimeLicKey")?.unwrap_or_default(),
+            control_source: p.string("ControlSource")?.unwrap_or_default(),
+            row_source: p.string("RowSource")?.unwrap_or_default(),
+            unknown_mask_bits: 0,
+        };
+
+        let blob = match block.get("OleObjectBlob") {
+            Some(value) => Some(
+                p.frx_entry("OleObjectBlob", value)?
+                    .ok_or_else(|| p.error("OleObjectBlob", value))?
+                    .to_vec(),
+            ),
+            None => None,
+        };
+        let cached = match class {
+            Class::Cached(cached) => cached,
+            Class::Other(clsid) => {
+                let bytes = blob.unwrap_or_default();
+                let index = site_classes
+                    .iter()
+                    .position(|info| info.cls_id == clsid)
+                    .unwrap_or_else(|| {
+                        site_classes.push(SiteClassInfo::new(clsid));
+                        site_classes.len() - 1
+                    });
+                site.clsid_cache_index = Clsid::ClassTable(index as u16);
+                return Ok((site, Some(Control::Unknown { clsid, bytes })));
+            }
+        };
+        site.clsid_cache_index = Clsid::Global(cached as u16);
+        // A control that couldn't be parsed when it was exported
+        if let Some(bytes) = blob {
+            let clsid = cached.clsid();
+            return Ok((site, Some(Control::Unknown { clsid, bytes })));
+        }
+
+        let control = match cached {
+            C::CommandButton => {
+                let mut c = CommandButtonControl::default();
+                p.various(&mut c.various_property_bits, VariousPropertyBits::empty())?;
+                c.size = p.size(c.size)?;
+                c.fore_color = p.color("ForeColor")?.unwrap_or(c.fore_color);
+                c.back_color = p.color("BackColor")?.unwrap_or(c.back_color);
+                c.caption = p.string("Caption")?.unwrap_or_default();
+                c.picture = p.picture("Picture")?.unwrap_or(GuidAndPicture::EMPTY);
+                c.mouse_icon = p.picture("MouseIcon")?.unwrap_or(GuidAndPicture::EMPTY);
+                c.accelerator = p.char("Accelerator")?.unwrap_or(0);
+                c.take_focus_on_click = p.bool("TakeFocusOnClick")?.unwrap_or(true);
+                c.picture_position = p.int("PicturePosition")?.unwrap_or(c.picture_position);
+                c.mouse_pointer = p.enumeration("MousePointer")?.unwrap_or(c.mouse_pointer);
+                c.text_props = p.text_props()?;
+                Control::CommandButton(c)
+            }
+            C::Label => {
+                let mut c = LabelControl::default();
+                p.various(&mut c.various_property_bits, VariousPropertyBits::empty())?;
+                c.size = p.size(c.size)?;
+                c.fore_color = p.color("ForeColor")?.unwrap_or(c.fore_color);
+                c.back_color = p.color("BackColor")?.unwrap_or(c.back_color);
+                c.border_color = p.color("BorderColor")?.unwrap_or(c.border_color);
+                c.border_style = p.enumeration("BorderStyle")?.unwrap_or(c.border_style);
+                c.special_effect = p.enumeration("SpecialEffect")?.unwrap_or(c.special_effect);
+                c.caption = p.string("Caption")?.unwrap_or_default();
+                c.picture = p.picture("Picture")?.unwrap_or(GuidAndPicture::EMPTY);
+                c.mouse_icon = p.picture("MouseIcon")?.unwrap_or(GuidAndPicture::EMPTY);
+                c.accelerator = p.char("Accelerator")?.unwrap_or(0);
+                c.picture_position = p.int("PicturePosition")?.unwrap_or(c.picture_position);
+                c.mouse_pointer = p.enumeration("MousePointer")?.unwrap_or(c.mouse_pointer);
+                c.text_props = p.text_props()?;
+                Control::Label(c)
+            }
+            C::TextBox
+            | C::MorphData
+            | C::ListBox
+            | C::ComboBox
+            | C::CheckBox
+            | C::OptionButton
+            | C::ToggleButton => {
+                let mut display_style = match cached {
+                    C::ListBox => DisplayStyle::List,
+                    C::ComboBox if p.int::<i64>("Style")? == Some(2) => DisplayStyle::DropList,
+                    C::ComboBox => DisplayStyle::Combo,
+                    C::CheckBox => DisplayStyle::CheckBox,
+                    C::OptionButton => DisplayStyle::OptionButton,
+                    C::ToggleButton => DisplayStyle::Toggle,
+                    _ => DisplayStyle::Text,
+                };
+                if let Some(style) = p.enumeration("DisplayStyle")? {
+                    display_style = style;
+                }
+                let mut c = MorphDataControl {
+                    display_style,
+                    ..MorphDataControl::default()
+                };
+                p.various(&mut c.various_property_bits, VariousPropertyBits::empty())?;
+                c.size = p.size(c.size)?;
+                c.fore_color = p.color("ForeColor")?.unwrap_or(c.fore_color);
+                c.back_color = p.color("BackColor")?.unwrap_or(c.back_color);
+                c.border_color = p.color("BorderColor")?.unwrap_or(c.border_color);
+                c.border_style = p.enumeration("BorderStyle")?.unwrap_or(c.border_style);
+                c.special_effect = p.enumeration("SpecialEffect")?.unwrap_or(c.special_effect);
+                c.caption = p.string("Caption")?.unwrap_or_default();
+                let value = match cached {
+                    C::TextBox | C::MorphData => "Text",
+                    _ => "Value",
+                };
+                c.value = p.string(value)?.unwrap_or_default();
+                c.group_name = p.string("GroupName")?.unwrap_or_default();
+                c.picture = p.picture("Picture")?.unwrap_or(GuidAndPicture::EMPTY);
+                c.mouse_icon = p.picture("MouseIcon")?.unwrap_or(GuidAndPicture::EMPTY);
+                c.accelerator = p.char("Accelerator")?.unwrap_or(0);
+                c.password_char = p.char("PasswordChar")?.unwrap_or(0);
+                c.max_length = p.int("MaxLength")?.unwrap_or(c.max_length);
+                c.column_count = p.int("ColumnCount")?.unwrap_or(c.column_count);
+                c.list_rows = p.int("ListRows")?.unwrap_or(c.list_rows);
+                c.match_entry = p.int("MatchEntry")?.unwrap_or(c.match_entry);
+                c.multi_select = p.int("MultiSelect")?.unwrap_or(c.multi_select);
+                c.scroll_bars = p.int("ScrollBars")?.unwrap_or(c.scroll_bars);
+                c.list_width = p.length("ListWidth")?.unwrap_or(c.list_width);
+                c.bound_column = p.int("BoundColumn")?.unwrap_or(c.bound_column);
+                c.text_column = p.int("TextColumn")?.unwrap_or(c.text_column);
+                if let Some(widths) = p.string("ColumnWidths")? {
+                    c.column_info = widths
+                        .split(';')
+                        .map(|width| match parse_frm_int(width)? {
+                            -1 => Some(-1),
+                            width => i32::try_from(twips_to_himetric(width)).ok(),
+                        })
+                        .map(|width| width.map(|column_width| ColumnInfo { column_width }))
+                        .collect::<Option<_>>()
+                        .ok_or_else(|| p.error("ColumnWidths", &widths))?;
+                }
+                c.list_style = p.int("ListStyle")?.unwrap_or(c.list_style);
+                c.show_drop_button_when = p
+                    .int("ShowDropButtonWhen")?
+                    .unwrap_or(c.show_drop_button_when);
+                c.drop_button_style = p.int("DropButtonStyle")?.unwrap_or(c.drop_button_style);
+                c.picture_position = p.int("PicturePosition")?.unwrap_or(c.picture_position);
+                c.mouse_pointer = p.enumeration("MousePointer")?.unwrap_or(c.mouse_pointer);
+                c.text_props = p.text_props()?;
+                match cached {
+                    C::TextBox => Control::TextBox(c),
+                    C::ListBox => Control::ListBox(c),
+                    C::ComboBox => Control::ComboBox(c),
+                    C::CheckBox => Control::CheckBox(c),
+                    C::OptionButton => Control::OptionButton(c),
+                    C::ToggleButton => Control::ToggleButton(c),
+                    _ => Control::TextBox(c),
+                }
+            }
+            C::Image => {
+                let mut c = ImageControl::default();
+                p.various(&mut c.various_property_bits, VariousPropertyBits::AUTO_SIZE)?;
+                c.size = p.size(c.size)?;
+                c.auto_size = p.bool("AutoSize")?.unwrap_or(c.auto_size);
+                c.back_color = p.color("BackColor")?.unwrap_or(c.back_color);
+                c.border_color = p.color("BorderColor")?.unwrap_or(c.border_color);
+                c.border_style = p.enumeration("BorderStyle")?.unwrap_or(c.border_style);
+                c.special_effect = p.enumeration("SpecialEffect")?.unwrap_or(c.special_effect);
+                c.picture = p.picture("Picture")?.unwrap_or(GuidAndPicture::EMPTY);
+                c.mouse_icon = p.picture("MouseIcon")?.unwrap_or(GuidAndPicture::EMPTY);
+                c.picture_alignment = p
+                    .enumeration("PictureAlignment")?
+                    .unwrap_or(c.picture_alignment);
+                c.picture_size_mode = p
+                    .enumeration("PictureSizeMode")?
+                    .unwrap_or(c.picture_size_mode);
+                c.picture_tiling = p.bool("PictureTiling")?.unwrap_or(c.picture_tiling);
+                c.mouse_pointer = p.enumeration("MousePointer")?.unwrap_or(c.mouse_pointer);
+                Control::Image(c)
+            }
+            C::SpinButton => {
+                let mut c = SpinButtonControl::default();
+                p.various(&mut c.various_property_bits, VariousPropertyBits::empty())?;
+                c.size = p.size(c.size)?;
+                c.fore_color = p.color("ForeColor")?.unwrap_or(c.fore_color);
+                c.back_color = p.color("BackColor")?.unwrap_or(c.back_color);
+                c.min = p.int("Min")?.unwrap_or(c.min);
+                c.max = p.int("Max")?.unwrap_or(c.max);
+                c.position = p.int("Value")?.unwrap_or(c.position);
+                c.small_change = p.int("SmallChange")?.unwrap_or(c.small_change);
+                c.orientation = p.int("Orientation")?.unwrap_or(c.orientation);
+                c.delay = p.int("Delay")?.unwrap_or(c.delay);
+                c.prev_enabled = p.int("PrevEnabled")?.unwrap_or(c.prev_enabled);
+                c.next_enabled = p.int("NextEnabled")?.unwrap_or(c.next_enabled);
+                c.mouse_icon = p.picture("MouseIcon")?.unwrap_or(GuidAndPicture::EMPTY);
+                c.mouse_pointer = p.enumeration("MousePointer")?.unwrap_or(c.mouse_pointer);
+                Control::SpinButton(c)
+            }
+            C::ScrollBar => {
+                let mut c = ScrollBarControl::default();
+                p.various(&mut c.various_property_bits, VariousPropertyBits::empty())?;
+                c.size = p.size(c.size)?;
+                c.fore_color = p.color("ForeColor")?.unwrap_or(c.fore_color);
+                c.back_color = p.color("BackColor")?.unwrap_or(c.back_color);
+                c.min = p.int("Min")?.unwrap_or(c.min);
+                c.max = p.int("Max")?.unwrap_or(c.max);
+                c.position = p.int("Value")?.unwrap_or(c.position);
+                c.small_change = p.int("SmallChange")?.unwrap_or(c.small_change);
+                c.large_change = p.int("LargeChange")?.unwrap_or(c.large_change);
+                c.orientation = p.int("Orientation")?.unwrap_or(c.orientation);
+                // A number, or `True` for -1
+                if let Some(value) = block.get("ProportionalThumb") {
+                    c.proportional_thumb = parse_frm_int(value)
+                        .and_then(|i| i16::try_from(i).ok())
+                        .or_else(|| parse_frm_bool(value).map(|b| if b { -1 } else { 0 }))
+                        .ok_or_else(|| p.error("ProportionalThumb", value))?;
+                }
+                c.delay = p.int("Delay")?.unwrap_or(c.delay);
+                c.prev_enabled = p.int("PrevEnabled")?.unwrap_or(c.prev_enabled);
+                c.next_enabled = p.int("NextEnabled")?.unwrap_or(c.next_enabled);
+                c.mouse_icon = p.picture("MouseIcon")?.unwrap_or(GuidAndPicture::EMPTY);
+                c.mouse_pointer = p.enumeration("MousePointer")?.unwrap_or(c.mouse_pointer);
+                Control::ScrollBar(c)
+            }
+            C::TabStrip => {
+                let mut c = TabStripControl::default();
+                p.various(&mut c.various_property_bits, VariousPropertyBits::empty())?;
+                c.size = p.size(c.size)?;
+                c.fore_color = p.color("ForeColor")?.unwrap_or(c.fore_color);
+                c.back_color = p.color("BackColor")?.unwrap_or(c.back_color);
+                c.list_index = p.int("Value")?.unwrap_or(c.list_index);
+                c.tab_style = p.int("Style")?.unwrap_or(c.tab_style);
+                c.tab_orientation = p.int("TabOrientation")?.unwrap_or(c.tab_orientation);
+                c.multi_row = p.bool("MultiRow")?.unwrap_or(c.multi_row);
+                c.items = p.list("Tabs")?;
+                c.tip_strings = p.list("TabToolTips")?;
+                c.tab_names = p.list("TabNames")?;
+                c.tags = p.list("TabTags")?;
+                c.accelerators = p.list("TabAccelerators")?;
+                c.tab_fixed_width = p.length("TabFixedWidth")?.unwrap_or(c.tab_fixed_width);
+                c.tab_fixed_height = p.length("TabFixedHeight")?.unwrap_or(c.tab_fixed_height);
+                c.tooltips = p.bool("ShowTips")?.unwrap_or(c.tooltips);
+                if let Some(flags) = p.string("TabFlags")? {
+                    c.tab_flags = flags
+                        .split(';')
+                        .map(|f| parse_frm_int(f).and_then(|f| u32::try_from(f).ok()))
+                        .map(|f| f.map(TabFlags::from_bits_retain))
+                        .collect::<Option<_>>()
+                        .ok_or_else(|| p.error("TabFlags", &flags))?;
+                }
+                c.tabs_allocated = p.int("TabsAllocated")?.unwrap_or(c.items.len() as u32);
+                c.mouse_icon = p.picture("MouseIcon")?.unwrap_or(GuidAndPicture::EMPTY);
+                c.mouse_pointer = p.enumeration("MousePointer")?.unwrap_or(c.mouse_pointer);
+                c.text_props = p.text_props()?;
+                Control::TabStrip(c)
+            }
+            C::Frame => return Ok((site, None)),
+            C::MultiPage | C::Form => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("{}: {:?} controls are not supported", block.name, cached),
+                ))
+            }
+        };
+        Ok((site, Some(control)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, path::Path};
+
+    use super::import_frm;
+    use crate::{
+        common::CompObj,
+        controls::{
+            command_button::CommandButtonControl,
+            morph_data::{DisplayStyle, MorphDataControl},
+            Control,
+        },
+        fixture::{site, FormBuilder},
+        frm::export_frm,
+        properties::{font::FontEffects, Size},
+        OFormsFile,
+    };
+
+    #[test]
+    fn test_import_frm() {
+        let frm = "VERSION 5.00\r\n\
+            Begin {C62A69F0-16DC-11CE-9E98-00AA00574A4F} Form1 \r\n\
+            \x20  Caption         =   \"Hello\"\r\n\
+            \x20  ClientHeight    =   1080\r\n\
+            \x20  ClientWidth     =   1928\r\n\
+            \x20  StartUpPosition =   1  'CenterOwner\r\n\
+            \x20  Begin MSForms.CommandButton cmdOK \r\n\
+            \x20     Caption         =   \"OK\"\r\n\
+            \x20     Height          =   375\r\n\
+            \x20     Left            =   120\r\n\
+            \x20     TabIndex        =   0\r\n\
+            \x20     Top             =   240\r\n\
+            \x20     Width           =   975\r\n\
+            \x20     BeginProperty Font \r\n\
+            \x20        Name            =   \"Tahoma\"\r\n\
+            \x20        Size            =   9.75\r\n\
+            \x20        Weight          =   700\r\n\
+            \x20     EndProperty\r\n\
+            \x20  End\r\n\
+            \x20  Begin MSForms.Frame fra \r\n\
+            \x20     Caption         =   \"Group\"\r\n\
+            \x20     Height          =   600\r\n\
+            \x20     Width           =   900\r\n\
+            \x20     Begin MSForms.CheckBox chk \r\n\
+            \x20        Value           =   \"1\"\r\n\
+            \x20        Visible         =   0   'False\r\n\
+            \x20     End\r\n\
+            \x20  End\r\n\
+            End\r\n\
+            Attribute VB_Name = \"Form1\"\r\n";
+        let imported = import_frm(frm, &[]).unwrap();
+        let vb_frame = imported.vb_frame.as_deref().unwrap();
+        assert!(vb_frame.contains("StartUpPosition =   1\r\n"));
+        assert!(vb_frame.ends_with("End\r\n"));
+        assert_eq!(imported.form.next_available_id, 4);
+        assert_eq!(imported.children[0].0, 2);
+
+        let mut buf = io::Cursor::new(Vec::new());
+        {
+            let mut cfb = cfb::CompoundFile::create(&mut buf).unwrap();
+            imported.write_to(&mut cfb, Path::new("/")).unwrap();
+            cfb.flush().unwrap();
+        }
+        buf.set_position(0);
+        let mut file = OFormsFile::open(buf).unwrap();
+        assert_eq!(file.root_comp_obj().unwrap(), CompObj::user_form());
+        let mut form = file.root_form().unwrap();
+        assert_eq!(form.form_control().caption, "Hello");
+        let controls: Vec<_> = form
+            .controls()
+            .map(|(site, control)| (site.clone(), control.unwrap()))
+            .collect();
+        assert_eq!(controls.len(), 2);
+        assert_eq!(controls[0].0.name, "cmdOK");
+        match &controls[0].1 {
+            Control::CommandButton(c) => {
+                assert_eq!(c.caption, "OK");
+                assert_eq!(c.size, Size::new(1720, 661));
+                assert_eq!(c.text_props.font_name, "Tahoma");
+                assert_eq!(c.text_props.font_height, 195);
+                assert!(c.text_props.font_effects.contains(FontEffects::BOLD));
+            }
+            c => panic!("unexpected control {:?}", c),
+        }
+        let frame = file.form_in("i02").unwrap().into_form_control();
+        assert_eq!(frame.caption, "Group");
+        assert_eq!(frame.sites.len(), 1);
+
+        let export = export_frm(&mut file, "Form1").unwrap();
+        assert!(export.frm.contains("Begin MSForms.Frame fra"));
+        assert!(export
+            .frm
+            .contains("         Visible         =   0   'False"));
+    }
+
+    #[test]
+    fn test_import_ole_object_blob() {
+        let frm = "VERSION 5.00\r\n\
+            Begin {C62A69F0-16DC-11CE-9E98-00AA00574A4F} UserForm1 \r\n\
+            \x20  Caption         =   \"UserForm1\"\r\n\
+            \x20  ClientHeight    =   3015\r\n\
+            \x20  ClientLeft      =   120\r\n\
+            \x20  ClientTop       =   465\r\n\
+            \x20  ClientWidth     =   4560\r\n\
+            \x20  OleObjectBlob   =   \"UserForm1.frx\":0000\r\n\
+            \x20  StartUpPosition =   1  'CenterOwner\r\n\
+            End\r\n\
+            Attribute VB_Name = \"UserForm1\"\r\n";
+        let size = Size::new(8043, 5318);
+        let check_box = MorphDataControl {
+            display_style: DisplayStyle::CheckBox,
+            ..MorphDataControl::default()
+        };
+        let frame = FormBuilder::new(size).control(site(3, "chk"), Control::CheckBox(check_box));
+        let button = Control::CommandButton(CommandButtonControl::default());
+        let controls = FormBuilder::new(size)
+            .control(site(1, "cmdOK"), button)
+            .frame(site(2, "fra"), frame);
+        // The VBA editor writes a 24 byte header before the compound file
+        let mut frx = vec![0; 24];
+        frx.extend_from_slice(&controls.compound_file("/"));
+
+        let imported = import_frm(frm, &frx).unwrap();
+        let vb_frame = imported.vb_frame.as_deref().unwrap();
+        assert!(vb_frame.contains("ClientLeft      =   120\r\n"));
+        assert!(!vb_frame.contains("OleObjectBlob"));
+        assert_eq!(imported.form.sites.len(), 2);
+        assert_eq!(imported.form.next_available_id, 3);
+        assert_eq!(imported.children.len(), 1);
+        assert_eq!(imported.children[0].0, 2);
+        assert_eq!(imported.children[0].1.form.sites.len(), 1);
+
+        let mut buf = io::Cursor::new(Vec::new());
+        {
+            let mut cfb = cfb::CompoundFile::create(&mut buf).unwrap();
+            imported.write_to(&mut cfb, Path::new("/")).unwrap();
+            cfb.flush().unwrap();
+        }
+        buf.set_position(0);
+        let mut file = OFormsFile::open(buf).unwrap();
+        let mut form = file.root_form().unwrap();
+        let names: Vec<_> = form.controls().map(|(site, _)| site.name.clone()).collect();
+        assert_eq!(names, ["cmdOK", "fra"]);
+        let frame = file.form_in("i02").unwrap().into_form_control();
+        assert_eq!(frame.sites.len(), 1);
+    }
+
+    #[test]
+    fn test_import_missing_ole_object_blob() {
+        let frm = "VERSION 5.00\r\n\
+            Begin {C62A69F0-16DC-11CE-9E98-00AA00574A4F} Form1\r\n\
+            \x20  OleObjectBlob   =   \"Form1.frx\":0000\r\n\
+            End\r\n";
+        let err = import_frm(frm, &[]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = import_frm(frm, &[0; 64]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_import_invalid_frx_ref() {
+        let frm = |caption: &str| {
+            format!(
+                "VERSION 5.00\r\n\
+                Begin {{C62A69F0-16DC-11CE-9E98-00AA00574A4F}} Form1\r\n\
+                \x20  Caption         =   \"Form1.frx\":{}\r\n\
+                End\r\n",
+                caption
+            )
+        };
+        let frx = [2, 0, 0, 0, b'O', b'K'];
+        let imported = import_frm(&frm("0000"), &frx).unwrap();
+        assert_eq!(imported.form.caption, "OK");
+        for offset in ["0002", "FFFFFFFFFFFFFFFE", "FFFFFFFFFFFFFFFF"] {
+            let err = import_frm(&frm(offset), &frx).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{}", offset);
+        }
+        let frx = [0xFF, 0xFF, 0xFF, 0xFF];
+        let err = import_frm(&frm("0000"), &frx).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_import_invalid_size() {
+        let frm = |props: &str| {
+            format!(
+                "VERSION 5.00\r\n\
+                Begin {{C62A69F0-16DC-11CE-9E98-00AA00574A4F}} Form1\r\n\
+                {}\
+                End\r\n",
+                props
+            )
+        };
+        let props = [
+            "\x20  ClientWidth     =   -15\r\n",
+            "\x20  ClientHeight    =   -15\r\n",
+            "\x20  BeginProperty Font\r\n\
+             \x20     Size            =   -8.25\r\n\
+             \x20  EndProperty\r\n",
+            "\x20  BeginProperty Font\r\n\
+             \x20     Size            =   1e12\r\n\
+             \x20  EndProperty\r\n",
+        ];
+        for props in props {
+            let err = import_frm(&frm(props), &[]).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{}", props);
+        }
+    }
+}
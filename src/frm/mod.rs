@@ -0,0 +1,151 @@
+//! # `.frm` / `.frx` files
+//!
+//! The VBA editor exports a form as a `.frm` text file and a `.frx` binary file. The `.frm`
+//! only has the properties of the designer window, and the whole form is stored as a compound
+//! file in the `.frx`, referenced by the `OleObjectBlob` property. [`import_frm`] reads such a
+//! form from the `.frx` as is.
+//!
+//! To make forms readable and diffable, [`export_frm`] instead writes the controls as a
+//! hierarchy of `Begin ... End` blocks, similar to VB6, which [`import_frm`] also reads back
+//! into a form stream and an object stream:
+//!
+//! ```text
+//! VERSION 5.00
+//! Begin {C62A69F0-16DC-11CE-9E98-00AA00574A4F} UserForm1
+//!    Caption         =   "UserForm1"
+//!    Begin MSForms.CommandButton cmdOK
+//!       Caption         =   "OK"
+//!       Height          =   375
+//!       ...
+//!    End
+//! End
+//! ```
+//!
+//! All positions and sizes are in twips. Values that don't fit on a line (pictures, lists and
+//! text with line breaks) are stored in the `.frx` file and referenced as `"Name.frx":XXXX`,
+//! where `XXXX` is the hexadecimal offset of the entry.
+//!
+//! [`export_frm`] writes every property of the form, the sites and the controls that differs
+//! from the value [`import_frm`] assumes when it is missing, so importing an export gives the
+//! same form and object streams again. Properties that VB has no name for use names of this
+//! crate, e.g. `ID` for the ID of a site or `PropertyBits` for unnamed bits of
+//! [`VariousPropertyBits`]. The exceptions are:
+//!
+//! - positions and sizes are rounded to twips
+//! - MultiPage controls and their pages can't be exported
+//! - the class table of a form only has the classes of its controls
+//! - only fonts of forms that are a `StdFont` are exported
+//!
+//! Controls that can't be parsed are exported with the `OleObjectBlob` of their bytes, like
+//! controls of other classes, and imported unchanged.
+//!
+//! **The `.frx` written by [`export_frm`] uses a layout of this crate**, which is not the
+//! layout of VB6 or the VBA editor, and the VBA editor can't import these files. Every entry
+//! is a `u32` length followed by the payload:
+//!
+//! - pictures: the picture data (e.g. a bitmap or a GIF file)
+//! - text: UTF-8
+//! - lists: a `u16` count, then each item as a `u16` length and UTF-8
+//! - the `OleObjectBlob` of other controls: the bytes of the object stream
+
+mod import;
+mod reader;
+mod writer;
+
+pub use import::*;
+pub use reader::*;
+pub use writer::*;
+
+use crate::{
+    controls::user_form::{ole_site_concrete::SiteFlags, Cycle, FormControl},
+    properties::{PictureAlignment, PictureSizeMode, VariousPropertyBits},
+};
+
+/// The name of a control type in a `Begin` line
+pub(crate) const CLASS_PREFIX: &str = "MSForms.";
+
+/// The boolean properties of a site, by their name in a `Begin` block
+///
+/// The other bits are stored together, as `SiteFlags`.
+const SITE_FLAGS: [(&str, SiteFlags); 4] = [
+    ("TabStop", SiteFlags::TAB_STOP),
+    ("Visible", SiteFlags::VISIBLE),
+    ("Default", SiteFlags::DEFAULT),
+    ("Cancel", SiteFlags::CANCEL),
+];
+
+/// The boolean properties in [`VariousPropertyBits`], by their name in a `Begin` block
+///
+/// `BackStyle` and `IMEMode` are written as numbers, all other bits together as
+/// `PropertyBits`, see [`various_flags`].
+const VARIOUS_FLAGS: [(&str, VariousPropertyBits); 19] = [
+    ("Enabled", VariousPropertyBits::ENABLED),
+    ("Locked", VariousPropertyBits::LOCKED),
+    ("ColumnHeads", VariousPropertyBits::COLUMN_HEADS),
+    ("IntegralHeight", VariousPropertyBits::INTEGRAL_HEIGHT),
+    ("MatchRequired", VariousPropertyBits::MATCH_REQUIRED),
+    ("Alignment", VariousPropertyBits::ALIGNMENT),
+    ("Editable", VariousPropertyBits::EDITABLE),
+    ("DragBehavior", VariousPropertyBits::DRAG_BEHAVIOR),
+    ("EnterKeyBehavior", VariousPropertyBits::ENTER_KEY_BEHAVIOR),
+    (
+        "EnterFieldBehavior",
+        VariousPropertyBits::ENTER_FIELD_BEHAVIOR,
+    ),
+    ("TabKeyBehavior", VariousPropertyBits::TAB_KEY_BEHAVIOR),
+    ("WordWrap", VariousPropertyBits::WORD_WRAP),
+    ("BordersSuppress", VariousPropertyBits::BORDERS_SUPPRESS),
+    ("SelectionMargin", VariousPropertyBits::SELECTION_MARGIN),
+    ("AutoWordSelect", VariousPropertyBits::AUTO_WORD_SELECT),
+    ("AutoSize", VariousPropertyBits::AUTO_SIZE),
+    ("HideSelection", VariousPropertyBits::HIDE_SELECTION),
+    ("AutoTab", VariousPropertyBits::AUTO_TAB),
+    ("MultiLine", VariousPropertyBits::MULTI_LINE),
+];
+
+/// The boolean properties in [`VariousPropertyBits`] of a control, by their name
+///
+/// The bits in `except` are stored in `PropertyBits`, e.g. `AutoSize` of an Image, which has
+/// its own field.
+fn various_flags(
+    except: VariousPropertyBits,
+) -> impl Iterator<Item = (&'static str, VariousPropertyBits)> {
+    VARIOUS_FLAGS
+        .iter()
+        .copied()
+        .filter(move |(_, flag)| !except.contains(*flag))
+}
+
+/// The bits of [`VariousPropertyBits`] that have a name in a `Begin` block, see [`various_flags`]
+fn named_various_bits(except: VariousPropertyBits) -> u32 {
+    let named = VariousPropertyBits::BACK_STYLE | VariousPropertyBits::IME_MODE;
+    various_flags(except).fold(named.bits(), |all, (_, flag)| all | flag.bits())
+}
+
+/// The bits of [`SiteFlags`] that have a name in a `Begin` block
+fn named_site_flags() -> u32 {
+    SITE_FLAGS
+        .iter()
+        .fold(0, |all, (_, flag)| all | flag.bits())
+}
+
+/// The flags of a site that has no boolean properties in its `Begin` block
+fn default_site_flags(parent: bool) -> SiteFlags {
+    let flags = SiteFlags::TAB_STOP | SiteFlags::VISIBLE | SiteFlags::AUTO_SIZE;
+    match parent {
+        true => flags | SiteFlags::PROMOTE_CONTROLS,
+        false => flags | SiteFlags::STREAMED,
+    }
+}
+
+/// The form of a block that has no form properties
+fn default_form() -> FormControl {
+    FormControl {
+        draw_buffer: 32000,
+        cycle: Cycle::AllForms,
+        picture_alignment: PictureAlignment::Center,
+        picture_size_mode: PictureSizeMode::Clip,
+        picture_tiling: false,
+        ..FormControl::default()
+    }
+}
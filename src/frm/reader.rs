@@ -0,0 +1,212 @@
+use std::io;
+
+/// A `Begin ... End` block of a `.frm` file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrmBlock {
+    /// The class of the control, e.g. `MSForms.CommandButton` or a `{GUID}`
+    pub class: String,
+    /// The name of the control
+    pub name: String,
+    /// The properties, in file order, with comments removed from the values
+    pub properties: Vec<(String, String)>,
+    /// The `BeginProperty ... EndProperty` groups, e.g. `Font`
+    pub groups: Vec<(String, Vec<(String, String)>)>,
+    /// The nested blocks
+    pub children: Vec<FrmBlock>,
+}
+
+fn find<'a>(properties: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    properties
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .map(|(_, v)| v.as_str())
+}
+
+impl FrmBlock {
+    /// Get the raw value of a property, ignoring ASCII case
+    pub fn get(&self, key: &str) -> Option<&str> {
+        find(&self.properties, key)
+    }
+
+    /// Get the properties of a `BeginProperty` group, ignoring ASCII case
+    pub fn group(&self, name: &str) -> Option<&[(String, String)]> {
+        self.groups
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_slice())
+    }
+
+    /// Get a property from a `BeginProperty` group
+    pub fn group_get(&self, name: &str, key: &str) -> Option<&str> {
+        self.group(name).and_then(|props| find(props, key))
+    }
+}
+
+/// The contents of a `.frm` file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrmDocument {
+    /// The version from the `VERSION` line, e.g. `5.00`
+    pub version: String,
+    /// The form
+    pub form: FrmBlock,
+    /// Everything after the form, i.e. the `Attribute` lines and the code
+    pub code: String,
+}
+
+fn invalid(line: usize, msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("line {}: {}", line + 1, msg),
+    )
+}
+
+/// Remove a trailing `'` comment that is not within a string
+fn strip_comment(value: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in value.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '\'' if !in_string => return value[..i].trim_end(),
+            _ => {}
+        }
+    }
+    value.trim_end()
+}
+
+fn property(line: &str) -> Option<(String, String)> {
+    let (key, value) = line.split_once('=')?;
+    Some((
+        key.trim().to_owned(),
+        strip_comment(value.trim()).to_owned(),
+    ))
+}
+
+/// Parse the text of a `.frm` file
+pub fn parse_frm(text: &str) -> io::Result<FrmDocument> {
+    let mut doc = FrmDocument::default();
+    let mut stack: Vec<FrmBlock> = Vec::new();
+    let mut group: Option<(String, Vec<(String, String)>)> = None;
+    let mut lines = text.lines().enumerate();
+
+    for (n, line) in &mut lines {
+        let line = line.trim();
+        let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let rest = rest.trim();
+        if line.is_empty() {
+            continue;
+        } else if let Some((name, props)) = &mut group {
+            if keyword.eq_ignore_ascii_case("EndProperty") {
+                let (name, props) = (std::mem::take(name), std::mem::take(props));
+                let block = stack.last_mut().ok_or_else(|| invalid(n, "no block"))?;
+                block.groups.push((name, props));
+                group = None;
+            } else {
+                props.extend(property(line));
+            }
+        } else if keyword.eq_ignore_ascii_case("VERSION") && stack.is_empty() {
+            doc.version = rest.to_owned();
+        } else if keyword.eq_ignore_ascii_case("Begin") {
+            let (class, name) = rest.split_once(' ').unwrap_or((rest, ""));
+            stack.push(FrmBlock {
+                class: class.to_owned(),
+                name: name.trim().to_owned(),
+                ..FrmBlock::default()
+            });
+        } else if keyword.eq_ignore_ascii_case("BeginProperty") {
+            let name = rest.split(' ').next().unwrap_or_default();
+            group = Some((name.to_owned(), Vec::new()));
+        } else if keyword.eq_ignore_ascii_case("End") {
+            let block = stack.pop().ok_or_else(|| invalid(n, "unexpected End"))?;
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(block),
+                None => {
+                    doc.form = block;
+                    break;
+                }
+            }
+        } else {
+            let block = stack
+                .last_mut()
+                .ok_or_else(|| invalid(n, "expected Begin"))?;
+            let prop = property(line).ok_or_else(|| invalid(n, "expected a property"))?;
+            block.properties.push(prop);
+        }
+    }
+    if !stack.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "missing End of the form",
+        ));
+    }
+    doc.code = lines.map(|(_, l)| format!("{}\r\n", l)).collect();
+    Ok(doc)
+}
+
+/// Parse a quoted string value, e.g. `"Say ""Hi"""`
+pub fn parse_frm_string(value: &str) -> Option<String> {
+    let inner = value.strip_prefix('"')?.strip_suffix('"')?;
+    Some(inner.replace("\"\"", "\""))
+}
+
+/// Parse a reference to a `.frx` entry, e.g. `"UserForm1.frx":0010`, to the offset
+pub fn parse_frx_ref(value: &str) -> Option<usize> {
+    let (file, offset) = value.rsplit_once(':')?;
+    parse_frm_string(file)?;
+    usize::from_str_radix(offset.trim(), 16).ok()
+}
+
+/// Parse a number, in decimal or as `&H...&` hexadecimal
+pub fn parse_frm_int(value: &str) -> Option<i64> {
+    let value = value.trim();
+    match value
+        .strip_prefix("&H")
+        .or_else(|| value.strip_prefix("&h"))
+    {
+        Some(hex) => {
+            let hex = hex.trim_end_matches('&');
+            u32::from_str_radix(hex, 16).ok().map(i64::from)
+        }
+        None => value.parse().ok(),
+    }
+}
+
+/// Parse a boolean, `-1`/`True` or `0`/`False`
+pub fn parse_frm_bool(value: &str) -> Option<bool> {
+    match value.trim() {
+        v if v.eq_ignore_ascii_case("True") => Some(true),
+        v if v.eq_ignore_ascii_case("False") => Some(false),
+        v => parse_frm_int(v).map(|i| i != 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_frm, parse_frm_int, parse_frx_ref};
+
+    #[test]
+    fn test_parse_frm() {
+        let text = "VERSION 5.00\r\n\
+            Begin {C62A69F0-16DC-11CE-9E98-00AA00574A4F} Form1 \r\n\
+            \x20  Caption         =   \"Don't \"\"quote\"\"\"   'comment\r\n\
+            \x20  Begin MSForms.Label lbl\r\n\
+            \x20     BeginProperty Font \r\n\
+            \x20        Name            =   \"Tahoma\"\r\n\
+            \x20     EndProperty\r\n\
+            \x20     BorderStyle     =   1   'Single\r\n\
+            \x20  End\r\n\
+            End\r\n\
+            Attribute VB_Name = \"Form1\"\r\n";
+        let doc = parse_frm(text).unwrap();
+        assert_eq!(doc.version, "5.00");
+        assert_eq!(doc.form.name, "Form1");
+        assert_eq!(doc.form.get("caption"), Some("\"Don't \"\"quote\"\"\""));
+        let label = &doc.form.children[0];
+        assert_eq!(label.class, "MSForms.Label");
+        assert_eq!(label.get("BorderStyle"), Some("1"));
+        assert_eq!(label.group_get("Font", "Name"), Some("\"Tahoma\""));
+        assert_eq!(doc.code, "Attribute VB_Name = \"Form1\"\r\n");
+
+        assert_eq!(parse_frm_int("&H8000000F&"), Some(0x8000000F));
+        assert_eq!(parse_frx_ref("\"Form1.frx\":001C"), Some(0x1C));
+    }
+}
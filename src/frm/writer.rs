@@ -0,0 +1,1081 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+    fmt::Debug,
+    io::{self, Read, Seek},
+    mem,
+    path::Path,
+};
+
+use num_traits::{FromPrimitive, ToPrimitive};
+use uuid::Uuid;
+
+use super::{
+    default_form, default_site_flags, named_site_flags, named_various_bits, various_flags,
+    CLASS_PREFIX, SITE_FLAGS,
+};
+use crate::{
+    controls::{
+        command_button::CommandButtonControl,
+        image::ImageControl,
+        label::LabelControl,
+        morph_data::{DisplayStyle, MorphDataControl},
+        scroll_bar::ScrollBarControl,
+        spin_button::SpinButtonControl,
+        tab_strip::TabStripControl,
+        user_form::{
+            ole_site_concrete::{Clsid, OleSiteConcreteControl},
+            FormControl, FormFlags, FormScrollBarFlags,
+        },
+        Control,
+    },
+    properties::{
+        color::OleColor,
+        font::{FontEffects, FontFlags, FormFont, StdFont, TextAlign, TextProps},
+        picture::GuidAndPicture,
+        units::himetric_to_twips,
+        FormEmbeddedActiveXControlCached, MousePointer, Size, VariousPropertyBits,
+    },
+    OFormsFile,
+};
+
+/// The CLSID of a UserForm in the `Begin` line
+const USER_FORM: &str = "{C62A69F0-16DC-11CE-9E98-00AA00574A4F}";
+
+/// A form exported to `.frm` and `.frx`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrmFile {
+    /// The text of the `.frm` file
+    pub frm: String,
+    /// The contents of the `.frx` file, in the layout of this crate (see the module docs)
+    pub frx: Vec<u8>,
+}
+
+/// Export the form in the root storage of `file`, as `{name}.frm` and `{name}.frx`
+///
+/// Parent controls (Frame, MultiPage) are exported with their children. If the storage has
+/// a `\x03VBFrame` stream, its header is used for the form. Controls that can't be parsed are
+/// exported with the `OleObjectBlob` of their bytes.
+///
+/// The `.frx` uses the layout described in the [module docs][super], which only
+/// [`import_frm`][super::import_frm] can read. The VBA editor can't import the result.
+pub fn export_frm<T: Read + Seek>(file: &mut OFormsFile<T>, name: &str) -> io::Result<FrmFile> {
+    let mut writer = FrmWriter {
+        frm: String::new(),
+        frx: Vec::new(),
+        frx_name: format!("{}.frx", name),
+        max_id: 0,
+    };
+    let form = file.root_form()?.into_form_control();
+    let header = file.root_vb_frame().ok();
+    let mut known = BTreeSet::new();
+    match &header {
+        Some(header) => {
+            // Everything but the final `End`
+            let body = header.trim_end();
+            let body = body.strip_suffix("End").unwrap_or(body).trim_end();
+            for line in body.lines() {
+                if let Some((key, _)) = line.split_once('=') {
+                    known.insert(key.trim().to_owned());
+                }
+                writer.frm.push_str(line.trim_end());
+                writer.frm.push_str("\r\n");
+            }
+        }
+        None => {
+            writer.frm.push_str("VERSION 5.00\r\n");
+            writer.line(0, &format!("Begin {} {}", USER_FORM, name));
+        }
+    }
+    let mut block = Block::default();
+    writer.form_props(&mut block, &form, true)?;
+    let children = writer.nested(|w| w.children(file, Path::new(""), 1))?;
+    writer.next_available_id(&mut block, &form);
+    block.props.retain(|k, _| !known.contains(*k));
+    writer.write_block(1, block);
+    writer.frm.push_str(&children);
+    writer.line(0, "End");
+    Ok(FrmFile {
+        frm: writer.frm,
+        frx: writer.frx,
+    })
+}
+
+enum Prop {
+    Value(String),
+    Font(TextProps),
+    StdFont(StdFont),
+}
+
+#[derive(Default)]
+struct Block {
+    props: BTreeMap<&'static str, Prop>,
+}
+
+impl Block {
+    fn set(&mut self, name: &'static str, value: impl ToString) {
+        self.props.insert(name, Prop::Value(value.to_string()));
+    }
+
+    fn bool(&mut self, name: &'static str, value: bool) {
+        self.set(name, bool_value(value));
+    }
+
+    fn enumeration(
+        &mut self,
+        name: &'static str,
+        value: impl Into<i64>,
+        label: impl std::fmt::Debug,
+    ) {
+        self.set(name, format!("{}   '{:?}", value.into(), label));
+    }
+
+    fn color(&mut self, name: &'static str, value: OleColor) {
+        self.set(name, format!("&H{:08X}&", value.to_u32()));
+    }
+
+    fn size(&mut self, size: Size) {
+        self.set("Width", himetric_to_twips(i64::from(size.width)));
+        self.set("Height", himetric_to_twips(i64::from(size.height)));
+    }
+
+    fn font(&mut self, text_props: &TextProps) {
+        self.props.insert("Font", Prop::Font(text_props.clone()));
+    }
+
+    /// A number, if it differs from `default`
+    fn int<T: PartialEq + ToString>(&mut self, name: &'static str, value: T, default: T) {
+        if value != default {
+            self.set(name, value);
+        }
+    }
+
+    /// A boolean, if it differs from `default`
+    fn flag(&mut self, name: &'static str, value: bool, default: bool) {
+        if value != default {
+            self.bool(name, value);
+        }
+    }
+
+    /// A value of an enumeration, if it differs from `default`
+    fn choice<T: PartialEq + ToPrimitive + Debug>(
+        &mut self,
+        name: &'static str,
+        value: T,
+        default: T,
+    ) {
+        if value != default {
+            self.enumeration(name, value.to_i64().unwrap_or_default(), value);
+        }
+    }
+
+    /// A color, if it differs from `default`
+    fn color_if(&mut self, name: &'static str, value: OleColor, default: OleColor) {
+        if value != default {
+            self.color(name, value);
+        }
+    }
+
+    /// Bits as a hexadecimal number, if they differ from `default`
+    fn hex(&mut self, name: &'static str, value: u32, default: u32) {
+        if value != default {
+            self.set(name, format!("&H{:08X}&", value));
+        }
+    }
+
+    /// A length in HIMETRIC units as twips, if it differs from `default`
+    fn twips(&mut self, name: &'static str, value: i64, default: i64) {
+        if value != default {
+            self.set(name, himetric_to_twips(value));
+        }
+    }
+}
+
+struct FrmWriter {
+    frm: String,
+    frx: Vec<u8>,
+    frx_name: String,
+    /// The highest ID of a site so far, to find the IDs that [`super::import_frm`] assigns
+    max_id: i32,
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+fn bool_value(value: bool) -> &'static str {
+    match value {
+        true => "-1  'True",
+        false => "0   'False",
+    }
+}
+
+impl FrmWriter {
+    fn line(&mut self, depth: usize, text: &str) {
+        for _ in 0..depth {
+            self.frm.push_str("   ");
+        }
+        self.frm.push_str(text);
+        self.frm.push_str("\r\n");
+    }
+
+    fn prop(&mut self, depth: usize, name: &str, value: impl std::fmt::Display) {
+        self.line(depth, &format!("{:<16}=   {}", name, value));
+    }
+
+    /// Run `f` and return the lines it wrote, instead of adding them to the `.frm`
+    fn nested(&mut self, f: impl FnOnce(&mut Self) -> io::Result<()>) -> io::Result<String> {
+        let frm = mem::take(&mut self.frm);
+        let result = f(self);
+        let nested = mem::replace(&mut self.frm, frm);
+        result.map(|()| nested)
+    }
+
+    fn write_block(&mut self, depth: usize, block: Block) {
+        for (name, prop) in block.props {
+            match prop {
+                Prop::Value(value) => self.prop(depth, name, value),
+                Prop::Font(tp) => self.font(depth, name, &tp),
+                Prop::StdFont(font) => self.std_font(depth, name, &font),
+            }
+        }
+    }
+
+    fn font(&mut self, depth: usize, name: &str, tp: &TextProps) {
+        let effects = tp.font_effects;
+        let flag = |f| bool_value(effects.contains(f));
+        self.line(depth, &format!("BeginProperty {}", name));
+        let d = depth + 1;
+        self.prop(d, "Name", quote(&tp.font_name));
+        self.prop(d, "Size", f64::from(tp.font_height) / 20.0);
+        self.prop(d, "Charset", tp.font_char_set);
+        self.prop(d, "Weight", tp.font_weight);
+        self.prop(d, "Underline", flag(FontEffects::UNDERLINE));
+        self.prop(d, "Italic", flag(FontEffects::ITALIC));
+        self.prop(d, "Strikethrough", flag(FontEffects::STRIKEOUT));
+        if effects.contains(FontEffects::BOLD) != (tp.font_weight >= 700) {
+            self.prop(d, "Bold", flag(FontEffects::BOLD));
+        }
+        if tp.font_pitch_and_family != 0 {
+            self.prop(d, "PitchAndFamily", tp.font_pitch_and_family);
+        }
+        let named = FontEffects::BOLD
+            | FontEffects::ITALIC
+            | FontEffects::UNDERLINE
+            | FontEffects::STRIKEOUT;
+        let other = effects.bits() & !named.bits();
+        if other != 0 {
+            self.prop(d, "Effects", format!("&H{:08X}&", other));
+        }
+        self.line(depth, "EndProperty");
+    }
+
+    fn std_font(&mut self, depth: usize, name: &str, font: &StdFont) {
+        let flag = |f| bool_value(font.flags.contains(f));
+        self.line(depth, &format!("BeginProperty {}", name));
+        let d = depth + 1;
+        self.prop(d, "Name", quote(&font.font_face));
+        self.prop(d, "Size", font.size_points());
+        self.prop(d, "Charset", font.charset);
+        self.prop(d, "Weight", font.weight);
+        self.prop(d, "Underline", flag(FontFlags::UNDERLINE));
+        self.prop(d, "Italic", flag(FontFlags::ITALIC));
+        self.prop(d, "Strikethrough", flag(FontFlags::STRIKETHROUGH));
+        if font.flags.contains(FontFlags::BOLD) {
+            self.prop(d, "Bold", flag(FontFlags::BOLD));
+        }
+        self.line(depth, "EndProperty");
+    }
+
+    /// Append an entry to the `.frx` and return the reference to it
+    ///
+    /// This is the `u32` length prefix of this crate, see the module docs.
+    fn frx_entry(&mut self, payload: &[u8]) -> io::Result<String> {
+        let offset = self.frx.len();
+        let len = u32::try_from(payload.len())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.frx.extend_from_slice(&len.to_le_bytes());
+        self.frx.extend_from_slice(payload);
+        Ok(format!("\"{}\":{:04X}", self.frx_name, offset))
+    }
+
+    /// A string, or a reference to a UTF-8 `.frx` entry if it doesn't fit on a line
+    fn string(&mut self, block: &mut Block, name: &'static str, value: &str) -> io::Result<()> {
+        if value.contains(['\r', '\n']) || value.len() > 255 {
+            let entry = self.frx_entry(value.as_bytes())?;
+            block.set(name, entry);
+        } else if !value.is_empty() {
+            block.set(name, quote(value));
+        }
+        Ok(())
+    }
+
+    fn picture(
+        &mut self,
+        block: &mut Block,
+        name: &'static str,
+        picture: &GuidAndPicture,
+    ) -> io::Result<()> {
+        if let Some(data) = picture.data() {
+            let entry = self.frx_entry(data)?;
+            block.set(name, entry);
+        }
+        Ok(())
+    }
+
+    /// A `.frx` entry with a `u16` count and the items as `u16` length and UTF-8
+    fn list(&mut self, block: &mut Block, name: &'static str, items: &[String]) -> io::Result<()> {
+        if items.is_empty() {
+            return Ok(());
+        }
+        let invalid = |e| io::Error::new(io::ErrorKind::InvalidData, e);
+        let mut payload = Vec::new();
+        let count = u16::try_from(items.len()).map_err(invalid)?;
+        payload.extend_from_slice(&count.to_le_bytes());
+        for item in items {
+            let len = u16::try_from(item.len()).map_err(invalid)?;
+            payload.extend_from_slice(&len.to_le_bytes());
+            payload.extend_from_slice(item.as_bytes());
+        }
+        let entry = self.frx_entry(&payload)?;
+        block.set(name, entry);
+        Ok(())
+    }
+
+    /// The properties of a [`FormControl`], for the root form or a parent control
+    ///
+    /// `NextAvailableID` depends on the controls of the form, see [`Self::next_available_id`].
+    fn form_props(&mut self, block: &mut Block, form: &FormControl, root: bool) -> io::Result<()> {
+        let default = default_form();
+        block.color("BackColor", form.back_color);
+        block.color("ForeColor", form.fore_color);
+        block.color_if("BorderColor", form.border_color, default.border_color);
+        block.choice("BorderStyle", form.border_style, default.border_style);
+        block.choice("SpecialEffect", form.special_effect, default.special_effect);
+        block.int("Zoom", form.zoom, default.zoom);
+        self.string(block, "Caption", &form.caption)?;
+        let size = form.displayed_size;
+        if root {
+            block.set("ClientWidth", himetric_to_twips(i64::from(size.width)));
+            block.set("ClientHeight", himetric_to_twips(i64::from(size.height)));
+        }
+        let logical = form.logical_size;
+        block.twips("ScrollWidth", logical.width.into(), size.width.into());
+        block.twips("ScrollHeight", logical.height.into(), size.height.into());
+        let scroll = form.scroll_position;
+        block.twips(
+            "ScrollLeft",
+            scroll.left.into(),
+            default.scroll_position.left.into(),
+        );
+        block.twips(
+            "ScrollTop",
+            scroll.top.into(),
+            default.scroll_position.top.into(),
+        );
+        let keep = FormScrollBarFlags::KEEP_HORIZONTAL | FormScrollBarFlags::KEEP_VERTICAL;
+        let bars = form.scroll_bars.bits();
+        block.int(
+            "ScrollBars",
+            bars & !keep.bits(),
+            default.scroll_bars.bits() & !keep.bits(),
+        );
+        block.int(
+            "KeepScrollBarsVisible",
+            (bars & keep.bits()) >> 2,
+            (default.scroll_bars.bits() & keep.bits()) >> 2,
+        );
+        block.choice("Cycle", form.cycle, default.cycle);
+        block.choice("MousePointer", form.mouse_pointer, default.mouse_pointer);
+        block.int("DrawBuffer", form.draw_buffer, default.draw_buffer);
+        block.int("GroupCount", form.group_count, default.group_count);
+        block.int("ShapeCookie", form.shape_cookie, default.shape_cookie);
+        let enabled = FormFlags::ENABLED;
+        block.flag(
+            "Enabled",
+            form.boolean_properties.contains(enabled),
+            default.boolean_properties.contains(enabled),
+        );
+        block.hex(
+            "FormFlags",
+            form.boolean_properties.bits() & !enabled.bits(),
+            default.boolean_properties.bits() & !enabled.bits(),
+        );
+        if let FormFont::StdFont(font) = &form.font.font {
+            block.props.insert("Font", Prop::StdFont(font.clone()));
+        }
+        self.picture(block, "Picture", &form.picture)?;
+        self.picture(block, "MouseIcon", &form.mouse_icon)?;
+        block.choice(
+            "PictureAlignment",
+            form.picture_alignment,
+            default.picture_alignment,
+        );
+        block.choice(
+            "PictureSizeMode",
+            form.picture_size_mode,
+            default.picture_size_mode,
+        );
+        block.flag("PictureTiling", form.picture_tiling, default.picture_tiling);
+        Ok(())
+    }
+
+    /// `NextAvailableID` of a form, once the IDs of all its controls were seen
+    fn next_available_id(&self, block: &mut Block, form: &FormControl) {
+        let default = u32::try_from(self.max_id.saturating_add(1)).unwrap_or_default();
+        block.int("NextAvailableID", form.next_available_id, default);
+    }
+
+    /// Write the controls of the form in `storage` as `Begin ... End` blocks
+    fn children<T: Read + Seek>(
+        &mut self,
+        file: &mut OFormsFile<T>,
+        storage: &Path,
+        depth: usize,
+    ) -> io::Result<()> {
+        let mut form = file.form_in(storage)?;
+        let classes: Vec<Uuid> = form
+            .form_control()
+            .site_classes
+            .iter()
+            .map(|info| info.cls_id)
+            .collect();
+        let controls: Vec<(OleSiteConcreteControl, io::Result<Control>)> = form
+            .controls()
+            .map(|(site, control)| (site.clone(), control))
+            .collect();
+        let mut objects = Vec::new();
+        file.read_stream_into(storage.join("o"), &mut objects)?;
+        let mut offset = 0;
+        for (site, control) in controls {
+            let start = offset;
+            offset += site.object_stream_size as usize;
+            let control = match control {
+                Ok(control) => control,
+                // Keep the bytes, so that the control is imported as it was
+                Err(_) => Control::Unknown {
+                    clsid: match site.clsid_cache_index {
+                        Clsid::Global(index) => FormEmbeddedActiveXControlCached::from_u16(index)
+                            .map(|cached| cached.clsid()),
+                        Clsid::ClassTable(index) => classes.get(usize::from(index)).copied(),
+                        Clsid::Invalid => None,
+                    }
+                    .unwrap_or_else(Uuid::nil),
+                    bytes: objects.get(start..offset).unwrap_or_default().to_vec(),
+                },
+            };
+            self.control(file, &site, &control, depth)?;
+        }
+        Ok(())
+    }
+
+    fn control<T: Read + Seek>(
+        &mut self,
+        file: &mut OFormsFile<T>,
+        site: &OleSiteConcreteControl,
+        control: &Control,
+        depth: usize,
+    ) -> io::Result<()> {
+        let class = match control {
+            Control::Unknown { clsid, .. } => format!("{{{}}}", clsid.hyphenated()).to_uppercase(),
+            _ => format!(
+                "{}{}",
+                CLASS_PREFIX,
+                control.type_name().unwrap_or_default()
+            ),
+        };
+        self.line(depth, &format!("Begin {} {}", class, site.name));
+
+        let mut block = Block::default();
+        self.site_props(&mut block, site, control)?;
+        if let Some(size) = control.size() {
+            block.size(size);
+        }
+        if let Some(color) = control.fore_color() {
+            block.color("ForeColor", color);
+        }
+        if let Some(color) = control.back_color() {
+            block.color("BackColor", color);
+        }
+        if let Some(text_props) = control.text_props() {
+            block.font(text_props);
+            block.choice("TextAlign", text_props.paragraph_align, TextAlign::Left);
+        }
+        if let Some(caption) = control.caption() {
+            self.string(&mut block, "Caption", caption)?;
+        }
+        if let Some(picture) = control.picture() {
+            self.picture(&mut block, "Picture", picture)?;
+        }
+
+        let mut parent = None;
+        match control {
+            Control::CommandButton(c) => {
+                let d = CommandButtonControl::default();
+                various(
+                    &mut block,
+                    c.various_property_bits,
+                    d.various_property_bits,
+                    VariousPropertyBits::empty(),
+                );
+                accelerator(&mut block, c.accelerator);
+                block.int("PicturePosition", c.picture_position, d.picture_position);
+                block.flag(
+                    "TakeFocusOnClick",
+                    c.take_focus_on_click,
+                    d.take_focus_on_click,
+                );
+                self.pointer(&mut block, c.mouse_pointer, &c.mouse_icon)?;
+            }
+            Control::Label(c) => {
+                let d = LabelControl::default();
+                various(
+                    &mut block,
+                    c.various_property_bits,
+                    d.various_property_bits,
+                    VariousPropertyBits::empty(),
+                );
+                accelerator(&mut block, c.accelerator);
+                block.int("PicturePosition", c.picture_position, d.picture_position);
+                block.choice("BorderStyle", c.border_style, d.border_style);
+                block.color_if("BorderColor", c.border_color, d.border_color);
+                block.choice("SpecialEffect", c.special_effect, d.special_effect);
+                self.pointer(&mut block, c.mouse_pointer, &c.mouse_icon)?;
+            }
+            Control::TextBox(c)
+            | Control::ListBox(c)
+            | Control::ComboBox(c)
+            | Control::CheckBox(c)
+            | Control::OptionButton(c)
+            | Control::ToggleButton(c) => {
+                let d = MorphDataControl::default();
+                various(
+                    &mut block,
+                    c.various_property_bits,
+                    d.various_property_bits,
+                    VariousPropertyBits::empty(),
+                );
+                accelerator(&mut block, c.accelerator);
+                block.choice("BorderStyle", c.border_style, d.border_style);
+                block.color_if("BorderColor", c.border_color, d.border_color);
+                block.choice("SpecialEffect", c.special_effect, d.special_effect);
+                let value = match control {
+                    Control::TextBox(_) => "Text",
+                    _ => "Value",
+                };
+                self.string(&mut block, value, &c.value)?;
+                self.string(&mut block, "GroupName", &c.group_name)?;
+                let style = match control {
+                    Control::ListBox(_) => DisplayStyle::List,
+                    Control::ComboBox(_) if c.display_style == DisplayStyle::DropList => {
+                        block.set("Style", "2   'fmStyleDropDownList");
+                        DisplayStyle::DropList
+                    }
+                    Control::ComboBox(_) => DisplayStyle::Combo,
+                    Control::CheckBox(_) => DisplayStyle::CheckBox,
+                    Control::OptionButton(_) => DisplayStyle::OptionButton,
+                    Control::ToggleButton(_) => DisplayStyle::Toggle,
+                    _ => DisplayStyle::Text,
+                };
+                block.choice("DisplayStyle", c.display_style, style);
+                block.int("MaxLength", c.max_length, d.max_length);
+                if c.password_char != 0 {
+                    let p = char::from_u32(u32::from(c.password_char)).unwrap_or('*');
+                    block.set("PasswordChar", quote(&p.to_string()));
+                }
+                block.int("ScrollBars", c.scroll_bars, d.scroll_bars);
+                block.twips("ListWidth", c.list_width.into(), d.list_width.into());
+                block.int("BoundColumn", c.bound_column, d.bound_column);
+                block.int("TextColumn", c.text_column, d.text_column);
+                block.int("ColumnCount", c.column_count, d.column_count);
+                block.int("ListRows", c.list_rows, d.list_rows);
+                if !c.column_info.is_empty() {
+                    let widths: Vec<_> = c
+                        .column_info
+                        .iter()
+                        .map(|info| match info.column_width {
+                            -1 => -1,
+                            width => himetric_to_twips(i64::from(width)),
+                        })
+                        .map(|width| width.to_string())
+                        .collect();
+                    block.set("ColumnWidths", quote(&widths.join(";")));
+                }
+                block.int("MatchEntry", c.match_entry, d.match_entry);
+                block.int("ListStyle", c.list_style, d.list_style);
+                block.int(
+                    "ShowDropButtonWhen",
+                    c.show_drop_button_when,
+                    d.show_drop_button_when,
+                );
+                block.int("DropButtonStyle", c.drop_button_style, d.drop_button_style);
+                block.int("MultiSelect", c.multi_select, d.multi_select);
+                block.int("PicturePosition", c.picture_position, d.picture_position);
+                self.pointer(&mut block, c.mouse_pointer, &c.mouse_icon)?;
+            }
+            Control::Image(c) => {
+                let d = ImageControl::default();
+                various(
+                    &mut block,
+                    c.various_property_bits,
+                    d.various_property_bits,
+                    VariousPropertyBits::AUTO_SIZE,
+                );
+                block.flag("AutoSize", c.auto_size, d.auto_size);
+                block.choice("BorderStyle", c.border_style, d.border_style);
+                block.color_if("BorderColor", c.border_color, d.border_color);
+                block.choice("SpecialEffect", c.special_effect, d.special_effect);
+                block.enumeration(
+                    "PictureAlignment",
+                    c.picture_alignment as u8,
+                    c.picture_alignment,
+                );
+                block.enumeration(
+                    "PictureSizeMode",
+                    c.picture_size_mode as u8,
+                    c.picture_size_mode,
+                );
+                block.flag("PictureTiling", c.picture_tiling, d.picture_tiling);
+                self.pointer(&mut block, c.mouse_pointer, &c.mouse_icon)?;
+            }
+            Control::SpinButton(c) => {
+                let d = SpinButtonControl::default();
+                various(
+                    &mut block,
+                    c.various_property_bits,
+                    d.various_property_bits,
+                    VariousPropertyBits::empty(),
+                );
+                block.set("Min", c.min);
+                block.set("Max", c.max);
+                block.set("Value", c.position);
+                block.set("SmallChange", c.small_change);
+                block.set("Orientation", c.orientation);
+                block.set("Delay", c.delay);
+                block.int("PrevEnabled", c.prev_enabled, d.prev_enabled);
+                block.int("NextEnabled", c.next_enabled, d.next_enabled);
+                self.pointer(&mut block, c.mouse_pointer, &c.mouse_icon)?;
+            }
+            Control::ScrollBar(c) => {
+                let d = ScrollBarControl::default();
+                various(
+                    &mut block,
+                    c.various_property_bits,
+                    d.various_property_bits,
+                    VariousPropertyBits::empty(),
+                );
+                block.set("Min", c.min);
+                block.set("Max", c.max);
+                block.set("Value", c.position);
+                block.set("SmallChange", c.small_change);
+                block.set("LargeChange", c.large_change);
+                block.set("Orientation", c.orientation);
+                block.set("Delay", c.delay);
+                block.int("PrevEnabled", c.prev_enabled, d.prev_enabled);
+                block.int("NextEnabled", c.next_enabled, d.next_enabled);
+                block.int(
+                    "ProportionalThumb",
+                    c.proportional_thumb,
+                    d.proportional_thumb,
+                );
+                self.pointer(&mut block, c.mouse_pointer, &c.mouse_icon)?;
+            }
+            Control::TabStrip(c) => {
+                let d = TabStripControl::default();
+                various(
+                    &mut block,
+                    c.various_property_bits,
+                    d.various_property_bits,
+                    VariousPropertyBits::empty(),
+                );
+                block.set("Value", c.list_index);
+                block.set("Style", c.tab_style);
+                block.set("TabOrientation", c.tab_orientation);
+                block.flag("MultiRow", c.multi_row, d.multi_row);
+                block.twips(
+                    "TabFixedWidth",
+                    c.tab_fixed_width.into(),
+                    d.tab_fixed_width.into(),
+                );
+                block.twips(
+                    "TabFixedHeight",
+                    c.tab_fixed_height.into(),
+                    d.tab_fixed_height.into(),
+                );
+                block.flag("ShowTips", c.tooltips, d.tooltips);
+                self.list(&mut block, "Tabs", &c.items)?;
+                self.list(&mut block, "TabToolTips", &c.tip_strings)?;
+                self.list(&mut block, "TabNames", &c.tab_names)?;
+                self.list(&mut block, "TabTags", &c.tags)?;
+                self.list(&mut block, "TabAccelerators", &c.accelerators)?;
+                if !c.tab_flags.is_empty() {
+                    let flags: Vec<_> = c.tab_flags.iter().map(|f| f.bits().to_string()).collect();
+                    block.set("TabFlags", quote(&flags.join(";")));
+                }
+                block.int("TabsAllocated", c.tabs_allocated, c.items.len() as u32);
+                self.pointer(&mut block, c.mouse_pointer, &c.mouse_icon)?;
+            }
+            Control::Frame(p) | Control::MultiPage(p) | Control::Page(p) => {
+                let form = file.form_in(&p.storage)?.into_form_control();
+                block.size(form.displayed_size);
+                self.form_props(&mut block, &form, false)?;
+                let children = self.nested(|w| w.children(file, &p.storage, depth + 1))?;
+                self.next_available_id(&mut block, &form);
+                parent = Some(children);
+            }
+            Control::Unknown { bytes, .. } => {
+                let entry = self.frx_entry(bytes)?;
+                block.set("OleObjectBlob", entry);
+            }
+        }
+        self.write_block(depth + 1, block);
+        if let Some(children) = parent {
+            self.frm.push_str(&children);
+        }
+        self.line(depth, "End");
+        Ok(())
+    }
+
+    /// The properties of the site of a control
+    fn site_props(
+        &mut self,
+        block: &mut Block,
+        site: &OleSiteConcreteControl,
+        control: &Control,
+    ) -> io::Result<()> {
+        let id = self.max_id.saturating_add(1);
+        block.int("ID", site.id, id);
+        self.max_id = self.max_id.max(site.id);
+        block.set(
+            "Left",
+            himetric_to_twips(i64::from(site.site_position.left)),
+        );
+        block.set("Top", himetric_to_twips(i64::from(site.site_position.top)));
+        block.int("TabIndex", site.tab_index, -1);
+        let flags = default_site_flags(control.parent_control().is_some());
+        for (name, flag) in SITE_FLAGS {
+            block.flag(
+                name,
+                site.bit_flags.contains(flag.clone()),
+                flags.contains(flag),
+            );
+        }
+        let named = named_site_flags();
+        block.hex(
+            "SiteFlags",
+            site.bit_flags.bits() & !named,
+            flags.bits() & !named,
+        );
+        block.int("HelpContextID", site.help_context_id, 0);
+        if let Some(group) = site.group_id {
+            block.set("GroupID", group);
+        }
+        self.string(block, "ControlTipText", &site.control_tip_text)?;
+        self.string(block, "Tag", &site.tag)?;
+        self.string(block, "ControlSource", &site.control_source)?;
+        self.string(block, "RowSource", &site.row_source)?;
+        self.string(block, "RuntimeLicKey", &site.runtime_lic_key)?;
+        Ok(())
+    }
+
+    /// The mouse pointer and icon of a control
+    fn pointer(
+        &mut self,
+        block: &mut Block,
+        pointer: MousePointer,
+        icon: &GuidAndPicture,
+    ) -> io::Result<()> {
+        block.choice("MousePointer", pointer, MousePointer::Default);
+        self.picture(block, "MouseIcon", icon)
+    }
+}
+
+/// The properties in [`VariousPropertyBits`] that differ from the control's default
+///
+/// The bits in `except` are only written in `PropertyBits`, see [`various_flags`].
+fn various(
+    block: &mut Block,
+    bits: VariousPropertyBits,
+    default: VariousPropertyBits,
+    except: VariousPropertyBits,
+) {
+    for (name, flag) in various_flags(except) {
+        block.flag(name, bits.contains(flag), default.contains(flag));
+    }
+    let back_style = VariousPropertyBits::BACK_STYLE;
+    if bits.contains(back_style) != default.contains(back_style) {
+        match bits.contains(back_style) {
+            true => block.set("BackStyle", "1   'fmBackStyleOpaque"),
+            false => block.set("BackStyle", "0   'fmBackStyleTransparent"),
+        }
+    }
+    let ime_mode = |bits: VariousPropertyBits| (bits & VariousPropertyBits::IME_MODE).bits() >> 15;
+    block.int("IMEMode", ime_mode(bits), ime_mode(default));
+    let named = named_various_bits(except);
+    block.hex(
+        "PropertyBits",
+        bits.bits() & !named,
+        default.bits() & !named,
+    );
+}
+
+fn accelerator(block: &mut Block, accelerator: u16) {
+    if let Some(c) = char::from_u32(u32::from(accelerator)).filter(|c| *c != '\0') {
+        block.set("Accelerator", quote(&c.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Write};
+
+    use std::{
+        num::{NonZeroU16, NonZeroU32},
+        path::Path,
+    };
+
+    use super::export_frm;
+    use crate::{
+        common::CLSID_STD_FONT,
+        controls::{
+            command_button::CommandButtonControl,
+            image::ImageControl,
+            label::LabelControl,
+            morph_data::{ColumnInfo, DisplayStyle, MorphDataControl},
+            scroll_bar::ScrollBarControl,
+            tab_strip::{TabFlags, TabStripControl},
+            user_form::{
+                ole_site_concrete::SiteFlags, BorderStyle, Cycle, FormFlags, FormScrollBarFlags,
+            },
+            Control,
+        },
+        fixture::{site, FormBuilder},
+        frm::{import_frm, ImportedForm},
+        properties::{
+            color::OleColor,
+            font::{FontEffects, FontFlags, FormFont, GuidAndFont, StdFont, TextAlign},
+            units::twips_to_himetric,
+            MousePointer, Position, Size, SpecialEffect, VariousPropertyBits,
+        },
+        BufferedOFormsFile, OFormsFile,
+    };
+
+    /// A length of `twips`, in HIMETRIC units
+    fn himetric(twips: i64) -> i32 {
+        twips_to_himetric(twips) as i32
+    }
+
+    fn assert_same_form(actual: &ImportedForm, expected: &ImportedForm) {
+        assert_eq!(actual.form, expected.form);
+        assert_eq!(actual.objects, expected.objects);
+        assert_eq!(actual.children.len(), expected.children.len());
+        for ((id, actual), (expected_id, expected)) in
+            actual.children.iter().zip(&expected.children)
+        {
+            assert_eq!(id, expected_id);
+            assert_same_form(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_export_empty_form() {
+        #[rustfmt::skip]
+        let form = [
+            0x00, 0x04, 0x0C, 0x00, // version, cbForm
+            0x00, 0x04, 0x00, 0x00, // PropMask: fDisplayedSize
+            0x48, 0x0D, 0x00, 0x00, // DisplayedSize.Width
+            0x6C, 0x07, 0x00, 0x00, // DisplayedSize.Height
+            0x00, 0x00, // CountOfSiteClassInfo
+            0x00, 0x00, 0x00, 0x00, // CountOfSites
+            0x00, 0x00, 0x00, 0x00, // CountOfBytes
+        ];
+        let vb_frame = "VERSION 5.00\r\nBegin {C62A69F0-16DC-11CE-9E98-00AA00574A4F} Form1 \r\n   Caption         =   \"Form1\"\r\n   ClientHeight    =   1080\r\nEnd\r\n";
+        let mut buf = io::Cursor::new(Vec::new());
+        {
+            let mut cfb = cfb::CompoundFile::create(&mut buf).unwrap();
+            cfb.create_stream("/f").unwrap().write_all(&form).unwrap();
+            cfb.create_stream("/o").unwrap();
+            let mut stream = cfb.create_stream("/\x03VBFrame").unwrap();
+            stream.write_all(vb_frame.as_bytes()).unwrap();
+            drop(stream);
+            cfb.flush().unwrap();
+        }
+        buf.set_position(0);
+        let mut file = OFormsFile::open(buf).unwrap();
+        let export = export_frm(&mut file, "Form1").unwrap();
+        let lines: Vec<_> = export.frm.lines().collect();
+        assert_eq!(lines[0], "VERSION 5.00");
+        assert_eq!(
+            lines[1],
+            "Begin {C62A69F0-16DC-11CE-9E98-00AA00574A4F} Form1"
+        );
+        assert_eq!(lines[2], "   Caption         =   \"Form1\"");
+        assert_eq!(lines[3], "   ClientHeight    =   1080");
+        assert!(lines.contains(&"   BackColor       =   &H8000000F&"));
+        assert!(lines.contains(&"   ClientWidth     =   1928"));
+        assert_eq!(lines.last(), Some(&"End"));
+        assert!(export.frx.is_empty());
+    }
+
+    #[test]
+    fn test_export_import() {
+        let size = |w, h| Size::new(himetric(w) as u32, himetric(h) as u32);
+        let mut button = CommandButtonControl {
+            caption: "OK".to_owned(),
+            size: size(975, 375),
+            mouse_pointer: MousePointer::Arrow,
+            picture_position: 0x0007_0001,
+            accelerator: u16::from(b'O'),
+            take_focus_on_click: false,
+            ..CommandButtonControl::default()
+        };
+        button.various_property_bits |=
+            VariousPropertyBits::WORD_WRAP | VariousPropertyBits::from_bits_retain(0x0001_8001);
+        button.text_props.font_name = "Tahoma".to_owned();
+        button.text_props.font_height = 195;
+        button.text_props.font_weight = 600;
+        button.text_props.font_pitch_and_family = 34;
+        button.text_props.paragraph_align = TextAlign::Center;
+        button.text_props.font_effects = FontEffects::BOLD | FontEffects::AUTOCOLOR;
+        let label = LabelControl {
+            caption: "Line 1\r\nLine 2".to_owned(),
+            border_style: BorderStyle::Single,
+            border_color: OleColor::from_u32(0x0000_00FF).unwrap(),
+            special_effect: SpecialEffect::Sunken,
+            ..LabelControl::default()
+        };
+        let combo_box = MorphDataControl {
+            display_style: DisplayStyle::DropList,
+            value: "b".to_owned(),
+            list_width: himetric(1440) as u32,
+            bound_column: 2,
+            text_column: 3,
+            column_count: 2,
+            column_info: vec![
+                ColumnInfo {
+                    column_width: himetric(720),
+                },
+                ColumnInfo { column_width: -1 },
+            ],
+            show_drop_button_when: 2,
+            ..MorphDataControl::default()
+        };
+        let scroll_bar = ScrollBarControl {
+            max: 10,
+            position: 5,
+            prev_enabled: 0,
+            proportional_thumb: 0,
+            ..ScrollBarControl::default()
+        };
+        let tab_strip = TabStripControl {
+            items: vec!["One".to_owned(), "Two".to_owned()],
+            tab_fixed_width: himetric(600) as u32,
+            tab_flags: vec![TabFlags::VISIBLE, TabFlags::VISIBLE | TabFlags::ENABLED],
+            tabs_allocated: 3,
+            tooltips: true,
+            ..TabStripControl::default()
+        };
+        let image = ImageControl {
+            auto_size: true,
+            mouse_pointer: MousePointer::Cross,
+            ..ImageControl::default()
+        };
+        let mut button_site = site(3, "cmdOK");
+        button_site.tab_index = 0;
+        button_site.bit_flags |= SiteFlags::DEFAULT | SiteFlags::PRESERVE_HEIGHT;
+        button_site.group_id = NonZeroU16::new(2);
+        button_site.site_position = Position {
+            left: himetric(120),
+            top: himetric(240),
+        };
+        button_site.control_tip_text = "Tip".to_owned();
+        button_site.runtime_lic_key = "key".to_owned();
+        let mut hidden = site(1, "lbl");
+        hidden.bit_flags.remove(SiteFlags::VISIBLE);
+        let frame = FormBuilder::new(size(900, 600))
+            .with_form(|form| {
+                form.caption = "Group".to_owned();
+                form.special_effect = SpecialEffect::Etched;
+                form.boolean_properties.remove(FormFlags::ENABLED);
+            })
+            .control(site(7, "img"), Control::Image(image));
+        let form = FormBuilder::new(size(4560, 3015))
+            .with_form(|form| {
+                form.caption = "Form1".to_owned();
+                form.logical_size = size(6000, 4000);
+                form.scroll_bars = FormScrollBarFlags::VERTICAL | FormScrollBarFlags::KEEP_VERTICAL;
+                form.scroll_position = Position {
+                    left: 0,
+                    top: himetric(300),
+                };
+                form.cycle = Cycle::CurrentForm;
+                form.draw_buffer = 64000;
+                form.shape_cookie = 5;
+                form.boolean_properties |= FormFlags::DONTSAVECLASSTABLE;
+                form.font = GuidAndFont {
+                    guid: CLSID_STD_FONT,
+                    font: FormFont::StdFont(StdFont {
+                        charset: 0,
+                        flags: FontFlags::ITALIC,
+                        weight: 700,
+                        height: NonZeroU32::new(82500).unwrap(),
+                        font_face: "Tahoma".to_owned(),
+                    }),
+                };
+            })
+            .control(button_site, Control::CommandButton(button))
+            .control(hidden, Control::Label(label))
+            .control(site(4, "cbo"), Control::ComboBox(combo_box))
+            .control(site(5, "scb"), Control::ScrollBar(scroll_bar))
+            .control(site(6, "tab"), Control::TabStrip(tab_strip))
+            .frame(site(2, "fra"), frame)
+            .with_form(|form| form.next_available_id = 12);
+        let mut file = form.file();
+        let expected = ImportedForm::read_from(&mut file, Path::new("")).unwrap();
+
+        let export = export_frm(&mut file, "Form1").unwrap();
+        let imported = import_frm(&export.frm, &export.frx).unwrap();
+        let mut buf = io::Cursor::new(Vec::new());
+        {
+            let mut cfb = cfb::CompoundFile::create(&mut buf).unwrap();
+            imported.write_to(&mut cfb, Path::new("/")).unwrap();
+            cfb.flush().unwrap();
+        }
+        let mut file = BufferedOFormsFile::from_bytes(buf.into_inner()).unwrap();
+        let actual = ImportedForm::read_from(&mut file, Path::new("")).unwrap();
+        assert_same_form(&actual, &expected);
+    }
+
+    #[test]
+    fn test_export_unparsable_control() {
+        let button = CommandButtonControl {
+            caption: "OK".to_owned(),
+            ..CommandButtonControl::default()
+        };
+        let size = Size::new(himetric(2268) as u32, himetric(1701) as u32);
+        let form = FormBuilder::new(size)
+            .control(site(1, "cmdOK"), Control::CommandButton(button))
+            // An unknown version of the CommandButton
+            .with_objects(|objects| objects[1] = 0x09);
+        let mut file = form.file();
+        let expected = ImportedForm::read_from(&mut file, Path::new("")).unwrap();
+
+        let export = export_frm(&mut file, "Form1").unwrap();
+        assert!(export
+            .frm
+            .contains("   Begin {D7053240-CE69-11CD-A777-00DD01143C57} cmdOK\r\n"));
+        assert!(export
+            .frm
+            .contains("      OleObjectBlob   =   \"Form1.frx\":0000\r\n"));
+        let imported = import_frm(&export.frm, &export.frx).unwrap();
+        let mut buf = io::Cursor::new(Vec::new());
+        {
+            let mut cfb = cfb::CompoundFile::create(&mut buf).unwrap();
+            imported.write_to(&mut cfb, Path::new("/")).unwrap();
+            cfb.flush().unwrap();
+        }
+        let mut file = BufferedOFormsFile::from_bytes(buf.into_inner()).unwrap();
+        let actual = ImportedForm::read_from(&mut file, Path::new("")).unwrap();
+        assert_same_form(&actual, &expected);
+    }
+}
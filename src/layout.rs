@@ -0,0 +1,486 @@
+//! Absolute positions of the controls of a form
+//!
+//! The position of an embedded control is relative to the [`logical_size`] of the form or
+//! parent control (Frame, MultiPage) that contains it. The visible part of that area starts
+//! at the [`scroll_position`] and has the [`displayed_size`], and the controls are scaled
+//! by the [`zoom`] of the form.
+//!
+//! A [`Layout`] resolves all of this to rectangles in form coordinates: HIMETRIC units
+//! relative to the top-left corner of the client area of the root form.
+//!
+//! [`logical_size`]: crate::controls::user_form::FormControl::logical_size
+//! [`scroll_position`]: crate::controls::user_form::FormControl::scroll_position
+//! [`displayed_size`]: crate::controls::user_form::FormControl::displayed_size
+//! [`zoom`]: crate::controls::user_form::FormControl::zoom
+
+use std::{
+    io::{self, Read, Seek},
+    path::Path,
+};
+
+use crate::{
+    controls::user_form::{ole_site_concrete::SiteFlags, FormControl},
+    properties::{Position, Size},
+    OFormsFile,
+};
+
+/// A rectangle in HIMETRIC units
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rect {
+    /// The top-left corner
+    pub position: Position,
+    /// The width and height
+    pub size: Size,
+}
+
+impl Rect {
+    pub const fn new(position: Position, size: Size) -> Self {
+        Self { position, size }
+    }
+
+    /// The x coordinate of the left edge
+    pub fn left(&self) -> i64 {
+        i64::from(self.position.left)
+    }
+
+    /// The y coordinate of the top edge
+    pub fn top(&self) -> i64 {
+        i64::from(self.position.top)
+    }
+
+    /// The x coordinate of the right edge (exclusive)
+    pub fn right(&self) -> i64 {
+        self.left() + i64::from(self.size.width)
+    }
+
+    /// The y coordinate of the bottom edge (exclusive)
+    pub fn bottom(&self) -> i64 {
+        self.top() + i64::from(self.size.height)
+    }
+
+    /// Whether the point is inside of this rectangle
+    pub fn contains(&self, point: Position) -> bool {
+        let (x, y) = (i64::from(point.left), i64::from(point.top));
+        self.left() <= x && x < self.right() && self.top() <= y && y < self.bottom()
+    }
+
+    /// Whether the rectangles share a part with a non-zero area
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// The common part of both rectangles, if it has a non-zero area
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let left = self.left().max(other.left());
+        let top = self.top().max(other.top());
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        if left < right && top < bottom {
+            Some(Rect::new(
+                Position::new(left as i32, top as i32),
+                Size::new((right - left) as u32, (bottom - top) as u32),
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// An embedded control in a [`Layout`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutItem {
+    /// The ID of the site
+    pub id: i32,
+    /// The name of the control
+    pub name: String,
+    /// The index of the parent control in [`Layout::items`], if any
+    pub parent: Option<usize>,
+    /// Whether the control and all its parent controls are visible
+    pub visible: bool,
+    /// The area of the control, in form coordinates
+    pub rect: Rect,
+    /// The visible area of the parent control (or the form), in form coordinates
+    pub clip: Rect,
+    /// The area that the parent control (or the form) can be scrolled to, in form coordinates
+    pub scrollable: Rect,
+}
+
+impl LayoutItem {
+    /// Whether no part of the control is visible without scrolling
+    pub fn is_outside_visible_area(&self) -> bool {
+        !self.rect.intersects(&self.clip)
+    }
+
+    /// Whether no part of the control can be made visible by scrolling
+    pub fn is_outside_scrollable_area(&self) -> bool {
+        !self.rect.intersects(&self.scrollable)
+    }
+}
+
+/// The absolute rectangles of all controls of a form, see the [module docs][self]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    items: Vec<LayoutItem>,
+    client_area: Rect,
+    skipped: Vec<String>,
+}
+
+/// The placement of the contents of a form
+#[derive(Clone, Copy)]
+struct Frame {
+    parent: Option<usize>,
+    visible: bool,
+    /// The absolute position of the origin of the logical area
+    origin: (f64, f64),
+    scale: f64,
+    clip: Rect,
+    scrollable: Rect,
+}
+
+fn round(value: f64) -> i32 {
+    value
+        .round()
+        .clamp(f64::from(i32::MIN), f64::from(i32::MAX)) as i32
+}
+
+fn scaled_size(size: Size, scale: f64) -> Size {
+    let scale = |v: u32| {
+        (f64::from(v) * scale)
+            .round()
+            .clamp(0.0, f64::from(u32::MAX)) as u32
+    };
+    Size::new(scale(size.width), scale(size.height))
+}
+
+impl Frame {
+    /// The frame for the contents of `form`, which is displayed at `rect`
+    fn new(
+        form: &FormControl,
+        rect: Rect,
+        outer: Option<&Frame>,
+        parent: Option<usize>,
+        visible: bool,
+    ) -> Self {
+        let scale = outer.map_or(1.0, |f| f.scale) * f64::from(form.zoom) / 100.0;
+        let origin = (
+            rect.left() as f64 - f64::from(form.scroll_position.left) * scale,
+            rect.top() as f64 - f64::from(form.scroll_position.top) * scale,
+        );
+        let clip = match outer {
+            Some(outer) => rect
+                .intersection(&outer.clip)
+                .unwrap_or(Rect::new(rect.position, Size::new(0, 0))),
+            None => rect,
+        };
+        let logical = Size::new(
+            match form.logical_size.width {
+                0 => form.displayed_size.width,
+                w => w,
+            },
+            match form.logical_size.height {
+                0 => form.displayed_size.height,
+                h => h,
+            },
+        );
+        Self {
+            parent,
+            visible,
+            origin,
+            scale,
+            clip,
+            scrollable: Rect::new(
+                Position::new(round(origin.0), round(origin.1)),
+                scaled_size(logical, scale),
+            ),
+        }
+    }
+
+    /// The absolute rectangle of a control in this frame
+    fn place(&self, position: Position, size: Size) -> Rect {
+        Rect::new(
+            Position::new(
+                round(self.origin.0 + f64::from(position.left) * self.scale),
+                round(self.origin.1 + f64::from(position.top) * self.scale),
+            ),
+            scaled_size(size, self.scale),
+        )
+    }
+}
+
+impl Layout {
+    /// Compute the layout of the root form of `file`, including all parent controls
+    pub fn compute<T: Read + Seek>(file: &mut OFormsFile<T>) -> io::Result<Self> {
+        let form = file.root_form()?.into_form_control();
+        let client_area = Rect::new(Position::default(), form.displayed_size);
+        let mut layout = Layout {
+            items: Vec::new(),
+            client_area,
+            skipped: Vec::new(),
+        };
+        let frame = Frame::new(&form, client_area, None, None, true);
+        layout.walk(file, Path::new(""), &frame)?;
+        Ok(layout)
+    }
+
+    fn walk<T: Read + Seek>(
+        &mut self,
+        file: &mut OFormsFile<T>,
+        storage: &Path,
+        frame: &Frame,
+    ) -> io::Result<()> {
+        let mut form = file.form_in(storage)?;
+        let controls: Vec<_> = form
+            .controls()
+            .map(|(site, control)| (site.clone(), control))
+            .collect();
+        for (site, control) in controls {
+            let control = match control {
+                Ok(control) => control,
+                Err(_) => {
+                    self.skipped.push(site.name);
+                    continue;
+                }
+            };
+            let inner = match control.parent_control() {
+                Some(p) => Some((file.form_in(&p.storage)?.into_form_control(), &p.storage)),
+                None => None,
+            };
+            let size = match (&inner, control.size()) {
+                (Some((form, _)), _) => form.displayed_size,
+                (None, Some(size)) => size,
+                (None, None) => {
+                    self.skipped.push(site.name);
+                    continue;
+                }
+            };
+            let index = self.items.len();
+            let visible = frame.visible && site.bit_flags.contains(SiteFlags::VISIBLE);
+            let rect = frame.place(site.site_position, size);
+            self.items.push(LayoutItem {
+                id: site.id,
+                name: site.name,
+                parent: frame.parent,
+                visible,
+                rect,
+                clip: frame.clip,
+                scrollable: frame.scrollable,
+            });
+            if let Some((form, storage)) = inner {
+                let child = Frame::new(&form, rect, Some(frame), Some(index), visible);
+                self.walk(file, storage, &child)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// All controls, in file order with parent controls before their children
+    pub fn items(&self) -> &[LayoutItem] {
+        &self.items
+    }
+
+    /// The client area of the root form, in form coordinates
+    pub fn client_area(&self) -> Rect {
+        self.client_area
+    }
+
+    /// The names of the controls that were skipped because their size is unknown, or they
+    /// could not be parsed
+    pub fn skipped(&self) -> &[String] {
+        &self.skipped
+    }
+
+    /// Find a control by name
+    pub fn get(&self, name: &str) -> Option<&LayoutItem> {
+        self.items.iter().find(|item| item.name == name)
+    }
+
+    /// The topmost visible control at `point`, in form coordinates
+    ///
+    /// Children are above their parent controls, and later controls are above earlier ones.
+    pub fn control_at(&self, point: Position) -> Option<&LayoutItem> {
+        self.items
+            .iter()
+            .rev()
+            .find(|item| item.visible && item.rect.contains(point) && item.clip.contains(point))
+    }
+
+    /// All pairs of visible controls in the same parent that overlap
+    pub fn overlapping(&self) -> Vec<(&LayoutItem, &LayoutItem)> {
+        let mut pairs = Vec::new();
+        for (i, a) in self.items.iter().enumerate() {
+            for b in &self.items[i + 1..] {
+                if a.visible && b.visible && a.parent == b.parent && a.rect.intersects(&b.rect) {
+                    pairs.push((a, b));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// All controls that are fully outside the visible area of their parent control (or the form)
+    pub fn outside_visible_area(&self) -> impl Iterator<Item = &LayoutItem> {
+        self.items
+            .iter()
+            .filter(|item| item.is_outside_visible_area())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{Layout, Rect};
+    use crate::{
+        controls::{
+            label::LabelControl,
+            user_form::ole_site_concrete::{OleSiteConcreteControl, SiteFlags},
+            Control,
+        },
+        fixture::{site, FormBuilder},
+        properties::{Position, Size},
+        BufferedOFormsFile,
+    };
+
+    fn layout(form: FormBuilder) -> Layout {
+        Layout::compute(&mut form.file()).unwrap()
+    }
+
+    fn names<'a>(items: impl IntoIterator<Item = &'a super::LayoutItem>) -> Vec<&'a str> {
+        items.into_iter().map(|i| &i.name[..]).collect()
+    }
+
+    fn label(width: u32, height: u32) -> Control {
+        Control::Label(LabelControl {
+            size: Size::new(width, height),
+            ..LabelControl::default()
+        })
+    }
+
+    fn at(id: i32, name: &str, left: i32, top: i32) -> OleSiteConcreteControl {
+        OleSiteConcreteControl {
+            site_position: Position::new(left, top),
+            ..site(id, name)
+        }
+    }
+
+    /// Two overlapping labels `a` and `b`, and a Frame `f` with a label `c` and a label `d`
+    /// outside of its visible area
+    fn controls() -> FormBuilder {
+        let frame = FormBuilder::new(Size::new(2540, 2540))
+            .control(at(4, "c", 1270, 1270), label(635, 635))
+            .control(at(5, "d", 5080, 0), label(635, 635));
+        FormBuilder::new(Size::new(5080, 2540))
+            .control(at(1, "a", 0, 0), label(1270, 1270))
+            .control(at(2, "b", 635, 635), label(1270, 1270))
+            .frame(at(3, "f", 2540, 0), frame)
+    }
+
+    #[test]
+    fn test_rect() {
+        let a = Rect::new(Position::new(0, 0), Size::new(10, 10));
+        let b = Rect::new(Position::new(5, 5), Size::new(10, 10));
+        let c = Rect::new(Position::new(10, 0), Size::new(10, 10));
+        assert_eq!(
+            a.intersection(&b),
+            Some(Rect::new(Position::new(5, 5), Size::new(5, 5)))
+        );
+        assert!(!a.intersects(&c), "touching edges don't intersect");
+        assert!(a.contains(Position::new(0, 0)));
+        assert!(
+            !a.contains(Position::new(10, 5)),
+            "the right edge is exclusive"
+        );
+    }
+
+    #[test]
+    fn test_nested_position() {
+        let layout = layout(controls());
+        assert_eq!(
+            layout.client_area(),
+            Rect::new(Position::new(0, 0), Size::new(5080, 2540))
+        );
+        let c = layout.get("c").unwrap();
+        assert_eq!(c.rect.position, Position::new(2540 + 1270, 1270));
+        assert_eq!(layout.items()[c.parent.unwrap()].name, "f");
+        assert_eq!(layout.get("a").unwrap().parent, None);
+        assert!(layout.get("missing").is_none());
+        assert!(layout.skipped().is_empty());
+    }
+
+    #[test]
+    fn test_control_at() {
+        let layout = layout(controls());
+        let at = |left, top| {
+            layout
+                .control_at(Position::new(left, top))
+                .map(|i| &i.name[..])
+        };
+        assert_eq!(at(4000, 1500), Some("c"), "children are above their parent");
+        assert_eq!(at(100, 100), Some("a"));
+        assert_eq!(
+            at(700, 700),
+            Some("b"),
+            "later controls are above earlier ones"
+        );
+        assert_eq!(at(3000, 2000), Some("f"));
+        assert_eq!(at(2000, 2000), None);
+    }
+
+    #[test]
+    fn test_hidden_controls() {
+        let frame = FormBuilder::new(Size::new(2540, 2540)).control(site(2, "c"), label(635, 635));
+        let mut hidden = site(1, "f");
+        hidden.bit_flags.remove(SiteFlags::VISIBLE);
+        let layout = layout(
+            FormBuilder::new(Size::new(4000, 3000))
+                .frame(hidden, frame)
+                .control(site(3, "a"), label(1270, 1270)),
+        );
+        assert!(!layout.get("c").unwrap().visible, "hidden with its parent");
+        assert_eq!(
+            layout
+                .control_at(Position::new(100, 100))
+                .map(|i| &i.name[..]),
+            Some("a")
+        );
+        assert!(layout.overlapping().is_empty());
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let layout = layout(controls());
+        let overlapping: Vec<_> = layout
+            .overlapping()
+            .into_iter()
+            .map(|(a, b)| (&a.name[..], &b.name[..]))
+            .collect();
+        // `c` is inside of `f`, but has a different parent
+        assert_eq!(overlapping, [("a", "b")]);
+    }
+
+    #[test]
+    fn test_outside_visible_area() {
+        let layout = layout(controls());
+        assert_eq!(names(layout.outside_visible_area()), ["d"]);
+        // without a scroll area, the logical size of `f` is its displayed size
+        assert!(layout.get("d").unwrap().is_outside_scrollable_area());
+    }
+
+    #[test]
+    fn test_empty_form() {
+        let layout = layout(FormBuilder::new(Size::new(4000, 3000)));
+        assert!(layout.items().is_empty());
+        assert!(layout.control_at(Position::new(0, 0)).is_none());
+    }
+
+    #[test]
+    fn test_missing_form() {
+        let mut buf = io::Cursor::new(Vec::new());
+        cfb::CompoundFile::create(&mut buf)
+            .unwrap()
+            .flush()
+            .unwrap();
+        let mut file = BufferedOFormsFile::from_bytes(buf.into_inner()).unwrap();
+        assert!(Layout::compute(&mut file).is_err());
+    }
+}
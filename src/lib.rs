@@ -20,29 +20,57 @@ use std::{
 };
 
 use cfb::{CompoundFile, Stream};
-use common::{parse_comp_obj, CompObj};
-use controls::user_form::{
-    class_table::SiteClassInfo,
-    ole_site_concrete::{Clsid, OleSiteConcreteControl},
-    parse_form_control, FormControl, Site, SiteKind,
+use common::{parse_comp_obj, CompObj, ParseContext, ParseMode, UnknownBits};
+use controls::{
+    registry::CLSID_PAGE,
+    user_form::{
+        class_table::SiteClassInfo,
+        ole_site_concrete::{Clsid, OleSiteConcreteControl, SiteFlags},
+        parse_form_control_with, FormControl, Site, SiteKind,
+    },
+    Control, ParentControl,
 };
+use encoding_rs::Encoding;
 use nom::{error::VerboseError, Err};
 use num_traits::FromPrimitive;
-use properties::FormEmbeddedActiveXControl;
+use properties::{FormEmbeddedActiveXControl, FormEmbeddedActiveXControlCached};
+use uuid::Uuid;
 
 #[macro_use]
 extern crate bitflags;
 #[macro_use]
 extern crate num_derive;
 
+#[cfg(feature = "async")]
+pub mod async_io;
 pub mod common;
 pub mod controls;
+pub mod diff;
+pub mod events;
+#[cfg(test)]
+mod fixture;
+pub mod frm;
+pub mod layout;
+pub mod lint;
+pub mod merge;
 pub mod properties;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod render;
+pub mod strings;
+pub mod trace;
+pub mod validate;
+#[cfg(feature = "vba")]
+pub mod vba;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 /// An OForms file is a [`cfb::CompoundFile`].
 pub struct OFormsFile<F> {
     inner: CompoundFile<F>,
     prefix: PathBuf,
+    mode: ParseMode,
+    encoding: Option<&'static Encoding>,
 }
 
 fn map_verbose_err(input: &[u8]) -> impl Fn(Err<VerboseError<&[u8]>>) -> io::Error + 'static {
@@ -61,6 +89,21 @@ fn map_verbose_err(input: &[u8]) -> impl Fn(Err<VerboseError<&[u8]>>) -> io::Err
     }
 }
 
+/// Like [`map_verbose_err`], but name the unknown mask bits of properties that can't be read
+fn map_parse_err<'b>(
+    input: &[u8],
+    ctx: &'b ParseContext,
+) -> impl Fn(Err<VerboseError<&[u8]>>) -> io::Error + 'b {
+    let map = map_verbose_err(input);
+    move |e| match ctx.take_unreadable() {
+        Some(bits) => io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: the data of these properties can't be read", bits),
+        ),
+        None => map(e),
+    }
+}
+
 fn read_to_end<T: Read + Seek>(f_stream: &mut Stream<T>) -> io::Result<Vec<u8>> {
     let f_stream_len = usize::try_from(f_stream.len())
         .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
@@ -73,6 +116,8 @@ fn read_to_end<T: Read + Seek>(f_stream: &mut Stream<T>) -> io::Result<Vec<u8>>
 pub struct Form<F> {
     form_control: FormControl,
     obj_stream: Stream<F>,
+    storage: PathBuf,
+    ctx: ParseContext,
 }
 
 impl<F> Form<F> {
@@ -86,6 +131,30 @@ impl<F> Form<F> {
         }
     }
 
+    /// Return an iterator over all sites and their parsed [`Control`]s
+    pub fn controls(&mut self) -> Controls<'_, F> {
+        Controls {
+            stream: &mut self.obj_stream,
+            offset: 0,
+            sites: self.form_control.sites.iter(),
+            classes: &self.form_control.site_classes,
+            storage: &self.storage,
+            ctx: &self.ctx,
+        }
+    }
+
+    /// The unknown bits that were kept while parsing the form and its controls so far
+    ///
+    /// This is always empty in [`ParseMode::Strict`].
+    pub fn warnings(&self) -> Vec<UnknownBits> {
+        self.ctx.warnings()
+    }
+
+    /// Get the path of the storage of this form, relative to the [`OFormsFile`]
+    pub fn storage(&self) -> &Path {
+        &self.storage
+    }
+
     /// Get the parsed [`FormControl`]
     pub fn form_control(&self) -> &FormControl {
         &self.form_control
@@ -146,12 +215,139 @@ impl<'a, F> Iterator for SiteIter<'a, F> {
     }
 }
 
+/// Iterator returned by [`Form::controls`]
+pub struct Controls<'a, F> {
+    stream: &'a mut Stream<F>,
+    offset: u64,
+    sites: std::slice::Iter<'a, Site>,
+    classes: &'a [SiteClassInfo],
+    storage: &'a Path,
+    ctx: &'a ParseContext,
+}
+
+impl<'a, F: Read + Seek> Controls<'a, F> {
+    fn class(&self, clsid: &Clsid) -> io::Result<Option<FormEmbeddedActiveXControl<'a>>> {
+        match clsid {
+            Clsid::Invalid => Ok(None),
+            Clsid::ClassTable(c) => match self.classes.get(*c as usize) {
+                Some(info) => Ok(Some(FormEmbeddedActiveXControl::ControlNonCached(info))),
+                None => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("missing class table entry {}", c),
+                )),
+            },
+            Clsid::Global(idx) => match FormEmbeddedActiveXControlCached::from_u16(*idx) {
+                Some(cached) => Ok(Some(FormEmbeddedActiveXControl::ControlCached(cached))),
+                None => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unexpected clsid cache index {}", idx),
+                )),
+            },
+        }
+    }
+
+    fn read_site(&mut self, site: &OleSiteConcreteControl, start: u64) -> io::Result<Control> {
+        let class = self.class(&site.clsid_cache_index)?;
+        let kind = class.as_ref().and_then(FormEmbeddedActiveXControl::resolve);
+        if !site.bit_flags.contains(SiteFlags::STREAMED) {
+            let parent = || ParentControl {
+                storage: self.storage.join(format!("i{:02}", site.id)),
+            };
+            match kind {
+                Some(FormEmbeddedActiveXControlCached::Frame) => {
+                    return Ok(Control::Frame(parent()))
+                }
+                Some(FormEmbeddedActiveXControlCached::MultiPage) => {
+                    return Ok(Control::MultiPage(parent()))
+                }
+                None if class.as_ref().map(|c| c.clsid()) == Some(CLSID_PAGE) => {
+                    return Ok(Control::Page(parent()))
+                }
+                _ => {}
+            }
+        }
+
+        self.stream.seek(io::SeekFrom::Start(start))?;
+        let mut bytes = Vec::with_capacity(site.object_stream_size as usize);
+        (&mut *self.stream)
+            .take(u64::from(site.object_stream_size))
+            .read_to_end(&mut bytes)?;
+        match kind {
+            Some(kind) => {
+                let (_rest, control) =
+                    Control::parse_with::<VerboseError<_>>(kind, &bytes, self.ctx)
+                        .map_err(map_parse_err(&bytes, self.ctx))?;
+                Ok(control)
+            }
+            None => Ok(Control::Unknown {
+                clsid: class.map(|c| c.clsid()).unwrap_or_else(Uuid::nil),
+                bytes,
+            }),
+        }
+    }
+}
+
+impl<'a, F> Controls<'a, F> {
+    /// Also return the unknown bits that were kept in each control, see [`ParseMode::Lenient`]
+    pub fn with_warnings(self) -> ControlsWithWarnings<'a, F> {
+        ControlsWithWarnings { inner: self }
+    }
+}
+
+impl<'a, F: Read + Seek> Iterator for Controls<'a, F> {
+    /// The site and the parsed control (or the error for that control)
+    type Item = (&'a OleSiteConcreteControl, io::Result<Control>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let s = self.sites.next()?;
+        let SiteKind::Ole(ole_site) = &s.kind;
+        let start = self.offset;
+        self.offset += u64::from(ole_site.object_stream_size);
+        Some((ole_site, self.read_site(ole_site, start)))
+    }
+}
+
+/// Iterator returned by [`Controls::with_warnings`]
+pub struct ControlsWithWarnings<'a, F> {
+    inner: Controls<'a, F>,
+}
+
+impl<'a, F: Read + Seek> Iterator for ControlsWithWarnings<'a, F> {
+    /// The site, the parsed control (or the error for that control) and the unknown bits
+    /// that were kept while parsing it
+    type Item = (
+        &'a OleSiteConcreteControl,
+        io::Result<Control>,
+        Vec<UnknownBits>,
+    );
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.inner.ctx.warning_count();
+        let (site, control) = self.inner.next()?;
+        Some((site, control, self.inner.ctx.warnings_since(start)))
+    }
+}
+
+/// An [`OFormsFile`] that was loaded into memory
+pub type BufferedOFormsFile = OFormsFile<io::Cursor<Vec<u8>>>;
+
+impl BufferedOFormsFile {
+    /// Create a new instance from the bytes of a compound file
+    ///
+    /// This doesn't need a filesystem, e.g. on `wasm32-unknown-unknown`.
+    pub fn from_bytes(bytes: Vec<u8>) -> io::Result<Self> {
+        Self::open(io::Cursor::new(bytes))
+    }
+}
+
 impl<T: Read + Seek> OFormsFile<T> {
     /// Create a new instance by opening the underlying [`cfb::CompoundFile`]
     pub fn open(buf: T) -> io::Result<Self> {
         Ok(Self {
             inner: CompoundFile::open(buf)?,
             prefix: PathBuf::from("/"),
+            mode: ParseMode::Strict,
+            encoding: None,
         })
     }
 
@@ -160,13 +356,51 @@ impl<T: Read + Seek> OFormsFile<T> {
         Ok(Self {
             inner: CompoundFile::open(buf)?,
             prefix,
+            mode: ParseMode::Strict,
+            encoding: None,
         })
     }
 
+    /// Set how unknown bits in masks and flags are handled, see [`ParseMode`]
+    pub fn with_parse_mode(mut self, mode: ParseMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Decode compressed strings with `encoding`, see [`ParseContext::with_encoding`]
+    pub fn with_encoding(mut self, encoding: &'static Encoding) -> Self {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    fn context(&self) -> ParseContext {
+        let ctx = ParseContext::new(self.mode);
+        match self.encoding {
+            Some(encoding) => ctx.with_encoding(encoding),
+            None => ctx,
+        }
+    }
+
     pub fn open_stream<P: AsRef<Path>>(&mut self, path: P) -> io::Result<cfb::Stream<T>> {
         self.inner.open_stream(self.prefix.join(path))
     }
 
+    /// Read a whole stream into `buf`, replacing its contents
+    ///
+    /// When scanning many files, this allows reusing one buffer together with the borrowing
+    /// parsers like [`parse_form_control_ref`][controls::user_form::parse_form_control_ref].
+    pub fn read_stream_into<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        buf: &mut Vec<u8>,
+    ) -> io::Result<()> {
+        let mut stream = self.open_stream(path)?;
+        buf.clear();
+        buf.reserve(usize::try_from(stream.len()).unwrap_or(0));
+        stream.read_to_end(buf)?;
+        Ok(())
+    }
+
     /// Get the form stream (`f`)
     ///
     /// See <https://learn.microsoft.com/en-us/openspecs/office_file_formats/ms-oforms/cb5df5d6-e090-4bf3-a328-c4edaff0c66b>
@@ -188,6 +422,15 @@ impl<T: Read + Seek> OFormsFile<T> {
         self.open_stream("\x01CompObj")
     }
 
+    /// Get the designer stream (`\003VBFrame`) of a VBA form
+    ///
+    /// This is the text header of the `.frm` file, with the properties of the designer window.
+    pub fn root_vb_frame(&mut self) -> io::Result<String> {
+        let mut stream = self.open_stream("\x03VBFrame")?;
+        let bytes = read_to_end(&mut stream)?;
+        Ok(encoding_rs::WINDOWS_1252.decode(&bytes).0.into_owned())
+    }
+
     pub fn root_comp_obj(&mut self) -> io::Result<CompObj> {
         let mut f_stream = self.root_comp_obj_stream()?;
         let bytes = read_to_end(&mut f_stream)?;
@@ -198,16 +441,50 @@ impl<T: Read + Seek> OFormsFile<T> {
     pub fn root_form_control(&mut self) -> io::Result<FormControl> {
         let mut f_stream = self.root_form_stream()?;
         let bytes = read_to_end(&mut f_stream)?;
-        let (_rest, form_control) = parse_form_control(&bytes).map_err(map_verbose_err(&bytes))?;
+        let ctx = self.context();
+        let (_rest, form_control) =
+            parse_form_control_with(&bytes, &ctx).map_err(map_parse_err(&bytes, &ctx))?;
         Ok(form_control)
     }
 
     pub fn root_form(&mut self) -> io::Result<Form<T>> {
-        let form_control = self.root_form_control()?;
-        let obj_stream = self.root_object_stream()?;
+        self.form_in("")
+    }
+
+    /// Find the storages of all forms below the prefix, relative to the prefix
+    ///
+    /// These are the storages with a form (`f`) and an object (`o`) stream. The storages of
+    /// parent controls, which are nested in another form, are not included.
+    pub fn form_storages(&self) -> io::Result<Vec<PathBuf>> {
+        let root = Path::new("/").join(&self.prefix);
+        let mut storages: Vec<PathBuf> = Vec::new();
+        for entry in self.inner.walk_storage(&root)? {
+            let path = entry.path();
+            if !entry.is_storage() || storages.iter().any(|s| path.starts_with(root.join(s))) {
+                continue;
+            }
+            if self.inner.is_stream(path.join("f")) && self.inner.is_stream(path.join("o")) {
+                let relative = path.strip_prefix(&root).unwrap_or(path);
+                storages.push(relative.to_path_buf());
+            }
+        }
+        Ok(storages)
+    }
+
+    /// Open the form in a (relative) storage, e.g. of a [`ParentControl`]
+    pub fn form_in<P: AsRef<Path>>(&mut self, storage: P) -> io::Result<Form<T>> {
+        let storage = storage.as_ref().to_path_buf();
+        let mut f_stream = self.open_stream(storage.join("f"))?;
+        let bytes = read_to_end(&mut f_stream)?;
+        let ctx = self.context();
+        let (_rest, form_control) =
+            parse_form_control_with(&bytes, &ctx).map_err(map_parse_err(&bytes, &ctx))?;
+        let obj_stream = self.open_stream(storage.join("o"))?;
         Ok(Form {
             form_control,
             obj_stream,
+            storage,
+            ctx,
         })
     }
 }
@@ -225,3 +502,93 @@ impl<T> DerefMut for OFormsFile<T> {
         &mut self.inner
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::{ParseMode, UnknownBits},
+        controls::{command_button::CommandButtonControl, label::LabelControl, Control},
+        fixture::{site, FormBuilder},
+        properties::{color::OleColor, Size},
+        BufferedOFormsFile,
+    };
+
+    #[test]
+    fn test_controls_with_warnings() {
+        let bytes = FormBuilder::new(Size::new(4000, 3000))
+            .control(site(1, "cmd"), Control::CommandButton(Default::default()))
+            .control(site(2, "lbl"), Control::Label(LabelControl::default()))
+            // an unknown bit in the mask of the CommandButton, after the header
+            .with_objects(|objects| objects[7] |= 0x80)
+            .compound_file("/");
+
+        let mut file = BufferedOFormsFile::from_bytes(bytes.clone()).unwrap();
+        let mut form = file.root_form().unwrap();
+        assert!(form.controls().next().unwrap().1.is_err());
+
+        let mut file = BufferedOFormsFile::from_bytes(bytes)
+            .unwrap()
+            .with_parse_mode(ParseMode::Lenient);
+        let mut form = file.root_form().unwrap();
+        let controls: Vec<_> = form
+            .controls()
+            .with_warnings()
+            .map(|(site, control, warnings)| (site.name.clone(), control.is_ok(), warnings))
+            .collect();
+        assert_eq!(
+            controls,
+            [
+                (
+                    String::from("cmd"),
+                    true,
+                    vec![UnknownBits {
+                        name: "CommandButtonPropMask",
+                        bits: 0x8000_0000,
+                    }]
+                ),
+                (String::from("lbl"), true, vec![]),
+            ]
+        );
+        assert_eq!(form.warnings().len(), 1);
+
+        // The bit is kept, and written back
+        let mut controls = form.controls();
+        let (site, control) = controls.next().unwrap();
+        let control = control.unwrap();
+        match &control {
+            Control::CommandButton(button) => assert_eq!(button.unknown_mask_bits, 0x8000_0000),
+            other => panic!("unexpected {:?}", other),
+        }
+        let mut written = Vec::new();
+        control.write(&mut written).unwrap();
+        assert_eq!(written.len(), site.object_stream_size as usize);
+        assert_eq!(written[4..8], [0x20, 0x00, 0x00, 0x80]);
+    }
+
+    #[test]
+    fn test_unknown_properties_with_data() {
+        let button = CommandButtonControl {
+            fore_color: OleColor::WINDOWTEXT,
+            ..Default::default()
+        };
+        let bytes = FormBuilder::new(Size::new(4000, 3000))
+            .control(site(1, "cmd"), Control::CommandButton(button))
+            // an unknown bit instead of the ForeColor, which is followed by the Size
+            .with_objects(|objects| {
+                objects[4] &= !0x01;
+                objects[7] |= 0x80;
+            })
+            .compound_file("/");
+
+        let mut file = BufferedOFormsFile::from_bytes(bytes)
+            .unwrap()
+            .with_parse_mode(ParseMode::Lenient);
+        let mut form = file.root_form().unwrap();
+        let error = form.controls().next().unwrap().1.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unknown bits 0x80000000 in CommandButtonPropMask: \
+             the data of these properties can't be read"
+        );
+    }
+}
@@ -0,0 +1,569 @@
+//! Checks for accessibility and usability issues in forms
+//!
+//! ```no_run
+//! # use ms_oforms::{lint::Linter, OFormsFile};
+//! # fn main() -> std::io::Result<()> {
+//! let file = std::fs::File::open("form.bin")?;
+//! let mut oforms = OFormsFile::open(file)?;
+//! for diagnostic in Linter::default().lint(&mut oforms)? {
+//!     println!("{}", diagnostic);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    io::{self, Read, Seek},
+    path::PathBuf,
+};
+
+use crate::{
+    common::json_string,
+    controls::{
+        user_form::ole_site_concrete::{OleSiteConcreteControl, SiteFlags},
+        Control,
+    },
+    layout::Layout,
+    properties::{
+        color::{palette::ColorResolver, OleColor},
+        VariousPropertyBits,
+    },
+    OFormsFile,
+};
+
+/// The kind of issue found by a [`Linter`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LintCode {
+    /// A control that can receive focus has no position in the tab order
+    MissingTabIndex,
+    /// Two controls in the same container have the same tab index
+    DuplicateTabIndex,
+    /// A control that is not visible is still a tab stop
+    HiddenTabStop,
+    /// Two visible controls in the same container overlap
+    Overlap,
+    /// A label with a caption has no accelerator key
+    MissingAccelerator,
+    /// The text color has too little contrast to the background color
+    LowContrast,
+    /// A control is (partly) outside the visible area of its container
+    OutsideForm,
+    /// Two controls have the same name
+    DuplicateName,
+    /// A button has neither a caption nor a picture
+    EmptyCaption,
+}
+
+impl LintCode {
+    /// A stable, kebab-case name for the code, e.g. `missing-tab-index`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::MissingTabIndex => "missing-tab-index",
+            Self::DuplicateTabIndex => "duplicate-tab-index",
+            Self::HiddenTabStop => "hidden-tab-stop",
+            Self::Overlap => "overlap",
+            Self::MissingAccelerator => "missing-accelerator",
+            Self::LowContrast => "low-contrast",
+            Self::OutsideForm => "outside-form",
+            Self::DuplicateName => "duplicate-name",
+            Self::EmptyCaption => "empty-caption",
+        }
+    }
+
+    /// The default severity of the issue
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::DuplicateName => Severity::Error,
+            Self::MissingAccelerator => Severity::Info,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+/// How severe an issue is
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// An issue with a control
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub code: LintCode,
+    pub severity: Severity,
+    /// The name of the control
+    pub control: String,
+    /// The ID of the site of the control
+    pub id: i32,
+    /// A description of the issue
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(code: LintCode, site: &OleSiteConcreteControl, message: String) -> Self {
+        Self {
+            code,
+            severity: code.severity(),
+            control: site.name.clone(),
+            id: site.id,
+            message,
+        }
+    }
+
+    /// The diagnostic as a JSON object
+    pub fn to_json(&self) -> String {
+        format!(
+            r#"{{"code":{},"severity":{},"control":{},"id":{},"message":{}}}"#,
+            json_string(self.code.as_str()),
+            json_string(self.severity.as_str()),
+            json_string(&self.control),
+            self.id,
+            json_string(&self.message)
+        )
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} (ID {}): {} [{}]",
+            self.severity.as_str(),
+            self.control,
+            self.id,
+            self.message,
+            self.code.as_str()
+        )
+    }
+}
+
+/// Format a list of diagnostics as a JSON array
+pub fn diagnostics_to_json(diagnostics: &[Diagnostic]) -> String {
+    let items: Vec<String> = diagnostics.iter().map(Diagnostic::to_json).collect();
+    format!("[{}]", items.join(","))
+}
+
+/// Checks forms for accessibility and usability issues
+#[derive(Debug, Clone)]
+pub struct Linter {
+    resolver: ColorResolver,
+    min_contrast: f64,
+    allowed: BTreeSet<LintCode>,
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self::new(ColorResolver::default())
+    }
+}
+
+/// The controls of one form or parent control
+struct Container {
+    back_color: OleColor,
+    controls: Vec<(OleSiteConcreteControl, Control)>,
+}
+
+/// Whether the control can receive the focus
+fn takes_focus(control: &Control) -> bool {
+    !matches!(control, Control::Label(_) | Control::Image(_))
+}
+
+impl Linter {
+    /// Create a new linter that resolves colors with `resolver`
+    ///
+    /// The minimum contrast is 4.5, as recommended by WCAG 2 for normal text.
+    pub fn new(resolver: ColorResolver) -> Self {
+        Self {
+            resolver,
+            min_contrast: 4.5,
+            allowed: BTreeSet::new(),
+        }
+    }
+
+    /// Set the minimum contrast ratio between the text and background color
+    pub fn with_min_contrast(mut self, min_contrast: f64) -> Self {
+        self.min_contrast = min_contrast;
+        self
+    }
+
+    /// Don't report issues of this kind
+    pub fn allow(mut self, code: LintCode) -> Self {
+        self.allowed.insert(code);
+        self
+    }
+
+    /// Check the root form of `file` and all its parent controls
+    pub fn lint<T: Read + Seek>(&self, file: &mut OFormsFile<T>) -> io::Result<Vec<Diagnostic>> {
+        let mut containers = BTreeMap::new();
+        let mut pending = vec![PathBuf::new()];
+        while let Some(storage) = pending.pop() {
+            let mut form = file.form_in(&storage)?;
+            let back_color = form.form_control().back_color;
+            let mut controls = Vec::new();
+            for (site, control) in form.controls() {
+                // Controls that can't be parsed are not checked
+                if let Ok(control) = control {
+                    if let Some(p) = control.parent_control() {
+                        pending.push(p.storage.clone());
+                    }
+                    controls.push((site.clone(), control));
+                }
+            }
+            containers.insert(
+                storage,
+                Container {
+                    back_color,
+                    controls,
+                },
+            );
+        }
+        let layout = Layout::compute(file)?;
+
+        let mut out = Vec::new();
+        let mut names = BTreeMap::new();
+        for container in containers.values() {
+            self.check_container(container, &mut out);
+            for (site, _) in &container.controls {
+                let key = site.name.to_lowercase();
+                match names.get(&key) {
+                    Some(first) => out.push(Diagnostic::new(
+                        LintCode::DuplicateName,
+                        site,
+                        format!("has the same name as the control with ID {}", first),
+                    )),
+                    None => {
+                        names.insert(key, site.id);
+                    }
+                }
+            }
+        }
+        let sites: BTreeMap<i32, &OleSiteConcreteControl> = containers
+            .values()
+            .flat_map(|c| c.controls.iter().map(|(site, _)| (site.id, site)))
+            .collect();
+        for (a, b) in layout.overlapping() {
+            if let Some(site) = sites.get(&b.id) {
+                out.push(Diagnostic::new(
+                    LintCode::Overlap,
+                    site,
+                    format!("overlaps {}", a.name),
+                ));
+            }
+        }
+        for item in layout.items() {
+            let site = match sites.get(&item.id) {
+                Some(site) => site,
+                None => continue,
+            };
+            if item.is_outside_visible_area() {
+                out.push(Diagnostic::new(
+                    LintCode::OutsideForm,
+                    site,
+                    String::from("is outside the visible area"),
+                ));
+            } else if item.rect.intersection(&item.clip) != Some(item.rect) {
+                out.push(Diagnostic::new(
+                    LintCode::OutsideForm,
+                    site,
+                    String::from("is partly outside the visible area"),
+                ));
+            }
+        }
+        out.retain(|d| !self.allowed.contains(&d.code));
+        Ok(out)
+    }
+
+    /// The checks within one form or parent control
+    fn check_container(&self, container: &Container, out: &mut Vec<Diagnostic>) {
+        let mut tab_indices = BTreeMap::new();
+        for (site, control) in &container.controls {
+            let visible = site.bit_flags.contains(SiteFlags::VISIBLE);
+            let tab_stop = site.bit_flags.contains(SiteFlags::TAB_STOP);
+            if site.tab_index < 0 {
+                if takes_focus(control) && tab_stop {
+                    out.push(Diagnostic::new(
+                        LintCode::MissingTabIndex,
+                        site,
+                        String::from("has no tab index"),
+                    ));
+                }
+            } else if let Some(first) = tab_indices.insert(site.tab_index, &site.name) {
+                out.push(Diagnostic::new(
+                    LintCode::DuplicateTabIndex,
+                    site,
+                    format!("has the same tab index ({}) as {}", site.tab_index, first),
+                ));
+            }
+            if !visible && tab_stop && takes_focus(control) {
+                out.push(Diagnostic::new(
+                    LintCode::HiddenTabStop,
+                    site,
+                    String::from("is not visible, but a tab stop"),
+                ));
+            }
+
+            let caption = control.caption().unwrap_or_default();
+            let has_picture = control.picture().and_then(|p| p.data()).is_some();
+            match control {
+                Control::Label(_) if !caption.is_empty() && control.accelerator().is_none() => out
+                    .push(Diagnostic::new(
+                        LintCode::MissingAccelerator,
+                        site,
+                        String::from("has no accelerator key"),
+                    )),
+                Control::CommandButton(_)
+                | Control::ToggleButton(_)
+                | Control::CheckBox(_)
+                | Control::OptionButton(_)
+                    if caption.trim().is_empty() && !has_picture =>
+                {
+                    out.push(Diagnostic::new(
+                        LintCode::EmptyCaption,
+                        site,
+                        String::from("has neither a caption nor a picture"),
+                    ))
+                }
+                _ => {}
+            }
+
+            let has_text = !caption.is_empty() || matches!(control, Control::TextBox(_));
+            if let (true, Some(fore), Some(back)) =
+                (has_text, control.fore_color(), control.back_color())
+            {
+                let opaque = control
+                    .various_property_bits()
+                    .map_or(true, |bits| bits.contains(VariousPropertyBits::BACK_STYLE));
+                let back = match opaque {
+                    true => back,
+                    false => container.back_color,
+                };
+                let (fore, back) = (self.resolver.resolve(fore), self.resolver.resolve(back));
+                let ratio = fore.contrast_ratio(&back);
+                if ratio < self.min_contrast {
+                    out.push(Diagnostic::new(
+                        LintCode::LowContrast,
+                        site,
+                        format!(
+                            "has a contrast ratio of {:.2} between {} and {}",
+                            ratio, fore, back
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diagnostics_to_json, LintCode, Linter};
+    use crate::{
+        controls::{
+            command_button::CommandButtonControl,
+            label::LabelControl,
+            morph_data::MorphDataControl,
+            user_form::ole_site_concrete::{OleSiteConcreteControl, SiteFlags},
+            Control,
+        },
+        fixture::{site, FormBuilder},
+        properties::{
+            color::{OleColor, RgbColor},
+            Position, Size,
+        },
+    };
+
+    /// A form that is large enough for all controls
+    fn form() -> FormBuilder {
+        FormBuilder::new(Size::new(10160, 5080))
+    }
+
+    fn lint_with(linter: &Linter, form: FormBuilder) -> Vec<(LintCode, String)> {
+        let mut found: Vec<_> = linter
+            .lint(&mut form.file())
+            .unwrap()
+            .into_iter()
+            .map(|d| (d.code, d.control))
+            .collect();
+        found.sort();
+        found
+    }
+
+    fn lint(form: FormBuilder) -> Vec<(LintCode, String)> {
+        lint_with(&Linter::default(), form)
+    }
+
+    fn found(items: &[(LintCode, &str)]) -> Vec<(LintCode, String)> {
+        items.iter().map(|&(c, n)| (c, n.to_string())).collect()
+    }
+
+    fn at(id: i32, name: &str, left: i32, top: i32) -> OleSiteConcreteControl {
+        OleSiteConcreteControl {
+            site_position: Position::new(left, top),
+            ..site(id, name)
+        }
+    }
+
+    fn tab_stop(site: OleSiteConcreteControl, tab_index: i16) -> OleSiteConcreteControl {
+        OleSiteConcreteControl { tab_index, ..site }
+    }
+
+    const SIZE: Size = Size::new(1270, 635);
+
+    fn button(caption: &str) -> Control {
+        Control::CommandButton(CommandButtonControl {
+            caption: caption.to_string(),
+            size: SIZE,
+            ..CommandButtonControl::default()
+        })
+    }
+
+    fn label(caption: &str, accelerator: Option<char>) -> LabelControl {
+        LabelControl {
+            caption: caption.to_string(),
+            accelerator: accelerator.map_or(0, |c| c as u16),
+            size: SIZE,
+            ..LabelControl::default()
+        }
+    }
+
+    fn text_box() -> Control {
+        Control::TextBox(MorphDataControl {
+            size: SIZE,
+            ..MorphDataControl::default()
+        })
+    }
+
+    fn buttons() -> FormBuilder {
+        form()
+            .control(tab_stop(site(1, "cmd"), 0), button("OK"))
+            .control(tab_stop(at(2, "CMD", 635, 0), 0), button(""))
+    }
+
+    #[test]
+    fn test_clean_form() {
+        let controls = form()
+            .control(site(1, "lbl"), Control::Label(label("Name", Some('N'))))
+            .control(tab_stop(at(2, "txt", 2540, 0), 0), text_box());
+        assert_eq!(lint(controls), []);
+        assert_eq!(lint(form()), []);
+    }
+
+    #[test]
+    fn test_buttons() {
+        assert_eq!(
+            lint(buttons()),
+            found(&[
+                (LintCode::DuplicateTabIndex, "CMD"),
+                (LintCode::Overlap, "CMD"),
+                (LintCode::DuplicateName, "CMD"),
+                (LintCode::EmptyCaption, "CMD"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_tab_stops() {
+        let mut hidden = site(1, "txt");
+        hidden.bit_flags.remove(SiteFlags::VISIBLE);
+        let mut no_tab_stop = at(2, "notab", 2540, 0);
+        no_tab_stop.bit_flags.remove(SiteFlags::TAB_STOP);
+        let controls = form()
+            .control(hidden, text_box())
+            .control(no_tab_stop, text_box())
+            .control(at(3, "lbl", 0, 1270), Control::Label(label("", None)));
+        // Labels don't take the focus, and controls that aren't tab stops need no index
+        assert_eq!(
+            lint(controls),
+            found(&[
+                (LintCode::MissingTabIndex, "txt"),
+                (LintCode::HiddenTabStop, "txt"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_contrast() {
+        let controls = || {
+            let lbl = LabelControl {
+                fore_color: OleColor::Default(RgbColor::from_rgb(0xD0, 0xD0, 0xD0)),
+                ..label("Name", Some('N'))
+            };
+            form().control(site(1, "lbl"), Control::Label(lbl))
+        };
+        assert_eq!(lint(controls()), found(&[(LintCode::LowContrast, "lbl")]));
+        let linter = Linter::default().with_min_contrast(1.0);
+        assert_eq!(lint_with(&linter, controls()), []);
+    }
+
+    #[test]
+    fn test_missing_accelerator() {
+        let controls = form()
+            .control(site(1, "lbl"), Control::Label(label("Name", None)))
+            .control(at(2, "empty", 0, 1270), Control::Label(label("", None)));
+        assert_eq!(
+            lint(controls),
+            found(&[(LintCode::MissingAccelerator, "lbl")])
+        );
+    }
+
+    #[test]
+    fn test_nested_controls() {
+        let frame = FormBuilder::new(Size::new(2540, 2540))
+            .with_form(|form| form.caption = String::from("Group"))
+            .control(tab_stop(at(2, "Fra", 5080, 0), 0), text_box());
+        let controls = form().frame(tab_stop(site(1, "fra"), 0), frame);
+        // Tab indices are per parent, names are per form
+        assert_eq!(
+            lint(controls),
+            found(&[
+                (LintCode::OutsideForm, "Fra"),
+                (LintCode::DuplicateName, "Fra"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_allow() {
+        let linter = Linter::default()
+            .allow(LintCode::Overlap)
+            .allow(LintCode::DuplicateName);
+        assert_eq!(
+            lint_with(&linter, buttons()),
+            found(&[
+                (LintCode::DuplicateTabIndex, "CMD"),
+                (LintCode::EmptyCaption, "CMD"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let mut file = buttons().file();
+        let diagnostics = Linter::default().lint(&mut file).unwrap();
+        assert_eq!(
+            diagnostics[0].to_json(),
+            r#"{"code":"duplicate-tab-index","severity":"warning","control":"CMD","id":2,"message":"has the same tab index (0) as cmd"}"#
+        );
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "warning: CMD (ID 2): has the same tab index (0) as cmd [duplicate-tab-index]"
+        );
+        let json = diagnostics_to_json(&diagnostics);
+        assert!(json.starts_with("[{") && json.ends_with("}]"));
+        assert_eq!(diagnostics_to_json(&[]), "[]");
+    }
+}
@@ -0,0 +1,732 @@
+//! Three-way merge of forms
+//!
+//! [`merge`] combines the changes that two versions (*ours* and *theirs*) made to a common
+//! *base* version of a form. Controls are matched by ID, or by name if the ID is not in the
+//! base version, like in [`diff`][crate::diff::diff]. Properties of the form, the sites and
+//! the controls are merged one by one:
+//!
+//! - a property that only changed on one side takes that value,
+//! - a property that changed to different values on both sides is a [`Conflict`], and keeps
+//!   the value from *ours*.
+//!
+//! Controls that were added on both sides are kept, with new IDs for the controls from
+//! *theirs* if necessary. Parent controls (Frame, MultiPage) are merged like other controls,
+//! the forms in their storages can be merged separately. A parent control added by *theirs*
+//! is never renumbered, because its storage is named after its ID: if *ours* uses the ID for
+//! another control, this is a conflict and the parent control is left out.
+
+use std::{
+    collections::BTreeSet,
+    convert::TryFrom,
+    fmt,
+    io::{self, Read, Seek},
+};
+
+use crate::{
+    controls::{
+        user_form::{
+            class_table::SiteClassInfo,
+            ole_site_concrete::{Clsid, OleSiteConcreteControl},
+            write_form_control, FormControl, Site, SiteKind,
+        },
+        Control,
+    },
+    properties::values::PropValue,
+    Form,
+};
+
+/// A form with all its controls, that can be edited and written back
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormContents {
+    /// The form control
+    ///
+    /// The `sites` are rebuilt from `controls` by [`FormContents::form_stream`], they only
+    /// provide the depth of the site with the same ID.
+    pub form: FormControl,
+    /// The sites and their controls, in order
+    pub controls: Vec<(OleSiteConcreteControl, Control)>,
+}
+
+impl FormContents {
+    /// Read the form control and all controls of a form
+    pub fn read<F: Read + Seek>(form: &mut Form<F>) -> io::Result<Self> {
+        let controls = form
+            .controls()
+            .map(|(site, control)| Ok((site.clone(), control?)))
+            .collect::<io::Result<_>>()?;
+        Ok(Self {
+            form: form.form_control().clone(),
+            controls,
+        })
+    }
+
+    /// The contents of the form stream (`f`), with one site per control
+    pub fn form_stream(&self) -> io::Result<Vec<u8>> {
+        let mut form = self.form.clone();
+        form.sites.clear();
+        let mut buf = Vec::new();
+        for (site, control) in &self.controls {
+            buf.clear();
+            control.write(&mut buf)?;
+            let object_stream_size = u32::try_from(buf.len())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            form.sites.push(Site {
+                depth: site_depth(&self.form, site.id),
+                kind: SiteKind::Ole(OleSiteConcreteControl {
+                    object_stream_size,
+                    ..site.clone()
+                }),
+            });
+        }
+        let mut out = Vec::new();
+        write_form_control(&mut out, &form)?;
+        Ok(out)
+    }
+
+    /// The contents of the object stream (`o`)
+    pub fn object_stream(&self) -> io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        for (_, control) in &self.controls {
+            control.write(&mut out)?;
+        }
+        Ok(out)
+    }
+}
+
+/// The depth of the site with ID `id` in `form`, or 0 if there is no such site
+fn site_depth(form: &FormControl, id: i32) -> u8 {
+    form.sites
+        .iter()
+        .find_map(|site| {
+            let SiteKind::Ole(ole) = &site.kind;
+            (ole.id == id).then_some(site.depth)
+        })
+        .unwrap_or(0)
+}
+
+/// A property that was changed to different values in both versions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// The ID of the control, or `None` for the form itself
+    pub id: Option<i32>,
+    /// The name of the control
+    pub name: String,
+    /// The property, e.g. `caption` or `control.fore_color`, or `control` if the whole control
+    /// was changed in one version and removed or replaced in the other. For a parent control
+    /// added by *theirs* with an ID that *ours* uses for another control, this is `site.id`
+    /// and the values are the names of the controls.
+    pub property: String,
+    /// The value in the base version, `None` if the control does not exist there
+    pub base: Option<String>,
+    /// The value in our version, which is kept in the merged form
+    pub ours: Option<String>,
+    /// The value in their version
+    pub theirs: Option<String>,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |v: &Option<String>| v.clone().unwrap_or_else(|| String::from("(none)"));
+        match self.id {
+            Some(id) => write!(f, "! {} (ID {}): ", self.name, id)?,
+            None => write!(f, "! form: ")?,
+        }
+        write!(
+            f,
+            "{}: base {}, ours {}, theirs {}",
+            self.property,
+            value(&self.base),
+            value(&self.ours),
+            value(&self.theirs)
+        )
+    }
+}
+
+/// The result of [`merge`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeResult {
+    /// The merged form
+    pub contents: FormContents,
+    /// The conflicts, which were resolved in favor of *ours*
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Collects the conflicts for one control (or the form)
+struct Fields<'a> {
+    conflicts: &'a mut Vec<Conflict>,
+    id: Option<i32>,
+    name: &'a str,
+}
+
+impl Fields<'_> {
+    fn conflict(
+        &mut self,
+        property: &str,
+        base: Option<String>,
+        ours: Option<String>,
+        theirs: Option<String>,
+    ) {
+        self.conflicts.push(Conflict {
+            id: self.id,
+            name: self.name.to_owned(),
+            property: property.to_owned(),
+            base,
+            ours,
+            theirs,
+        });
+    }
+
+    /// Merge one property into `ours`
+    fn field<T: Clone + PartialEq + PropValue>(
+        &mut self,
+        property: &str,
+        base: &T,
+        ours: &mut T,
+        theirs: &T,
+    ) {
+        if ours == theirs || base == theirs {
+            return;
+        }
+        if base == ours {
+            *ours = theirs.clone();
+        } else {
+            self.conflict(
+                property,
+                Some(base.prop_value()),
+                Some(ours.prop_value()),
+                Some(theirs.prop_value()),
+            );
+        }
+    }
+}
+
+/// Merges the listed fields of three values with [`Fields::field`]
+macro_rules! merge_fields {
+    ($fields:expr, $prefix:literal, $base:expr, $ours:expr, $theirs:expr; $($field:ident),* $(,)?) => {{
+        $($fields.field(
+            concat!($prefix, stringify!($field)),
+            &$base.$field,
+            &mut $ours.$field,
+            &$theirs.$field,
+        );)*
+    }};
+}
+
+fn merge_control(fields: &mut Fields<'_>, base: &Control, ours: &mut Control, theirs: &Control) {
+    match (base, &mut *ours, theirs) {
+        (Control::CommandButton(b), Control::CommandButton(o), Control::CommandButton(t)) => {
+            merge_fields!(fields, "control.", b, o, t;
+                fore_color, back_color, various_property_bits, caption, picture_position, size,
+                mouse_pointer, picture, accelerator, take_focus_on_click, mouse_icon, text_props,
+            )
+        }
+        (Control::Label(b), Control::Label(o), Control::Label(t)) => {
+            merge_fields!(fields, "control.", b, o, t;
+                fore_color, back_color, various_property_bits, caption, picture_position, size,
+                mouse_pointer, border_color, border_style, special_effect, picture, accelerator,
+                mouse_icon, text_props,
+            )
+        }
+        (Control::TextBox(b), Control::TextBox(o), Control::TextBox(t))
+        | (Control::ListBox(b), Control::ListBox(o), Control::ListBox(t))
+        | (Control::ComboBox(b), Control::ComboBox(o), Control::ComboBox(t))
+        | (Control::CheckBox(b), Control::CheckBox(o), Control::CheckBox(t))
+        | (Control::OptionButton(b), Control::OptionButton(o), Control::OptionButton(t))
+        | (Control::ToggleButton(b), Control::ToggleButton(o), Control::ToggleButton(t)) => {
+            merge_fields!(fields, "control.", b, o, t;
+                various_property_bits, back_color, fore_color, max_length, border_style,
+                scroll_bars, display_style, mouse_pointer, size, password_char, list_width,
+                bound_column, text_column, column_count, list_rows, column_info,
+                match_entry, list_style, show_drop_button_when, drop_button_style, multi_select,
+                value, caption, picture_position, border_color, special_effect, mouse_icon,
+                picture, accelerator, group_name, text_props,
+            )
+        }
+        (Control::Image(b), Control::Image(o), Control::Image(t)) => {
+            merge_fields!(fields, "control.", b, o, t;
+                auto_size, border_color, back_color, border_style, mouse_pointer,
+                picture_size_mode, special_effect, size, picture, picture_alignment,
+                picture_tiling, various_property_bits, mouse_icon,
+            )
+        }
+        (Control::SpinButton(b), Control::SpinButton(o), Control::SpinButton(t)) => {
+            merge_fields!(fields, "control.", b, o, t;
+                fore_color, back_color, various_property_bits, size, min, max, position,
+                prev_enabled, next_enabled, small_change, orientation, delay, mouse_icon,
+                mouse_pointer,
+            )
+        }
+        (Control::ScrollBar(b), Control::ScrollBar(o), Control::ScrollBar(t)) => {
+            merge_fields!(fields, "control.", b, o, t;
+                fore_color, back_color, various_property_bits, size, mouse_pointer, min, max,
+                position, prev_enabled, next_enabled, small_change, large_change, orientation,
+                proportional_thumb, delay, mouse_icon,
+            )
+        }
+        (Control::TabStrip(b), Control::TabStrip(o), Control::TabStrip(t)) => {
+            merge_fields!(fields, "control.", b, o, t;
+                list_index, back_color, fore_color, size, items, mouse_pointer,
+                tab_orientation, tab_style, multi_row, tab_fixed_width, tab_fixed_height,
+                tooltips, tip_strings, tab_names, various_property_bits, tabs_allocated, tags,
+                tab_flags, accelerators, mouse_icon, text_props,
+            )
+        }
+        // Parent and unknown controls, or a control that was replaced by another type
+        _ => {
+            if ours == theirs || base == theirs {
+                // nothing to merge
+            } else if base == ours {
+                *ours = theirs.clone();
+            } else {
+                let name = |c: &Control| Some(c.type_name().unwrap_or("Unknown").to_owned());
+                fields.conflict("control", name(base), name(ours), name(theirs));
+            }
+        }
+    }
+}
+
+fn merge_site(
+    conflicts: &mut Vec<Conflict>,
+    (base_site, base_control): &(OleSiteConcreteControl, Control),
+    ours: &mut (OleSiteConcreteControl, Control),
+    (theirs_site, theirs_control): &(OleSiteConcreteControl, Control),
+) {
+    let name = ours.0.name.clone();
+    let mut fields = Fields {
+        conflicts,
+        id: Some(ours.0.id),
+        name: &name,
+    };
+    merge_fields!(fields, "site.", base_site, ours.0, theirs_site;
+        name, help_context_id, bit_flags, tab_index, clsid_cache_index, group_id, tag,
+        site_position, control_tip_text, runtime_lic_key, control_source, row_source,
+    );
+    merge_control(&mut fields, base_control, &mut ours.1, theirs_control);
+}
+
+/// Point a class table index of a site from `from` to the same class in `classes`
+fn remap_class(
+    classes: &mut Vec<SiteClassInfo>,
+    from: &[SiteClassInfo],
+    site: &mut OleSiteConcreteControl,
+) {
+    if let Clsid::ClassTable(index) = site.clsid_cache_index {
+        if let Some(info) = from.get(usize::from(index)) {
+            let pos = match classes.iter().position(|c| c == info) {
+                Some(pos) => pos,
+                None => {
+                    classes.push(info.clone());
+                    classes.len() - 1
+                }
+            };
+            site.clsid_cache_index = Clsid::ClassTable(pos as u16);
+        }
+    }
+}
+
+/// For each control in `base`, find the index of the same control in `version`
+fn match_controls(
+    base: &[(OleSiteConcreteControl, Control)],
+    version: &[(OleSiteConcreteControl, Control)],
+) -> Vec<Option<usize>> {
+    let base_ids: BTreeSet<i32> = base.iter().map(|(s, _)| s.id).collect();
+    let version_ids: BTreeSet<i32> = version.iter().map(|(s, _)| s.id).collect();
+    base.iter()
+        .map(|(b, _)| {
+            version.iter().position(|(v, _)| {
+                v.id == b.id
+                    || (!version_ids.contains(&b.id)
+                        && !base_ids.contains(&v.id)
+                        && v.name.eq_ignore_ascii_case(&b.name))
+            })
+        })
+        .collect()
+}
+
+/// Merge the changes from `ours` and `theirs` to `base`, see the [module docs][self]
+///
+/// The merged form has a `next_available_id` that is above all IDs in both versions, and a
+/// new `shape_cookie` if both versions changed it.
+pub fn merge(base: &FormContents, ours: &FormContents, theirs: &FormContents) -> MergeResult {
+    let mut conflicts = Vec::new();
+    let mut form = ours.form.clone();
+    form.sites.clear();
+
+    {
+        let mut fields = Fields {
+            conflicts: &mut conflicts,
+            id: None,
+            name: "form",
+        };
+        merge_fields!(fields, "", base.form, form, theirs.form;
+            back_color, boolean_properties, border_color, border_style, caption, cycle,
+            displayed_size, draw_buffer, font, fore_color, group_count, logical_size,
+            mouse_icon, mouse_pointer, picture, picture_alignment, picture_size_mode,
+            picture_tiling, scroll_bars, scroll_position, special_effect, zoom,
+        );
+    }
+
+    // Use the class table of ours for all versions
+    let remap = |classes: &mut Vec<SiteClassInfo>, version: &FormContents| {
+        let mut controls = version.controls.clone();
+        for (site, _) in &mut controls {
+            remap_class(classes, &version.form.site_classes, site);
+        }
+        controls
+    };
+    let base_controls = remap(&mut form.site_classes, base);
+    let theirs_controls = remap(&mut form.site_classes, theirs);
+    let ours_controls = &ours.controls;
+
+    let ours_match = match_controls(&base_controls, ours_controls);
+    let theirs_match = match_controls(&base_controls, &theirs_controls);
+
+    let mut controls = Vec::new();
+    let mut depths = Vec::new();
+    for (oi, o) in ours_controls.iter().enumerate() {
+        match ours_match.iter().position(|m| *m == Some(oi)) {
+            Some(bi) => match theirs_match[bi] {
+                Some(ti) => {
+                    let mut merged = o.clone();
+                    merge_site(
+                        &mut conflicts,
+                        &base_controls[bi],
+                        &mut merged,
+                        &theirs_controls[ti],
+                    );
+                    controls.push(merged);
+                    depths.push(site_depth(&ours.form, o.0.id));
+                }
+                None if *o == base_controls[bi] => {}
+                None => {
+                    conflicts.push(Conflict {
+                        id: Some(o.0.id),
+                        name: o.0.name.clone(),
+                        property: String::from("control"),
+                        base: Some(String::from("present")),
+                        ours: Some(String::from("changed")),
+                        theirs: None,
+                    });
+                    controls.push(o.clone());
+                    depths.push(site_depth(&ours.form, o.0.id));
+                }
+            },
+            None => {
+                controls.push(o.clone());
+                depths.push(site_depth(&ours.form, o.0.id));
+            }
+        }
+    }
+
+    // Controls that were removed by ours, but changed by theirs
+    for (bi, b) in base_controls.iter().enumerate() {
+        if let (None, Some(ti)) = (ours_match[bi], theirs_match[bi]) {
+            let t = &theirs_controls[ti];
+            if t != b {
+                conflicts.push(Conflict {
+                    id: Some(b.0.id),
+                    name: b.0.name.clone(),
+                    property: String::from("control"),
+                    base: Some(String::from("present")),
+                    ours: None,
+                    theirs: Some(String::from("changed")),
+                });
+            }
+        }
+    }
+
+    let mut next_id = ours_controls
+        .iter()
+        .chain(&theirs_controls)
+        .map(|(s, _)| i64::from(s.id) + 1)
+        .chain(
+            [ours.form.next_available_id, theirs.form.next_available_id]
+                .iter()
+                .map(|n| i64::from(*n)),
+        )
+        .max()
+        .unwrap_or(0);
+    let mut used: BTreeSet<i32> = controls.iter().map(|(s, _)| s.id).collect();
+
+    // Controls that were added by theirs
+    for (ti, t) in theirs_controls.iter().enumerate() {
+        if theirs_match.contains(&Some(ti)) {
+            continue;
+        }
+        let same_name = controls
+            .iter()
+            .find(|(s, _)| s.name.eq_ignore_ascii_case(&t.0.name));
+        if let Some(o) = same_name {
+            let equal = o.1 == t.1
+                && OleSiteConcreteControl {
+                    id: t.0.id,
+                    ..o.0.clone()
+                } == t.0;
+            if !equal {
+                conflicts.push(Conflict {
+                    id: Some(o.0.id),
+                    name: o.0.name.clone(),
+                    property: String::from("control"),
+                    base: None,
+                    ours: o.1.type_name().map(str::to_owned),
+                    theirs: t.1.type_name().map(str::to_owned),
+                });
+            }
+            continue;
+        }
+        let mut added = t.clone();
+        if used.contains(&added.0.id) {
+            if added.1.parent_control().is_some() {
+                let ours_name = controls
+                    .iter()
+                    .find(|(s, _)| s.id == added.0.id)
+                    .map(|(s, _)| s.name.clone());
+                conflicts.push(Conflict {
+                    id: Some(added.0.id),
+                    name: added.0.name.clone(),
+                    property: String::from("site.id"),
+                    base: None,
+                    ours: ours_name,
+                    theirs: Some(added.0.name.clone()),
+                });
+                continue;
+            }
+            added.0.id = next_id as i32;
+            next_id += 1;
+        }
+        used.insert(added.0.id);
+        controls.push(added);
+        depths.push(site_depth(&theirs.form, t.0.id));
+    }
+    form.sites = controls
+        .iter()
+        .zip(depths)
+        .map(|((site, _), depth)| Site {
+            depth,
+            kind: SiteKind::Ole(site.clone()),
+        })
+        .collect();
+
+    form.next_available_id = next_id.max(0) as u32;
+    form.shape_cookie = if ours.form.shape_cookie != base.form.shape_cookie
+        && theirs.form.shape_cookie != base.form.shape_cookie
+    {
+        ours.form
+            .shape_cookie
+            .max(theirs.form.shape_cookie)
+            .wrapping_add(1)
+    } else {
+        ours.form.shape_cookie.max(theirs.form.shape_cookie)
+    };
+
+    MergeResult {
+        contents: FormContents { form, controls },
+        conflicts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge, FormContents};
+    use crate::{
+        controls::{
+            command_button::CommandButtonControl, label::LabelControl,
+            morph_data::MorphDataControl, user_form::parse_form_control, Control,
+        },
+        fixture::{site, FormBuilder},
+        properties::{Position, Size},
+    };
+
+    fn contents(form: FormBuilder) -> FormContents {
+        let mut file = form.file();
+        FormContents::read(&mut file.root_form().unwrap()).unwrap()
+    }
+
+    /// A form with `caption` and a CommandButton `cmd`
+    fn with_button(caption: &str, button_caption: &str, left: i32) -> FormBuilder {
+        let mut cmd = site(1, "cmd");
+        cmd.site_position = Position::new(left, 0);
+        let button = CommandButtonControl {
+            caption: button_caption.to_string(),
+            ..CommandButtonControl::default()
+        };
+        FormBuilder::new(Size::new(4000, 3000))
+            .with_form(|form| form.caption = caption.to_string())
+            .control(cmd, Control::CommandButton(button))
+    }
+
+    fn form(caption: &str, button_caption: &str, left: i32) -> FormContents {
+        contents(with_button(caption, button_caption, left))
+    }
+
+    fn ids(contents: &FormContents) -> Vec<(i32, &str)> {
+        contents
+            .controls
+            .iter()
+            .map(|(s, _)| (s.id, &s.name[..]))
+            .collect()
+    }
+
+    #[test]
+    fn test_unchanged() {
+        let base = form("Base", "", 0);
+        let result = merge(&base, &base, &base);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(ids(&result.contents), [(1, "cmd")]);
+        assert_eq!(result.contents.form.next_available_id, 2);
+    }
+
+    #[test]
+    fn test_one_sided_changes() {
+        let base = form("Base", "", 0);
+        let ours = form("Base", "OK", 0);
+        let theirs = form("Theirs", "", 2540);
+
+        let result = merge(&base, &ours, &theirs);
+        assert!(result.conflicts.is_empty());
+        let merged = &result.contents;
+        assert_eq!(merged.form.caption, "Theirs");
+        let (site, control) = &merged.controls[0];
+        assert_eq!(site.site_position.left, 2540);
+        match control {
+            Control::CommandButton(c) => assert_eq!(c.caption, "OK"),
+            _ => panic!("expected a CommandButton"),
+        }
+    }
+
+    #[test]
+    fn test_conflict() {
+        let base = form("Base", "", 0);
+        let ours = form("Ours", "OK", 0);
+        let theirs = form("Theirs", "Okay", 0);
+
+        let result = merge(&base, &ours, &theirs);
+        let conflicts: Vec<_> = result.conflicts.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            conflicts,
+            [
+                "! form: caption: base \"Base\", ours \"Ours\", theirs \"Theirs\"",
+                "! cmd (ID 1): control.caption: base \"\", ours \"OK\", theirs \"Okay\"",
+            ]
+        );
+        assert_eq!(result.contents.form.caption, "Ours");
+    }
+
+    #[test]
+    fn test_added_on_both_sides() {
+        let base = form("Base", "", 0);
+        let mut ours = base.clone();
+        let mut label = ours.controls[0].clone();
+        label.0.id = 2;
+        label.0.name = String::from("lbl");
+        ours.controls.push(label);
+        ours.form.next_available_id = 3;
+        let theirs = contents(with_button("Base", "", 0).control(
+            site(2, "txt"),
+            Control::TextBox(MorphDataControl::default()),
+        ));
+
+        let result = merge(&base, &ours, &theirs);
+        assert!(result.conflicts.is_empty());
+        // `txt` has ID 2 in theirs, which is already used by `lbl`
+        assert_eq!(ids(&result.contents), [(1, "cmd"), (2, "lbl"), (3, "txt")]);
+        assert_eq!(result.contents.form.next_available_id, 4);
+        result.contents.form_stream().unwrap();
+        result.contents.object_stream().unwrap();
+    }
+
+    #[test]
+    fn test_added_with_same_name() {
+        let base = form("Base", "", 0);
+        let added = |control: Control| {
+            contents(with_button("Base", "", 0).control(site(2, "new"), control))
+        };
+        let label = || Control::Label(LabelControl::default());
+        let text_box = Control::TextBox(MorphDataControl::default());
+
+        // the same control on both sides is merged
+        let result = merge(&base, &added(label()), &added(label()));
+        assert!(result.conflicts.is_empty());
+        assert_eq!(ids(&result.contents), [(1, "cmd"), (2, "new")]);
+
+        let result = merge(&base, &added(label()), &added(text_box));
+        assert_eq!(
+            result.conflicts[0].to_string(),
+            "! new (ID 2): control: base (none), ours Label, theirs TextBox"
+        );
+        assert_eq!(ids(&result.contents), [(1, "cmd"), (2, "new")]);
+    }
+
+    #[test]
+    fn test_removed() {
+        let base = contents(
+            with_button("Base", "", 0)
+                .control(site(2, "lbl"), Control::Label(LabelControl::default())),
+        );
+        let mut removed = base.clone();
+        removed.controls.pop();
+
+        for (ours, theirs) in [(&removed, &base), (&base, &removed)] {
+            let result = merge(&base, ours, theirs);
+            assert!(result.conflicts.is_empty());
+            assert_eq!(ids(&result.contents), [(1, "cmd")]);
+            assert_eq!(result.contents.form.next_available_id, 3);
+        }
+    }
+
+    #[test]
+    fn test_modified_and_removed() {
+        let base = form("Base", "", 0);
+        let changed = form("Base", "OK", 0);
+        let mut removed = base.clone();
+        removed.controls.clear();
+
+        let result = merge(&base, &removed, &changed);
+        assert_eq!(
+            result.conflicts[0].to_string(),
+            "! cmd (ID 1): control: base present, ours (none), theirs changed"
+        );
+        assert!(result.contents.controls.is_empty());
+
+        let result = merge(&base, &changed, &removed);
+        assert_eq!(
+            result.conflicts[0].to_string(),
+            "! cmd (ID 1): control: base present, ours changed, theirs (none)"
+        );
+        assert_eq!(ids(&result.contents), [(1, "cmd")]);
+    }
+
+    #[test]
+    fn test_added_parent_with_used_id() {
+        let base = form("Base", "", 0);
+        let mut ours = base.clone();
+        let mut label = ours.controls[0].clone();
+        label.0.id = 2;
+        label.0.name = String::from("lbl");
+        ours.controls.push(label);
+        let frame = FormBuilder::new(Size::new(2000, 1000));
+        let theirs = contents(with_button("Base", "", 0).frame(site(2, "fra"), frame));
+
+        // The storage of `fra` is `i02`, so it can't get a new ID
+        let result = merge(&base, &ours, &theirs);
+        let conflicts: Vec<_> = result.conflicts.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            conflicts,
+            ["! fra (ID 2): site.id: base (none), ours lbl, theirs fra"]
+        );
+        assert_eq!(ids(&result.contents), [(1, "cmd"), (2, "lbl")]);
+    }
+
+    #[test]
+    fn test_site_depth() {
+        let base = form("Base", "", 0);
+        let mut ours = base.clone();
+        ours.form.sites[0].depth = 1;
+
+        let result = merge(&base, &ours, &base);
+        assert_eq!(result.contents.form.sites[0].depth, 1);
+        let stream = result.contents.form_stream().unwrap();
+        let (_, form) = parse_form_control::<nom::error::Error<_>>(&stream).unwrap();
+        assert_eq!(form.sites[0].depth, 1);
+    }
+}
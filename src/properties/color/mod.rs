@@ -1,3 +1,4 @@
+pub mod palette;
 mod parser;
 use core::fmt;
 use num_traits::FromPrimitive;
@@ -25,6 +26,24 @@ impl RgbColor {
     pub const fn from_bgr(blue: u8, green: u8, red: u8) -> Self {
         Self { blue, green, red }
     }
+
+    /// The relative luminance, from 0 (black) to 1 (white), as defined by WCAG 2
+    pub fn relative_luminance(&self) -> f64 {
+        let linear = |c: u8| {
+            let c = f64::from(c) / 255.0;
+            match c <= 0.03928 {
+                true => c / 12.92,
+                false => ((c + 0.055) / 1.055).powf(2.4),
+            }
+        };
+        0.2126 * linear(self.red) + 0.7152 * linear(self.green) + 0.0722 * linear(self.blue)
+    }
+
+    /// The contrast ratio to `other`, from 1 (no contrast) to 21, as defined by WCAG 2
+    pub fn contrast_ratio(&self, other: &RgbColor) -> f64 {
+        let (a, b) = (self.relative_luminance(), other.relative_luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
 }
 
 pub type PaletteIndex = u16;
@@ -100,43 +119,10 @@ pub enum SystemColor {
     InfoBackground = 0x18,
 }
 
+/// Uses the [`SystemColorScheme::WINDOWS_10`][palette::SystemColorScheme::WINDOWS_10] theme
 impl From<SystemColor> for RgbColor {
     fn from(value: SystemColor) -> Self {
-        match value {
-            SystemColor::ScrollBars => RgbColor::from_rgb(200, 200, 200),
-            SystemColor::Desktop => RgbColor::from_rgb(0, 0, 0),
-            SystemColor::ActiveTitleBar => RgbColor::from_rgb(153, 180, 209),
-            SystemColor::InactiveTitleBar => RgbColor::from_rgb(191, 205, 219),
-            SystemColor::MenuBar => RgbColor::from_rgb(255, 255, 255),
-            SystemColor::WindowBackground => RgbColor::from_rgb(255, 255, 255),
-            SystemColor::WindowFrame => RgbColor::from_rgb(100, 100, 100),
-            SystemColor::MenuText => RgbColor::from_rgb(0, 0, 0),
-            SystemColor::WindowText => RgbColor::from_rgb(0, 0, 0),
-            SystemColor::TitleBarText => RgbColor::from_rgb(0, 0, 0),
-            SystemColor::ActiveBorder => RgbColor::from_rgb(180, 180, 180),
-            SystemColor::InactiveBorder => RgbColor::from_rgb(244, 247, 252),
-            SystemColor::ApplicationWorkspace => RgbColor::from_rgb(171, 171, 171),
-            SystemColor::Highlight => RgbColor::from_rgb(0, 120, 215),
-            SystemColor::HighlightText => RgbColor::from_rgb(255, 255, 255),
-            SystemColor::ButtonFace => RgbColor::from_rgb(255, 255, 255),
-            SystemColor::ButtonShadow => RgbColor::from_rgb(160, 160, 160),
-            SystemColor::GrayText => RgbColor::from_rgb(109, 109, 109),
-            SystemColor::ButtonText => RgbColor::from_rgb(0, 0, 0),
-            SystemColor::InactiveCaptionText => RgbColor::from_rgb(0, 0, 0),
-            SystemColor::_3DHighlight => RgbColor::from_rgb(255, 255, 255),
-            SystemColor::_3DDKShadow => RgbColor::from_rgb(105, 105, 105),
-            SystemColor::_3DLight => RgbColor::from_rgb(227, 227, 227),
-            SystemColor::InfoText => RgbColor::from_rgb(0, 0, 0),
-            SystemColor::InfoBackground => RgbColor::from_rgb(255, 255, 225),
-            /*
-            SystemColor::ButtonAlternateFace => RgbColor::from_rgb(255, 255, 255),
-            SystemColor::HotTrackingColor => RgbColor::from_rgb(0, 102, 204),
-            SystemColor::GradientActiveTitle => RgbColor::from_rgb(185, 209, 234),
-            SystemColor::GradientInactiveTitle => RgbColor::from_rgb(215, 228, 242),
-            SystemColor::MenuHilight => RgbColor::from_rgb(51, 153, 255),
-            SystemColor::MenuBar => RgbColor::from_rgb(255, 255, 255)
-            */
-        }
+        palette::SystemColorScheme::WINDOWS_10.get(value)
     }
 }
 
@@ -201,6 +187,32 @@ impl OleColor {
     }
 }
 
+impl OleColor {
+    /// The `OLE_COLOR` value, as stored in the file
+    pub const fn to_u32(&self) -> u32 {
+        match *self {
+            OleColor::Default(c) => u32::from_le_bytes([c.blue, c.green, c.red, Self::TAG_DEFAULT]),
+            OleColor::PaletteEntry(i) => {
+                let [a, b] = i.to_le_bytes();
+                u32::from_le_bytes([a, b, 0, Self::TAG_PALETTE_ENTRY])
+            }
+            OleColor::RgbColor(c) => {
+                u32::from_le_bytes([c.blue, c.green, c.red, Self::TAG_RGB_COLOR])
+            }
+            OleColor::SystemPalette(SystemPaletteIndex(i)) => {
+                let [a, b] = i.to_le_bytes();
+                u32::from_le_bytes([a, b, 0, Self::TAG_SYSTEM_PALETTE])
+            }
+        }
+    }
+}
+
+impl From<OleColor> for u32 {
+    fn from(value: OleColor) -> Self {
+        value.to_u32()
+    }
+}
+
 impl TryFrom<u32> for OleColor {
     type Error = u32;
 
@@ -253,6 +265,8 @@ mod tests {
     fn test_system_colors() {
         assert_eq!(OleColor::from_u32(0x80000012).unwrap(), OleColor::BTNTEXT);
         assert_eq!(OleColor::from_u32(0x8000000f).unwrap(), OleColor::BTNFACE);
+        assert_eq!(OleColor::BTNFACE.to_u32(), 0x8000000F);
+        assert_eq!(OleColor::from_u32(0xFFCC00).unwrap().to_u32(), 0xFFCC00);
         assert_eq!(
             OleColor::from_u32(0xFFCC00).unwrap(),
             OleColor::Default(RgbColor {
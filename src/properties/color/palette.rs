@@ -0,0 +1,271 @@
+//! Resolving an [`OleColor`] to an [`RgbColor`]
+//!
+//! System colors depend on the theme of the machine that displays a form, palette entries on
+//! the logical palette that is selected. A [`ColorResolver`] combines a [`SystemColorScheme`]
+//! with an optional logical palette.
+
+use num_traits::FromPrimitive;
+
+use super::{OleColor, RgbColor, SystemColor};
+
+const fn rgb(red: u8, green: u8, blue: u8) -> RgbColor {
+    RgbColor::from_rgb(red, green, blue)
+}
+
+/// The number of entries in a [`SystemColorScheme`]
+pub const SYSTEM_COLOR_COUNT: usize = SystemColor::InfoBackground as usize + 1;
+
+/// The RGB values of all [`SystemColor`]s for a specific theme
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SystemColorScheme {
+    colors: [RgbColor; SYSTEM_COLOR_COUNT],
+}
+
+impl SystemColorScheme {
+    /// The default theme of Windows 10
+    pub const WINDOWS_10: Self = Self::new([
+        rgb(200, 200, 200), // ScrollBars
+        rgb(0, 0, 0),       // Desktop
+        rgb(153, 180, 209), // ActiveTitleBar
+        rgb(191, 205, 219), // InactiveTitleBar
+        rgb(240, 240, 240), // MenuBar
+        rgb(255, 255, 255), // WindowBackground
+        rgb(100, 100, 100), // WindowFrame
+        rgb(0, 0, 0),       // MenuText
+        rgb(0, 0, 0),       // WindowText
+        rgb(0, 0, 0),       // TitleBarText
+        rgb(180, 180, 180), // ActiveBorder
+        rgb(244, 247, 252), // InactiveBorder
+        rgb(171, 171, 171), // ApplicationWorkspace
+        rgb(0, 120, 215),   // Highlight
+        rgb(255, 255, 255), // HighlightText
+        rgb(240, 240, 240), // ButtonFace
+        rgb(160, 160, 160), // ButtonShadow
+        rgb(109, 109, 109), // GrayText
+        rgb(0, 0, 0),       // ButtonText
+        rgb(0, 0, 0),       // InactiveCaptionText
+        rgb(255, 255, 255), // _3DHighlight
+        rgb(105, 105, 105), // _3DDKShadow
+        rgb(227, 227, 227), // _3DLight
+        rgb(0, 0, 0),       // InfoText
+        rgb(255, 255, 225), // InfoBackground
+    ]);
+
+    /// The "Windows Classic" theme of Windows 2000 and later
+    pub const WINDOWS_CLASSIC: Self = Self::new([
+        rgb(212, 208, 200), // ScrollBars
+        rgb(58, 110, 165),  // Desktop
+        rgb(10, 36, 106),   // ActiveTitleBar
+        rgb(128, 128, 128), // InactiveTitleBar
+        rgb(212, 208, 200), // MenuBar
+        rgb(255, 255, 255), // WindowBackground
+        rgb(0, 0, 0),       // WindowFrame
+        rgb(0, 0, 0),       // MenuText
+        rgb(0, 0, 0),       // WindowText
+        rgb(255, 255, 255), // TitleBarText
+        rgb(212, 208, 200), // ActiveBorder
+        rgb(212, 208, 200), // InactiveBorder
+        rgb(128, 128, 128), // ApplicationWorkspace
+        rgb(10, 36, 106),   // Highlight
+        rgb(255, 255, 255), // HighlightText
+        rgb(212, 208, 200), // ButtonFace
+        rgb(128, 128, 128), // ButtonShadow
+        rgb(128, 128, 128), // GrayText
+        rgb(0, 0, 0),       // ButtonText
+        rgb(212, 208, 200), // InactiveCaptionText
+        rgb(255, 255, 255), // _3DHighlight
+        rgb(64, 64, 64),    // _3DDKShadow
+        rgb(212, 208, 200), // _3DLight
+        rgb(0, 0, 0),       // InfoText
+        rgb(255, 255, 225), // InfoBackground
+    ]);
+
+    /// The "High Contrast Black" theme
+    pub const HIGH_CONTRAST_BLACK: Self = Self::new([
+        rgb(0, 0, 0),       // ScrollBars
+        rgb(0, 0, 0),       // Desktop
+        rgb(0, 0, 0),       // ActiveTitleBar
+        rgb(0, 0, 0),       // InactiveTitleBar
+        rgb(0, 0, 0),       // MenuBar
+        rgb(0, 0, 0),       // WindowBackground
+        rgb(255, 255, 255), // WindowFrame
+        rgb(255, 255, 255), // MenuText
+        rgb(255, 255, 255), // WindowText
+        rgb(255, 255, 255), // TitleBarText
+        rgb(128, 128, 128), // ActiveBorder
+        rgb(128, 128, 128), // InactiveBorder
+        rgb(0, 0, 0),       // ApplicationWorkspace
+        rgb(26, 235, 255),  // Highlight
+        rgb(0, 0, 0),       // HighlightText
+        rgb(0, 0, 0),       // ButtonFace
+        rgb(128, 128, 128), // ButtonShadow
+        rgb(63, 242, 63),   // GrayText
+        rgb(255, 255, 255), // ButtonText
+        rgb(63, 242, 63),   // InactiveCaptionText
+        rgb(255, 255, 255), // _3DHighlight
+        rgb(128, 128, 128), // _3DDKShadow
+        rgb(255, 255, 255), // _3DLight
+        rgb(255, 255, 255), // InfoText
+        rgb(0, 0, 0),       // InfoBackground
+    ]);
+
+    /// Create a custom scheme, indexed by [`SystemColor`]
+    pub const fn new(colors: [RgbColor; SYSTEM_COLOR_COUNT]) -> Self {
+        Self { colors }
+    }
+
+    /// Return a copy of this scheme with one color replaced
+    pub const fn with(mut self, color: SystemColor, value: RgbColor) -> Self {
+        self.colors[color as usize] = value;
+        self
+    }
+
+    /// Get the RGB value of a system color
+    pub const fn get(&self, color: SystemColor) -> RgbColor {
+        self.colors[color as usize]
+    }
+}
+
+impl Default for SystemColorScheme {
+    fn default() -> Self {
+        Self::WINDOWS_10
+    }
+}
+
+/// The 20 static colors of the default logical palette
+pub const DEFAULT_PALETTE: [RgbColor; 20] = [
+    rgb(0, 0, 0),
+    rgb(128, 0, 0),
+    rgb(0, 128, 0),
+    rgb(128, 128, 0),
+    rgb(0, 0, 128),
+    rgb(128, 0, 128),
+    rgb(0, 128, 128),
+    rgb(192, 192, 192),
+    rgb(192, 220, 192),
+    rgb(166, 202, 240),
+    rgb(255, 251, 240),
+    rgb(160, 160, 164),
+    rgb(128, 128, 128),
+    rgb(255, 0, 0),
+    rgb(0, 255, 0),
+    rgb(255, 255, 0),
+    rgb(0, 0, 255),
+    rgb(255, 0, 255),
+    rgb(0, 255, 255),
+    rgb(255, 255, 255),
+];
+
+/// Resolves any [`OleColor`] to an [`RgbColor`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorResolver {
+    scheme: SystemColorScheme,
+    palette: Option<Vec<RgbColor>>,
+    fallback: RgbColor,
+}
+
+impl Default for ColorResolver {
+    fn default() -> Self {
+        Self::new(SystemColorScheme::default())
+    }
+}
+
+impl ColorResolver {
+    /// Create a new resolver for a system color scheme, using the [`DEFAULT_PALETTE`]
+    pub fn new(scheme: SystemColorScheme) -> Self {
+        Self {
+            scheme,
+            palette: None,
+            fallback: RgbColor::from_rgb(0, 0, 0),
+        }
+    }
+
+    /// Use a logical palette for [`OleColor::PaletteEntry`] colors
+    pub fn with_palette(mut self, palette: Vec<RgbColor>) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+
+    /// Set the color for invalid system or palette indices (default: black)
+    pub fn with_fallback(mut self, fallback: RgbColor) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// Get the system color scheme
+    pub fn scheme(&self) -> &SystemColorScheme {
+        &self.scheme
+    }
+
+    /// Get the logical palette
+    pub fn palette(&self) -> &[RgbColor] {
+        self.palette.as_deref().unwrap_or(&DEFAULT_PALETTE)
+    }
+
+    /// Resolve a color
+    pub fn resolve(&self, color: OleColor) -> RgbColor {
+        match color {
+            OleColor::Default(rgb) | OleColor::RgbColor(rgb) => rgb,
+            OleColor::PaletteEntry(index) => self
+                .palette()
+                .get(usize::from(index))
+                .copied()
+                .unwrap_or(self.fallback),
+            OleColor::SystemPalette(index) => SystemColor::from_u16(index.0)
+                .map(|c| self.scheme.get(c))
+                .unwrap_or(self.fallback),
+        }
+    }
+}
+
+impl OleColor {
+    /// Resolve this color with the given [`ColorResolver`]
+    pub fn resolve(&self, resolver: &ColorResolver) -> RgbColor {
+        resolver.resolve(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ColorResolver, SystemColorScheme};
+    use crate::properties::color::{OleColor, RgbColor, SystemColor};
+
+    #[test]
+    fn test_resolve() {
+        let win10 = ColorResolver::default();
+        assert_eq!(
+            win10.resolve(OleColor::BTNFACE),
+            RgbColor::from_rgb(240, 240, 240)
+        );
+        let classic = ColorResolver::new(SystemColorScheme::WINDOWS_CLASSIC);
+        assert_eq!(
+            classic.resolve(OleColor::BTNFACE),
+            RgbColor::from_rgb(212, 208, 200)
+        );
+        let custom = ColorResolver::new(
+            SystemColorScheme::WINDOWS_10
+                .with(SystemColor::ButtonFace, RgbColor::from_rgb(1, 2, 3)),
+        )
+        .with_palette(vec![RgbColor::from_rgb(4, 5, 6)]);
+        assert_eq!(
+            custom.resolve(OleColor::BTNFACE),
+            RgbColor::from_rgb(1, 2, 3)
+        );
+        assert_eq!(
+            custom.resolve(OleColor::PaletteEntry(0)),
+            RgbColor::from_rgb(4, 5, 6)
+        );
+        assert_eq!(
+            custom.resolve(OleColor::PaletteEntry(1)),
+            RgbColor::from_rgb(0, 0, 0)
+        );
+        assert_eq!(
+            win10.resolve(OleColor::from_u32(0x800000FF).unwrap()),
+            RgbColor::from_rgb(0, 0, 0)
+        );
+        assert_eq!(
+            win10.resolve(OleColor::PaletteEntry(13)),
+            RgbColor::from_rgb(255, 0, 0)
+        );
+    }
+}
@@ -5,6 +5,7 @@ use nom::error::{FromExternalError, ParseError};
 use nom::number::complete::le_u32;
 use nom::IResult;
 
+#[allow(clippy::multiple_bound_locations)]
 pub fn parse_ole_color<'a, E: ParseError<&'a [u8]>>(
     input: &'a [u8],
 ) -> IResult<&'a [u8], OleColor, E>
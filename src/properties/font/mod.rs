@@ -1,8 +1,13 @@
 mod parser;
+mod writer;
+use encoding_rs::Encoding;
 pub use parser::*;
-use std::num::NonZeroU32;
+use std::{convert::TryFrom, num::NonZeroU32};
 use uuid::Uuid;
 
+use super::{string::charset_encoding, units::twips_to_points};
+pub use writer::*;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct StdFont {
     /// A signed integer that specifies the character set of the font.
@@ -34,8 +39,114 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// Specifies the effects of the font in a [`TextProps`].
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct FontEffects: u32 {
+        /// Specifies whether the font is bold.
+        const BOLD = 0x00000001;
+        /// Specifies whether the font is italic.
+        const ITALIC = 0x00000002;
+        /// Specifies whether the font is underlined.
+        const UNDERLINE = 0x00000004;
+        /// Specifies whether the font is struck out.
+        const STRIKEOUT = 0x00000008;
+        /// Specifies whether the text is displayed as disabled.
+        const DISABLED = 0x00002000;
+        /// Specifies whether the system color is used for the text.
+        const AUTOCOLOR = 0x40000000;
+    }
+}
+
+/// Specifies the alignment of text in a paragraph (`fmTextAlign`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive, ToPrimitive)]
+pub enum TextAlign {
+    /// Aligns the first character of displayed text with the left edge of the control.
+    Left = 0x01,
+    /// Centers the text in the control.
+    Center = 0x02,
+    /// Aligns the last character of displayed text with the right edge of the control.
+    Right = 0x03,
+}
+
+/// ## 2.3.1 TextProps
+///
+/// Specifies the font properties of a control.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct TextProps {}
+pub struct TextProps {
+    /// An fmString that specifies the name of the font.
+    ///
+    /// The file format default is a zero-length string.
+    pub font_name: String,
+    /// A bit field that specifies the effects of the font.
+    ///
+    /// The file format default is 0x00000000.
+    pub font_effects: FontEffects,
+    /// An unsigned integer that specifies the height, in twips, of the font.
+    ///
+    /// The file format default is 160 (8 points).
+    pub font_height: u32,
+    /// An unsigned integer that specifies the character set of the font.
+    ///
+    /// The file format default is 0x01, DEFAULT_CHARSET.
+    pub font_char_set: u8,
+    /// An unsigned integer that specifies the pitch and family of the font.
+    ///
+    /// The file format default is 0x00.
+    pub font_pitch_and_family: u8,
+    /// An fmTextAlign that specifies the alignment of the text.
+    ///
+    /// The file format default is 0x01, fmTextAlignLeft.
+    pub paragraph_align: TextAlign,
+    /// An unsigned integer that specifies the weight of the font.
+    ///
+    /// The file format default is 0x0190 (400, normal).
+    pub font_weight: u16,
+    /// The bits of the property mask that are not defined in [MS-OFORMS]
+    ///
+    /// These are only kept in [`ParseMode::Lenient`][crate::common::ParseMode::Lenient], for
+    /// properties without data, and are written back unchanged. Zero by default.
+    pub unknown_mask_bits: u32,
+}
+
+impl StdFont {
+    /// The height of the font in points
+    pub fn size_points(&self) -> f64 {
+        f64::from(self.height.get()) / 10000.0
+    }
+
+    /// The encoding of the charset of the font, see [`charset_encoding`]
+    pub fn encoding(&self) -> Option<&'static Encoding> {
+        u8::try_from(self.charset).ok().and_then(charset_encoding)
+    }
+}
+
+impl TextProps {
+    /// The height of the font in points
+    pub fn size_points(&self) -> f64 {
+        twips_to_points(i64::from(self.font_height))
+    }
+
+    /// The encoding of the charset of the font, see [`charset_encoding`]
+    pub fn encoding(&self) -> Option<&'static Encoding> {
+        charset_encoding(self.font_char_set)
+    }
+}
+
+impl Default for TextProps {
+    fn default() -> Self {
+        Self {
+            font_name: String::new(),
+            font_effects: FontEffects::empty(),
+            font_height: 160,
+            font_char_set: 1,
+            font_pitch_and_family: 0,
+            paragraph_align: TextAlign::Left,
+            font_weight: 400,
+            unknown_mask_bits: 0,
+        }
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FormFont {
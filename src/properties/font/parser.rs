@@ -1,16 +1,149 @@
 use std::num::NonZeroU32;
 
-use super::{DdsForm21FontNew, FontFlags, FormFont, GuidAndFont, StdFont};
-use crate::common::{parse_guid, CLSID_DT_DDSFORM_21_FONT_NEW, CLSID_STD_FONT};
+use super::{
+    DdsForm21FontNew, FontEffects, FontFlags, FormFont, GuidAndFont, StdFont, TextAlign, TextProps,
+};
+use crate::common::{
+    parse_guid, AlignedParser, ParseContext, CLSID_DT_DDSFORM_21_FONT_NEW, CLSID_STD_FONT,
+    CLSID_TEXT_PROPS,
+};
+use crate::properties::string::stream::CountOfBytesWithCompressionFlag;
 use nom::bytes::complete::tag;
 use nom::combinator::{map, map_opt, verify};
-use nom::error::ParseError;
-use nom::multi::length_data;
+use nom::error::{ErrorKind, ParseError};
+use nom::multi::{length_data, length_value};
 use nom::number::complete::{le_i16, le_u8};
+use nom::sequence::preceded;
 use nom::{
     number::complete::{le_u16, le_u32},
     IResult,
 };
+use num_traits::FromPrimitive;
+
+bitflags! {
+    pub(super) struct TextPropsPropMask: u32 {
+        /// Specifies whether the FontName property is stored in the TextProps.
+        const FONT_NAME = 1 << 0;
+        /// Specifies whether the FontEffects property is stored in the TextProps.
+        const FONT_EFFECTS = 1 << 1;
+        /// Specifies whether the FontHeight property is stored in the TextProps.
+        const FONT_HEIGHT = 1 << 2;
+        /// Unused, the FontOffset property is never stored.
+        const UNUSED1 = 1 << 3;
+        /// Specifies whether the FontCharSet property is stored in the TextProps.
+        const FONT_CHAR_SET = 1 << 4;
+        /// Specifies whether the FontPitchAndFamily property is stored in the TextProps.
+        const FONT_PITCH_AND_FAMILY = 1 << 5;
+        /// Specifies whether the ParagraphAlign property is stored in the TextProps.
+        const PARAGRAPH_ALIGN = 1 << 6;
+        /// Specifies whether the FontWeight property is stored in the TextProps.
+        const FONT_WEIGHT = 1 << 7;
+    }
+}
+
+fn parse_text_props_header<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], u16, E>
+where
+    E: ParseError<&'a [u8]>,
+{
+    preceded(tag([0x00, 0x02]), le_u16)(input)
+}
+
+/// Parse a [`TextProps`] structure (2.3.1)
+pub fn parse_text_props<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], TextProps, E>
+where
+    E: ParseError<&'a [u8]>,
+{
+    parse_text_props_with(input, &ParseContext::default())
+}
+
+/// Parse a [`TextProps`] with the [`ParseMode`][crate::common::ParseMode] of `ctx`
+pub fn parse_text_props_with<'a, E>(
+    input: &'a [u8],
+    ctx: &ParseContext,
+) -> IResult<&'a [u8], TextProps, E>
+where
+    E: ParseError<&'a [u8]>,
+{
+    length_value(
+        |i| ctx.field("Header", i, parse_text_props_header),
+        |i| _parse_text_props(i, ctx),
+    )(input)
+}
+
+fn _parse_text_props<'a, E>(input: &'a [u8], ctx: &ParseContext) -> IResult<&'a [u8], TextProps, E>
+where
+    E: ParseError<&'a [u8]>,
+{
+    let ap = AlignedParser::new();
+    let d = TextProps::default();
+    let (_i, mask) = ctx.aligned("PropMask", &ap, input, |i| {
+        ap.bitfield32(i, ctx.flags::<TextPropsPropMask>())
+    })?;
+
+    let (_i, font_name_data) = if mask.contains(TextPropsPropMask::FONT_NAME) {
+        ctx.aligned("FontNameData", &ap, _i, |_i| ap.length_and_compression(_i))?
+    } else {
+        (_i, CountOfBytesWithCompressionFlag::EMPTY)
+    };
+    let (_i, font_effects) = if mask.contains(TextPropsPropMask::FONT_EFFECTS) {
+        ctx.aligned("FontEffects", &ap, _i, |_i| {
+            ap.bitfield32(_i, ctx.flags::<FontEffects>())
+        })?
+    } else {
+        (_i, d.font_effects)
+    };
+    let (_i, font_height) = if mask.contains(TextPropsPropMask::FONT_HEIGHT) {
+        ctx.aligned("FontHeight", &ap, _i, |_i| ap.le_u32(_i))?
+    } else {
+        (_i, d.font_height)
+    };
+    let (_i, font_char_set) = if mask.contains(TextPropsPropMask::FONT_CHAR_SET) {
+        ctx.aligned("FontCharSet", &ap, _i, |_i| ap.le_u8(_i))?
+    } else {
+        (_i, d.font_char_set)
+    };
+    let (_i, font_pitch_and_family) = if mask.contains(TextPropsPropMask::FONT_PITCH_AND_FAMILY) {
+        ctx.aligned("FontPitchAndFamily", &ap, _i, |_i| ap.le_u8(_i))?
+    } else {
+        (_i, d.font_pitch_and_family)
+    };
+    let (_i, paragraph_align) = if mask.contains(TextPropsPropMask::PARAGRAPH_ALIGN) {
+        ctx.aligned("ParagraphAlign", &ap, _i, |_i| {
+            ap.bitfield8(_i, TextAlign::from_u8)
+        })?
+    } else {
+        (_i, d.paragraph_align)
+    };
+    let (_i, font_weight) = if mask.contains(TextPropsPropMask::FONT_WEIGHT) {
+        ctx.aligned("FontWeight", &ap, _i, |_i| ap.le_u16(_i))?
+    } else {
+        (_i, d.font_weight)
+    };
+
+    let (_i, font_name) = if mask.contains(TextPropsPropMask::FONT_NAME) {
+        ctx.aligned("FontName", &ap, _i, |_i| {
+            ap.fm_string(_i, font_name_data, ctx.encoding())
+        })?
+    } else {
+        (_i, d.font_name)
+    };
+
+    ctx.check_mask_layout(&mask, ap.offset(), _i.len(), _i)?;
+
+    Ok((
+        _i,
+        TextProps {
+            font_name,
+            font_effects,
+            font_height,
+            font_char_set,
+            font_pitch_and_family,
+            paragraph_align,
+            font_weight,
+            unknown_mask_bits: mask.bits() & !TextPropsPropMask::all().bits(),
+        },
+    ))
+}
 
 pub fn parse_std_font<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], StdFont, E>
 where
@@ -48,17 +181,25 @@ where
     Ok((input, DdsForm21FontNew { _d1, _d2 }))
 }
 
-pub fn parse_guid_and_font<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], GuidAndFont, E>
+/// Parse a GUID and the font it identifies
+///
+/// Fails with [`ErrorKind::Switch`] for any other GUID, because the size of the font data
+/// is not known.
+pub fn parse_guid_and_font<'a, E>(start: &'a [u8]) -> IResult<&'a [u8], GuidAndFont, E>
 where
     E: ParseError<&'a [u8]>,
 {
-    let (input, guid) = parse_guid(input)?;
+    let (input, guid) = parse_guid(start)?;
     let (input, font) = match guid {
         CLSID_DT_DDSFORM_21_FONT_NEW => {
             map(parse_dds_form21_font_new, FormFont::DdsForm21FontNew)(input)
         }
         CLSID_STD_FONT => map(parse_std_font, FormFont::StdFont)(input),
-        _ => unimplemented!("{}", guid),
+        CLSID_TEXT_PROPS => map(parse_text_props, FormFont::TextProps)(input),
+        _ => Err(nom::Err::Error(E::from_error_kind(
+            start,
+            ErrorKind::Switch,
+        ))),
     }?;
     Ok((input, GuidAndFont { guid, font }))
 }
@@ -67,9 +208,11 @@ where
 mod tests {
     use std::num::NonZeroU32;
 
+    use nom::error::ErrorKind;
+
     use super::{
         super::{FontFlags, StdFont},
-        parse_std_font,
+        parse_guid_and_font, parse_std_font,
     };
 
     #[test]
@@ -92,4 +235,16 @@ mod tests {
             ))
         )
     }
+
+    #[test]
+    fn test_parse_unknown_font() {
+        let bytes = [0xAB; 24];
+        assert_eq!(
+            parse_guid_and_font::<nom::error::Error<_>>(&bytes),
+            Err(nom::Err::Error(nom::error::Error::new(
+                &bytes[..],
+                ErrorKind::Switch
+            )))
+        )
+    }
 }
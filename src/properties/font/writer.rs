@@ -0,0 +1,88 @@
+use std::{convert::TryFrom, io};
+
+use super::{FormFont, GuidAndFont, StdFont, TextAlign, TextProps, TextPropsPropMask};
+use crate::common::{write_guid, write_with_header, AlignedWriter};
+
+/// Write a [`TextProps`] structure (2.3.1), storing only the properties that differ from the
+/// file format default
+pub fn write_text_props(out: &mut Vec<u8>, text_props: &TextProps) -> io::Result<()> {
+    let d = TextProps::default();
+    let mut w = AlignedWriter::new();
+    let mut mask = TextPropsPropMask::empty();
+    w.le_u32(0);
+
+    if text_props.font_name != d.font_name {
+        mask |= TextPropsPropMask::FONT_NAME;
+        w.length_and_compression(&text_props.font_name)?;
+    }
+    if text_props.font_effects != d.font_effects {
+        mask |= TextPropsPropMask::FONT_EFFECTS;
+        w.le_u32(text_props.font_effects.bits());
+    }
+    if text_props.font_height != d.font_height {
+        mask |= TextPropsPropMask::FONT_HEIGHT;
+        w.le_u32(text_props.font_height);
+    }
+    if text_props.font_char_set != d.font_char_set {
+        mask |= TextPropsPropMask::FONT_CHAR_SET;
+        w.le_u8(text_props.font_char_set);
+    }
+    if text_props.font_pitch_and_family != d.font_pitch_and_family {
+        mask |= TextPropsPropMask::FONT_PITCH_AND_FAMILY;
+        w.le_u8(text_props.font_pitch_and_family);
+    }
+    if text_props.paragraph_align != TextAlign::Left {
+        mask |= TextPropsPropMask::PARAGRAPH_ALIGN;
+        w.le_u8(text_props.paragraph_align as u8);
+    }
+    if text_props.font_weight != d.font_weight {
+        mask |= TextPropsPropMask::FONT_WEIGHT;
+        w.le_u16(text_props.font_weight);
+    }
+    if mask.contains(TextPropsPropMask::FONT_NAME) {
+        w.fm_string(&text_props.font_name)?;
+    }
+
+    w.patch_u32(
+        0,
+        mask.bits() | text_props.unknown_mask_bits & !TextPropsPropMask::all().bits(),
+    );
+    write_with_header(out, [0x00, 0x02], &w.finish())
+}
+
+/// Write a [`StdFont`] structure
+pub fn write_std_font(out: &mut Vec<u8>, font: &StdFont) -> io::Result<()> {
+    let len = u8::try_from(font.font_face.len())
+        .ok()
+        .filter(|len| *len < 32 && font.font_face.is_ascii())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid StdFont face name {:?}", font.font_face),
+            )
+        })?;
+    out.push(1);
+    out.extend_from_slice(&font.charset.to_le_bytes());
+    out.push(font.flags.bits());
+    out.extend_from_slice(&font.weight.to_le_bytes());
+    out.extend_from_slice(&font.height.get().to_le_bytes());
+    out.push(len);
+    out.extend_from_slice(font.font_face.as_bytes());
+    Ok(())
+}
+
+/// Write a [`GuidAndFont`], the inverse of [`parse_guid_and_font`][super::parse_guid_and_font]
+pub fn write_guid_and_font(out: &mut Vec<u8>, font: &GuidAndFont) -> io::Result<()> {
+    write_guid(out, font.guid);
+    match &font.font {
+        FormFont::Empty => Ok(()),
+        FormFont::DdsForm21FontNew(f) => {
+            out.extend_from_slice(&[0x00, 0x00, 0x08, 0x00]);
+            out.extend_from_slice(&f._d1.to_le_bytes());
+            out.extend_from_slice(&f._d2.to_le_bytes());
+            Ok(())
+        }
+        FormFont::StdFont(f) => write_std_font(out, f),
+        FormFont::TextProps(t) => write_text_props(out, t),
+    }
+}
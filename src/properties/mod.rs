@@ -8,11 +8,15 @@ use num_traits::{FromPrimitive, ToPrimitive};
 pub mod color;
 pub mod font;
 mod parser;
+#[allow(unused_imports)]
 pub use parser::*;
+mod writer;
 
 use crate::controls::user_form::class_table::SiteClassInfo;
 pub mod picture;
 pub mod string;
+pub mod units;
+pub(crate) mod values;
 
 /// An unsigned integer that specifies the type of icon displayed as the mouse pointer for the control.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive, ToPrimitive)]
@@ -92,6 +96,64 @@ pub enum PictureSizeMode {
     Zoom = 0x03,
 }
 
+bitflags! {
+    /// A bit field that specifies Boolean properties of a control (`VariousPropertyBits`).
+    ///
+    /// Bits that are not defined here are unused and MUST be ignored.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct VariousPropertyBits: u32 {
+        /// Specifies whether the control can receive the focus and respond to user-generated events.
+        const ENABLED               = 0x00000002;
+        /// Specifies whether the control can be edited.
+        const LOCKED                = 0x00000004;
+        /// Specifies whether the background of the control is opaque.
+        const BACK_STYLE            = 0x00000008;
+        /// Specifies whether column headings are displayed.
+        const COLUMN_HEADS          = 0x00000400;
+        /// Specifies whether the control only shows complete lines of text.
+        const INTEGRAL_HEIGHT       = 0x00000800;
+        /// Specifies whether a value entered MUST match an entry in the list.
+        const MATCH_REQUIRED        = 0x00001000;
+        /// Specifies the position of the caption relative to the control.
+        const ALIGNMENT             = 0x00002000;
+        /// Specifies whether the user can type into the control.
+        const EDITABLE              = 0x00004000;
+        /// Specifies the default run-time mode of the Input Method Editor.
+        const IME_MODE              = 0x00078000;
+        /// Specifies whether dragging and dropping is enabled for the control.
+        const DRAG_BEHAVIOR         = 0x00080000;
+        /// Specifies whether the ENTER key creates a new line.
+        const ENTER_KEY_BEHAVIOR    = 0x00100000;
+        /// Specifies how text is selected when the control is entered.
+        const ENTER_FIELD_BEHAVIOR  = 0x00200000;
+        /// Specifies whether the TAB key inserts a tab character.
+        const TAB_KEY_BEHAVIOR      = 0x00400000;
+        /// Specifies whether the contents wrap to the next line.
+        const WORD_WRAP             = 0x00800000;
+        /// Specifies whether the borders of the control are hidden.
+        const BORDERS_SUPPRESS      = 0x02000000;
+        /// Specifies whether a selection margin is displayed.
+        const SELECTION_MARGIN      = 0x04000000;
+        /// Specifies whether selection extends by whole words.
+        const AUTO_WORD_SELECT      = 0x08000000;
+        /// Specifies whether the control resizes to display its entire contents.
+        const AUTO_SIZE             = 0x10000000;
+        /// Specifies whether the selection stays highlighted when the control loses focus.
+        const HIDE_SELECTION        = 0x20000000;
+        /// Specifies whether focus moves on when the maximum length is reached.
+        const AUTO_TAB              = 0x40000000;
+        /// Specifies whether the control can display multiple lines of text.
+        const MULTI_LINE            = 0x80000000;
+    }
+}
+
+impl VariousPropertyBits {
+    /// Read the property bits, keeping any unused bits
+    pub const fn from_u32(bits: u32) -> Option<Self> {
+        Some(Self::from_bits_retain(bits))
+    }
+}
+
 pub type HiMetric = u32;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
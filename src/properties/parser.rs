@@ -1,4 +1,5 @@
 use super::{Position, Size};
+use crate::common::AlignedParser;
 use nom::{
     error::ParseError,
     number::complete::{le_i32, le_u32},
@@ -26,3 +27,16 @@ impl Position {
         Ok((input, Position { top, left }))
     }
 }
+
+impl AlignedParser {
+    /// Read a 4-byte aligned `fmSize` from an ExtraDataBlock
+    pub(crate) fn fm_size<'a, E>(&self, input: &'a [u8]) -> IResult<&'a [u8], Size, E>
+    where
+        E: ParseError<&'a [u8]>,
+    {
+        let (input, _) = self.align(input, 4)?;
+        let (input, size) = Size::parse(input)?;
+        self.inc(8);
+        Ok((input, size))
+    }
+}
@@ -1,11 +1,22 @@
-//use crate::common::Uuid;
+use nom::{
+    bytes::complete::tag, combinator::map, error::ParseError, multi::length_data,
+    number::complete::le_u32, sequence::preceded, IResult,
+};
 
-#[derive(Debug)]
+use std::{convert::TryFrom, io};
+
+use crate::common::{tag_guid, write_guid, CLSID_STD_PICTURE};
+
+/// ## 2.4.13 StdPicture
+///
+/// The picture data, usually a Windows Bitmap, Icon, Metafile or GIF/JPEG file.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct StdPicture {
     pub data: Vec<u8>,
 }
 
-#[derive(Debug)]
+/// ## 2.4.9 GuidAndPicture
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum GuidAndPicture {
     Empty,
     StdPicture(StdPicture),
@@ -13,4 +24,57 @@ pub enum GuidAndPicture {
 
 impl GuidAndPicture {
     pub const EMPTY: Self = GuidAndPicture::Empty;
+
+    /// Get the picture data, if any
+    pub fn data(&self) -> Option<&[u8]> {
+        match self {
+            Self::Empty => None,
+            Self::StdPicture(p) => Some(&p.data),
+        }
+    }
+}
+
+/// Parse a [`StdPicture`], starting at the `0x0000746C` preamble
+pub fn parse_std_picture<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], StdPicture, E>
+where
+    E: ParseError<&'a [u8]>,
+{
+    map(
+        preceded(tag([0x6C, 0x74, 0x00, 0x00]), length_data(le_u32)),
+        |data: &[u8]| StdPicture {
+            data: data.to_vec(),
+        },
+    )(input)
+}
+
+/// Parse a [`GuidAndPicture`], which MUST use the StdPicture CLSID
+pub fn parse_guid_and_picture<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], GuidAndPicture, E>
+where
+    E: ParseError<&'a [u8]>,
+{
+    map(
+        preceded(tag_guid(CLSID_STD_PICTURE), parse_std_picture),
+        GuidAndPicture::StdPicture,
+    )(input)
+}
+
+/// Write a [`StdPicture`], starting with the `0x0000746C` preamble
+pub fn write_std_picture(out: &mut Vec<u8>, picture: &StdPicture) -> io::Result<()> {
+    let len = u32::try_from(picture.data.len())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    out.extend_from_slice(&[0x6C, 0x74, 0x00, 0x00]);
+    out.extend_from_slice(&len.to_le_bytes());
+    out.extend_from_slice(&picture.data);
+    Ok(())
+}
+
+/// Write a [`GuidAndPicture`] with the StdPicture CLSID. Writes nothing for an empty picture.
+pub fn write_guid_and_picture(out: &mut Vec<u8>, picture: &GuidAndPicture) -> io::Result<()> {
+    match picture {
+        GuidAndPicture::Empty => Ok(()),
+        GuidAndPicture::StdPicture(p) => {
+            write_guid(out, CLSID_STD_PICTURE);
+            write_std_picture(out, p)
+        }
+    }
 }
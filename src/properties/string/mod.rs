@@ -1,3 +1,173 @@
+//! ## 2.4.10 fmString
 mod parser;
+mod writer;
+use std::{borrow::Cow, fmt};
+
+use encoding_rs::Encoding;
+
 pub use parser::*;
+pub use writer::*;
 pub mod stream;
+
+/// A borrowed fmString, decoded on demand
+///
+/// Compressed strings are stored in the ANSI code page of the system that saved the form,
+/// and decoded as Latin-1 unless another encoding is set. Uncompressed strings are UTF-16LE.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FmStr<'a> {
+    bytes: &'a [u8],
+    compressed: bool,
+    encoding: Option<&'static Encoding>,
+}
+
+impl<'a> FmStr<'a> {
+    /// Create a new instance from the raw bytes
+    pub const fn new(bytes: &'a [u8], compressed: bool) -> Self {
+        Self {
+            bytes,
+            compressed,
+            encoding: None,
+        }
+    }
+
+    /// Decode a compressed string with `encoding` (`None` for Latin-1)
+    pub const fn with_encoding(self, encoding: Option<&'static Encoding>) -> Self {
+        Self { encoding, ..self }
+    }
+
+    /// The raw bytes of the string, as stored in the file
+    pub const fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Whether the string is compressed (one byte per character)
+    pub const fn is_compressed(&self) -> bool {
+        self.compressed
+    }
+
+    /// Whether the string is empty
+    pub const fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Decode the string
+    ///
+    /// Compressed strings that are pure ASCII are borrowed without allocating.
+    pub fn decode(&self) -> Cow<'a, str> {
+        match (self.compressed, self.encoding) {
+            (true, Some(encoding)) => encoding.decode_without_bom_handling(self.bytes).0,
+            (true, None) => decode_latin1(self.bytes),
+            (false, _) => decode_utf16_le(self.bytes),
+        }
+    }
+}
+
+/// The encoding of a Windows code page, e.g. the `PROJECTCODEPAGE` of a VBA project
+///
+/// Returns `None` for code pages that are not supported by [`encoding_rs`].
+pub fn code_page_encoding(code_page: u16) -> Option<&'static Encoding> {
+    use encoding_rs::*;
+    Some(match code_page {
+        874 => WINDOWS_874,
+        932 => SHIFT_JIS,
+        936 => GBK,
+        949 => EUC_KR,
+        950 => BIG5,
+        1250 => WINDOWS_1250,
+        1251 => WINDOWS_1251,
+        1252 => WINDOWS_1252,
+        1253 => WINDOWS_1253,
+        1254 => WINDOWS_1254,
+        1255 => WINDOWS_1255,
+        1256 => WINDOWS_1256,
+        1257 => WINDOWS_1257,
+        1258 => WINDOWS_1258,
+        10000 => MACINTOSH,
+        20866 => KOI8_R,
+        21866 => KOI8_U,
+        28591 => WINDOWS_1252,
+        28592 => ISO_8859_2,
+        28595 => ISO_8859_5,
+        28597 => ISO_8859_7,
+        54936 => GB18030,
+        65001 => UTF_8,
+        _ => return None,
+    })
+}
+
+/// The encoding of a Windows charset, e.g. of a [`StdFont`][crate::properties::font::StdFont]
+///
+/// Returns `None` for `DEFAULT_CHARSET` (1), `SYMBOL_CHARSET` (2), `OEM_CHARSET` (255) and
+/// other charsets that don't map to a code page.
+pub fn charset_encoding(charset: u8) -> Option<&'static Encoding> {
+    let code_page = match charset {
+        0 => 1252,   // ANSI_CHARSET
+        77 => 10000, // MAC_CHARSET
+        128 => 932,  // SHIFTJIS_CHARSET
+        129 => 949,  // HANGUL_CHARSET
+        134 => 936,  // GB2312_CHARSET
+        136 => 950,  // CHINESEBIG5_CHARSET
+        161 => 1253, // GREEK_CHARSET
+        162 => 1254, // TURKISH_CHARSET
+        163 => 1258, // VIETNAMESE_CHARSET
+        177 => 1255, // HEBREW_CHARSET
+        178 => 1256, // ARABIC_CHARSET
+        186 => 1257, // BALTIC_CHARSET
+        204 => 1251, // RUSSIAN_CHARSET
+        222 => 874,  // THAI_CHARSET
+        238 => 1250, // EASTEUROPE_CHARSET
+        _ => return None,
+    };
+    code_page_encoding(code_page)
+}
+
+impl fmt::Display for FmStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.decode())
+    }
+}
+
+impl From<FmStr<'_>> for String {
+    fn from(value: FmStr<'_>) -> Self {
+        value.decode().into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::{charset_encoding, code_page_encoding, encode_fm_string, FmStr};
+
+    #[test]
+    fn test_decode() {
+        let s = FmStr::new(b"Label1", true);
+        assert!(matches!(s.decode(), Cow::Borrowed("Label1")));
+        let s = FmStr::new(b"\xE4", true);
+        assert_eq!(s.decode(), "ä");
+        let s = FmStr::new(b"O\x00K\x00", false);
+        assert_eq!(String::from(s), "OK");
+
+        let s = FmStr::new(b"\x8f\x49\x97\xb9", true);
+        assert_eq!(s.with_encoding(code_page_encoding(932)).decode(), "終了");
+        let s = FmStr::new(b"\xce\xea", true);
+        assert_eq!(s.with_encoding(charset_encoding(204)).decode(), "Ок");
+    }
+
+    #[test]
+    fn test_encode() {
+        let cp1251 = code_page_encoding(1251);
+        for value in ["OK", "Café", "Ок", "終了", ""] {
+            let (len, bytes) = encode_fm_string(value).unwrap();
+            assert_eq!(len.len() as usize, bytes.len());
+            let s = FmStr::new(&bytes, len.compressed()).with_encoding(cp1251);
+            assert_eq!(s.decode(), value);
+        }
+        let (len, bytes) = encode_fm_string("OK").unwrap();
+        assert!(len.compressed());
+        assert_eq!(bytes, b"OK");
+        let (len, bytes) = encode_fm_string("é").unwrap();
+        assert!(!len.compressed());
+        assert_eq!(bytes, [0xE9, 0x00]);
+    }
+}
@@ -1,24 +1,98 @@
 use std::borrow::Cow;
 
-use super::stream::*;
-use encoding_rs::{mem::decode_latin1, UTF_16LE};
-use nom::{bytes::complete::take, combinator::map, error::ParseError, IResult};
+use super::{stream::*, FmStr};
+use crate::common::AlignedParser;
+use encoding_rs::{Encoding, UTF_16LE};
+use nom::{
+    bytes::complete::take,
+    combinator::{map, map_opt},
+    error::ParseError,
+    IResult,
+};
 
-fn decode_utf16_le(bytes: &[u8]) -> Cow<'_, str> {
+pub(super) use encoding_rs::mem::decode_latin1; // Isomorphic Decode
+
+pub(super) fn decode_utf16_le(bytes: &[u8]) -> Cow<'_, str> {
     UTF_16LE.decode(bytes).0
 }
 
+/// Parse an fmString without decoding it
+pub fn parse_str_ref<'a, E: ParseError<&'a [u8]>>(
+    length_and_compression: CountOfBytesWithCompressionFlag,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], FmStr<'a>, E> {
+    let compressed = length_and_compression.compressed();
+    map(take(length_and_compression.len()), move |bytes| {
+        FmStr::new(bytes, compressed)
+    })
+}
+
 pub fn parse_string<'a, E: ParseError<&'a [u8]>>(
     length_and_compression: CountOfBytesWithCompressionFlag,
 ) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], String, E> {
-    map(
-        map(
-            take(length_and_compression.len()),
-            match length_and_compression.compressed() {
-                true => decode_latin1, // Isomorphic Decode
-                false => decode_utf16_le,
-            },
-        ),
-        Cow::into_owned,
-    )
+    map(parse_str_ref(length_and_compression), String::from)
+}
+
+impl AlignedParser {
+    /// Read the `fmStringLengthAndCompression` of a string property
+    pub(crate) fn length_and_compression<'a, E>(
+        &self,
+        input: &'a [u8],
+    ) -> IResult<&'a [u8], CountOfBytesWithCompressionFlag, E>
+    where
+        E: ParseError<&'a [u8]>,
+    {
+        map_opt(
+            |i| self.le_u32(i),
+            CountOfBytesWithCompressionFlag::from_bits,
+        )(input)
+    }
+
+    /// Read a 4-byte aligned `fmString` from an ExtraDataBlock
+    ///
+    /// Compressed strings are decoded with `encoding`, see [`FmStr::with_encoding`].
+    pub(crate) fn fm_string<'a, E>(
+        &self,
+        input: &'a [u8],
+        length_and_compression: CountOfBytesWithCompressionFlag,
+        encoding: Option<&'static Encoding>,
+    ) -> IResult<&'a [u8], String, E>
+    where
+        E: ParseError<&'a [u8]>,
+    {
+        let (input, _) = self.align(input, 4)?;
+        let (input, s) = map(parse_str_ref(length_and_compression), |s| {
+            String::from(s.with_encoding(encoding))
+        })(input)?;
+        self.inc(length_and_compression.len() as usize);
+        Ok((input, s))
+    }
+
+    /// Read an array of `fmString`s with a total size of `size` bytes from an ExtraDataBlock
+    ///
+    /// Each element is an `fmStringLengthAndCompression` followed by the string data, padded
+    /// to a multiple of 4 bytes.
+    pub(crate) fn fm_string_array<'a, E>(
+        &self,
+        input: &'a [u8],
+        size: u32,
+        encoding: Option<&'static Encoding>,
+    ) -> IResult<&'a [u8], Vec<String>, E>
+    where
+        E: ParseError<&'a [u8]>,
+    {
+        if size == 0 {
+            return Ok((input, Vec::new()));
+        }
+        let (mut input, _) = self.align(input, 4)?;
+        let end = self.offset() + size as usize;
+        let mut result = Vec::new();
+        while self.offset() < end {
+            let (_i, length_and_compression) = self.length_and_compression(input)?;
+            let (_i, s) = self.fm_string(_i, length_and_compression, encoding)?;
+            let (_i, _) = self.align(_i, 4)?;
+            result.push(s);
+            input = _i;
+        }
+        Ok((input, result))
+    }
 }
@@ -0,0 +1,61 @@
+use std::{convert::TryFrom, io};
+
+use super::stream::CountOfBytesWithCompressionFlag;
+use crate::common::AlignedWriter;
+
+/// Encode an fmString, compressed if all characters are ASCII
+///
+/// Compressed strings are decoded with the code page of the file, so other characters are
+/// stored as UTF-16 to read back the same under every code page.
+pub fn encode_fm_string(value: &str) -> io::Result<(CountOfBytesWithCompressionFlag, Vec<u8>)> {
+    let (bytes, flag): (Vec<u8>, _) = if value.is_ascii() {
+        let bytes = value.as_bytes().to_vec();
+        (bytes, CountOfBytesWithCompressionFlag::COMPRESSION_FLAG)
+    } else {
+        let bytes = value.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        (bytes, CountOfBytesWithCompressionFlag::EMPTY)
+    };
+    let len = u32::try_from(bytes.len())
+        .ok()
+        .and_then(CountOfBytesWithCompressionFlag::from_bits)
+        .filter(|len| !len.compressed())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "string too long"))?;
+    Ok((len | flag, bytes))
+}
+
+impl AlignedWriter {
+    /// Write the `fmStringLengthAndCompression` of a string property
+    pub(crate) fn length_and_compression(&mut self, value: &str) -> io::Result<()> {
+        let (len, _) = encode_fm_string(value)?;
+        self.le_u32(len.bits());
+        Ok(())
+    }
+
+    /// Write a 4-byte aligned `fmString` to an ExtraDataBlock
+    pub(crate) fn fm_string(&mut self, value: &str) -> io::Result<()> {
+        let (_, bytes) = encode_fm_string(value)?;
+        self.align(4);
+        self.bytes(&bytes);
+        Ok(())
+    }
+
+    /// Write an array of `fmString`s to an ExtraDataBlock
+    pub(crate) fn fm_string_array(&mut self, values: &[String]) -> io::Result<()> {
+        for value in values {
+            self.length_and_compression(value)?;
+            self.fm_string(value)?;
+            self.align(4);
+        }
+        Ok(())
+    }
+}
+
+/// The size of an array of `fmString`s, as written by [`AlignedWriter::fm_string_array`]
+pub(crate) fn fm_string_array_size(values: &[String]) -> io::Result<u32> {
+    let mut size = 0u32;
+    for value in values {
+        let (len, _) = encode_fm_string(value)?;
+        size = size.saturating_add(4 + ((len.len() + 3) & !3));
+    }
+    Ok(size)
+}
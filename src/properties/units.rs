@@ -0,0 +1,215 @@
+//! Conversions between HIMETRIC and other units
+//!
+//! Sizes and positions in a form are stored in HIMETRIC units (0.01 mm), while `.frm` files
+//! use twips, VBA uses points and screens use pixels:
+//!
+//! | Unit     | Per inch |
+//! |----------|----------|
+//! | HIMETRIC | 2540     |
+//! | twips    | 1440     |
+//! | points   | 72       |
+//! | pixels   | DPI      |
+//!
+//! Integer conversions round to the nearest value, with halves away from zero, like the
+//! `MulDiv` function that OLE uses to convert between HIMETRIC and device units.
+
+use std::convert::TryFrom;
+
+use super::{Position, Size};
+
+/// HIMETRIC units per inch
+pub const HIMETRIC_PER_INCH: i64 = 2540;
+/// Twips per inch
+pub const TWIPS_PER_INCH: i64 = 1440;
+/// Points per inch
+pub const POINTS_PER_INCH: i64 = 72;
+/// The resolution of Windows at 100% scaling, and of CSS pixels
+pub const DEFAULT_DPI: u32 = 96;
+
+/// Compute `value * num / den`, rounding halves away from zero
+///
+/// Returns `None` if `den` is zero or the result doesn't fit in an `i64`.
+pub fn mul_div(value: i64, num: i64, den: i64) -> Option<i64> {
+    let product = i128::from(value) * i128::from(num);
+    let den = i128::from(den);
+    let half = den.abs() / 2;
+    let quotient = (product.abs() + half).checked_div(den.abs())?;
+    let result = if (product < 0) == (den < 0) {
+        quotient
+    } else {
+        -quotient
+    };
+    i64::try_from(result).ok()
+}
+
+/// [`mul_div`] by a positive `den`, saturating at the limits of `i64`
+fn scale(value: i64, num: i64, den: i64) -> i64 {
+    mul_div(value, num, den).unwrap_or(if (value < 0) == (num < 0) {
+        i64::MAX
+    } else {
+        i64::MIN
+    })
+}
+
+/// Convert HIMETRIC units to twips
+pub fn himetric_to_twips(value: i64) -> i64 {
+    scale(value, TWIPS_PER_INCH, HIMETRIC_PER_INCH)
+}
+
+/// Convert twips to HIMETRIC units
+pub fn twips_to_himetric(value: i64) -> i64 {
+    scale(value, HIMETRIC_PER_INCH, TWIPS_PER_INCH)
+}
+
+/// Convert HIMETRIC units to points
+pub fn himetric_to_points(value: i64) -> f64 {
+    value as f64 * POINTS_PER_INCH as f64 / HIMETRIC_PER_INCH as f64
+}
+
+/// Convert points to HIMETRIC units, rounding to the nearest unit
+pub fn points_to_himetric(value: f64) -> i64 {
+    (value * HIMETRIC_PER_INCH as f64 / POINTS_PER_INCH as f64).round() as i64
+}
+
+/// Convert HIMETRIC units to pixels at `dpi`
+pub fn himetric_to_pixels(value: i64, dpi: u32) -> f64 {
+    value as f64 * f64::from(dpi) / HIMETRIC_PER_INCH as f64
+}
+
+/// Convert HIMETRIC units to whole pixels at `dpi`, as Office does when drawing a form
+pub fn himetric_to_device_pixels(value: i64, dpi: u32) -> i64 {
+    scale(value, i64::from(dpi), HIMETRIC_PER_INCH)
+}
+
+/// Convert pixels at `dpi` to HIMETRIC units, rounding to the nearest unit
+pub fn pixels_to_himetric(value: f64, dpi: u32) -> i64 {
+    (value * HIMETRIC_PER_INCH as f64 / f64::from(dpi)).round() as i64
+}
+
+/// Convert twips to points
+pub fn twips_to_points(value: i64) -> f64 {
+    value as f64 / 20.0
+}
+
+/// Convert points to twips, rounding to the nearest twip
+pub fn points_to_twips(value: f64) -> i64 {
+    (value * 20.0).round() as i64
+}
+
+/// Convert twips to pixels at `dpi`
+pub fn twips_to_pixels(value: i64, dpi: u32) -> f64 {
+    value as f64 * f64::from(dpi) / TWIPS_PER_INCH as f64
+}
+
+/// Convert HIMETRIC units to the value of a VBA `Left`, `Top`, `Width` or `Height` property
+///
+/// VBA reports these properties in points as a `Single`, so the result is rounded to an
+/// [`f32`].
+pub fn himetric_to_vba_points(value: i64) -> f32 {
+    himetric_to_points(value) as f32
+}
+
+/// Convert the value of a VBA `Left`, `Top`, `Width` or `Height` property to HIMETRIC units
+pub fn vba_points_to_himetric(value: f32) -> i64 {
+    points_to_himetric(f64::from(value))
+}
+
+/// Scale `value` by `zoom` percent, see [`FormControl::zoom`][crate::controls::user_form::FormControl::zoom]
+pub fn zoom(value: i64, zoom: u32) -> i64 {
+    scale(value, i64::from(zoom), 100)
+}
+
+impl Size {
+    /// The size in twips, as `(width, height)`
+    pub fn to_twips(&self) -> (i64, i64) {
+        (
+            himetric_to_twips(i64::from(self.width)),
+            himetric_to_twips(i64::from(self.height)),
+        )
+    }
+
+    /// The size in points, as `(width, height)`
+    pub fn to_points(&self) -> (f64, f64) {
+        (
+            himetric_to_points(i64::from(self.width)),
+            himetric_to_points(i64::from(self.height)),
+        )
+    }
+
+    /// The size in pixels at `dpi`, as `(width, height)`
+    pub fn to_pixels(&self, dpi: u32) -> (f64, f64) {
+        (
+            himetric_to_pixels(i64::from(self.width), dpi),
+            himetric_to_pixels(i64::from(self.height), dpi),
+        )
+    }
+
+    /// The size scaled by `zoom` percent
+    pub fn zoomed(&self, zoom: u32) -> Size {
+        let scale = |v: u32| self::zoom(i64::from(v), zoom).clamp(0, i64::from(u32::MAX)) as u32;
+        Size::new(scale(self.width), scale(self.height))
+    }
+}
+
+impl Position {
+    /// The position in twips, as `(left, top)`
+    pub fn to_twips(&self) -> (i64, i64) {
+        (
+            himetric_to_twips(i64::from(self.left)),
+            himetric_to_twips(i64::from(self.top)),
+        )
+    }
+
+    /// The position in points, as `(left, top)`
+    pub fn to_points(&self) -> (f64, f64) {
+        (
+            himetric_to_points(i64::from(self.left)),
+            himetric_to_points(i64::from(self.top)),
+        )
+    }
+
+    /// The position in pixels at `dpi`, as `(left, top)`
+    pub fn to_pixels(&self, dpi: u32) -> (f64, f64) {
+        (
+            himetric_to_pixels(i64::from(self.left), dpi),
+            himetric_to_pixels(i64::from(self.top), dpi),
+        )
+    }
+
+    /// The position scaled by `zoom` percent
+    pub fn zoomed(&self, zoom: u32) -> Position {
+        let scale = |v: i32| {
+            self::zoom(i64::from(v), zoom).clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32
+        };
+        Position::new(scale(self.left), scale(self.top))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        himetric_to_device_pixels, himetric_to_twips, himetric_to_vba_points, mul_div,
+        points_to_himetric, twips_to_himetric, zoom,
+    };
+    use crate::properties::{Position, Size};
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(mul_div(5, 1, 2), Some(3));
+        assert_eq!(mul_div(-5, 1, 2), Some(-3));
+        assert_eq!(mul_div(5, -1, -2), Some(3));
+        assert_eq!(mul_div(5, 1, 0), None);
+        assert_eq!(mul_div(i64::MAX, 2, 1), None);
+        assert_eq!(twips_to_himetric(i64::MIN), i64::MIN);
+        assert_eq!(zoom(i64::MAX, 400), i64::MAX);
+        assert_eq!(himetric_to_twips(3400), 1928);
+        assert_eq!(himetric_to_twips(-3400), -1928);
+        assert_eq!(twips_to_himetric(1440), 2540);
+        assert_eq!(himetric_to_vba_points(2540), 72.0);
+        assert_eq!(points_to_himetric(72.0), 2540);
+        assert_eq!(himetric_to_device_pixels(2540, 120), 120);
+        assert_eq!(Size::new(4000, 3000).zoomed(50), Size::new(2000, 1500));
+        assert_eq!(Position::new(-100, 10).zoomed(150), Position::new(-150, 15));
+        assert_eq!(Size::new(2540, 1270).to_pixels(96), (96.0, 48.0));
+    }
+}
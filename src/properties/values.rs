@@ -0,0 +1,288 @@
+//! Printable values of the properties of forms, sites and controls
+//!
+//! [`form_props`], [`site_props`] and [`control_props`] flatten the properties into a
+//! [`PropMap`], e.g. for [`diff`][crate::diff] and the Python and WebAssembly bindings.
+
+use std::{collections::BTreeMap, num::NonZeroU16};
+
+use crate::{
+    controls::{
+        morph_data::{ColumnInfo, DisplayStyle},
+        tab_strip::TabFlags,
+        user_form::{
+            ole_site_concrete::{Clsid, OleSiteConcreteControl, SiteFlags},
+            BorderStyle, Cycle, FormControl, FormFlags, FormScrollBarFlags,
+        },
+        Control,
+    },
+    properties::{
+        color::OleColor,
+        font::{GuidAndFont, TextProps},
+        picture::GuidAndPicture,
+        MousePointer, PictureAlignment, PictureSizeMode, Position, Size, SpecialEffect,
+        VariousPropertyBits,
+    },
+};
+#[cfg(feature = "python")]
+use crate::{properties::font::FormFont, python::Native};
+
+/// A printable value of a property
+pub(crate) trait PropValue {
+    fn prop_value(&self) -> String;
+
+    /// The value as native data, see [`Native`]
+    #[cfg(feature = "python")]
+    fn native(&self) -> Native;
+}
+
+/// Implements [`PropValue`] with [`ToString`], and the given [`Native`] variant
+macro_rules! display_value {
+    ($native:ident; $($ty:ty),*) => {
+        $(impl PropValue for $ty {
+            fn prop_value(&self) -> String {
+                self.to_string()
+            }
+
+            #[cfg(feature = "python")]
+            fn native(&self) -> Native {
+                Native::$native((*self).into())
+            }
+        })*
+    };
+}
+
+/// Implements [`PropValue`] with [`fmt::Debug`], and the given conversion to [`Native`]
+macro_rules! debug_value {
+    ($native:expr; $($ty:ty),*) => {
+        $(impl PropValue for $ty {
+            fn prop_value(&self) -> String {
+                format!("{:?}", self)
+            }
+
+            #[cfg(feature = "python")]
+            fn native(&self) -> Native {
+                let native: fn(&Self) -> Native = $native;
+                native(self)
+            }
+        })*
+    };
+}
+
+display_value!(Int; u8, u16, u32, i16, i32);
+display_value!(Bool; bool);
+debug_value!(|v| Native::Str(v.clone()); String);
+debug_value!(|v| Native::List(v.iter().cloned().map(Native::Str).collect()); Vec<String>);
+debug_value!(
+    |v| Native::List(v.iter().map(|f| Native::Int(f.bits().into())).collect());
+    Vec<TabFlags>
+);
+debug_value!(
+    |v| {
+        let column = |c: &ColumnInfo| Native::Map(vec![("column_width", c.column_width.native())]);
+        Native::List(v.iter().map(column).collect())
+    };
+    Vec<ColumnInfo>
+);
+debug_value!(
+    |v| Native::Map(vec![("width", v.width.native()), ("height", v.height.native())]);
+    Size
+);
+debug_value!(
+    |v| Native::Map(vec![("left", v.left.native()), ("top", v.top.native())]);
+    Position
+);
+debug_value!(
+    |v| Native::Map(vec![
+        ("font_name", v.font_name.native()),
+        ("font_effects", Native::Int(v.font_effects.bits().into())),
+        ("font_height", v.font_height.native()),
+        ("font_char_set", v.font_char_set.native()),
+        ("font_pitch_and_family", v.font_pitch_and_family.native()),
+        ("paragraph_align", Native::Str(format!("{:?}", v.paragraph_align))),
+        ("font_weight", v.font_weight.native()),
+    ]);
+    TextProps
+);
+debug_value!(
+    |v| match &v.font {
+        FormFont::StdFont(f) => Native::Map(vec![
+            ("font_face", f.font_face.native()),
+            ("height", Native::Int(f.height.get().into())),
+            ("weight", f.weight.native()),
+            ("charset", f.charset.native()),
+            ("flags", Native::Int(f.flags.bits().into())),
+        ]),
+        FormFont::TextProps(t) => t.native(),
+        FormFont::Empty | FormFont::DdsForm21FontNew(_) => Native::None,
+    };
+    GuidAndFont
+);
+debug_value!(
+    |v| Native::Int(v.bits().into());
+    VariousPropertyBits,
+    SiteFlags,
+    FormFlags,
+    FormScrollBarFlags
+);
+debug_value!(
+    |v| Native::Str(format!("{:?}", v));
+    BorderStyle,
+    SpecialEffect,
+    PictureAlignment,
+    PictureSizeMode,
+    MousePointer,
+    Cycle,
+    DisplayStyle
+);
+debug_value!(
+    |v| match v {
+        Clsid::Invalid => Native::None,
+        Clsid::ClassTable(i) => Native::Map(vec![("class_table", i.native())]),
+        Clsid::Global(i) => Native::Map(vec![("global", i.native())]),
+    };
+    Clsid
+);
+debug_value!(
+    |v| v.map_or(Native::None, |n| n.get().native());
+    Option<NonZeroU16>
+);
+
+impl PropValue for OleColor {
+    fn prop_value(&self) -> String {
+        format!("&H{:08X}&", self.to_u32())
+    }
+
+    #[cfg(feature = "python")]
+    fn native(&self) -> Native {
+        Native::Int(self.to_u32().into())
+    }
+}
+
+/// A short summary of binary data, e.g. `1234 bytes, checksum 0a1b2c3d`
+fn bytes_summary(bytes: &[u8]) -> String {
+    // FNV-1a
+    let hash = bytes.iter().fold(0x811C9DC5u32, |h, b| {
+        (h ^ u32::from(*b)).wrapping_mul(0x01000193)
+    });
+    format!("{} bytes, checksum {:08x}", bytes.len(), hash)
+}
+
+/// Binary data, as a [`bytes_summary`]
+struct Bytes<'a>(&'a [u8]);
+
+impl PropValue for Bytes<'_> {
+    fn prop_value(&self) -> String {
+        bytes_summary(self.0)
+    }
+
+    #[cfg(feature = "python")]
+    fn native(&self) -> Native {
+        Native::Str(self.prop_value())
+    }
+}
+
+impl PropValue for GuidAndPicture {
+    fn prop_value(&self) -> String {
+        match self.data() {
+            Some(data) => format!("picture ({})", bytes_summary(data)),
+            None => String::from("none"),
+        }
+    }
+
+    #[cfg(feature = "python")]
+    fn native(&self) -> Native {
+        self.data()
+            .map_or(Native::None, |data| Native::Str(bytes_summary(data)))
+    }
+}
+
+/// A map of properties, with the [`PropValue`] of each property
+pub(crate) trait PropMap {
+    fn insert_prop<T: PropValue + ?Sized>(&mut self, key: String, value: &T);
+}
+
+impl PropMap for BTreeMap<String, String> {
+    fn insert_prop<T: PropValue + ?Sized>(&mut self, key: String, value: &T) {
+        self.insert(key, value.prop_value());
+    }
+}
+
+/// Adds the fields of a struct to a property map, with a prefix
+macro_rules! props {
+    ($map:expr, $prefix:expr, $value:expr; $($field:ident),* $(,)?) => {{
+        $(PropMap::insert_prop(
+            $map,
+            format!("{}{}", $prefix, stringify!($field)),
+            &$value.$field,
+        );)*
+    }};
+}
+
+pub(crate) fn form_props(map: &mut impl PropMap, prefix: &str, form: &FormControl) {
+    props!(map, prefix, form;
+        back_color, boolean_properties, border_color, border_style, caption, cycle,
+        displayed_size, draw_buffer, font, fore_color, group_count, logical_size, mouse_icon,
+        mouse_pointer, picture, picture_alignment, picture_size_mode, picture_tiling,
+        scroll_bars, scroll_position, special_effect, zoom,
+    );
+}
+
+pub(crate) fn site_props(map: &mut impl PropMap, site: &OleSiteConcreteControl) {
+    props!(map, "site.", site;
+        help_context_id, bit_flags, tab_index, clsid_cache_index, group_id, tag,
+        control_tip_text, runtime_lic_key, control_source, row_source,
+    );
+}
+
+pub(crate) fn control_props(map: &mut impl PropMap, control: &Control) {
+    let p = "control.";
+    match control {
+        Control::CommandButton(c) => props!(map, p, c;
+            fore_color, back_color, various_property_bits, caption, picture_position, size,
+            mouse_pointer, picture, accelerator, take_focus_on_click, mouse_icon, text_props,
+        ),
+        Control::Label(c) => props!(map, p, c;
+            fore_color, back_color, various_property_bits, caption, picture_position, size,
+            mouse_pointer, border_color, border_style, special_effect, picture, accelerator,
+            mouse_icon, text_props,
+        ),
+        Control::TextBox(c)
+        | Control::ListBox(c)
+        | Control::ComboBox(c)
+        | Control::CheckBox(c)
+        | Control::OptionButton(c)
+        | Control::ToggleButton(c) => props!(map, p, c;
+            various_property_bits, back_color, fore_color, max_length, border_style,
+            scroll_bars, display_style, mouse_pointer, size, password_char, list_width,
+            bound_column, text_column, column_count, list_rows, column_info, match_entry,
+            list_style, show_drop_button_when, drop_button_style, multi_select, value, caption,
+            picture_position, border_color, special_effect, mouse_icon, picture, accelerator,
+            group_name, text_props,
+        ),
+        Control::Image(c) => props!(map, p, c;
+            auto_size, border_color, back_color, border_style, mouse_pointer,
+            picture_size_mode, special_effect, size, picture, picture_alignment,
+            picture_tiling, various_property_bits, mouse_icon,
+        ),
+        Control::SpinButton(c) => props!(map, p, c;
+            fore_color, back_color, various_property_bits, size, min, max, position,
+            prev_enabled, next_enabled, small_change, orientation, delay, mouse_icon,
+            mouse_pointer,
+        ),
+        Control::ScrollBar(c) => props!(map, p, c;
+            fore_color, back_color, various_property_bits, size, mouse_pointer, min, max,
+            position, prev_enabled, next_enabled, small_change, large_change, orientation,
+            proportional_thumb, delay, mouse_icon,
+        ),
+        Control::TabStrip(c) => props!(map, p, c;
+            list_index, back_color, fore_color, size, items, mouse_pointer, tab_orientation,
+            tab_style, multi_row, tab_fixed_width, tab_fixed_height, tooltips, tip_strings,
+            tab_names, various_property_bits, tabs_allocated, tags, tab_flags, accelerators,
+            mouse_icon, text_props,
+        ),
+        Control::Frame(_) | Control::MultiPage(_) | Control::Page(_) => {}
+        Control::Unknown { bytes, .. } => {
+            map.insert_prop(format!("{}data", p), &Bytes(bytes));
+        }
+    }
+}
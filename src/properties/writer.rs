@@ -0,0 +1,34 @@
+use super::{color::OleColor, Position, Size};
+use crate::common::AlignedWriter;
+
+impl Size {
+    pub fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.width.to_le_bytes());
+        out.extend_from_slice(&self.height.to_le_bytes());
+    }
+}
+
+impl Position {
+    pub fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.left.to_le_bytes());
+        out.extend_from_slice(&self.top.to_le_bytes());
+    }
+}
+
+impl AlignedWriter {
+    /// Write a 4-byte aligned `fmSize` to an ExtraDataBlock
+    pub(crate) fn fm_size(&mut self, size: Size) {
+        self.le_u32(size.width);
+        self.le_u32(size.height);
+    }
+
+    /// Write a 4-byte aligned `fmPosition` to an ExtraDataBlock
+    pub(crate) fn fm_position(&mut self, position: Position) {
+        self.le_i32(position.left);
+        self.le_i32(position.top);
+    }
+
+    pub(crate) fn ole_color(&mut self, color: OleColor) {
+        self.le_u32(color.to_u32());
+    }
+}
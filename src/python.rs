@@ -0,0 +1,565 @@
+//! Python bindings (feature `python`)
+//!
+//! The `ms_oforms` Python module exposes an [`OFormsFile`] as `OFormsFile`, with its forms,
+//! sites and controls as read-only classes. All properties are also available as a `dict`
+//! (`to_dict`) or as JSON (`to_json`). The values of the properties are native values:
+//! `str`, `int`, `bool`, `None`, or a `list` or `dict` of these, e.g. `{"width": 2540,
+//! "height": 661}` for a size. Colors are the `int` value of the `OLE_COLOR`, enumerations
+//! the name of the value and flags the `int` value of the bits.
+//!
+//! Build the module into the current virtualenv with `maturin develop`, which enables this
+//! feature (see `pyproject.toml`) and builds the crate as a `cdylib`:
+//!
+//! ```text
+//! >>> import ms_oforms
+//! >>> f = ms_oforms.OFormsFile.open("vbaProject.bin", "UserForm1")
+//! >>> [c.name for c in f.form().controls]
+//! ['cmdOK', 'fraOptions', 'chkVerbose']
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    io::Cursor,
+    path::{Path, PathBuf},
+};
+
+use pyo3::{
+    prelude::*,
+    types::{PyDict, PyList},
+    IntoPyObjectExt,
+};
+
+use crate::{
+    common::json_string,
+    controls::{user_form::ole_site_concrete::OleSiteConcreteControl, Control},
+    properties::values::{control_props, form_props, site_props, PropMap, PropValue},
+    OFormsFile,
+};
+
+/// A property value as native data, for the Python bindings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Native {
+    None,
+    Bool(bool),
+    Int(i64),
+    Str(String),
+    List(Vec<Native>),
+    Map(Vec<(&'static str, Native)>),
+}
+
+impl PropMap for BTreeMap<String, Native> {
+    fn insert_prop<T: PropValue + ?Sized>(&mut self, key: String, value: &T) {
+        self.insert(key, value.native());
+    }
+}
+
+impl<'py> IntoPyObject<'py> for &Native {
+    type Target = PyAny;
+    type Output = Bound<'py, PyAny>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        match self {
+            Native::None => Ok(py.None().into_bound(py)),
+            Native::Bool(b) => b.into_bound_py_any(py),
+            Native::Int(i) => i.into_bound_py_any(py),
+            Native::Str(s) => s.into_bound_py_any(py),
+            Native::List(items) => Ok(PyList::new(py, items)?.into_any()),
+            Native::Map(fields) => {
+                let dict = PyDict::new(py);
+                for (key, value) in fields {
+                    dict.set_item(key, value)?;
+                }
+                Ok(dict.into_any())
+            }
+        }
+    }
+}
+
+/// Write `value` as JSON
+fn write_native_json(out: &mut String, value: &Native) {
+    match value {
+        Native::None => out.push_str("null"),
+        Native::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Native::Int(i) => out.push_str(&i.to_string()),
+        Native::Str(s) => out.push_str(&json_string(s)),
+        Native::List(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_native_json(out, item);
+            }
+            out.push(']');
+        }
+        Native::Map(fields) => write_json_object(out, fields.iter().map(|(k, v)| (*k, v))),
+    }
+}
+
+/// Write the `fields` as a JSON object
+fn write_json_object<'a>(out: &mut String, fields: impl Iterator<Item = (&'a str, &'a Native)>) {
+    out.push('{');
+    for (i, (key, value)) in fields.enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(&json_string(key));
+        out.push(':');
+        write_native_json(out, value);
+    }
+    out.push('}');
+}
+
+/// Write a map of properties as a JSON object
+fn write_json_props(out: &mut String, map: &BTreeMap<String, Native>) {
+    write_json_object(out, map.iter().map(|(k, v)| (k.as_str(), v)));
+}
+
+/// The properties from `map` that start with `prefix`, without the prefix
+fn strip_prefix(map: BTreeMap<String, Native>, prefix: &str) -> BTreeMap<String, Native> {
+    map.into_iter()
+        .filter_map(|(k, v)| Some((k.strip_prefix(prefix)?.to_owned(), v)))
+        .collect()
+}
+
+fn opt_json(value: Option<&str>) -> String {
+    value.map_or_else(|| String::from("null"), json_string)
+}
+
+/// The site of a control on a form
+#[pyclass(name = "Site", module = "ms_oforms")]
+#[derive(Debug, Clone)]
+pub struct PySite {
+    /// The ID of the site
+    #[pyo3(get)]
+    id: i32,
+    /// The name of the control
+    #[pyo3(get)]
+    name: String,
+    /// The position within the parent control (or the form), in HIMETRIC units
+    #[pyo3(get)]
+    left: i32,
+    #[pyo3(get)]
+    top: i32,
+    /// The other properties of the site, e.g. `tab_index` or `tag`
+    #[pyo3(get)]
+    properties: BTreeMap<String, Native>,
+}
+
+impl PySite {
+    fn new(site: &OleSiteConcreteControl) -> Self {
+        let mut properties = BTreeMap::new();
+        site_props(&mut properties, site);
+        Self {
+            id: site.id,
+            name: site.name.clone(),
+            left: site.site_position.left,
+            top: site.site_position.top,
+            properties: strip_prefix(properties, "site."),
+        }
+    }
+
+    fn write_json(&self, out: &mut String) {
+        let _ = write!(
+            out,
+            "{{\"id\":{},\"name\":{},\"left\":{},\"top\":{},\"properties\":",
+            self.id,
+            json_string(&self.name),
+            self.left,
+            self.top
+        );
+        write_json_props(out, &self.properties);
+        out.push('}');
+    }
+}
+
+#[pymethods]
+impl PySite {
+    /// The site as a `dict`
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("id", self.id)?;
+        dict.set_item("name", &self.name)?;
+        dict.set_item("left", self.left)?;
+        dict.set_item("top", self.top)?;
+        dict.set_item("properties", &self.properties)?;
+        Ok(dict)
+    }
+
+    /// The site as a JSON object
+    fn to_json(&self) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out);
+        out
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<Site {} {:?}>", self.id, self.name)
+    }
+}
+
+/// A control on a form, with its site
+#[pyclass(name = "Control", module = "ms_oforms")]
+#[derive(Debug, Clone)]
+pub struct PyControl {
+    /// The site of the control
+    #[pyo3(get)]
+    site: PySite,
+    /// The type of the control, e.g. `CommandButton`, or `None` for unknown controls
+    #[pyo3(get)]
+    type_name: Option<&'static str>,
+    /// The storage of the form of a Frame, MultiPage or Page, for `OFormsFile.form`
+    #[pyo3(get)]
+    storage: Option<String>,
+    /// The error, if the control could not be parsed
+    #[pyo3(get)]
+    error: Option<String>,
+    /// The properties of the control, e.g. `caption` or `size`
+    #[pyo3(get)]
+    properties: BTreeMap<String, Native>,
+}
+
+impl PyControl {
+    fn new(site: &OleSiteConcreteControl, control: std::io::Result<Control>) -> Self {
+        let mut properties = BTreeMap::new();
+        let (type_name, storage, error) = match &control {
+            Ok(control) => {
+                control_props(&mut properties, control);
+                let storage = control
+                    .parent_control()
+                    .map(|p| p.storage.to_string_lossy().into_owned());
+                (control.type_name(), storage, None)
+            }
+            Err(e) => (None, None, Some(e.to_string())),
+        };
+        Self {
+            site: PySite::new(site),
+            type_name,
+            storage,
+            error,
+            properties: strip_prefix(properties, "control."),
+        }
+    }
+
+    fn write_json(&self, out: &mut String) {
+        out.push_str("{\"site\":");
+        self.site.write_json(out);
+        let _ = write!(
+            out,
+            ",\"type_name\":{},\"storage\":{},\"error\":{},\"properties\":",
+            opt_json(self.type_name),
+            opt_json(self.storage.as_deref()),
+            opt_json(self.error.as_deref())
+        );
+        write_json_props(out, &self.properties);
+        out.push('}');
+    }
+}
+
+#[pymethods]
+impl PyControl {
+    /// The name of the control
+    #[getter]
+    fn name(&self) -> &str {
+        &self.site.name
+    }
+
+    /// The control (and its site) as a `dict`
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("site", self.site.to_dict(py)?)?;
+        dict.set_item("type_name", self.type_name)?;
+        dict.set_item("storage", &self.storage)?;
+        dict.set_item("error", &self.error)?;
+        dict.set_item("properties", &self.properties)?;
+        Ok(dict)
+    }
+
+    /// The control (and its site) as a JSON object
+    fn to_json(&self) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out);
+        out
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "<Control {} {:?}>",
+            self.type_name.unwrap_or("Unknown"),
+            self.site.name
+        )
+    }
+}
+
+/// A form, e.g. the root form or the form of a Frame
+#[pyclass(name = "Form", module = "ms_oforms")]
+#[derive(Debug, Clone)]
+pub struct PyForm {
+    /// The storage of the form, relative to the prefix of the file
+    #[pyo3(get)]
+    storage: String,
+    /// The properties of the form, e.g. `caption` or `displayed_size`
+    #[pyo3(get)]
+    properties: BTreeMap<String, Native>,
+    /// The controls on the form, in the order of the sites
+    #[pyo3(get)]
+    controls: Vec<PyControl>,
+}
+
+impl PyForm {
+    fn write_json(&self, out: &mut String) {
+        let _ = write!(
+            out,
+            "{{\"storage\":{},\"properties\":",
+            json_string(&self.storage)
+        );
+        write_json_props(out, &self.properties);
+        out.push_str(",\"controls\":[");
+        for (i, control) in self.controls.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            control.write_json(out);
+        }
+        out.push_str("]}");
+    }
+}
+
+#[pymethods]
+impl PyForm {
+    /// The sites of all controls on the form
+    #[getter]
+    fn sites(&self) -> Vec<PySite> {
+        self.controls.iter().map(|c| c.site.clone()).collect()
+    }
+
+    /// The form (and its controls) as a `dict`
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("storage", &self.storage)?;
+        dict.set_item("properties", &self.properties)?;
+        let controls = self
+            .controls
+            .iter()
+            .map(|c| c.to_dict(py))
+            .collect::<PyResult<Vec<_>>>()?;
+        dict.set_item("controls", controls)?;
+        Ok(dict)
+    }
+
+    /// The form (and its controls) as a JSON object
+    fn to_json(&self) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out);
+        out
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "<Form {:?} with {} controls>",
+            self.storage,
+            self.controls.len()
+        )
+    }
+}
+
+/// An OForms file, e.g. a `vbaProject.bin` or a form storage in a compound file
+#[pyclass(name = "OFormsFile", module = "ms_oforms", unsendable)]
+pub struct PyOFormsFile {
+    inner: OFormsFile<Cursor<Vec<u8>>>,
+}
+
+#[pymethods]
+impl PyOFormsFile {
+    /// Open a compound file from `bytes`, with the forms below `prefix`
+    #[new]
+    #[pyo3(signature = (data, prefix = None))]
+    fn new(data: Vec<u8>, prefix: Option<PathBuf>) -> PyResult<Self> {
+        let prefix = prefix.unwrap_or_else(|| PathBuf::from("/"));
+        let inner = OFormsFile::open_in(Cursor::new(data), prefix)?;
+        Ok(Self { inner })
+    }
+
+    /// Open a compound file from a path, with the forms below `prefix`
+    #[staticmethod]
+    #[pyo3(signature = (path, prefix = None))]
+    fn open(path: PathBuf, prefix: Option<PathBuf>) -> PyResult<Self> {
+        Self::new(std::fs::read(path)?, prefix)
+    }
+
+    /// The storages of all forms, see `form`
+    fn form_storages(&self) -> PyResult<Vec<String>> {
+        let storages = self.inner.form_storages()?;
+        Ok(storages
+            .iter()
+            .map(|s| s.to_string_lossy().into_owned())
+            .collect())
+    }
+
+    /// Read the form in a storage, e.g. of a Frame (default: the prefix itself)
+    #[pyo3(signature = (storage = None))]
+    fn form(&mut self, storage: Option<PathBuf>) -> PyResult<PyForm> {
+        let storage = storage.unwrap_or_default();
+        let mut form = self.inner.form_in(&storage)?;
+        let mut properties = BTreeMap::new();
+        form_props(&mut properties, "", form.form_control());
+        let controls = form
+            .controls()
+            .map(|(site, control)| PyControl::new(site, control))
+            .collect();
+        Ok(PyForm {
+            storage: Path::new(&storage).to_string_lossy().into_owned(),
+            properties,
+            controls,
+        })
+    }
+
+    /// Read all forms, see `form_storages`
+    fn forms(&mut self) -> PyResult<Vec<PyForm>> {
+        let storages = self.inner.form_storages()?;
+        storages
+            .into_iter()
+            .map(|storage| self.form(Some(storage)))
+            .collect()
+    }
+
+    fn __repr__(&self) -> String {
+        String::from("<OFormsFile>")
+    }
+}
+
+/// The `ms_oforms` Python module
+#[pymodule]
+fn ms_oforms(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyOFormsFile>()?;
+    m.add_class::<PyForm>()?;
+    m.add_class::<PySite>()?;
+    m.add_class::<PyControl>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pyo3::{prelude::*, types::PyDict};
+
+    use super::{Native, PyOFormsFile};
+    use crate::{
+        controls::{
+            command_button::CommandButtonControl,
+            morph_data::{DisplayStyle, MorphDataControl},
+            Control,
+        },
+        fixture::{site, FormBuilder},
+        properties::Size,
+    };
+
+    const SIZE: Size = Size::new(4000, 3000);
+
+    /// A form with a CommandButton `cmdOK` and a Frame `fra` with a CheckBox `chk`
+    fn file(storage: &str, prefix: Option<&str>) -> PyOFormsFile {
+        let button = CommandButtonControl {
+            caption: String::from("OK"),
+            size: Size::new(1720, 635),
+            ..CommandButtonControl::default()
+        };
+        let check_box = MorphDataControl {
+            display_style: DisplayStyle::CheckBox,
+            ..MorphDataControl::default()
+        };
+        let frame = FormBuilder::new(SIZE).control(site(3, "chk"), Control::CheckBox(check_box));
+        let bytes = FormBuilder::new(SIZE)
+            .with_form(|form| form.caption = String::from("Hello"))
+            .control(site(1, "cmdOK"), Control::CommandButton(button))
+            .frame(site(2, "fra"), frame)
+            .compound_file(storage);
+        PyOFormsFile::new(bytes, prefix.map(PathBuf::from)).unwrap()
+    }
+
+    #[test]
+    fn test_form() {
+        let form = file("/", None).form(None).unwrap();
+        assert_eq!(form.storage, "");
+        assert_eq!(
+            form.properties["caption"],
+            Native::Str(String::from("Hello"))
+        );
+        let names: Vec<_> = form.sites().into_iter().map(|s| s.name).collect();
+        assert_eq!(names, ["cmdOK", "fra"]);
+        let cmd = &form.controls[0];
+        assert_eq!(cmd.type_name, Some("CommandButton"));
+        assert_eq!(cmd.storage, None);
+        assert!(!cmd.properties.contains_key("control.caption"));
+        assert!(cmd.properties.contains_key("caption"));
+        assert!(cmd.site.properties.contains_key("tab_index"));
+        assert_eq!(cmd.site.properties["tab_index"], Native::Int(-1));
+        assert_eq!(cmd.properties["take_focus_on_click"], Native::Bool(true));
+    }
+
+    #[test]
+    fn test_nested_form() {
+        let mut file = file("/", None);
+        let form = file.form(None).unwrap();
+        let storage = form.controls[1].storage.clone().unwrap();
+        assert_eq!(storage, "i02");
+        let inner = file.form(Some(PathBuf::from(&storage))).unwrap();
+        assert_eq!(inner.storage, "i02");
+        assert_eq!(inner.controls[0].type_name, Some("CheckBox"));
+        assert_eq!(file.forms().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_prefix() {
+        let mut file = file("/Macros/Form1", Some("/Macros"));
+        assert_eq!(file.form_storages().unwrap(), ["Form1"]);
+        let form = file.form(Some(PathBuf::from("Form1"))).unwrap();
+        assert_eq!(form.storage, "Form1");
+        assert_eq!(form.controls.len(), 2);
+        assert!(file.form(None).is_err(), "the prefix is not a form");
+    }
+
+    #[test]
+    fn test_invalid_file() {
+        assert!(PyOFormsFile::new(b"not a compound file".to_vec(), None).is_err());
+        let mut file = file("/", None);
+        assert!(file.form(Some(PathBuf::from("missing"))).is_err());
+    }
+
+    #[test]
+    fn test_json() {
+        let form = file("/", None).form(None).unwrap();
+        let json = form.to_json();
+        assert!(json.starts_with("{\"storage\":\"\",\"properties\":{"));
+        assert!(json.contains("\"type_name\":\"CommandButton\",\"storage\":null,\"error\":null"));
+        assert!(json.contains("\"type_name\":\"Frame\",\"storage\":\"i02\""));
+        assert!(json.contains("\"caption\":\"Hello\""));
+        assert!(json.contains("\"caption\":\"OK\""));
+        assert!(json.contains("\"take_focus_on_click\":true"));
+        assert!(json.contains("\"size\":{\"width\":1720,\"height\":"));
+        assert!(form.controls[0]
+            .site
+            .to_json()
+            .starts_with("{\"id\":1,\"name\":\"cmdOK\",\"left\":0,\"top\":0,"));
+    }
+
+    #[test]
+    fn test_to_dict() {
+        let form = file("/", None).form(None).unwrap();
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let dict = form.controls[0].to_dict(py).unwrap();
+            let props = dict.get_item("properties").unwrap().unwrap();
+            let props = props.downcast::<PyDict>().unwrap();
+            let caption = props.get_item("caption").unwrap().unwrap();
+            assert_eq!(caption.extract::<String>().unwrap(), "OK");
+            let focus = props.get_item("take_focus_on_click").unwrap().unwrap();
+            assert!(focus.extract::<bool>().unwrap());
+            let size = props.get_item("size").unwrap().unwrap();
+            let width = size.get_item("width").unwrap().extract::<i64>().unwrap();
+            assert_eq!(width, 1720);
+        });
+    }
+}
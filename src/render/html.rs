@@ -0,0 +1,614 @@
+//! Export a [`FormControl`] and its controls to an HTML document
+//!
+//! Every control becomes an absolutely positioned HTML element:
+//!
+//! | Control | Element |
+//! |---------|---------|
+//! | CommandButton, ToggleButton | `<button>` |
+//! | Label | `<label>` |
+//! | TextBox | `<input type="text">`, `<input type="password">` or `<textarea>` |
+//! | ListBox, ComboBox | `<select>` |
+//! | CheckBox, OptionButton | `<label>` with an `<input type="checkbox">` or `<input type="radio">` |
+//! | SpinButton, ScrollBar | `<input type="number">`, `<input type="range">` |
+//! | Image | `<img>` |
+//! | TabStrip | `<div role="tablist">` |
+//! | Frame, MultiPage | `<fieldset>` |
+//!
+//! The controls of a form are written in tab order, with the children of a parent control
+//! inside its element, so the order of the document is the tab order of the whole form and
+//! the `tabindex` is only `0` or `-1`. A `z-index` keeps the order of the sites for
+//! overlapping controls.
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Write},
+};
+
+use super::{css_font, escape, himetric_to_px, picture_data_uri};
+use crate::{
+    controls::{
+        morph_data::{DisplayStyle, MorphDataControl},
+        user_form::{
+            ole_site_concrete::{OleSiteConcreteControl, SiteFlags},
+            BorderStyle, FormControl,
+        },
+        Control,
+    },
+    properties::{
+        color::{palette::ColorResolver, OleColor},
+        font::{TextAlign, TextProps},
+        PictureAlignment, PictureSizeMode, Size, VariousPropertyBits,
+    },
+};
+
+struct Parent {
+    size: Size,
+    caption: String,
+    children: String,
+}
+
+/// Exports forms to HTML
+#[derive(Default)]
+pub struct HtmlExporter {
+    resolver: ColorResolver,
+    parents: HashMap<i32, Parent>,
+}
+
+impl HtmlExporter {
+    /// Create a new exporter that resolves colors with `resolver`
+    pub fn new(resolver: ColorResolver) -> Self {
+        Self {
+            resolver,
+            parents: HashMap::new(),
+        }
+    }
+
+    /// Use the form and controls of the parent control (Frame, MultiPage) with ID `id`
+    ///
+    /// The children are exported immediately, so nested parents need to be added first.
+    pub fn with_parent<'a, I>(mut self, id: i32, form: &FormControl, controls: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a OleSiteConcreteControl, &'a Control)>,
+    {
+        let mut children = String::new();
+        self.write_controls(&mut children, controls)
+            .expect("writing to a String can't fail");
+        self.parents.insert(
+            id,
+            Parent {
+                size: form.displayed_size,
+                caption: form.caption.clone(),
+                children,
+            },
+        );
+        self
+    }
+
+    /// Export the form to a string
+    pub fn export<'a, I>(&self, form: &FormControl, controls: I) -> String
+    where
+        I: IntoIterator<Item = (&'a OleSiteConcreteControl, &'a Control)>,
+    {
+        let mut out = String::new();
+        self.export_to(&mut out, form, controls)
+            .expect("writing to a String can't fail");
+        out
+    }
+
+    /// Export the form to a writer
+    pub fn export_to<'a, W, I>(&self, out: &mut W, form: &FormControl, controls: I) -> fmt::Result
+    where
+        W: Write,
+        I: IntoIterator<Item = (&'a OleSiteConcreteControl, &'a Control)>,
+    {
+        let title = escape(&form.caption);
+        writeln!(out, "<!DOCTYPE html>")?;
+        writeln!(out, "<html>")?;
+        writeln!(out, "<head>")?;
+        writeln!(out, r#"<meta charset="utf-8">"#)?;
+        writeln!(out, "<title>{}</title>", title)?;
+        writeln!(out, "<style>")?;
+        writeln!(
+            out,
+            ".userform {{ position: relative; overflow: hidden; box-sizing: border-box; }}"
+        )?;
+        writeln!(
+            out,
+            ".userform .ctrl {{ position: absolute; box-sizing: border-box; margin: 0; }}"
+        )?;
+        writeln!(
+            out,
+            ".userform fieldset.ctrl {{ padding: 0; border: 2px groove; }}"
+        )?;
+        writeln!(out, "</style>")?;
+        writeln!(out, "</head>")?;
+        writeln!(out, "<body>")?;
+
+        let mut style = format!(
+            "width:{:.0}px;height:{:.0}px;background-color:{}",
+            himetric_to_px(i64::from(form.displayed_size.width)),
+            himetric_to_px(i64::from(form.displayed_size.height)),
+            self.resolver.resolve(form.back_color),
+        );
+        if form.border_style == BorderStyle::Single {
+            write!(
+                style,
+                ";border:1px solid {}",
+                self.resolver.resolve(form.border_color)
+            )?;
+        }
+        if let Some(uri) = form.picture.data().and_then(picture_data_uri) {
+            write!(
+                style,
+                ";background-image:url('{}');background-position:{};background-size:{};background-repeat:{}",
+                uri,
+                css_position(form.picture_alignment),
+                css_background_size(form.picture_size_mode),
+                if form.picture_tiling { "repeat" } else { "no-repeat" },
+            )?;
+        }
+        writeln!(
+            out,
+            r#"<form class="userform" aria-label="{}" style="{}">"#,
+            title, style
+        )?;
+        if form.zoom != 100 {
+            writeln!(
+                out,
+                r#"<div style="transform:scale({});transform-origin:0 0">"#,
+                f64::from(form.zoom) / 100.0
+            )?;
+        }
+        self.write_controls(out, controls)?;
+        if form.zoom != 100 {
+            writeln!(out, "</div>")?;
+        }
+        writeln!(out, "</form>")?;
+        writeln!(out, "</body>")?;
+        writeln!(out, "</html>")
+    }
+
+    fn write_controls<'a, W, I>(&self, out: &mut W, controls: I) -> fmt::Result
+    where
+        W: Write,
+        I: IntoIterator<Item = (&'a OleSiteConcreteControl, &'a Control)>,
+    {
+        let mut controls: Vec<_> = controls.into_iter().enumerate().collect();
+        controls.sort_by_key(|(_, (site, _))| match site.tab_index {
+            tab_index if tab_index >= 0 => i32::from(tab_index),
+            _ => i32::MAX,
+        });
+        for (z, (site, control)) in controls {
+            self.write_control(out, site, control, z + 1)?;
+        }
+        Ok(())
+    }
+
+    fn write_control<W: Write>(
+        &self,
+        out: &mut W,
+        site: &OleSiteConcreteControl,
+        control: &Control,
+        z_index: usize,
+    ) -> fmt::Result {
+        let parent = self.parents.get(&site.id);
+        let size = match control {
+            Control::Frame(_) | Control::MultiPage(_) | Control::Page(_) => parent.map(|p| p.size),
+            _ => control.size(),
+        };
+        let size = match size {
+            Some(size) => size,
+            None => return writeln!(out, "<!-- {}: unknown control -->", escape(&site.name)),
+        };
+
+        let mut style = format!(
+            "left:{:.0}px;top:{:.0}px;width:{:.0}px;height:{:.0}px;z-index:{}",
+            himetric_to_px(i64::from(site.site_position.left)),
+            himetric_to_px(i64::from(site.site_position.top)),
+            himetric_to_px(i64::from(size.width)),
+            himetric_to_px(i64::from(size.height)),
+            z_index,
+        );
+        if let Some(color) = control.fore_color() {
+            write!(style, ";color:{}", self.resolver.resolve(color))?;
+        }
+        if let Some(text_props) = control.text_props() {
+            write!(style, ";{}", css_font(text_props))?;
+            write!(style, ";text-align:{}", css_text_align(text_props))?;
+        }
+        if let Some(color) = self.back_color(control) {
+            write!(style, ";background-color:{}", self.resolver.resolve(color))?;
+        }
+        if let Control::Image(c) = control {
+            write!(
+                style,
+                ";object-fit:{};object-position:{}",
+                css_object_fit(c.picture_size_mode),
+                css_position(c.picture_alignment)
+            )?;
+        }
+
+        // The attributes of the focusable element, and of the element that is positioned
+        let mut focus_attrs = format!(r#"id="{}""#, escape(&site.name));
+        if site.bit_flags.contains(SiteFlags::TAB_STOP) && site.tab_index >= 0 {
+            write!(focus_attrs, r#" tabindex="0""#)?;
+        } else {
+            write!(focus_attrs, r#" tabindex="-1""#)?;
+        }
+        if !site.control_tip_text.is_empty() {
+            write!(
+                focus_attrs,
+                r#" title="{}""#,
+                escape(&site.control_tip_text)
+            )?;
+        }
+        let mut box_attrs = format!(r#"class="ctrl" style="{}""#, escape(&style));
+        if let Some(group_id) = site.group_id {
+            write!(box_attrs, r#" data-group="{}""#, group_id)?;
+        }
+        if !site.bit_flags.contains(SiteFlags::VISIBLE) {
+            box_attrs.push_str(" hidden");
+        }
+        let attrs = format!("{} {}", focus_attrs, box_attrs);
+        let name = escape(&site.name);
+
+        match control {
+            Control::CommandButton(c) => {
+                let disabled = disabled(c.various_property_bits);
+                writeln!(
+                    out,
+                    r#"<button type="button" name="{}" {}{}>{}</button>"#,
+                    name,
+                    attrs,
+                    disabled,
+                    text(&c.caption)
+                )
+            }
+            Control::ToggleButton(c) => writeln!(
+                out,
+                r#"<button type="button" name="{}" aria-pressed="{}" {}{}>{}</button>"#,
+                name,
+                c.value == "1",
+                attrs,
+                disabled(c.various_property_bits),
+                text(&c.caption)
+            ),
+            Control::Label(c) => writeln!(out, "<label {}>{}</label>", attrs, text(&c.caption)),
+            Control::TextBox(c) => {
+                let bits = c.various_property_bits;
+                let mut extra = String::from(disabled(bits));
+                if bits.contains(VariousPropertyBits::LOCKED) {
+                    extra.push_str(" readonly");
+                }
+                if c.max_length > 0 {
+                    write!(extra, r#" maxlength="{}""#, c.max_length)?;
+                }
+                if bits.contains(VariousPropertyBits::MULTI_LINE) {
+                    writeln!(
+                        out,
+                        r#"<textarea name="{}" {}{}>{}</textarea>"#,
+                        name,
+                        attrs,
+                        extra,
+                        escape(&c.value)
+                    )
+                } else {
+                    let input_type = match c.password_char {
+                        0 => "text",
+                        _ => "password",
+                    };
+                    writeln!(
+                        out,
+                        r#"<input type="{}" name="{}" value="{}" {}{}>"#,
+                        input_type,
+                        name,
+                        escape(&c.value),
+                        attrs,
+                        extra
+                    )
+                }
+            }
+            Control::ListBox(c) | Control::ComboBox(c) => {
+                let mut extra = String::from(disabled(c.various_property_bits));
+                if let Control::ListBox(_) = control {
+                    write!(extra, r#" size="{}""#, c.list_rows.max(2))?;
+                    if c.multi_select != 0 {
+                        extra.push_str(" multiple");
+                    }
+                }
+                writeln!(out, r#"<select name="{}" {}{}>"#, name, attrs, extra)?;
+                if !c.value.is_empty() {
+                    writeln!(out, "<option selected>{}</option>", escape(&c.value))?;
+                }
+                writeln!(out, "</select>")
+            }
+            Control::CheckBox(c) | Control::OptionButton(c) => {
+                let input = match c.display_style {
+                    DisplayStyle::OptionButton => format!(
+                        r#"<input type="radio" name="{}" value="{}""#,
+                        escape(&radio_group(site, c)),
+                        name
+                    ),
+                    _ => format!(r#"<input type="checkbox" name="{}""#, name),
+                };
+                let checked = match c.value.as_str() {
+                    "1" => " checked",
+                    _ => "",
+                };
+                writeln!(
+                    out,
+                    "<label {}>{} {}{}{}> {}</label>",
+                    box_attrs,
+                    input,
+                    focus_attrs,
+                    checked,
+                    disabled(c.various_property_bits),
+                    text(&c.caption)
+                )
+            }
+            Control::SpinButton(c) => writeln!(
+                out,
+                r#"<input type="number" name="{}" min="{}" max="{}" step="{}" value="{}" {}{}>"#,
+                name,
+                c.min.min(c.max),
+                c.max.max(c.min),
+                c.small_change,
+                c.position,
+                attrs,
+                disabled(c.various_property_bits)
+            ),
+            Control::ScrollBar(c) => writeln!(
+                out,
+                r#"<input type="range" name="{}" min="{}" max="{}" step="{}" value="{}" {}{}>"#,
+                name,
+                c.min.min(c.max),
+                c.max.max(c.min),
+                c.small_change,
+                c.position,
+                attrs,
+                disabled(c.various_property_bits)
+            ),
+            Control::Image(c) => match c.picture.data().and_then(picture_data_uri) {
+                Some(uri) => writeln!(out, r#"<img src="{}" alt="{}" {}>"#, uri, name, attrs),
+                None => writeln!(
+                    out,
+                    r#"<div role="img" aria-label="{}" {}></div>"#,
+                    name, attrs
+                ),
+            },
+            Control::TabStrip(c) => {
+                writeln!(out, r#"<div role="tablist" {}>"#, attrs)?;
+                for (i, item) in c.items.iter().enumerate() {
+                    let tip = c.tip_strings.get(i).filter(|t| !t.is_empty());
+                    let title = tip
+                        .map(|t| format!(r#" title="{}""#, escape(t)))
+                        .unwrap_or_default();
+                    writeln!(
+                        out,
+                        r#"<button type="button" role="tab" aria-selected="{}"{}>{}</button>"#,
+                        i as i64 == i64::from(c.list_index),
+                        title,
+                        text(item)
+                    )?;
+                }
+                writeln!(out, "</div>")
+            }
+            Control::Frame(_) | Control::MultiPage(_) | Control::Page(_) => {
+                let caption = parent.map_or(site.name.as_str(), |p| &p.caption);
+                writeln!(out, "<fieldset {}>", attrs)?;
+                writeln!(out, "<legend>{}</legend>", text(caption))?;
+                if let Some(parent) = parent {
+                    writeln!(out, r#"<div style="position:relative">"#)?;
+                    out.write_str(&parent.children)?;
+                    writeln!(out, "</div>")?;
+                }
+                writeln!(out, "</fieldset>")
+            }
+            Control::Unknown { clsid, .. } => writeln!(
+                out,
+                r#"<object data-clsid="{}" {}></object>"#,
+                clsid.hyphenated(),
+                attrs
+            ),
+        }
+    }
+
+    fn back_color(&self, control: &Control) -> Option<OleColor> {
+        let bits = match control {
+            Control::Label(c) => c.various_property_bits,
+            Control::Image(c) => c.various_property_bits,
+            Control::CheckBox(c) | Control::OptionButton(c) => c.various_property_bits,
+            _ => VariousPropertyBits::BACK_STYLE,
+        };
+        match bits.contains(VariousPropertyBits::BACK_STYLE) {
+            true => control.back_color(),
+            false => None,
+        }
+    }
+}
+
+fn disabled(bits: VariousPropertyBits) -> &'static str {
+    match bits.contains(VariousPropertyBits::ENABLED) {
+        true => "",
+        false => " disabled",
+    }
+}
+
+/// Escape a caption, keeping line breaks
+fn text(caption: &str) -> String {
+    escape(caption)
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+/// The `name` of an OptionButton: the GroupName, or the control group, or the form
+fn radio_group(site: &OleSiteConcreteControl, c: &MorphDataControl) -> String {
+    match (&c.group_name, site.group_id) {
+        (name, _) if !name.is_empty() => name.clone(),
+        (_, Some(id)) => format!("group-{}", id),
+        (_, None) => String::from("options"),
+    }
+}
+
+fn css_text_align(text_props: &TextProps) -> &'static str {
+    match text_props.paragraph_align {
+        TextAlign::Left => "left",
+        TextAlign::Center => "center",
+        TextAlign::Right => "right",
+    }
+}
+
+fn css_position(alignment: PictureAlignment) -> &'static str {
+    match alignment {
+        PictureAlignment::TopLeft => "left top",
+        PictureAlignment::TopRight => "right top",
+        PictureAlignment::Center => "center",
+        PictureAlignment::BottomLeft => "left bottom",
+        PictureAlignment::BottomRight => "right bottom",
+    }
+}
+
+fn css_background_size(size_mode: PictureSizeMode) -> &'static str {
+    match size_mode {
+        PictureSizeMode::Clip => "auto",
+        PictureSizeMode::Stretch => "100% 100%",
+        PictureSizeMode::Zoom => "contain",
+    }
+}
+
+fn css_object_fit(size_mode: PictureSizeMode) -> &'static str {
+    match size_mode {
+        PictureSizeMode::Clip => "none",
+        PictureSizeMode::Stretch => "fill",
+        PictureSizeMode::Zoom => "contain",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU16;
+
+    use nom::error::VerboseError;
+
+    use super::HtmlExporter;
+    use crate::{
+        controls::{
+            morph_data::{DisplayStyle, MorphDataControl},
+            user_form::{
+                ole_site_concrete::{Clsid, OleSiteConcreteControl, SiteFlags},
+                parse_form_control,
+            },
+            Control,
+        },
+        fixture::{site, FormBuilder},
+        properties::{FormEmbeddedActiveXControlCached, Position, Size},
+    };
+
+    #[test]
+    fn test_export_command_button() {
+        #[rustfmt::skip]
+        let form = [
+            0x00, 0x04, 0x04, 0x00, // version, cbForm
+            0x00, 0x00, 0x00, 0x00, // PropMask
+            0x00, 0x00, // CountOfSiteClassInfo
+            0x00, 0x00, 0x00, 0x00, // CountOfSites
+            0x00, 0x00, 0x00, 0x00, // CountOfBytes
+        ];
+        #[rustfmt::skip]
+        let button = [
+            0x00, 0x02, 0x14, 0x00, // version, cbCommandButton
+            0x28, 0x00, 0x00, 0x00, // PropMask: fCaption, fSize
+            0x02, 0x00, 0x00, 0x80, // Caption: 2 bytes, compressed
+            b'O', b'K', 0x00, 0x00, // Caption, padding
+            0xE8, 0x03, 0x00, 0x00, // Size.Width
+            0xF4, 0x01, 0x00, 0x00, // Size.Height
+            0x00, 0x02, 0x04, 0x00, // TextProps: version, cbTextProps
+            0x00, 0x00, 0x00, 0x00, // TextProps: PropMask
+        ];
+        let (_, form) = parse_form_control::<VerboseError<_>>(&form).unwrap();
+        let (_, button) = Control::parse::<VerboseError<_>>(
+            FormEmbeddedActiveXControlCached::CommandButton,
+            &button,
+        )
+        .unwrap();
+        let site = OleSiteConcreteControl {
+            id: 1,
+            bit_flags: SiteFlags::TAB_STOP | SiteFlags::VISIBLE | SiteFlags::STREAMED,
+            object_stream_size: 32,
+            tab_index: 2,
+            clsid_cache_index: Clsid::Global(17),
+            group_id: NonZeroU16::new(4),
+            name: String::from("cmdOK"),
+            site_position: Position::new(254, 508),
+            control_tip_text: String::from("Save \"all\""),
+            ..OleSiteConcreteControl::default()
+        };
+        let html = HtmlExporter::default().export(&form, [(&site, &button)]);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        let line = html.lines().find(|l| l.starts_with("<button")).unwrap();
+        assert!(line.contains(r#"name="cmdOK""#));
+        assert!(line.contains("left:10px;top:19px;width:38px;height:19px"));
+        assert!(line.contains(r#"tabindex="0""#));
+        assert!(line.contains(r#"title="Save &quot;all&quot;""#));
+        assert!(line.contains(r#"data-group="4""#));
+        assert!(line.ends_with(">OK</button>"));
+    }
+
+    #[test]
+    fn test_export_tab_order() {
+        let size = Size::new(4000, 3000);
+        let at = |id, name, tab_index| OleSiteConcreteControl {
+            tab_index,
+            ..site(id, name)
+        };
+        let morph_data = |display_style| MorphDataControl {
+            display_style,
+            ..MorphDataControl::default()
+        };
+        let chk = OleSiteConcreteControl {
+            control_tip_text: String::from("Tip"),
+            ..at(3, "chk", 1)
+        };
+        let frame = FormBuilder::new(size)
+            .control(chk, Control::CheckBox(morph_data(DisplayStyle::CheckBox)))
+            .control(
+                at(4, "opt", 0),
+                Control::OptionButton(morph_data(DisplayStyle::OptionButton)),
+            );
+        let mut file = FormBuilder::new(size)
+            .control(at(1, "cmd", 2), Control::CommandButton(Default::default()))
+            .frame(at(2, "fra", 0), frame)
+            .control(
+                at(5, "txt", 1),
+                Control::TextBox(morph_data(DisplayStyle::Text)),
+            )
+            .file();
+        let read = |file: &mut crate::BufferedOFormsFile, storage: &str| {
+            let mut form = file.form_in(storage).unwrap();
+            let controls: Vec<_> = form
+                .controls()
+                .map(|(site, control)| (site.clone(), control.unwrap()))
+                .collect();
+            (form.into_form_control(), controls)
+        };
+        let (form, controls) = read(&mut file, "");
+        let (frame, children) = read(&mut file, "i02");
+        let html = HtmlExporter::default()
+            .with_parent(2, &frame, children.iter().map(|(s, c)| (s, c)))
+            .export(&form, controls.iter().map(|(s, c)| (s, c)));
+
+        let ids: Vec<_> = html
+            .split(r#" id=""#)
+            .skip(1)
+            .map(|rest| &rest[..rest.find('"').unwrap()])
+            .collect();
+        assert_eq!(ids, ["fra", "opt", "chk", "txt", "cmd"]);
+        assert!(!html.contains(r#"tabindex="1"#));
+        assert!(html.contains(";z-index:2\""));
+
+        let line = html.lines().find(|l| l.contains(r#"name="chk""#)).unwrap();
+        assert!(line.starts_with(r#"<label class="ctrl" style=""#));
+        assert!(line
+            .contains(r#"<input type="checkbox" name="chk" id="chk" tabindex="0" title="Tip">"#));
+    }
+}
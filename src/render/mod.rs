@@ -0,0 +1,141 @@
+//! Rendering previews of forms
+//!
+//! The output approximates the look of the controls at 96 DPI. It is meant for previews and
+//! not pixel-exact.
+
+use std::borrow::Cow;
+
+use crate::properties::{
+    font::TextProps,
+    units::{himetric_to_pixels, twips_to_pixels, DEFAULT_DPI},
+};
+
+pub mod html;
+pub mod svg;
+
+/// The default font of controls without a font name
+pub(crate) const DEFAULT_FONT: &str = "Tahoma";
+
+/// Convert HIMETRIC units (0.01 mm) to CSS pixels
+pub(crate) fn himetric_to_px(value: i64) -> f64 {
+    himetric_to_pixels(value, DEFAULT_DPI)
+}
+
+/// Convert twips (1/1440 inch) to CSS pixels
+pub(crate) fn twips_to_px(value: u32) -> f64 {
+    twips_to_pixels(i64::from(value), DEFAULT_DPI)
+}
+
+/// Escape a string for use in XML or HTML text and attribute values
+pub(crate) fn escape(input: &str) -> Cow<'_, str> {
+    if !input.contains(['&', '<', '>', '"', '\'']) {
+        return Cow::Borrowed(input);
+    }
+    let mut out = String::with_capacity(input.len() + 8);
+    for c in input.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// The CSS font properties for some [`TextProps`]
+pub(crate) fn css_font(text_props: &TextProps) -> String {
+    let family = match text_props.font_name.as_str() {
+        "" => DEFAULT_FONT,
+        name => name,
+    };
+    let effects = text_props.font_effects;
+    use crate::properties::font::FontEffects;
+    let weight = match effects.contains(FontEffects::BOLD) {
+        true => 700,
+        false => text_props.font_weight,
+    };
+    let mut css = format!(
+        "font-family:'{}';font-size:{:.2}px;font-weight:{}",
+        family.replace('\'', ""),
+        twips_to_px(text_props.font_height),
+        weight
+    );
+    if effects.contains(FontEffects::ITALIC) {
+        css.push_str(";font-style:italic");
+    }
+    match (
+        effects.contains(FontEffects::UNDERLINE),
+        effects.contains(FontEffects::STRIKEOUT),
+    ) {
+        (true, true) => css.push_str(";text-decoration:underline line-through"),
+        (true, false) => css.push_str(";text-decoration:underline"),
+        (false, true) => css.push_str(";text-decoration:line-through"),
+        (false, false) => {}
+    }
+    css
+}
+
+/// Guess the MIME type of picture data from its magic number
+pub fn picture_mime_type(data: &[u8]) -> Option<&'static str> {
+    match data {
+        [0x89, b'P', b'N', b'G', ..] => Some("image/png"),
+        [0xFF, 0xD8, 0xFF, ..] => Some("image/jpeg"),
+        [b'G', b'I', b'F', b'8', ..] => Some("image/gif"),
+        [b'B', b'M', ..] => Some("image/bmp"),
+        [0x00, 0x00, 0x01, 0x00, ..] => Some("image/x-icon"),
+        _ => None,
+    }
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode picture data as a `data:` URI, if it has a known type
+pub(crate) fn picture_data_uri(data: &[u8]) -> Option<String> {
+    let mime = picture_mime_type(data)?;
+    let mut out = String::with_capacity(mime.len() + 13 + data.len() * 4 / 3 + 4);
+    out.push_str("data:");
+    out.push_str(mime);
+    out.push_str(";base64,");
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = u32::from_be_bytes([0, b[0], b[1], b[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, picture_data_uri};
+
+    #[test]
+    fn test_helpers() {
+        assert_eq!(escape("a<b & 'c'"), "a&lt;b &amp; &#39;c&#39;");
+        assert_eq!(
+            picture_data_uri(b"GIF89a").as_deref(),
+            Some("data:image/gif;base64,R0lGODlh")
+        );
+        assert_eq!(
+            picture_data_uri(b"BMx").as_deref(),
+            Some("data:image/bmp;base64,Qk14")
+        );
+        assert_eq!(
+            picture_data_uri(b"BM").as_deref(),
+            Some("data:image/bmp;base64,Qk0=")
+        );
+        assert_eq!(picture_data_uri(b"\x01\x02"), None);
+    }
+}
@@ -0,0 +1,774 @@
+//! Render a [`FormControl`] and its controls to SVG
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Write},
+};
+
+use super::{css_font, escape, himetric_to_px, picture_data_uri};
+use crate::{
+    controls::{
+        morph_data::MorphDataControl,
+        user_form::{
+            ole_site_concrete::{OleSiteConcreteControl, SiteFlags},
+            BorderStyle, FormControl,
+        },
+        Control,
+    },
+    properties::{
+        color::{palette::ColorResolver, OleColor, RgbColor, SystemColor},
+        font::{TextAlign, TextProps},
+        picture::GuidAndPicture,
+        PictureAlignment, PictureSizeMode, Position, Size, SpecialEffect, VariousPropertyBits,
+    },
+};
+
+/// The height of the title bar, in pixels
+const TITLE_BAR: f64 = 23.0;
+/// The size of the box of a CheckBox or OptionButton, in pixels
+const CHECK_BOX: f64 = 13.0;
+
+#[derive(Copy, Clone)]
+struct Rect {
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+}
+
+impl Rect {
+    fn new(x: f64, y: f64, w: f64, h: f64) -> Self {
+        Self { x, y, w, h }
+    }
+
+    fn inset(self, d: f64) -> Self {
+        Self::new(
+            self.x + d,
+            self.y + d,
+            (self.w - 2.0 * d).max(0.0),
+            (self.h - 2.0 * d).max(0.0),
+        )
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum VAlign {
+    Top,
+    Middle,
+}
+
+/// Renders forms to SVG
+///
+/// Parent controls (Frame, MultiPage) keep their size and caption in their own storage, see
+/// [`SvgRenderer::with_parent`].
+#[derive(Debug, Clone, Default)]
+pub struct SvgRenderer {
+    resolver: ColorResolver,
+    parents: HashMap<i32, (Size, String)>,
+    show_hidden: bool,
+}
+
+impl SvgRenderer {
+    /// Create a new renderer that resolves colors with `resolver`
+    pub fn new(resolver: ColorResolver) -> Self {
+        Self {
+            resolver,
+            parents: HashMap::new(),
+            show_hidden: false,
+        }
+    }
+
+    /// Use the size and caption of the form of a parent control with ID `id`
+    pub fn with_parent(mut self, id: i32, form: &FormControl) -> Self {
+        self.parents
+            .insert(id, (form.displayed_size, form.caption.clone()));
+        self
+    }
+
+    /// Draw controls that are not visible at runtime with half opacity
+    pub fn with_hidden(mut self, show_hidden: bool) -> Self {
+        self.show_hidden = show_hidden;
+        self
+    }
+
+    /// Render the form to a string
+    pub fn render<'a, I>(&self, form: &FormControl, controls: I) -> String
+    where
+        I: IntoIterator<Item = (&'a OleSiteConcreteControl, &'a Control)>,
+    {
+        let mut out = String::new();
+        self.render_to(&mut out, form, controls)
+            .expect("writing to a String can't fail");
+        out
+    }
+
+    /// Render the form to a writer
+    pub fn render_to<'a, W, I>(&self, out: &mut W, form: &FormControl, controls: I) -> fmt::Result
+    where
+        W: Write,
+        I: IntoIterator<Item = (&'a OleSiteConcreteControl, &'a Control)>,
+    {
+        let mut canvas = Canvas {
+            out,
+            resolver: &self.resolver,
+        };
+        canvas.form(self, form, controls)
+    }
+}
+
+struct Canvas<'w, 'r, W> {
+    out: &'w mut W,
+    resolver: &'r ColorResolver,
+}
+
+impl<'w, 'r, W: Write> Canvas<'w, 'r, W> {
+    fn color(&self, color: OleColor) -> RgbColor {
+        self.resolver.resolve(color)
+    }
+
+    fn system(&self, color: SystemColor) -> RgbColor {
+        self.resolver.resolve(color.as_ole_color())
+    }
+
+    fn form<'a, I>(
+        &mut self,
+        renderer: &SvgRenderer,
+        form: &FormControl,
+        controls: I,
+    ) -> fmt::Result
+    where
+        I: IntoIterator<Item = (&'a OleSiteConcreteControl, &'a Control)>,
+    {
+        let w = himetric_to_px(i64::from(form.displayed_size.width));
+        let h = himetric_to_px(i64::from(form.displayed_size.height));
+        writeln!(
+            self.out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w:.0}" height="{th:.0}" viewBox="0 0 {w:.2} {th:.2}">"#,
+            w = w,
+            th = h + TITLE_BAR
+        )?;
+
+        // Title bar
+        let title = Rect::new(0.0, 0.0, w, TITLE_BAR);
+        let fill = self.system(SystemColor::ActiveTitleBar);
+        self.rect(title, Some(fill), None)?;
+        let title_font = TextProps {
+            font_name: String::from("Segoe UI"),
+            font_height: 180,
+            ..TextProps::default()
+        };
+        let text = self.system(SystemColor::TitleBarText);
+        self.text(
+            title.inset(4.0),
+            &form.caption,
+            &title_font,
+            text,
+            TextAlign::Left,
+            VAlign::Middle,
+        )?;
+
+        // Client area
+        writeln!(self.out, r#"<g transform="translate(0 {})">"#, TITLE_BAR)?;
+        let body = Rect::new(0.0, 0.0, w, h);
+        self.rect(body, Some(self.color(form.back_color)), None)?;
+        self.picture(
+            body,
+            &form.picture,
+            form.picture_alignment,
+            form.picture_size_mode,
+        )?;
+
+        let zoom = f64::from(form.zoom) / 100.0;
+        write!(self.out, r#"<g transform="scale({})"#, zoom)?;
+        if form.scroll_position != Position::default() {
+            write!(
+                self.out,
+                " translate({:.2} {:.2})",
+                -himetric_to_px(i64::from(form.scroll_position.left)),
+                -himetric_to_px(i64::from(form.scroll_position.top)),
+            )?;
+        }
+        writeln!(self.out, r#"">"#)?;
+        for (site, control) in controls {
+            let visible = site.bit_flags.contains(SiteFlags::VISIBLE);
+            if !visible && !renderer.show_hidden {
+                continue;
+            }
+            let size = match control {
+                Control::Frame(_) | Control::MultiPage(_) | Control::Page(_) => {
+                    renderer.parents.get(&site.id).map(|p| p.0)
+                }
+                _ => control.size(),
+            };
+            let size = match size {
+                Some(size) => size,
+                None => {
+                    writeln!(self.out, "<!-- {}: unknown size -->", escape(&site.name))?;
+                    continue;
+                }
+            };
+            let r = Rect::new(
+                himetric_to_px(i64::from(site.site_position.left)),
+                himetric_to_px(i64::from(site.site_position.top)),
+                himetric_to_px(i64::from(size.width)),
+                himetric_to_px(i64::from(size.height)),
+            );
+            match visible {
+                true => writeln!(self.out, r#"<g id="{}">"#, escape(&site.name))?,
+                false => writeln!(self.out, r#"<g id="{}" opacity="0.5">"#, escape(&site.name))?,
+            }
+            let parent_caption = renderer.parents.get(&site.id).map(|p| p.1.as_str());
+            self.control(r, site, control, parent_caption)?;
+            writeln!(self.out, "</g>")?;
+        }
+        writeln!(self.out, "</g>")?;
+
+        if form.border_style == BorderStyle::Single {
+            self.rect(body.inset(0.5), None, Some(self.color(form.border_color)))?;
+        }
+        self.effect(body, form.special_effect)?;
+        writeln!(self.out, "</g>")?;
+        writeln!(self.out, "</svg>")
+    }
+
+    fn control(
+        &mut self,
+        r: Rect,
+        site: &OleSiteConcreteControl,
+        control: &Control,
+        parent_caption: Option<&str>,
+    ) -> fmt::Result {
+        match control {
+            Control::CommandButton(c) => {
+                self.rect(r, Some(self.color(c.back_color)), None)?;
+                self.effect(r, SpecialEffect::Raised)?;
+                self.picture(
+                    r,
+                    &c.picture,
+                    PictureAlignment::Center,
+                    PictureSizeMode::Clip,
+                )?;
+                let align = c.text_props.paragraph_align;
+                let color = self.color(c.fore_color);
+                self.text(
+                    r.inset(3.0),
+                    &c.caption,
+                    &c.text_props,
+                    color,
+                    align,
+                    VAlign::Middle,
+                )
+            }
+            Control::Label(c) => {
+                self.background(r, c.various_property_bits, c.back_color)?;
+                self.border(r, c.border_style, c.border_color)?;
+                self.effect(r, c.special_effect)?;
+                self.picture(
+                    r,
+                    &c.picture,
+                    PictureAlignment::Center,
+                    PictureSizeMode::Clip,
+                )?;
+                let align = c.text_props.paragraph_align;
+                let color = self.color(c.fore_color);
+                self.text(
+                    r.inset(2.0),
+                    &c.caption,
+                    &c.text_props,
+                    color,
+                    align,
+                    VAlign::Top,
+                )
+            }
+            Control::TextBox(c) => {
+                self.field(r, c)?;
+                let valign = match c
+                    .various_property_bits
+                    .contains(VariousPropertyBits::MULTI_LINE)
+                {
+                    true => VAlign::Top,
+                    false => VAlign::Middle,
+                };
+                self.value(r.inset(3.0), c, valign)
+            }
+            Control::ListBox(c) => {
+                self.field(r, c)?;
+                self.value(r.inset(3.0), c, VAlign::Top)
+            }
+            Control::ComboBox(c) => {
+                self.field(r, c)?;
+                let b = (r.h - 4.0).clamp(0.0, 17.0);
+                let button = Rect::new(r.x + r.w - b - 2.0, r.y + 2.0, b, r.h - 4.0);
+                self.rect(button, Some(self.system(SystemColor::ButtonFace)), None)?;
+                self.effect(button, SpecialEffect::Raised)?;
+                let arrow = self.color(c.fore_color);
+                self.arrow(button, Direction::Down, arrow)?;
+                let mut text = r.inset(3.0);
+                text.w = (text.w - b).max(0.0);
+                self.value(text, c, VAlign::Middle)
+            }
+            Control::CheckBox(c) | Control::OptionButton(c) => {
+                self.background(r, c.various_property_bits, c.back_color)?;
+                let b = Rect::new(
+                    r.x + 2.0,
+                    r.y + (r.h - CHECK_BOX) / 2.0,
+                    CHECK_BOX,
+                    CHECK_BOX,
+                );
+                let window = self.system(SystemColor::WindowBackground);
+                let mark = self.color(c.fore_color);
+                let checked = c.value == "1";
+                if let Control::CheckBox(_) = control {
+                    self.rect(b, Some(window), None)?;
+                    self.effect(b, SpecialEffect::Sunken)?;
+                    if checked {
+                        writeln!(
+                            self.out,
+                            r#"<polyline points="{:.2},{:.2} {:.2},{:.2} {:.2},{:.2}" fill="none" stroke="{}" stroke-width="2"/>"#,
+                            b.x + 3.0,
+                            b.y + 6.5,
+                            b.x + 5.5,
+                            b.y + 9.0,
+                            b.x + 10.0,
+                            b.y + 4.0,
+                            mark
+                        )?;
+                    }
+                } else {
+                    let (cx, cy) = (b.x + b.w / 2.0, b.y + b.h / 2.0);
+                    writeln!(
+                        self.out,
+                        r#"<circle cx="{:.2}" cy="{:.2}" r="{:.2}" fill="{}" stroke="{}"/>"#,
+                        cx,
+                        cy,
+                        b.w / 2.0 - 0.5,
+                        window,
+                        self.system(SystemColor::ButtonShadow)
+                    )?;
+                    if checked {
+                        writeln!(
+                            self.out,
+                            r#"<circle cx="{:.2}" cy="{:.2}" r="2.5" fill="{}"/>"#,
+                            cx, cy, mark
+                        )?;
+                    }
+                }
+                let text = Rect::new(
+                    r.x + CHECK_BOX + 6.0,
+                    r.y,
+                    (r.w - CHECK_BOX - 6.0).max(0.0),
+                    r.h,
+                );
+                let align = c.text_props.paragraph_align;
+                self.text(text, &c.caption, &c.text_props, mark, align, VAlign::Middle)
+            }
+            Control::ToggleButton(c) => {
+                self.rect(r, Some(self.color(c.back_color)), None)?;
+                let effect = match c.value == "1" {
+                    true => SpecialEffect::Sunken,
+                    false => SpecialEffect::Raised,
+                };
+                self.effect(r, effect)?;
+                self.picture(
+                    r,
+                    &c.picture,
+                    PictureAlignment::Center,
+                    PictureSizeMode::Clip,
+                )?;
+                let align = c.text_props.paragraph_align;
+                let color = self.color(c.fore_color);
+                self.text(
+                    r.inset(3.0),
+                    &c.caption,
+                    &c.text_props,
+                    color,
+                    align,
+                    VAlign::Middle,
+                )
+            }
+            Control::Image(c) => {
+                self.background(r, c.various_property_bits, c.back_color)?;
+                self.picture(r, &c.picture, c.picture_alignment, c.picture_size_mode)?;
+                self.border(r, c.border_style, c.border_color)?;
+                self.effect(r, c.special_effect)
+            }
+            Control::SpinButton(c) => {
+                let arrow = self.color(c.fore_color);
+                self.rect(r, Some(self.color(c.back_color)), None)?;
+                self.arrows(r, c.orientation, r.w.min(r.h), arrow)
+            }
+            Control::ScrollBar(c) => {
+                let arrow = self.color(c.fore_color);
+                self.rect(r, Some(self.color(c.back_color)), None)?;
+                self.arrows(r, c.orientation, r.w.min(r.h), arrow)
+            }
+            Control::TabStrip(c) => {
+                let color = self.color(c.fore_color);
+                self.rect(r, Some(self.color(c.back_color)), None)?;
+                let tab_h = 20.0_f64.min(r.h);
+                let body = Rect::new(r.x, r.y + tab_h, r.w, (r.h - tab_h).max(0.0));
+                self.effect(body, SpecialEffect::Raised)?;
+                let mut x = r.x + 2.0;
+                for item in &c.items {
+                    let tab_w = item.chars().count() as f64 * 6.0 + 12.0;
+                    let tab = Rect::new(x, r.y + 2.0, tab_w, tab_h - 2.0);
+                    self.effect(tab, SpecialEffect::Raised)?;
+                    self.text(
+                        tab,
+                        item,
+                        &c.text_props,
+                        color,
+                        TextAlign::Center,
+                        VAlign::Middle,
+                    )?;
+                    x += tab_w;
+                }
+                Ok(())
+            }
+            Control::Frame(_) | Control::MultiPage(_) | Control::Page(_) => {
+                let caption = parent_caption.unwrap_or(&site.name);
+                let font = TextProps::default();
+                let text = self.system(SystemColor::ButtonText);
+                let frame = Rect::new(r.x, r.y + 6.0, r.w, (r.h - 6.0).max(0.0));
+                self.effect(frame, SpecialEffect::Etched)?;
+                let label_w = caption.chars().count() as f64 * 6.0 + 6.0;
+                let label = Rect::new(r.x + 6.0, r.y, label_w.min(r.w - 12.0).max(0.0), 12.0);
+                if !caption.is_empty() {
+                    let face = self.system(SystemColor::ButtonFace);
+                    self.rect(label, Some(face), None)?;
+                }
+                self.text(
+                    label,
+                    caption,
+                    &font,
+                    text,
+                    TextAlign::Center,
+                    VAlign::Middle,
+                )
+            }
+            Control::Unknown { .. } => {
+                let shadow = self.system(SystemColor::ButtonShadow);
+                writeln!(
+                    self.out,
+                    r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="none" stroke="{}" stroke-dasharray="4 2"/>"#,
+                    r.x, r.y, r.w, r.h, shadow
+                )?;
+                let font = TextProps::default();
+                self.text(
+                    r,
+                    &site.name,
+                    &font,
+                    shadow,
+                    TextAlign::Center,
+                    VAlign::Middle,
+                )
+            }
+        }
+    }
+
+    /// The box of a TextBox, ListBox or ComboBox
+    fn field(&mut self, r: Rect, c: &MorphDataControl) -> fmt::Result {
+        self.background(r, c.various_property_bits, c.back_color)?;
+        self.border(r, c.border_style, c.border_color)?;
+        self.effect(r, c.special_effect)
+    }
+
+    fn value(&mut self, r: Rect, c: &MorphDataControl, valign: VAlign) -> fmt::Result {
+        let color = self.color(c.fore_color);
+        let align = c.text_props.paragraph_align;
+        match c.password_char {
+            0 => self.text(r, &c.value, &c.text_props, color, align, valign),
+            p => {
+                let p = char::from_u32(u32::from(p)).unwrap_or('*');
+                let masked: String = c.value.chars().map(|_| p).collect();
+                self.text(r, &masked, &c.text_props, color, align, valign)
+            }
+        }
+    }
+
+    fn background(&mut self, r: Rect, bits: VariousPropertyBits, color: OleColor) -> fmt::Result {
+        match bits.contains(VariousPropertyBits::BACK_STYLE) {
+            true => self.rect(r, Some(self.color(color)), None),
+            false => Ok(()),
+        }
+    }
+
+    fn border(&mut self, r: Rect, style: BorderStyle, color: OleColor) -> fmt::Result {
+        match style {
+            BorderStyle::Single => self.rect(r.inset(0.5), None, Some(self.color(color))),
+            BorderStyle::None => Ok(()),
+        }
+    }
+
+    fn rect(&mut self, r: Rect, fill: Option<RgbColor>, stroke: Option<RgbColor>) -> fmt::Result {
+        write!(
+            self.out,
+            r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}""#,
+            r.x, r.y, r.w, r.h
+        )?;
+        match fill {
+            Some(fill) => write!(self.out, r#" fill="{}""#, fill)?,
+            None => write!(self.out, r#" fill="none""#)?,
+        }
+        if let Some(stroke) = stroke {
+            write!(self.out, r#" stroke="{}""#, stroke)?;
+        }
+        writeln!(self.out, "/>")
+    }
+
+    /// Draw the top-left and bottom-right edges of a rectangle
+    fn bevel(&mut self, r: Rect, top_left: RgbColor, bottom_right: RgbColor) -> fmt::Result {
+        let r = r.inset(0.5);
+        let (x0, y0, x1, y1) = (r.x, r.y, r.x + r.w, r.y + r.h);
+        writeln!(
+            self.out,
+            r#"<polyline points="{:.2},{:.2} {:.2},{:.2} {:.2},{:.2}" fill="none" stroke="{}"/>"#,
+            x0, y1, x0, y0, x1, y0, top_left
+        )?;
+        writeln!(
+            self.out,
+            r#"<polyline points="{:.2},{:.2} {:.2},{:.2} {:.2},{:.2}" fill="none" stroke="{}"/>"#,
+            x1, y0, x1, y1, x0, y1, bottom_right
+        )
+    }
+
+    fn effect(&mut self, r: Rect, effect: SpecialEffect) -> fmt::Result {
+        let highlight = self.system(SystemColor::_3DHighlight);
+        let light = self.system(SystemColor::_3DLight);
+        let shadow = self.system(SystemColor::ButtonShadow);
+        let dark = self.system(SystemColor::_3DDKShadow);
+        match effect {
+            SpecialEffect::Flat => Ok(()),
+            SpecialEffect::Raised => {
+                self.bevel(r, highlight, dark)?;
+                self.bevel(r.inset(1.0), light, shadow)
+            }
+            SpecialEffect::Sunken => {
+                self.bevel(r, shadow, highlight)?;
+                self.bevel(r.inset(1.0), dark, light)
+            }
+            SpecialEffect::Etched => {
+                self.bevel(r, shadow, highlight)?;
+                self.bevel(r.inset(1.0), highlight, shadow)
+            }
+            SpecialEffect::Bump => {
+                self.bevel(r, highlight, shadow)?;
+                self.bevel(r.inset(1.0), shadow, highlight)
+            }
+        }
+    }
+
+    fn text(
+        &mut self,
+        r: Rect,
+        text: &str,
+        props: &TextProps,
+        color: RgbColor,
+        align: TextAlign,
+        valign: VAlign,
+    ) -> fmt::Result {
+        if text.is_empty() {
+            return Ok(());
+        }
+        let lines: Vec<&str> = text.split('\n').map(|l| l.trim_end_matches('\r')).collect();
+        let (x, anchor) = match align {
+            TextAlign::Left => (r.x, "start"),
+            TextAlign::Center => (r.x + r.w / 2.0, "middle"),
+            TextAlign::Right => (r.x + r.w, "end"),
+        };
+        let (y, baseline, first_dy) = match valign {
+            VAlign::Top => (r.y, "hanging", 0.0),
+            VAlign::Middle => (r.y + r.h / 2.0, "central", -0.6 * (lines.len() - 1) as f64),
+        };
+        write!(
+            self.out,
+            r#"<text x="{:.2}" y="{:.2}" fill="{}" text-anchor="{}" dominant-baseline="{}" style="{}">"#,
+            x,
+            y,
+            color,
+            anchor,
+            baseline,
+            escape(&css_font(props))
+        )?;
+        for (i, line) in lines.iter().enumerate() {
+            let dy = if i == 0 { first_dy } else { 1.2 };
+            write!(
+                self.out,
+                r#"<tspan x="{:.2}" dy="{}em">{}</tspan>"#,
+                x,
+                dy,
+                escape(line)
+            )?;
+        }
+        writeln!(self.out, "</text>")
+    }
+
+    fn picture(
+        &mut self,
+        r: Rect,
+        picture: &GuidAndPicture,
+        alignment: PictureAlignment,
+        size_mode: PictureSizeMode,
+    ) -> fmt::Result {
+        let uri = match picture.data().and_then(picture_data_uri) {
+            Some(uri) => uri,
+            None => return Ok(()),
+        };
+        let align = match alignment {
+            PictureAlignment::TopLeft => "xMinYMin",
+            PictureAlignment::TopRight => "xMaxYMin",
+            PictureAlignment::Center => "xMidYMid",
+            PictureAlignment::BottomLeft => "xMinYMax",
+            PictureAlignment::BottomRight => "xMaxYMax",
+        };
+        // SVG can't show an image at its natural size without knowing it, so clipping is
+        // approximated by filling the control
+        let preserve = match size_mode {
+            PictureSizeMode::Stretch => String::from("none"),
+            PictureSizeMode::Zoom => format!("{} meet", align),
+            PictureSizeMode::Clip => format!("{} slice", align),
+        };
+        writeln!(
+            self.out,
+            r#"<image x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" preserveAspectRatio="{}" href="{}"/>"#,
+            r.x, r.y, r.w, r.h, preserve, uri
+        )
+    }
+
+    fn arrows(&mut self, r: Rect, orientation: i32, b: f64, color: RgbColor) -> fmt::Result {
+        let horizontal = match orientation {
+            0 => false,
+            1 => true,
+            _ => r.w > r.h,
+        };
+        let (first, second, d1, d2) = match horizontal {
+            true => (
+                Rect::new(r.x, r.y, b, r.h),
+                Rect::new(r.x + r.w - b, r.y, b, r.h),
+                Direction::Left,
+                Direction::Right,
+            ),
+            false => (
+                Rect::new(r.x, r.y, r.w, b),
+                Rect::new(r.x, r.y + r.h - b, r.w, b),
+                Direction::Up,
+                Direction::Down,
+            ),
+        };
+        let face = self.system(SystemColor::ButtonFace);
+        for (button, direction) in [(first, d1), (second, d2)] {
+            self.rect(button, Some(face), None)?;
+            self.effect(button, SpecialEffect::Raised)?;
+            self.arrow(button, direction, color)?;
+        }
+        Ok(())
+    }
+
+    fn arrow(&mut self, r: Rect, direction: Direction, color: RgbColor) -> fmt::Result {
+        let (cx, cy) = (r.x + r.w / 2.0, r.y + r.h / 2.0);
+        let s = (r.w.min(r.h) / 4.0).max(1.0);
+        let points = match direction {
+            Direction::Up => [
+                (cx - s, cy + s / 2.0),
+                (cx + s, cy + s / 2.0),
+                (cx, cy - s / 2.0),
+            ],
+            Direction::Down => [
+                (cx - s, cy - s / 2.0),
+                (cx + s, cy - s / 2.0),
+                (cx, cy + s / 2.0),
+            ],
+            Direction::Left => [
+                (cx + s / 2.0, cy - s),
+                (cx + s / 2.0, cy + s),
+                (cx - s / 2.0, cy),
+            ],
+            Direction::Right => [
+                (cx - s / 2.0, cy - s),
+                (cx - s / 2.0, cy + s),
+                (cx + s / 2.0, cy),
+            ],
+        };
+        writeln!(
+            self.out,
+            r#"<polygon points="{:.2},{:.2} {:.2},{:.2} {:.2},{:.2}" fill="{}"/>"#,
+            points[0].0, points[0].1, points[1].0, points[1].1, points[2].0, points[2].1, color
+        )
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::error::VerboseError;
+
+    use super::SvgRenderer;
+    use crate::{
+        controls::{
+            label::LabelControl,
+            user_form::{
+                ole_site_concrete::{Clsid, OleSiteConcreteControl, SiteFlags},
+                parse_form_control,
+            },
+            Control,
+        },
+        properties::{
+            color::OleColor, font::TextProps, picture::GuidAndPicture, MousePointer, Position,
+            Size, SpecialEffect, VariousPropertyBits,
+        },
+    };
+
+    #[test]
+    fn test_render_label() {
+        #[rustfmt::skip]
+        let bytes = [
+            0x00, 0x04, 0x04, 0x00, // version, cbForm
+            0x00, 0x00, 0x00, 0x00, // PropMask
+            0x00, 0x00, // CountOfSiteClassInfo
+            0x00, 0x00, 0x00, 0x00, // CountOfSites
+            0x00, 0x00, 0x00, 0x00, // CountOfBytes
+        ];
+        let (_, form) = parse_form_control::<VerboseError<_>>(&bytes).unwrap();
+        let site = OleSiteConcreteControl {
+            id: 1,
+            bit_flags: SiteFlags::VISIBLE | SiteFlags::STREAMED,
+            tab_index: 0,
+            clsid_cache_index: Clsid::Global(21),
+            name: String::from("Label1"),
+            site_position: Position::new(254, 508),
+            ..OleSiteConcreteControl::default()
+        };
+        let label = Control::Label(LabelControl {
+            fore_color: OleColor::BTNTEXT,
+            back_color: OleColor::BTNFACE,
+            various_property_bits: VariousPropertyBits::from_bits_retain(0x0080001B),
+            caption: String::from("Name & <Title>"),
+            picture_position: 0x00070001,
+            size: Size::new(2540, 508),
+            mouse_pointer: MousePointer::Default,
+            border_color: OleColor::WINDOWFRAME,
+            border_style: crate::controls::user_form::BorderStyle::None,
+            special_effect: SpecialEffect::Flat,
+            picture: GuidAndPicture::EMPTY,
+            accelerator: 0,
+            mouse_icon: GuidAndPicture::EMPTY,
+            text_props: TextProps::default(),
+            unknown_mask_bits: 0,
+        });
+        let svg = SvgRenderer::default().render(&form, [(&site, &label)]);
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains(r#"<g id="Label1">"#));
+        assert!(svg.contains(
+            r##"<rect x="9.60" y="19.20" width="96.00" height="19.20" fill="#F0F0F0"/>"##
+        ));
+        assert!(svg.contains("Name &amp; &lt;Title&gt;"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+}
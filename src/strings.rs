@@ -0,0 +1,565 @@
+//! String extraction for malware analysis
+//!
+//! Macros can hide payloads in the captions, tags and values of the controls on a form and
+//! read them back at runtime. [`extract_strings`] collects every non-empty string property of
+//! every form in a file, including the forms of Frame, MultiPage and Page controls, and flags the
+//! ones that look like encoded data, URLs or PowerShell commands.
+//!
+//! ```no_run
+//! # use ms_oforms::{strings::extract_strings, OFormsFile};
+//! # fn main() -> std::io::Result<()> {
+//! let mut file = OFormsFile::open(std::fs::File::open("vbaProject.bin")?)?;
+//! for s in extract_strings(&mut file)? {
+//!     if !s.flags.is_empty() {
+//!         println!("{}", s);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The list items of a ListBox or ComboBox are not part of the form, they are set by the
+//! macro code at runtime.
+//!
+//! The data of controls that can't be parsed, or that have an unknown class, is scanned for
+//! printable strings instead, like the `strings` tool. These are flagged as
+//! [`UNPARSED`][StringFlags::UNPARSED].
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, Read, Seek},
+    path::{Path, PathBuf},
+};
+
+use crate::{common::json_string, controls::Control, OFormsFile};
+
+bitflags! {
+    /// Kinds of suspicious content in a string
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub struct StringFlags: u8 {
+        /// The string is (URL-safe) base64 with at least 16 characters
+        const BASE64 = 0x01;
+        /// The string is hex encoded data with at least 16 digits
+        const HEX = 0x02;
+        /// The string contains a URL
+        const URL = 0x04;
+        /// The string contains PowerShell (or `cmd`) commands or parameters
+        const POWERSHELL = 0x08;
+        /// The string was found in the data of a control that could not be parsed
+        const UNPARSED = 0x10;
+    }
+}
+
+/// The minimum length of a string that is flagged as base64 or hex
+const MIN_ENCODED_LEN: usize = 16;
+
+/// The minimum length of a printable string in the data of a control
+const MIN_PRINTABLE_LEN: usize = 4;
+
+const URL_SCHEMES: &[&str] = &["http://", "https://", "ftp://", "file://", "\\\\"];
+
+const POWERSHELL_MARKERS: &[&str] = &[
+    "powershell",
+    "pwsh",
+    "-encodedcommand",
+    "-enc ",
+    "-nop ",
+    "-noprofile",
+    "-windowstyle hidden",
+    "-w hidden",
+    "-executionpolicy bypass",
+    "invoke-expression",
+    "iex(",
+    "iex (",
+    "invoke-webrequest",
+    "downloadstring",
+    "downloadfile",
+    "frombase64string",
+    "net.webclient",
+    "new-object",
+    "start-process",
+    "cmd /c",
+    "cmd.exe",
+];
+
+impl StringFlags {
+    /// Classify the content of `value`
+    pub fn classify(value: &str) -> Self {
+        let mut flags = Self::empty();
+        let trimmed = value.trim();
+        if is_base64(trimmed) {
+            flags |= Self::BASE64;
+        }
+        if is_hex(trimmed) {
+            flags |= Self::HEX;
+        }
+        let lower = value.to_ascii_lowercase();
+        if URL_SCHEMES.iter().any(|s| lower.contains(s)) {
+            flags |= Self::URL;
+        }
+        if POWERSHELL_MARKERS.iter().any(|m| lower.contains(m)) {
+            flags |= Self::POWERSHELL;
+        }
+        flags
+    }
+
+    /// The lowercase names of the flags, e.g. `["base64", "url"]`
+    pub fn names(&self) -> Vec<&'static str> {
+        let mut names = Vec::new();
+        for (flag, name) in [
+            (Self::BASE64, "base64"),
+            (Self::HEX, "hex"),
+            (Self::URL, "url"),
+            (Self::POWERSHELL, "powershell"),
+            (Self::UNPARSED, "unparsed"),
+        ] {
+            if self.contains(flag) {
+                names.push(name);
+            }
+        }
+        names
+    }
+}
+
+fn is_hex(value: &str) -> bool {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    digits.len() >= MIN_ENCODED_LEN
+        && digits.len() % 2 == 0
+        && digits.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn is_base64(value: &str) -> bool {
+    let data = value.trim_end_matches('=');
+    let padding = value.len() - data.len();
+    value.len() >= MIN_ENCODED_LEN
+        && padding <= 2
+        && (padding == 0 || value.len() % 4 == 0)
+        && data
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'-' | b'_'))
+        // Plain words and numbers are not base64
+        && data.bytes().any(|b| b.is_ascii_digit())
+        && data.bytes().any(|b| b.is_ascii_uppercase())
+        && data.bytes().any(|b| b.is_ascii_lowercase())
+}
+
+/// The runs of printable ASCII characters in `data`, as single bytes or as UTF-16LE
+fn printable_strings(data: &[u8]) -> Vec<String> {
+    let printable = |b: u8| b == b'\t' || (0x20..0x7F).contains(&b);
+    let mut strings = Vec::new();
+    let mut push = |run: &mut String| {
+        if run.len() >= MIN_PRINTABLE_LEN {
+            strings.push(run.clone());
+        }
+        run.clear();
+    };
+    let mut run = String::new();
+    for &b in data {
+        if printable(b) {
+            run.push(char::from(b));
+        } else {
+            push(&mut run);
+        }
+    }
+    push(&mut run);
+    for start in 0..2 {
+        for pair in data.get(start..).unwrap_or_default().chunks(2) {
+            match *pair {
+                [lo, 0] if printable(lo) => run.push(char::from(lo)),
+                _ => push(&mut run),
+            }
+        }
+        push(&mut run);
+    }
+    strings
+}
+
+/// The Shannon entropy of the characters of `value`, in bits per character
+pub fn entropy(value: &str) -> f64 {
+    let mut counts = BTreeMap::new();
+    let mut total = 0usize;
+    for c in value.chars() {
+        *counts.entry(c).or_insert(0usize) += 1;
+        total += 1;
+    }
+    counts
+        .values()
+        .map(|&n| {
+            let p = n as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// A string property of a form or control
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractedString {
+    /// The path of the property, e.g. `UserForm1/Frame1/TextBox3.Tag`
+    pub path: String,
+    /// The value of the property
+    pub value: String,
+    /// The number of characters in `value`
+    pub length: usize,
+    /// The [`entropy`] of `value`
+    pub entropy: f64,
+    /// The kinds of suspicious content in `value`
+    pub flags: StringFlags,
+}
+
+impl ExtractedString {
+    fn new(path: String, value: &str) -> Self {
+        Self {
+            path,
+            value: value.to_owned(),
+            length: value.chars().count(),
+            entropy: entropy(value),
+            flags: StringFlags::classify(value),
+        }
+    }
+
+    /// The string as a JSON object
+    pub fn to_json(&self) -> String {
+        let flags: Vec<String> = self.flags.names().into_iter().map(json_string).collect();
+        format!(
+            r#"{{"path":{},"length":{},"entropy":{:.3},"flags":[{}],"value":{}}}"#,
+            json_string(&self.path),
+            self.length,
+            self.entropy,
+            flags.join(","),
+            json_string(&self.value)
+        )
+    }
+}
+
+/// Tab separated path, length, entropy, flags and the escaped value
+impl fmt::Display for ExtractedString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{:.2}\t{}\t{:?}",
+            self.path,
+            self.length,
+            self.entropy,
+            self.flags.names().join(","),
+            self.value
+        )
+    }
+}
+
+/// Format a list of strings as a JSON array
+pub fn strings_to_json(strings: &[ExtractedString]) -> String {
+    let items: Vec<String> = strings.iter().map(ExtractedString::to_json).collect();
+    format!("[{}]", items.join(","))
+}
+
+/// Collects the strings of one form
+struct Extractor<'a> {
+    out: &'a mut Vec<ExtractedString>,
+}
+
+impl Extractor<'_> {
+    fn add(&mut self, path: &str, property: &str, value: &str) {
+        if !value.is_empty() {
+            let path = format!("{}.{}", path, property);
+            self.out.push(ExtractedString::new(path, value));
+        }
+    }
+
+    fn add_list(&mut self, path: &str, property: &str, values: &[String]) {
+        for (i, value) in values.iter().enumerate() {
+            self.add(path, &format!("{}({})", property, i), value);
+        }
+    }
+
+    /// Add the printable strings in the `data` of a control as `Data(i)`
+    fn unparsed(&mut self, path: &str, data: &[u8]) {
+        for (i, value) in printable_strings(data).iter().enumerate() {
+            let path = format!("{}.Data({})", path, i);
+            let mut string = ExtractedString::new(path, value);
+            string.flags |= StringFlags::UNPARSED;
+            self.out.push(string);
+        }
+    }
+
+    fn control(&mut self, path: &str, control: &Control) {
+        match control {
+            Control::CommandButton(c) => self.add(path, "Caption", &c.caption),
+            Control::Label(c) => self.add(path, "Caption", &c.caption),
+            Control::TextBox(c)
+            | Control::ListBox(c)
+            | Control::ComboBox(c)
+            | Control::CheckBox(c)
+            | Control::OptionButton(c)
+            | Control::ToggleButton(c) => {
+                self.add(path, "Caption", &c.caption);
+                self.add(path, "Value", &c.value);
+                self.add(path, "GroupName", &c.group_name);
+            }
+            Control::TabStrip(c) => {
+                self.add_list(path, "Tabs", &c.items);
+                self.add_list(path, "Names", &c.tab_names);
+                self.add_list(path, "ControlTipTexts", &c.tip_strings);
+                self.add_list(path, "Tags", &c.tags);
+            }
+            Control::Image(_)
+            | Control::SpinButton(_)
+            | Control::ScrollBar(_)
+            | Control::Frame(_)
+            | Control::MultiPage(_)
+            | Control::Page(_) => {}
+            Control::Unknown { bytes, .. } => self.unparsed(path, bytes),
+        }
+    }
+}
+
+/// Extract the strings of all forms in `file`, see the [module docs][self]
+pub fn extract_strings<T: Read + Seek>(
+    file: &mut OFormsFile<T>,
+) -> io::Result<Vec<ExtractedString>> {
+    let mut out = Vec::new();
+    for storage in file.form_storages()? {
+        let name = storage
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Form")
+            .to_owned();
+        let form = file.form_in(&storage)?;
+        Extractor { out: &mut out }.add(&name, "Caption", &form.form_control().caption);
+        extract_form(file, &storage, &name, &mut out)?;
+    }
+    Ok(out)
+}
+
+fn extract_form<T: Read + Seek>(
+    file: &mut OFormsFile<T>,
+    storage: &Path,
+    path: &str,
+    out: &mut Vec<ExtractedString>,
+) -> io::Result<()> {
+    let mut children: Vec<(String, PathBuf)> = Vec::new();
+    let mut objects = Vec::new();
+    file.read_stream_into(storage.join("o"), &mut objects)?;
+    {
+        let mut form = file.form_in(storage)?;
+        let mut extractor = Extractor { out: &mut *out };
+        let mut offset = 0;
+        for (site, control) in form.controls() {
+            let start = offset;
+            offset += site.object_stream_size as usize;
+            let path = format!("{}/{}", path, site.name);
+            extractor.add(&path, "Tag", &site.tag);
+            extractor.add(&path, "ControlTipText", &site.control_tip_text);
+            extractor.add(&path, "ControlSource", &site.control_source);
+            extractor.add(&path, "RowSource", &site.row_source);
+            match control {
+                Ok(control) => {
+                    extractor.control(&path, &control);
+                    if let Some(p) = control.parent_control() {
+                        children.push((path, p.storage.clone()));
+                    }
+                }
+                Err(_) => extractor.unparsed(&path, objects.get(start..offset).unwrap_or_default()),
+            }
+        }
+    }
+    for (path, storage) in children {
+        let form = file.form_in(&storage)?;
+        Extractor { out: &mut *out }.add(&path, "Caption", &form.form_control().caption);
+        extract_form(file, &storage, &path, out)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{entropy, extract_strings, strings_to_json, StringFlags};
+    use crate::{
+        controls::{command_button::CommandButtonControl, morph_data::MorphDataControl, Control},
+        fixture::{site, FormBuilder},
+        properties::Size,
+        BufferedOFormsFile,
+    };
+    use uuid::Uuid;
+
+    const SIZE: Size = Size::new(4000, 3000);
+
+    #[test]
+    fn test_classify_base64() {
+        assert_eq!(
+            StringFlags::classify("SGVsbG8sIFdvcmxkIQ=="),
+            StringFlags::BASE64
+        );
+        assert_eq!(
+            StringFlags::classify("  aHR0cHM6Ly9leGFtcGxl_Y29t  "),
+            StringFlags::BASE64
+        );
+        // too short, too much padding, padding that doesn't end a block
+        assert!(StringFlags::classify("SGVsbG8=").is_empty());
+        assert!(StringFlags::classify("SGVsbG8sIFdvcmxk1===").is_empty());
+        assert!(StringFlags::classify("SGVsbG8sIFdvcmxkIQ=").is_empty());
+        // words and numbers
+        assert!(StringFlags::classify("Abcdefghijklmnopqrstuvwxyz").is_empty());
+        assert!(StringFlags::classify("1234567890123456789").is_empty());
+    }
+
+    #[test]
+    fn test_classify_hex() {
+        assert_eq!(
+            StringFlags::classify("4d5a90000300000004000000"),
+            StringFlags::HEX
+        );
+        assert_eq!(
+            StringFlags::classify("0x4d5a900003000000"),
+            StringFlags::HEX
+        );
+        assert!(
+            StringFlags::classify("4d5a9000030000000").is_empty(),
+            "odd length"
+        );
+        assert!(StringFlags::classify("4d5a9000").is_empty(), "too short");
+    }
+
+    #[test]
+    fn test_classify_commands() {
+        assert_eq!(
+            StringFlags::classify("powershell -nop -w hidden -c \"iex(...)\""),
+            StringFlags::POWERSHELL
+        );
+        assert_eq!(
+            StringFlags::classify("see HTTPS://example.com"),
+            StringFlags::URL
+        );
+        assert_eq!(
+            StringFlags::classify("cmd /c copy \\\\server\\share\\a.exe"),
+            StringFlags::URL | StringFlags::POWERSHELL
+        );
+        assert_eq!(
+            (StringFlags::URL | StringFlags::BASE64).names(),
+            ["base64", "url"]
+        );
+        assert!(StringFlags::classify("OK").is_empty());
+        assert!(StringFlags::classify("").is_empty());
+    }
+
+    #[test]
+    fn test_entropy() {
+        assert_eq!(entropy(""), 0.0);
+        assert_eq!(entropy("aaaa"), 0.0);
+        assert_eq!(entropy("abcd"), 2.0);
+        assert_eq!(entropy("äöüß"), 2.0);
+    }
+
+    #[test]
+    fn test_extract_strings() {
+        let mut text_box = site(2, "TextBox3");
+        text_box.tag = String::from("aHR0cHM6Ly9leGFtcGxlLmNvbS9wYXlsb2Fk");
+        let frame =
+            FormBuilder::new(SIZE).control(text_box, Control::TextBox(MorphDataControl::default()));
+        let bytes = FormBuilder::new(SIZE)
+            .with_form(|form| form.caption = String::from("Invoice"))
+            .frame(site(1, "Frame1"), frame)
+            .compound_file("/Macros/UserForm1");
+        let mut file = BufferedOFormsFile::from_bytes(bytes).unwrap();
+        let strings = extract_strings(&mut file).unwrap();
+        let paths: Vec<_> = strings.iter().map(|s| &s.path[..]).collect();
+        assert_eq!(
+            paths,
+            ["UserForm1.Caption", "UserForm1/Frame1/TextBox3.Tag"]
+        );
+        assert_eq!(strings[1].length, 36);
+        assert_eq!(strings[1].flags, StringFlags::BASE64);
+        assert!(strings[1]
+            .to_string()
+            .starts_with("UserForm1/Frame1/TextBox3.Tag\t36\t"));
+    }
+
+    #[test]
+    fn test_extract_root_form() {
+        let button = CommandButtonControl {
+            caption: String::from("OK"),
+            ..CommandButtonControl::default()
+        };
+        let mut file = FormBuilder::new(SIZE)
+            .control(site(1, "cmd"), Control::CommandButton(button))
+            .file();
+        let strings = extract_strings(&mut file).unwrap();
+        // Empty values are skipped, the root storage has no name
+        assert_eq!(strings.len(), 1);
+        assert_eq!(strings[0].path, "Form/cmd.Caption");
+        assert_eq!(
+            strings_to_json(&strings),
+            r#"[{"path":"Form/cmd.Caption","length":2,"entropy":1.000,"flags":[],"value":"OK"}]"#
+        );
+        assert_eq!(strings_to_json(&[]), "[]");
+    }
+
+    #[test]
+    fn test_extract_multi_page() {
+        let mut text_box = site(3, "txt");
+        text_box.tag = String::from("https://example.com");
+        let page =
+            FormBuilder::new(SIZE).control(text_box, Control::TextBox(MorphDataControl::default()));
+        let multi_page = FormBuilder::new(SIZE).page(site(2, "Page1"), page);
+        let mut file = FormBuilder::new(SIZE)
+            .multi_page(site(1, "mp"), multi_page)
+            .file();
+        {
+            let mut form = file.form_in(Path::new("i01")).unwrap();
+            let (site, page) = form.controls().next().unwrap();
+            assert_eq!(site.name, "Page1");
+            assert!(matches!(page.unwrap(), Control::Page(_)));
+        }
+        let strings = extract_strings(&mut file).unwrap();
+        assert_eq!(strings.len(), 1);
+        assert_eq!(strings[0].path, "Form/mp/Page1/txt.Tag");
+        assert_eq!(strings[0].flags, StringFlags::URL);
+    }
+
+    #[test]
+    fn test_extract_unparsed() {
+        let button = CommandButtonControl {
+            caption: String::from("powershell -nop"),
+            ..CommandButtonControl::default()
+        };
+        let url: Vec<u8> = "https://example.com"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        let unknown = Control::Unknown {
+            clsid: Uuid::from_u128(0x1234),
+            bytes: [&[0xFF, 0x01, 0x02][..], &url].concat(),
+        };
+        let mut file = FormBuilder::new(SIZE)
+            .control(site(1, "cmd"), Control::CommandButton(button))
+            .control(site(2, "ocx"), unknown)
+            // an invalid version in the header of the CommandButton
+            .with_objects(|objects| objects[1] = 0x09)
+            .file();
+        let strings: Vec<_> = extract_strings(&mut file)
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.path, s.value, s.flags))
+            .collect();
+        assert_eq!(
+            strings,
+            [
+                (
+                    String::from("Form/cmd.Data(0)"),
+                    String::from("powershell -nop"),
+                    StringFlags::POWERSHELL | StringFlags::UNPARSED
+                ),
+                (
+                    String::from("Form/ocx.Data(0)"),
+                    String::from("https://example.com"),
+                    StringFlags::URL | StringFlags::UNPARSED
+                ),
+            ]
+        );
+    }
+}
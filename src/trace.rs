@@ -0,0 +1,289 @@
+//! Annotated hex dumps of the structures in a form
+//!
+//! When tracing is enabled on a [`ParseContext`], the parsers record the name, offset, length
+//! and alignment padding of every field they read, as a [`TraceField`]. This module runs the
+//! parsers with tracing for a form stream (`f`) and the controls in the object stream (`o`),
+//! and renders the result as a hex dump or JSON.
+//!
+//! ```text
+//! == FormControl (30 bytes)
+//! 00000000  00 04 10 00                                       Header
+//! 00000004  00 00 08 08                                       PropMask
+//! 00000008  02 00 00 80                                       CaptionLength
+//! 0000000C  00 7d 00 00                                       DrawBuffer
+//! 00000010  48 69                                             Caption
+//! 00000012  00 00                                             Padding
+//! 00000014  00 00                                             CountOfSiteClassInfo
+//! 00000016  00 00 00 00                                       CountOfSites
+//! 0000001A  00 00 00 00                                       CountOfBytes
+//! ```
+//!
+//! When parsing fails, the trace ends with the field that could not be read.
+
+use std::fmt::Write;
+
+use nom::error::VerboseError;
+
+use crate::{
+    common::{error_message, json_string, ParseContext, ParseMode, TraceField},
+    controls::{
+        user_form::{parse_form_control_with, SiteKind},
+        Control,
+    },
+    properties::FormEmbeddedActiveXControlCached,
+};
+
+/// The fields of a structure and the parse error, if any
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+    /// A name for the structure, e.g. `FormControl` or `CommandButton cmdOK`
+    pub name: String,
+    /// The fields that were read, in the order of the data
+    pub fields: Vec<TraceField>,
+    /// The parse error, if the structure could not be read
+    pub error: Option<String>,
+}
+
+impl Trace {
+    fn run<'a, O>(
+        name: String,
+        data: &'a [u8],
+        mode: ParseMode,
+        parser: impl FnOnce(
+            &'a [u8],
+            &ParseContext,
+        ) -> nom::IResult<&'a [u8], O, VerboseError<&'a [u8]>>,
+    ) -> (Self, Option<O>) {
+        let ctx = ParseContext::new(mode);
+        ctx.start_trace(data);
+        let result = parser(data, &ctx);
+        let fields = ctx.take_trace();
+        let (error, value) = match result {
+            Ok((_, value)) => (None, Some(value)),
+            Err(e) => (Some(error_message(data, e)), None),
+        };
+        (
+            Self {
+                name,
+                fields,
+                error,
+            },
+            value,
+        )
+    }
+
+    /// Render `data` as a hex dump, with the fields of this trace
+    ///
+    /// Each line has the offset, up to 16 bytes and the name of the field. Nested structures
+    /// are printed as a heading above their (indented) fields. Bytes that are not part of any
+    /// field are printed as `(unparsed)`.
+    pub fn hex_dump(&self, data: &[u8]) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "== {} ({} bytes)", self.name, data.len());
+        let mut pos = 0;
+        for (i, field) in self.fields.iter().enumerate() {
+            let indent = "  ".repeat(field.depth);
+            let is_group = self
+                .fields
+                .get(i + 1)
+                .is_some_and(|next| next.depth > field.depth);
+            let start = field.offset - field.padding;
+            if start > pos {
+                hex_lines(&mut out, data, pos, start, &format!("{}(unparsed)", indent));
+            }
+            if field.padding > 0 {
+                hex_lines(
+                    &mut out,
+                    data,
+                    start,
+                    field.offset,
+                    &format!("{}(padding)", indent),
+                );
+            }
+            match field.length {
+                Some(length) if is_group => {
+                    let _ = writeln!(
+                        out,
+                        "{:08X}  {:48}  {}{} ({} bytes)",
+                        field.offset, "", indent, field.name, length
+                    );
+                    pos = pos.max(field.offset);
+                }
+                Some(length) => {
+                    let end = (field.offset + length).min(data.len());
+                    hex_lines(
+                        &mut out,
+                        data,
+                        field.offset,
+                        end,
+                        &format!("{}{}", indent, field.name),
+                    );
+                    pos = pos.max(end);
+                }
+                None => {
+                    let end = match is_group {
+                        true => field.offset,
+                        false => (field.offset + 16).min(data.len()),
+                    };
+                    let label = format!("{}{} <- error", indent, field.name);
+                    if end > field.offset {
+                        hex_lines(&mut out, data, field.offset, end, &label);
+                    } else {
+                        let _ = writeln!(out, "{:08X}  {:48}  {}", field.offset, "", label);
+                    }
+                    pos = pos.max(field.offset);
+                }
+            }
+        }
+        if self.error.is_none() && pos < data.len() {
+            hex_lines(&mut out, data, pos, data.len(), "(unparsed)");
+        }
+        if let Some(error) = &self.error {
+            let _ = writeln!(out, "error: {}", error);
+        }
+        out
+    }
+
+    /// Serialize the trace as a JSON object
+    pub fn to_json(&self) -> String {
+        let mut out = format!("{{\"name\":{},\"fields\":[", json_string(&self.name));
+        for (i, field) in self.fields.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let length = field
+                .length
+                .map_or_else(|| String::from("null"), |l| l.to_string());
+            let _ = write!(
+                out,
+                "{{\"name\":{},\"depth\":{},\"offset\":{},\"length\":{},\"padding\":{}}}",
+                json_string(field.name),
+                field.depth,
+                field.offset,
+                length,
+                field.padding
+            );
+        }
+        out.push_str("],\"error\":");
+        match &self.error {
+            Some(error) => out.push_str(&json_string(error)),
+            None => out.push_str("null"),
+        }
+        out.push('}');
+        out
+    }
+}
+
+/// Write the bytes `data[start..end]` in lines of 16, with `label` on the first line
+fn hex_lines(out: &mut String, data: &[u8], start: usize, end: usize, label: &str) {
+    let bytes = data.get(start..end).unwrap_or_default();
+    for (i, chunk) in bytes.chunks(16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+        let label = if i == 0 { label } else { "" };
+        let _ = writeln!(
+            out,
+            "{:08X}  {:48}  {}",
+            start + i * 16,
+            hex.join(" "),
+            label
+        );
+    }
+}
+
+/// Trace the parsing of a form stream (`f`) with a `FormControl`
+pub fn trace_form_control(data: &[u8], mode: ParseMode) -> Trace {
+    let (trace, _) = Trace::run(String::from("FormControl"), data, mode, |i, ctx| {
+        parse_form_control_with(i, ctx)
+    });
+    trace
+}
+
+/// Trace the parsing of the data of a control, e.g. from the object stream (`o`)
+pub fn trace_control(
+    kind: FormEmbeddedActiveXControlCached,
+    data: &[u8],
+    mode: ParseMode,
+) -> Trace {
+    let (trace, _) = Trace::run(format!("{:?}", kind), data, mode, |i, ctx| {
+        Control::parse_with(kind, i, ctx)
+    });
+    trace
+}
+
+/// Trace a form stream (`f`) and the controls in its object stream (`o`)
+///
+/// The result has a trace for the `FormControl`, followed by a trace for each streamed Forms 2.0
+/// control, with the part of the object stream that it was parsed from.
+pub fn trace_streams<'a>(
+    form_stream: &'a [u8],
+    object_stream: &'a [u8],
+    mode: ParseMode,
+) -> Vec<(Trace, &'a [u8])> {
+    let (trace, form) = Trace::run(String::from("FormControl"), form_stream, mode, |i, ctx| {
+        parse_form_control_with(i, ctx)
+    });
+    let mut result = vec![(trace, form_stream)];
+    let form = match form {
+        Some(form) => form,
+        None => return result,
+    };
+    let mut offset = 0usize;
+    for site in &form.sites {
+        let SiteKind::Ole(site) = &site.kind;
+        let start = offset;
+        offset = offset.saturating_add(site.object_stream_size as usize);
+        let data = match object_stream.get(start..offset) {
+            Some(data) => data,
+            None => break,
+        };
+        if let Some(kind) = site.control_kind(&form.site_classes) {
+            let mut trace = trace_control(kind, data, mode);
+            trace.name = format!("{:?} {} at 0x{:X} in o", kind, site.name, start);
+            result.push((trace, data));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::trace_control;
+    use crate::{
+        common::ParseMode,
+        controls::spin_button::{write_spin_button, SpinButtonControl},
+        properties::{FormEmbeddedActiveXControlCached, Size},
+    };
+
+    #[test]
+    fn test_trace_spin_button() {
+        let control = SpinButtonControl {
+            size: Size::new(400, 1200),
+            max: 10,
+            ..SpinButtonControl::default()
+        };
+        let mut data = Vec::new();
+        write_spin_button(&mut data, &control).unwrap();
+
+        let kind = FormEmbeddedActiveXControlCached::SpinButton;
+        let trace = trace_control(kind, &data, ParseMode::Strict);
+        assert_eq!(trace.error, None);
+        let fields: Vec<_> = trace
+            .fields
+            .iter()
+            .map(|f| (f.name, f.offset, f.length))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("Header", 0, Some(4)),
+                ("PropMask", 4, Some(4)),
+                ("Max", 8, Some(4)),
+                ("Size", 12, Some(8)),
+            ]
+        );
+
+        let trace = trace_control(kind, &data[..10], ParseMode::Strict);
+        assert!(trace.error.is_some());
+        assert!(trace.hex_dump(&data[..10]).contains("Header"));
+    }
+}
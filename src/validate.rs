@@ -0,0 +1,448 @@
+//! Conformance checks against the MUST requirements of [MS-OFORMS]
+//!
+//! The parsers of this crate accept some structures that Office may reject, and the writers
+//! don't check their input. The functions in this module report these cases as
+//! [`Violation`]s with the section of the specification:
+//!
+//! - [`validate_form_control`] and friends check parsed (or generated) structures,
+//! - [`validate_streams`] checks the contents of a form (`f`) and object (`o`) stream,
+//!   including the bits of masks that are not kept in the parsed structures,
+//! - [`validate_file`] checks all forms in a file.
+//!
+//! Some requirements, like the picture index of 0xFFFF when a picture is present, are already
+//! checked by the parsers, and reported as a violation of the structure that failed to parse.
+//!
+//! [MS-OFORMS]: https://learn.microsoft.com/openspecs/office_file_formats/ms-oforms/
+
+use std::{
+    collections::BTreeSet,
+    convert::TryFrom,
+    fmt,
+    io::{self, Read, Seek},
+    path::{Path, PathBuf},
+};
+
+use nom::error::VerboseError;
+use num_traits::FromPrimitive;
+
+use crate::{
+    common::{error_message, ParseContext, ParseMode, VarFlags, VarType},
+    controls::{
+        command_button::CommandButtonPropMask,
+        morph_data::MorphDataPropMask,
+        scroll_bar::ScrollBarPropMask,
+        spin_button::SpinButtonPropMask,
+        user_form::{
+            class_table::SiteClassInfo,
+            ole_site_concrete::{Clsid, OleSiteConcreteControl, SiteFlags},
+            parse_form_control_with,
+            stream::FormPropMask,
+            FormControl, SiteKind,
+        },
+        Control,
+    },
+    properties::{
+        font::{FontFlags, FormFont, StdFont},
+        FormEmbeddedActiveXControlCached,
+    },
+    OFormsFile,
+};
+
+/// A requirement of the specification that is not met
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// The section of [MS-OFORMS], e.g. `2.2.10.3`
+    ///
+    /// [MS-OFORMS]: https://learn.microsoft.com/openspecs/office_file_formats/ms-oforms/
+    pub section: &'static str,
+    /// Where the violation was found, e.g. `FormControl` or `site cmdOK (ID 1)`
+    pub location: String,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} (MS-OFORMS {})",
+            self.location, self.message, self.section
+        )
+    }
+}
+
+/// Collects violations for one location
+struct Checker<'a> {
+    out: &'a mut Vec<Violation>,
+    location: String,
+}
+
+impl Checker<'_> {
+    fn check<F: FnOnce() -> String>(&mut self, ok: bool, section: &'static str, message: F) {
+        if !ok {
+            self.out.push(Violation {
+                section,
+                location: self.location.clone(),
+                message: message(),
+            });
+        }
+    }
+}
+
+fn site_location(site: &OleSiteConcreteControl) -> String {
+    format!("site {} (ID {})", site.name, site.id)
+}
+
+/// The section of the persisted structure of a control
+fn control_section(kind: FormEmbeddedActiveXControlCached) -> &'static str {
+    use FormEmbeddedActiveXControlCached as C;
+    match kind {
+        C::CommandButton => "2.2.1.1",
+        C::Frame => "2.2.2",
+        C::Image => "2.2.3.1",
+        C::Label => "2.2.4.1",
+        C::MorphData
+        | C::TextBox
+        | C::ListBox
+        | C::ComboBox
+        | C::CheckBox
+        | C::OptionButton
+        | C::ToggleButton => "2.2.5.1",
+        C::MultiPage => "2.2.6",
+        C::ScrollBar => "2.2.7.1",
+        C::SpinButton => "2.2.8.1",
+        C::TabStrip => "2.2.9.1",
+        C::Form => "2.2.10.1",
+    }
+}
+
+/// Check a [`StdFont`] (2.4.12)
+pub fn validate_std_font(font: &StdFont, location: &str) -> Vec<Violation> {
+    let mut out = Vec::new();
+    let mut c = Checker {
+        out: &mut out,
+        location: location.to_owned(),
+    };
+    c.check((0..=1000).contains(&font.weight), "2.4.12", || {
+        format!("font weight {} is not within 0..=1000", font.weight)
+    });
+    c.check(font.height.get() <= 655_350_000, "2.4.12", || {
+        format!("font height {} is above 655350000", font.height)
+    });
+    c.check(!font.flags.contains(FontFlags::BOLD), "2.4.12", || {
+        String::from("the bold flag is set, the weight specifies bold fonts")
+    });
+    out
+}
+
+/// Check a [`SiteClassInfo`] (2.2.10.10)
+pub fn validate_site_class_info(info: &SiteClassInfo, location: &str) -> Vec<Violation> {
+    let mut out = Vec::new();
+    let mut c = Checker {
+        out: &mut out,
+        location: location.to_owned(),
+    };
+    let bind = VarFlags::BINDABLE | VarFlags::DISPLAY_BIND;
+    c.check(
+        info.bind_type == VarType::EMPTY || info.var_flags.contains(bind),
+        "2.2.10.10",
+        || String::from("the default bindable property is not marked bindable and displaybind"),
+    );
+    out
+}
+
+/// Check an embedded site (2.2.10.12), with the type of its control
+pub fn validate_site(
+    site: &OleSiteConcreteControl,
+    kind: Option<FormEmbeddedActiveXControlCached>,
+) -> Vec<Violation> {
+    use FormEmbeddedActiveXControlCached as C;
+    let mut out = Vec::new();
+    let mut c = Checker {
+        out: &mut out,
+        location: site_location(site),
+    };
+    if let Some(kind) = kind {
+        let parent = matches!(kind, C::Frame | C::MultiPage);
+        c.check(
+            site.bit_flags.contains(SiteFlags::PROMOTE_CONTROLS) == parent,
+            "2.2.10.12",
+            || match parent {
+                true => format!("the {:?} does not set PROMOTE_CONTROLS", kind),
+                false => format!("PROMOTE_CONTROLS is set for a {:?}", kind),
+            },
+        );
+        c.check(
+            site.row_source.is_empty() || matches!(kind, C::ListBox | C::ComboBox),
+            "2.2.10.12",
+            || format!("RowSource is set for a {:?}", kind),
+        );
+    }
+    out
+}
+
+/// Check a [`FormControl`] (2.2.10), including its site class infos, sites and font
+pub fn validate_form_control(form: &FormControl) -> Vec<Violation> {
+    let mut out = Vec::new();
+    let mut c = Checker {
+        out: &mut out,
+        location: String::from("FormControl"),
+    };
+    c.check((10..=400).contains(&form.zoom), "2.2.10.3", || {
+        format!("zoom {} is not within 10..=400", form.zoom)
+    });
+    c.check(form.draw_buffer > 0, "2.2.10.3", || {
+        String::from("the draw buffer size is zero")
+    });
+
+    if let FormFont::StdFont(font) = &form.font.font {
+        out.extend(validate_std_font(font, "FormControl.Font"));
+    }
+    for (i, info) in form.site_classes.iter().enumerate() {
+        out.extend(validate_site_class_info(
+            info,
+            &format!("SiteClassInfo {}", i),
+        ));
+    }
+
+    let mut ids = BTreeSet::new();
+    for site in &form.sites {
+        let SiteKind::Ole(site) = &site.kind;
+        let mut c = Checker {
+            out: &mut out,
+            location: site_location(site),
+        };
+        c.check(ids.insert(site.id), "2.2.10.12", || {
+            format!("the ID {} is not unique", site.id)
+        });
+        match site.clsid_cache_index {
+            Clsid::Global(index) => c.check(
+                FormEmbeddedActiveXControlCached::from_u16(index).is_some(),
+                "2.2.10.12",
+                || format!("unknown CLSID cache index {}", index),
+            ),
+            Clsid::ClassTable(index) => {
+                c.check(
+                    usize::from(index) < form.site_classes.len(),
+                    "2.2.10.12",
+                    || format!("the class table has no entry {}", index),
+                );
+            }
+            Clsid::Invalid => {}
+        }
+        out.extend(validate_site(site, site.control_kind(&form.site_classes)));
+    }
+    out
+}
+
+fn mask_u32(bytes: &[u8]) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        <[u8; 4]>::try_from(bytes.get(4..8)?).ok()?,
+    ))
+}
+
+/// Check the bits of the property mask of a control that are not kept when parsing
+fn check_control_mask(c: &mut Checker<'_>, kind: FormEmbeddedActiveXControlCached, bytes: &[u8]) {
+    use FormEmbeddedActiveXControlCached as C;
+    let mask = mask_u32(bytes).unwrap_or_default();
+    match kind {
+        C::CommandButton => c.check(
+            mask & CommandButtonPropMask::SIZE.bits() != 0,
+            "2.2.1.2",
+            || String::from("fSize is not set"),
+        ),
+        C::MorphData
+        | C::TextBox
+        | C::ListBox
+        | C::ComboBox
+        | C::CheckBox
+        | C::OptionButton
+        | C::ToggleButton => c.check(
+            u64::from(mask) & MorphDataPropMask::RESERVED.bits() != 0,
+            "2.2.5.2",
+            || String::from("the reserved bit is not set"),
+        ),
+        C::SpinButton => c.check(
+            mask & SpinButtonPropMask::UNUSED1.bits() == 0,
+            "2.2.8.2",
+            || String::from("UnusedBits1 is set"),
+        ),
+        C::ScrollBar => c.check(
+            mask & ScrollBarPropMask::UNUSED1.bits() == 0,
+            "2.2.7.2",
+            || String::from("UnusedBits1 is set"),
+        ),
+        _ => {}
+    }
+}
+
+/// Check the persisted data of a control (the part of the object stream of its site)
+///
+/// This parses the control in [`ParseMode::Strict`] and checks the bits of its property mask
+/// that are not kept when parsing.
+pub fn validate_control(
+    kind: FormEmbeddedActiveXControlCached,
+    bytes: &[u8],
+    location: &str,
+) -> Vec<Violation> {
+    let mut out = Vec::new();
+    let mut c = Checker {
+        out: &mut out,
+        location: location.to_owned(),
+    };
+    check_control_mask(&mut c, kind, bytes);
+    let ctx = ParseContext::new(ParseMode::Strict);
+    if let Err(e) = Control::parse_with::<VerboseError<_>>(kind, bytes, &ctx) {
+        let message = error_message(bytes, e);
+        c.check(false, control_section(kind), || message);
+    }
+    out
+}
+
+/// Check the contents of a form stream (`f`) and its object stream (`o`)
+///
+/// This parses both streams in [`ParseMode::Strict`], and includes the checks of
+/// [`validate_form_control`] and [`validate_control`].
+pub fn validate_streams(form_stream: &[u8], object_stream: &[u8]) -> Vec<Violation> {
+    let mut out = Vec::new();
+    let mut c = Checker {
+        out: &mut out,
+        location: String::from("FormControl"),
+    };
+    let ctx = ParseContext::new(ParseMode::Strict);
+    let form = match parse_form_control_with::<VerboseError<_>>(form_stream, &ctx) {
+        Ok((_, form)) => form,
+        Err(e) => {
+            let message = error_message(form_stream, e);
+            c.check(false, "2.2.10.1", || message);
+            return out;
+        }
+    };
+    let mask = mask_u32(form_stream).unwrap_or_default();
+    c.check(
+        mask & FormPropMask::DRAW_BUFFER.bits() != 0,
+        "2.2.10.2",
+        || String::from("fDrawBuffer is not set"),
+    );
+
+    let total: u64 = form
+        .sites
+        .iter()
+        .map(|s| {
+            let SiteKind::Ole(site) = &s.kind;
+            u64::from(site.object_stream_size)
+        })
+        .sum();
+    c.check(total == object_stream.len() as u64, "2.2.10.12", || {
+        format!(
+            "the object stream sizes of the sites add up to {}, but the object stream has {} bytes",
+            total,
+            object_stream.len()
+        )
+    });
+
+    out.extend(validate_form_control(&form));
+
+    let mut offset = 0usize;
+    for s in &form.sites {
+        let SiteKind::Ole(site) = &s.kind;
+        let start = offset;
+        offset += site.object_stream_size as usize;
+        let bytes = match object_stream.get(start..offset) {
+            Some(bytes) => bytes,
+            None => break,
+        };
+        let kind = match site.control_kind(&form.site_classes) {
+            Some(kind) if site.bit_flags.contains(SiteFlags::STREAMED) => kind,
+            _ => continue,
+        };
+        out.extend(validate_control(kind, bytes, &site_location(site)));
+    }
+    out
+}
+
+fn read_stream<T: Read + Seek>(file: &mut OFormsFile<T>, path: &Path) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    file.read_stream_into(path, &mut buf)?;
+    Ok(buf)
+}
+
+/// Check all forms in `file`, including the forms of Frame, MultiPage and Page controls
+///
+/// The location of each violation is prefixed with the storage of the form.
+pub fn validate_file<T: Read + Seek>(file: &mut OFormsFile<T>) -> io::Result<Vec<Violation>> {
+    let mut out = Vec::new();
+    let mut pending = file.form_storages()?;
+    pending.reverse();
+    while let Some(storage) = pending.pop() {
+        let form_stream = read_stream(file, &storage.join("f"))?;
+        let object_stream = read_stream(file, &storage.join("o"))?;
+        for mut violation in validate_streams(&form_stream, &object_stream) {
+            violation.location = format!("{}: {}", storage.display(), violation.location);
+            out.push(violation);
+        }
+        // Parent controls, if the form can be parsed
+        if let Ok(mut form) = file.form_in(&storage) {
+            let children: Vec<PathBuf> = form
+                .controls()
+                .filter_map(|(_, control)| match control {
+                    Ok(control) => control.parent_control().map(|p| p.storage.clone()),
+                    Err(_) => None,
+                })
+                .collect();
+            pending.extend(children.into_iter().rev());
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use super::{validate_form_control, validate_streams};
+    use crate::{
+        common::CLSID_STD_FONT,
+        controls::user_form::FormControl,
+        merge::FormContents,
+        properties::font::{FontFlags, FormFont, GuidAndFont, StdFont},
+    };
+
+    #[test]
+    fn test_validate() {
+        let mut form = FormControl {
+            draw_buffer: 32000,
+            ..FormControl::default()
+        };
+        assert!(validate_form_control(&form).is_empty());
+
+        form.zoom = 500;
+        form.font = GuidAndFont {
+            guid: CLSID_STD_FONT,
+            font: FormFont::StdFont(StdFont {
+                charset: 0,
+                flags: FontFlags::BOLD,
+                weight: 700,
+                height: NonZeroU32::new(80_000).unwrap(),
+                font_face: String::from("Tahoma"),
+            }),
+        };
+        let sections: Vec<_> = validate_form_control(&form)
+            .iter()
+            .map(|v| v.section)
+            .collect();
+        assert_eq!(sections, ["2.2.10.3", "2.4.12"]);
+
+        let contents = FormContents {
+            form: FormControl { zoom: 100, ..form },
+            controls: Vec::new(),
+        };
+        let f = contents.form_stream().unwrap();
+        let violations = validate_streams(&f, &[0; 4]);
+        assert_eq!(violations.len(), 2);
+        assert_eq!(
+            violations[0].to_string(),
+            "FormControl: the object stream sizes of the sites add up to 0, \
+            but the object stream has 4 bytes (MS-OFORMS 2.2.10.12)"
+        );
+    }
+}
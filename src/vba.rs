@@ -0,0 +1,402 @@
+//! The VBA code of the forms in a `vbaProject.bin` (feature `vba`)
+//!
+//! In a VBA project, the designer storage of a form (with the `f` and `o` streams) is next to
+//! the `VBA` storage, which holds the source code of all modules. The `dir` stream in that
+//! storage lists the modules and the stream and offset of their (compressed) source.
+//!
+//! See [MS-OVBA] for the project structure and the compression algorithm.
+//!
+//! ```no_run
+//! # use ms_oforms::OFormsFile;
+//! # fn main() -> std::io::Result<()> {
+//! let file = std::fs::File::open("vbaProject.bin")?;
+//! let mut oforms = OFormsFile::open(file)?;
+//! for (storage, module) in oforms.form_modules()? {
+//!     println!("{}:\n{}", storage.display(), module.source);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [MS-OVBA]: https://learn.microsoft.com/en-us/openspecs/office_file_formats/ms-ovba/575462ba-bf67-4190-9fac-c275523c75fc
+
+use std::{
+    convert::TryFrom,
+    io::{self, Read, Seek},
+    path::{Path, PathBuf},
+};
+
+use encoding_rs::{Encoding, UTF_16LE, WINDOWS_1252};
+use nom::{
+    bytes::complete::take,
+    error::VerboseError,
+    number::complete::{le_u16, le_u32},
+    IResult,
+};
+
+use crate::{map_verbose_err, properties::string::code_page_encoding, OFormsFile};
+
+/// The size of a decompressed chunk
+const CHUNK_SIZE: usize = 4096;
+
+/// Decompress a `CompressedContainer`
+///
+/// See MS-OVBA 2.4.1
+pub fn decompress(data: &[u8]) -> io::Result<Vec<u8>> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let mut chunks = match data.split_first() {
+        Some((0x01, rest)) => rest,
+        _ => return Err(invalid("missing signature byte of compressed container")),
+    };
+    let mut out = Vec::with_capacity(data.len() * 2);
+    while chunks.len() >= 2 {
+        let header = u16::from_le_bytes([chunks[0], chunks[1]]);
+        if (header >> 12) & 0b111 != 0b011 {
+            return Err(invalid("invalid compressed chunk signature"));
+        }
+        let size = usize::from(header & 0x0FFF) + 3;
+        let chunk = chunks.get(2..size.min(chunks.len())).unwrap_or_default();
+        chunks = chunks.get(size..).unwrap_or_default();
+        if header & 0x8000 == 0 {
+            // Uncompressed chunk
+            if chunk.len() != CHUNK_SIZE {
+                return Err(invalid("uncompressed chunk must have 4096 bytes"));
+            }
+            out.extend_from_slice(chunk);
+            continue;
+        }
+        decompress_chunk(chunk, &mut out).map_err(invalid)?;
+    }
+    Ok(out)
+}
+
+/// Decompress the `TokenSequence`s of a compressed chunk into `out`
+///
+/// See MS-OVBA 2.4.1.3.2
+fn decompress_chunk(mut chunk: &[u8], out: &mut Vec<u8>) -> Result<(), &'static str> {
+    let start = out.len();
+    while let Some((&flags, rest)) = chunk.split_first() {
+        chunk = rest;
+        for bit in 0..8 {
+            if chunk.is_empty() {
+                break;
+            }
+            if flags & (1 << bit) == 0 {
+                out.push(chunk[0]);
+                chunk = &chunk[1..];
+                continue;
+            }
+            if chunk.len() < 2 {
+                return Err("truncated copy token");
+            }
+            let token = u16::from_le_bytes([chunk[0], chunk[1]]);
+            chunk = &chunk[2..];
+            let difference = out.len() - start;
+            let mut bit_count = 4;
+            while (1 << bit_count) < difference {
+                bit_count += 1;
+            }
+            let length_mask = 0xFFFF >> bit_count;
+            let length = usize::from(token & length_mask) + 3;
+            let offset = usize::from(token >> (16 - bit_count)) + 1;
+            if offset > difference {
+                return Err("copy token points before the start of the chunk");
+            }
+            let from = out.len() - offset;
+            for i in 0..length {
+                out.push(out[from + i]);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The type of a module
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ModuleKind {
+    /// A procedural (standard) module
+    Procedural,
+    /// A document, class or designer module, e.g. the code of a form
+    Document,
+}
+
+/// A module of a VBA project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VbaModule {
+    /// The name of the module, e.g. `UserForm1`
+    pub name: String,
+    /// The name of the stream in the `VBA` storage
+    ///
+    /// For a form, this is also the name of its designer storage.
+    pub stream_name: String,
+    /// The type of the module
+    pub kind: ModuleKind,
+    /// The offset of the compressed source in the module stream
+    pub offset: u32,
+    /// The source code of the module, including the `Attribute` lines
+    pub source: String,
+}
+
+/// A VBA project, from the `dir` stream and the module streams
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VbaProject {
+    /// The `PROJECTCODEPAGE`, which is used for all MBCS strings of the project
+    pub code_page: u16,
+    /// The modules, in the order of the `dir` stream
+    pub modules: Vec<VbaModule>,
+}
+
+impl VbaProject {
+    /// The encoding of the code page, e.g. for [`OFormsFile::with_encoding`]
+    pub fn encoding(&self) -> Option<&'static Encoding> {
+        code_page_encoding(self.code_page)
+    }
+
+    /// Find a module by name (case-insensitive, as in VBA)
+    pub fn module(&self, name: &str) -> Option<&VbaModule> {
+        self.modules
+            .iter()
+            .find(|m| m.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// The parts of the `dir` stream that are needed to find the module source
+#[derive(Debug, Default)]
+struct DirInfo {
+    code_page: u16,
+    modules: Vec<DirModule>,
+}
+
+#[derive(Debug)]
+struct DirModule {
+    name: Vec<u8>,
+    name_unicode: Option<Vec<u8>>,
+    stream_name: Vec<u8>,
+    stream_name_unicode: Option<Vec<u8>>,
+    kind: ModuleKind,
+    offset: u32,
+}
+
+/// The ID and data of a record in the `dir` stream
+type DirRecord<'a> = (u16, &'a [u8]);
+
+/// Parse a record of the `dir` stream
+///
+/// All records have a 32-bit size after the ID, except `PROJECTVERSION`, whose size field is
+/// always 4 but which is followed by 6 bytes.
+fn parse_dir_record(input: &[u8]) -> IResult<&[u8], DirRecord<'_>, VerboseError<&[u8]>> {
+    let (input, id) = le_u16(input)?;
+    let (input, size) = le_u32(input)?;
+    let size = if id == 0x0009 { 6 } else { size as usize };
+    let (input, data) = take(size)(input)?;
+    Ok((input, (id, data)))
+}
+
+/// Parse the (decompressed) `dir` stream
+///
+/// See MS-OVBA 2.3.4.2
+fn parse_dir(mut input: &[u8]) -> IResult<&[u8], DirInfo, VerboseError<&[u8]>> {
+    let mut info = DirInfo::default();
+    while !input.is_empty() {
+        let (rest, (id, data)) = parse_dir_record(input)?;
+        input = rest;
+        let module = info.modules.last_mut();
+        match (id, module) {
+            // PROJECTCODEPAGE
+            (0x0003, _) => {
+                let (_, code_page) = le_u16(data)?;
+                info.code_page = code_page;
+            }
+            // MODULENAME, which starts a MODULE record
+            (0x0019, _) => info.modules.push(DirModule {
+                name: data.to_vec(),
+                name_unicode: None,
+                stream_name: Vec::new(),
+                stream_name_unicode: None,
+                kind: ModuleKind::Procedural,
+                offset: 0,
+            }),
+            // MODULENAMEUNICODE
+            (0x0047, Some(module)) => module.name_unicode = Some(data.to_vec()),
+            // MODULESTREAMNAME
+            (0x001A, Some(module)) => module.stream_name = data.to_vec(),
+            (0x0032, Some(module)) => module.stream_name_unicode = Some(data.to_vec()),
+            // MODULEOFFSET
+            (0x0031, Some(module)) => {
+                let (_, offset) = le_u32(data)?;
+                module.offset = offset;
+            }
+            // MODULETYPE
+            (0x0021, Some(module)) => module.kind = ModuleKind::Procedural,
+            (0x0022, Some(module)) => module.kind = ModuleKind::Document,
+            // dir stream Terminator
+            (0x0010, _) => break,
+            _ => {}
+        }
+    }
+    Ok((input, info))
+}
+
+/// Decode an MBCS string, or the UTF-16 variant if there is one
+fn decode_name(mbcs: &[u8], unicode: Option<&[u8]>, encoding: &'static Encoding) -> String {
+    match unicode {
+        Some(unicode) => UTF_16LE.decode_without_bom_handling(unicode).0.into_owned(),
+        None => encoding.decode_without_bom_handling(mbcs).0.into_owned(),
+    }
+}
+
+impl<T: Read + Seek> OFormsFile<T> {
+    /// Read the VBA project from the `VBA` storage below the prefix
+    ///
+    /// The prefix must be the root of the project, e.g. `/` for a `vbaProject.bin` or
+    /// `_VBA_PROJECT_CUR` for a `.xls` file.
+    pub fn vba_project(&mut self) -> io::Result<VbaProject> {
+        let vba = Path::new("VBA");
+        let mut buf = Vec::new();
+        self.read_stream_into(vba.join("dir"), &mut buf)?;
+        let dir = decompress(&buf)?;
+        let (_, info) = parse_dir(&dir).map_err(map_verbose_err(&dir))?;
+        let encoding = code_page_encoding(info.code_page).unwrap_or(WINDOWS_1252);
+
+        let mut modules = Vec::with_capacity(info.modules.len());
+        for module in info.modules {
+            let stream_name = decode_name(
+                &module.stream_name,
+                module.stream_name_unicode.as_deref(),
+                encoding,
+            );
+            self.read_stream_into(vba.join(&stream_name), &mut buf)?;
+            let offset = usize::try_from(module.offset).unwrap_or(usize::MAX);
+            let compressed = buf.get(offset..).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("module offset beyond the end of stream {}", stream_name),
+                )
+            })?;
+            let source = decompress(compressed)?;
+            modules.push(VbaModule {
+                name: decode_name(&module.name, module.name_unicode.as_deref(), encoding),
+                stream_name,
+                kind: module.kind,
+                offset: module.offset,
+                source: encoding.decode_without_bom_handling(&source).0.into_owned(),
+            });
+        }
+        Ok(VbaProject {
+            code_page: info.code_page,
+            modules,
+        })
+    }
+
+    /// Find the module with the code of each form, see [`OFormsFile::form_storages`]
+    ///
+    /// The designer storage of a form has the same name as the stream of its module. Storages
+    /// without a matching module are not included.
+    pub fn form_modules(&mut self) -> io::Result<Vec<(PathBuf, VbaModule)>> {
+        let project = self.vba_project()?;
+        let mut out = Vec::new();
+        for storage in self.form_storages()? {
+            let name = storage.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            let module = project
+                .modules
+                .iter()
+                .find(|m| m.stream_name.eq_ignore_ascii_case(name));
+            if let Some(module) = module {
+                out.push((storage.clone(), module.clone()));
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use super::{decompress, ModuleKind};
+    use crate::OFormsFile;
+
+    /// A compressed container with only literal tokens
+    fn compress_literals(data: &[u8]) -> Vec<u8> {
+        let mut out = vec![0x01];
+        for chunk in data.chunks(4096) {
+            let mut tokens = Vec::new();
+            for group in chunk.chunks(8) {
+                tokens.push(0x00);
+                tokens.extend_from_slice(group);
+            }
+            let header = 0xB000 | (tokens.len() as u16 + 2 - 3);
+            out.extend_from_slice(&header.to_le_bytes());
+            out.extend_from_slice(&tokens);
+        }
+        out
+    }
+
+    fn record(out: &mut Vec<u8>, id: u16, data: &[u8]) {
+        out.extend_from_slice(&id.to_le_bytes());
+        out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        out.extend_from_slice(data);
+    }
+
+    #[test]
+    fn test_decompress() {
+        // MS-OVBA 3.2.2
+        let compressed = [
+            0x01, 0x2F, 0xB0, 0x00, 0x23, 0x61, 0x61, 0x61, 0x62, 0x63, 0x64, 0x65, 0x82, 0x66,
+            0x00, 0x70, 0x61, 0x67, 0x68, 0x69, 0x6A, 0x01, 0x38, 0x08, 0x61, 0x6B, 0x6C, 0x00,
+            0x30, 0x6D, 0x6E, 0x6F, 0x70, 0x06, 0x71, 0x02, 0x70, 0x04, 0x10, 0x72, 0x73, 0x74,
+            0x75, 0x76, 0x10, 0x77, 0x78, 0x79, 0x7A, 0x00, 0x3C,
+        ];
+        assert_eq!(
+            decompress(&compressed).unwrap(),
+            b"#aaabcdefaaaaghijaaaaaklaaamnopqaaaaaaaaaaaarstuvwxyzaaa"
+        );
+        assert!(decompress(&[0x00]).is_err());
+    }
+
+    #[test]
+    fn test_form_modules() {
+        let mut dir = Vec::new();
+        record(&mut dir, 0x0003, &1252u16.to_le_bytes());
+        dir.extend_from_slice(&[0x09, 0x00, 0x04, 0x00, 0x00, 0x00]);
+        dir.extend_from_slice(&[0x01, 0x00, 0x00, 0x00, 0x02, 0x00]);
+        record(&mut dir, 0x000F, &1u16.to_le_bytes());
+        record(&mut dir, 0x0019, b"UserForm1");
+        record(&mut dir, 0x001A, b"UserForm1");
+        record(&mut dir, 0x0031, &4u32.to_le_bytes());
+        record(&mut dir, 0x0022, &[]);
+        record(&mut dir, 0x002B, &[]);
+        record(&mut dir, 0x0010, &[]);
+
+        let source = "Attribute VB_Name = \"UserForm1\"\r\nPrivate Sub CommandButton1_Click()\r\nEnd Sub\r\n";
+        let mut module = vec![0xCA, 0xCE, 0xFE, 0xED];
+        module.extend(compress_literals(source.as_bytes()));
+
+        let mut cfb = cfb::CompoundFile::create(Cursor::new(Vec::new())).unwrap();
+        cfb.create_storage("/VBA").unwrap();
+        let streams = [
+            ("/VBA/dir", compress_literals(&dir)),
+            ("/VBA/UserForm1", module),
+        ];
+        for (path, data) in &streams {
+            cfb.create_stream(path).unwrap().write_all(data).unwrap();
+        }
+        cfb.create_storage("/UserForm1").unwrap();
+        cfb.create_stream("/UserForm1/f").unwrap();
+        cfb.create_stream("/UserForm1/o").unwrap();
+        cfb.flush().unwrap();
+        let bytes = cfb.into_inner().into_inner();
+
+        let mut file = OFormsFile::open(Cursor::new(bytes)).unwrap();
+        let project = file.vba_project().unwrap();
+        assert_eq!(project.code_page, 1252);
+        assert_eq!(
+            project.module("userform1").unwrap().kind,
+            ModuleKind::Document
+        );
+        let modules = file.form_modules().unwrap();
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].0.to_str(), Some("UserForm1"));
+        assert_eq!(modules[0].1.source, source);
+    }
+}
@@ -0,0 +1,231 @@
+//! JavaScript bindings for WebAssembly (feature `wasm`)
+//!
+//! The crate is only a `rlib` by default, so build the `cdylib` for `wasm-bindgen` with
+//! `cargo rustc`:
+//!
+//! ```text
+//! cargo rustc --lib --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/ms_oforms.wasm
+//! ```
+//!
+//! Both entry points take the bytes of a compound file, e.g. a `vbaProject.bin`, and read it
+//! from memory.
+//!
+//! ```text
+//! import init, { parseOForms, renderSvg } from "./pkg/ms_oforms.js";
+//! await init();
+//! const bytes = new Uint8Array(await file.arrayBuffer());
+//! const forms = JSON.parse(parseOForms(bytes));
+//! preview.innerHTML = renderSvg(bytes, forms[0].storage);
+//! ```
+
+use std::{
+    io::{self, Read, Seek},
+    path::{Path, PathBuf},
+};
+
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    common::{json_map, json_string},
+    controls::{user_form::ole_site_concrete::OleSiteConcreteControl, Control},
+    properties::values::{control_props, form_props, site_props},
+    render::svg::SvgRenderer,
+    BufferedOFormsFile, OFormsFile,
+};
+
+fn js_error(e: io::Error) -> JsValue {
+    JsValue::from_str(&e.to_string())
+}
+
+/// Serialize the form in `storage` as JSON, with the forms of parent controls nested below
+/// the control
+fn form_json<T: Read + Seek>(file: &mut OFormsFile<T>, storage: &Path) -> io::Result<String> {
+    let mut form = file.form_in(storage)?;
+    let mut properties = Default::default();
+    form_props(&mut properties, "", form.form_control());
+    let controls: Vec<(OleSiteConcreteControl, io::Result<Control>)> = form
+        .controls()
+        .map(|(site, control)| (site.clone(), control))
+        .collect();
+
+    let mut items = Vec::with_capacity(controls.len());
+    for (site, control) in controls {
+        let mut site_properties = Default::default();
+        site_props(&mut site_properties, &site);
+        let mut control_properties = Default::default();
+        let (type_name, error, children) = match &control {
+            Ok(control) => {
+                control_props(&mut control_properties, control);
+                let children = match control.parent_control() {
+                    Some(p) => Some(form_json(file, &p.storage)?),
+                    None => None,
+                };
+                (control.type_name(), None, children)
+            }
+            Err(e) => (None, Some(e.to_string()), None),
+        };
+        items.push(format!(
+            "{{\"id\":{},\"name\":{},\"type\":{},\"left\":{},\"top\":{},\"site\":{},\
+             \"properties\":{},\"error\":{},\"form\":{}}}",
+            site.id,
+            json_string(&site.name),
+            type_name.map_or_else(|| String::from("null"), json_string),
+            site.site_position.left,
+            site.site_position.top,
+            json_map(&site_properties),
+            json_map(&control_properties),
+            error.map_or_else(|| String::from("null"), |e| json_string(&e)),
+            children.unwrap_or_else(|| String::from("null"))
+        ));
+    }
+    Ok(format!(
+        "{{\"storage\":{},\"properties\":{},\"controls\":[{}]}}",
+        json_string(&storage.to_string_lossy()),
+        json_map(&properties),
+        items.join(",")
+    ))
+}
+
+/// Serialize all forms in `file` as a JSON array, see [`form_json`]
+fn forms_json<T: Read + Seek>(file: &mut OFormsFile<T>) -> io::Result<String> {
+    let mut forms = Vec::new();
+    for storage in file.form_storages()? {
+        forms.push(form_json(file, &storage)?);
+    }
+    Ok(format!("[{}]", forms.join(",")))
+}
+
+/// Render the form in `storage` to SVG, with the size and caption of its parent controls
+fn form_svg<T: Read + Seek>(file: &mut OFormsFile<T>, storage: &Path) -> io::Result<String> {
+    let mut form = file.form_in(storage)?;
+    let controls: Vec<(OleSiteConcreteControl, Control)> = form
+        .controls()
+        .filter_map(|(site, control)| Some((site.clone(), control.ok()?)))
+        .collect();
+    let form = form.into_form_control();
+    let mut renderer = SvgRenderer::default();
+    for (site, control) in &controls {
+        if let Some(p) = control.parent_control() {
+            let parent = file.form_in(&p.storage)?.into_form_control();
+            renderer = renderer.with_parent(site.id, &parent);
+        }
+    }
+    Ok(renderer.render(
+        &form,
+        controls.iter().map(|(site, control)| (site, control)),
+    ))
+}
+
+/// Parse all forms in a compound file and return them as a JSON array
+///
+/// Each form has its `storage`, `properties` and `controls`. The controls of a Frame,
+/// MultiPage or Page are in its `form`.
+#[wasm_bindgen(js_name = parseOForms)]
+pub fn parse_oforms(bytes: &[u8]) -> Result<String, JsValue> {
+    let mut file = BufferedOFormsFile::from_bytes(bytes.to_vec()).map_err(js_error)?;
+    forms_json(&mut file).map_err(js_error)
+}
+
+/// Render a form in a compound file to SVG
+///
+/// The `storage` is one of the storages returned by `parseOForms`, by default the first.
+#[wasm_bindgen(js_name = renderSvg)]
+pub fn render_svg(bytes: &[u8], storage: Option<String>) -> Result<String, JsValue> {
+    let mut file = BufferedOFormsFile::from_bytes(bytes.to_vec()).map_err(js_error)?;
+    let storage = match storage {
+        Some(storage) => PathBuf::from(storage),
+        None => file
+            .form_storages()
+            .map_err(js_error)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| JsValue::from_str("no form in file"))?,
+    };
+    form_svg(&mut file, &storage).map_err(js_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{form_svg, forms_json};
+    use crate::{
+        controls::{
+            morph_data::{DisplayStyle, MorphDataControl},
+            Control,
+        },
+        fixture::{site, FormBuilder},
+        properties::Size,
+        BufferedOFormsFile,
+    };
+
+    const SIZE: Size = Size::new(4000, 3000);
+
+    fn check_box() -> Control {
+        Control::CheckBox(MorphDataControl {
+            display_style: DisplayStyle::CheckBox,
+            ..MorphDataControl::default()
+        })
+    }
+
+    /// A form with a Frame `fra` with a CheckBox `chk`
+    fn file() -> BufferedOFormsFile {
+        let frame = FormBuilder::new(SIZE)
+            .with_form(|form| form.caption = String::from("Group"))
+            .control(site(2, "chk"), check_box());
+        let bytes = FormBuilder::new(SIZE)
+            .with_form(|form| form.caption = String::from("Hello"))
+            .frame(site(1, "fra"), frame)
+            .compound_file("/Form1");
+        BufferedOFormsFile::from_bytes(bytes).unwrap()
+    }
+
+    #[test]
+    fn test_forms_json() {
+        let json = forms_json(&mut file()).unwrap();
+        assert!(json.starts_with("[{\"storage\":\"Form1\",\"properties\":{"));
+        assert!(json.contains("\"name\":\"fra\",\"type\":\"Frame\""));
+        // the CheckBox is nested in the form of the Frame
+        assert!(
+            json.contains("\"form\":{\"storage\":\"Form1/i01\",\"properties\":{\"back_color\":")
+        );
+        assert!(json.contains("\"name\":\"chk\",\"type\":\"CheckBox\""));
+        assert!(json.ends_with("\"error\":null,\"form\":null}]}}]}]"));
+    }
+
+    #[test]
+    fn test_no_forms() {
+        let mut buf = std::io::Cursor::new(Vec::new());
+        cfb::CompoundFile::create(&mut buf)
+            .unwrap()
+            .flush()
+            .unwrap();
+        let mut file = BufferedOFormsFile::from_bytes(buf.into_inner()).unwrap();
+        assert_eq!(forms_json(&mut file).unwrap(), "[]");
+    }
+
+    #[test]
+    fn test_form_svg() {
+        let mut file = file();
+        let svg = form_svg(&mut file, Path::new("Form1")).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("Group"));
+        assert!(form_svg(&mut file, Path::new("Form2")).is_err());
+    }
+
+    #[test]
+    fn test_multi_page_json() {
+        let page = FormBuilder::new(SIZE).control(site(3, "chk"), check_box());
+        let pages = FormBuilder::new(SIZE).page(site(2, "Page1"), page);
+        let bytes = FormBuilder::new(SIZE)
+            .multi_page(site(1, "mp"), pages)
+            .compound_file("/Form1");
+        let mut file = BufferedOFormsFile::from_bytes(bytes).unwrap();
+        let json = forms_json(&mut file).unwrap();
+        assert!(json.contains("\"name\":\"mp\",\"type\":\"MultiPage\""));
+        assert!(json.contains("\"name\":\"Page1\",\"type\":\"Page\""));
+        assert!(json.contains("\"form\":{\"storage\":\"Form1/i01/i02\""));
+        assert!(json.contains("\"name\":\"chk\",\"type\":\"CheckBox\""));
+    }
+}